@@ -160,7 +160,7 @@ impl std::default::Default for SizeMinSummary {
 }
 
 impl SizedSummary for SizeMinSummary {
-    fn size(self) -> usize {
+    fn size(&self) -> usize {
         self.size
     }
 }
@@ -201,7 +201,7 @@ impl Data for MyData {
 // here the actual algorithms start
 
 // splits a segment inside the tree
-fn search_split<TR: ModifiableTreeRef<MyData>>(tree: TR, index: usize) {
+fn search_split<T: SomeTree<MyData>>(tree: &mut T, index: usize) {
     let mut walker = tree.walker();
     // using an empty range so that we'll only end up at a node
     // if we actually need to split that node
@@ -221,10 +221,7 @@ fn search_split<TR: ModifiableTreeRef<MyData>>(tree: TR, index: usize) {
 }
 
 /// solves the pyramid_base problem
-fn solve<T: SomeTree<MyData>>(m: usize, n: usize, budget: I, obstacles: Vec<Obstacle>) -> usize
-where
-    for<'b> &'b mut T: ModifiableTreeRef<MyData>,
-{
+fn solve<T: SomeTree<MyData>>(m: usize, n: usize, budget: I, obstacles: Vec<Obstacle>) -> usize {
     let (mut opening_edges, mut closing_edges): (Vec<Edge>, Vec<Edge>) =
         obstacles.iter().map(|x| x.edges()).unzip();
 
@@ -315,10 +312,7 @@ where
 
 ///////////////////////////////////////////// input handling ////////////////////////////////////////////
 // runs the solution on a given input
-fn run_from<R: Read, T: SomeTree<MyData>>(io: R) -> usize
-where
-    for<'b> &'b mut T: ModifiableTreeRef<MyData>,
-{
+fn run_from<R: Read, T: SomeTree<MyData>>(io: R) -> usize {
     let br = BufReader::new(io);
 
     let mut words_iter = br.lines().flat_map(|row| {
@@ -361,17 +355,14 @@ where
 
 // run the solution of a specific file,
 // and print some metadata
-fn run_on_file<T: SomeTree<MyData>>(name: &str) -> Result<(), Error>
-where
-    for<'b> &'b mut T: ModifiableTreeRef<MyData>,
-{
+fn run_on_file<T: SomeTree<MyData>>(name: &str) -> Result<(), Error> {
     let current_dir = std::path::PathBuf::from_str("../grove/pyramid_base_test_files").unwrap();
     print!("testing {: >8}.in:", name);
     let mut file_path = current_dir.clone();
     file_path.push(format!("{}.in", name));
     std::io::Write::flush(&mut std::io::stdout())?;
 
-    let computed_res = run_from(File::open(file_path)?);
+    let computed_res = run_from::<_, T>(File::open(file_path)?);
     print!("{: >7}: ", computed_res);
 
     let mut file_path = current_dir.clone();
@@ -394,10 +385,7 @@ where
 
 // run the tests in the test directory.
 // you need to manually put the tests in the folder.
-fn check_all_tests<T: SomeTree<MyData>>() -> Result<(), Error>
-where
-    for<'b> &'b mut T: ModifiableTreeRef<MyData>,
-{
+fn check_all_tests<T: SomeTree<MyData>>() -> Result<(), Error> {
     let current_dir = std::path::PathBuf::from_str("../grove/pyramid_base_test_files").unwrap();
     println!("Testing files from {:?}:", current_dir);
 
@@ -425,7 +413,7 @@ where
 
     let start = Instant::now();
     for filename in filenames {
-        run_on_file(&filename)?;
+        run_on_file::<T>(&filename)?;
     }
     let duration = Instant::now().duration_since(start);
     println!("done all files: {: <16} overall", format!("{:?}", duration));
@@ -164,7 +164,7 @@ impl Default for SegmentSize {
 }
 
 impl SizedSummary for SegmentSize {
-    fn size(self) -> usize {
+    fn size(&self) -> usize {
         self.size
     }
 }
@@ -190,8 +190,8 @@ impl Data for RevData {
 }
 
 // splits a segment inside the tree
-fn search_split<TR: SplittableTreeRef<RevData>>(tree: TR, index: usize) -> TR::T {
-    let mut walker = tree.walker();
+fn search_split<T: SplittableTree<RevData>>(tree: &mut T, index: usize) -> T {
+    let mut walker = tree.splittable_walker();
     // using an empty range so that we'll only end up at a node
     // if we actually need to split that node
     walker.search_subtree(index..index);
@@ -215,10 +215,7 @@ fn search_split<TR: SplittableTreeRef<RevData>>(tree: TR, index: usize) -> TR::T
     walker.split_right().unwrap()
 }
 
-fn yarra<T: ConcatenableTree<RevData>>(n: usize, k: usize) -> I
-where
-    for<'b> &'b mut T: SplittableTreeRef<RevData, T = T>,
-{
+fn yarra<T: SplittableTree<RevData>>(n: usize, k: usize) -> I {
     let inter = Interval {
         start: 0,
         end: (n - 1) as I,
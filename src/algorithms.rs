@@ -0,0 +1,299 @@
+//! Ready-made algorithms built on top of the trees.
+//!
+//! Most algorithms here keep a sorted multiset of values in a [`Treap`] with size summaries,
+//! and answer order-statistics questions about it: counting the values in a range of keys
+//! is a [`SomeTree::segment_summary`] with a [`ByKey`] locator, and finding the `k`-th smallest
+//! value is a search with the index locator `k`. They also serve as examples of these APIs.
+//! These trees hold references to the values, so the values don't need to be [`Clone`].
+//!
+//! In addition, [`diff`] compares two trees, using their summaries to skip identical segments.
+
+use crate::example_data::SizeData;
+use crate::locators::ByKey;
+use crate::trees::treap::Treap;
+use crate::*;
+use std::ops::Range;
+
+/// A sorted multiset of references, with size summaries.
+type Multiset<'a, T> = Treap<SizeData<&'a T>>;
+
+/// Returns the `k`-th smallest value in the multiset, starting from `0`.
+fn kth<'a, T: Ord>(multiset: &mut Multiset<'a, T>, k: usize) -> Option<&'a T> {
+    multiset.search(k).value().copied()
+}
+
+/// Counts the inversions in the sequence: the pairs of indices `i < j` such that
+/// `values[i] > values[j]`. Takes `O(n log n)` expected time.
+///
+/// For every value, the values before it that are bigger than it are counted,
+/// by querying the size of a range of keys.
+///```
+/// use grove::algorithms::count_inversions;
+///
+/// assert_eq!(count_inversions(&[3, 1, 2]), 2);
+/// assert_eq!(count_inversions(&[1, 1, 1]), 0);
+/// assert_eq!(count_inversions(&[5, 4, 3, 2, 1]), 10);
+///```
+pub fn count_inversions<T: Ord>(values: &[T]) -> usize {
+    let mut seen: Multiset<T> = Treap::new();
+    let mut inversions = 0;
+    for (i, value) in values.iter().enumerate() {
+        let not_bigger = seen.segment_summary(ByKey(..=&value)).size;
+        inversions += i - not_bigger;
+        seen.insert_sorted(value);
+    }
+    inversions
+}
+
+/// Returns the `k`-th smallest value, starting from `0`, of every window of `window`
+/// consecutive values, in order. For example, with an odd `window`, and `k = window / 2`,
+/// these are the sliding window medians.
+/// Takes `O(n log window)` expected time.
+///
+/// Panics if `k >= window`.
+///```
+/// use grove::algorithms::sliding_window_kth;
+///
+/// let values = [5, 1, 4, 2, 8, 3];
+/// // sliding window medians
+/// assert_eq!(sliding_window_kth(&values, 3, 1), vec![&4, &2, &4, &3]);
+/// // sliding window maximums
+/// assert_eq!(sliding_window_kth(&values, 2, 1), vec![&5, &4, &4, &8, &8]);
+///```
+pub fn sliding_window_kth<T: Ord>(values: &[T], window: usize, k: usize) -> Vec<&T> {
+    assert!(
+        k < window,
+        "k ({}) must be smaller than the window ({})",
+        k,
+        window
+    );
+    let mut multiset: Multiset<T> = Treap::new();
+    let mut res = vec![];
+    for (i, value) in values.iter().enumerate() {
+        multiset.insert_sorted(value);
+        if i >= window {
+            multiset.remove_by_key(&&values[i - window]);
+        }
+        if i + 1 >= window {
+            res.push(kth(&mut multiset, k).expect("Expected a full window"));
+        }
+    }
+    res
+}
+
+/// Answers queries of the form `(len, k)`: what is the `k`-th smallest value, starting from `0`,
+/// among the first `len` values. Returns [`None`] for queries with `k >= len`.
+/// Panics if `len > values.len()`.
+///
+/// The queries are answered offline: they are sorted by `len`, and the values are inserted
+/// into the tree one by one, answering the queries of every prefix after inserting it.
+/// Takes `O((n + q) log n)` expected time, for `q` queries.
+///```
+/// use grove::algorithms::prefix_kth;
+///
+/// let values = [5, 1, 4, 2, 8];
+/// let queries = [(5, 2), (1, 0), (3, 2), (2, 2)];
+/// assert_eq!(prefix_kth(&values, &queries), vec![Some(&4), Some(&5), Some(&5), None]);
+///```
+pub fn prefix_kth<'a, T: Ord>(values: &'a [T], queries: &[(usize, usize)]) -> Vec<Option<&'a T>> {
+    let mut order: Vec<usize> = (0..queries.len()).collect();
+    order.sort_by_key(|&i| queries[i].0);
+
+    let mut multiset: Multiset<T> = Treap::new();
+    let mut inserted = 0;
+    let mut res = vec![None; queries.len()];
+    for i in order {
+        let (len, k) = queries[i];
+        assert!(len <= values.len(), "prefix length {} out of bounds", len);
+        for value in &values[inserted..len] {
+            multiset.insert_sorted(value);
+        }
+        inserted = inserted.max(len);
+        res[i] = kth(&mut multiset, k);
+    }
+    res
+}
+
+/// A single operation of an edit script, as returned by [`diff`].
+/// The operations are applied in order, with a cursor that starts at the beginning of the sequence.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum EditOp<V> {
+    /// Keeps the given number of values, and moves the cursor past them.
+    Retain(usize),
+    /// Deletes the given number of values after the cursor.
+    Delete(usize),
+    /// Inserts the values at the cursor, and moves the cursor past them.
+    Insert(Vec<V>),
+}
+
+/// Pushes the operation to the edit script, merging it into the last operation if they
+/// are of the same kind. Empty operations are dropped.
+fn push_op<V>(ops: &mut Vec<EditOp<V>>, op: EditOp<V>) {
+    match (ops.last_mut(), op) {
+        (_, EditOp::Retain(0)) | (_, EditOp::Delete(0)) => (),
+        (_, EditOp::Insert(values)) if values.is_empty() => (),
+        (Some(EditOp::Retain(count)), EditOp::Retain(more)) => *count += more,
+        (Some(EditOp::Delete(count)), EditOp::Delete(more)) => *count += more,
+        (Some(EditOp::Insert(values)), EditOp::Insert(more)) => values.extend(more),
+        (_, op) => ops.push(op),
+    }
+}
+
+/// Returns the biggest length in `0..=max` that satisfies the predicate,
+/// assuming that it is satisfied by `0`, and by every length shorter than a length that satisfies it.
+fn longest<F: FnMut(usize) -> bool>(max: usize, mut pred: F) -> usize {
+    let (mut low, mut high) = (0, max);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if pred(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// Returns a shortest edit script that turns `a` into `b`, using Myers' algorithm.
+/// Takes `O((n + m) d)` time, where `d` is the number of inserted and deleted values.
+fn myers<V: PartialEq + Clone>(a: &[V], b: &[V]) -> Vec<EditOp<V>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    // `furthest[k + offset]` is the furthest `x` reached on the diagonal `k = x - y`.
+    let mut furthest = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = vec![];
+    'search: for d in 0..=n + m {
+        trace.push(furthest.clone());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && furthest[i - 1] < furthest[i + 1]) {
+                furthest[i + 1]
+            } else {
+                furthest[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[i] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walk back from the end, collecting the operations in reverse order
+    let mut reversed = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, furthest) in trace.iter().enumerate().rev() {
+        let (d, k) = (d as isize, x - y);
+        let i = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && furthest[i - 1] < furthest[i + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = furthest[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            reversed.push(EditOp::Retain(1));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                reversed.push(EditOp::Insert(vec![b[y as usize - 1].clone()]));
+            } else {
+                reversed.push(EditOp::Delete(1));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    let mut ops = vec![];
+    for op in reversed.into_iter().rev() {
+        push_op(&mut ops, op);
+    }
+    ops
+}
+
+/// Returns an edit script that turns the sequence in the tree `a` into the sequence in the tree `b`.
+/// The operations are merged into runs, and consecutive operations are of different kinds.
+///
+/// The summaries are used to find the longest common prefix and suffix of the two trees,
+/// by binary searching for the longest prefix and suffix with equal summaries,
+/// using `O(log^2 n)` summary queries. Therefore, the summaries should identify the values
+/// of their segments, such as [`example_data::HashSummary`].
+/// Equal summaries are trusted to be of equal segments, so with hash summaries,
+/// the result might be wrong with a small probability.
+///
+/// The remaining middle parts are compared value by value, using Myers' algorithm,
+/// which finds a shortest edit script in `O(k d)` time, where `k` is the length of the middle parts
+/// and `d` is the number of inserted and deleted values.
+/// Therefore, similar trees are compared quickly, even if they are huge.
+///```
+/// use grove::{SomeTree, treap::Treap};
+/// use grove::algorithms::{diff, EditOp};
+/// use grove::example_data::{HashSummary, Unit};
+///
+/// type D = (char, HashSummary<257, 1_000_000_007>, Unit);
+/// let mut a: Treap<D> = "the quick brown fox".chars().collect();
+/// let mut b: Treap<D> = "the quick red fox".chars().collect();
+///
+/// let ops = diff(&mut a, &mut b);
+/// assert_eq!(ops, vec![
+///     EditOp::Retain(10),
+///     EditOp::Delete(1),
+///     EditOp::Retain(1),
+///     EditOp::Delete(3),
+///     EditOp::Insert(vec!['e', 'd']),
+///     EditOp::Retain(4),
+/// ]);
+/// # a.assert_correctness();
+///```
+pub fn diff<D, T>(a: &mut T, b: &mut T) -> Vec<EditOp<D::Value>>
+where
+    D: Data,
+    D::Value: PartialEq + Clone,
+    D::Summary: SizedSummary + PartialEq,
+    T: SomeTree<D>,
+{
+    let (n, m) = (a.subtree_summary().size(), b.subtree_summary().size());
+    let (prefix, suffix) = common_ends(n, m, |a_range, b_range| {
+        a.segment_summary(a_range) == b.segment_summary(b_range)
+    });
+    let a_middle: Vec<D::Value> = a.iter_locator(prefix..n - suffix).cloned().collect();
+    let b_middle: Vec<D::Value> = b.iter_locator(prefix..m - suffix).cloned().collect();
+    edit_script(prefix, &a_middle, &b_middle, suffix)
+}
+
+/// Returns the lengths of the longest common prefix and suffix of two sequences of lengths
+/// `n` and `m`, where `same` compares a segment of the first sequence to a segment of the second.
+/// The prefix and suffix don't overlap. See [`diff`].
+pub(crate) fn common_ends<F>(n: usize, m: usize, mut same: F) -> (usize, usize)
+where
+    F: FnMut(Range<usize>, Range<usize>) -> bool,
+{
+    let prefix = longest(n.min(m), |len| same(0..len, 0..len));
+    let suffix = longest(n.min(m) - prefix, |len| same(n - len..n, m - len..m));
+    (prefix, suffix)
+}
+
+/// Returns the edit script of two sequences, given the lengths of their common prefix and suffix,
+/// and the values between them. See [`diff`].
+pub(crate) fn edit_script<V: PartialEq + Clone>(
+    prefix: usize,
+    a_middle: &[V],
+    b_middle: &[V],
+    suffix: usize,
+) -> Vec<EditOp<V>> {
+    let mut ops = vec![];
+    push_op(&mut ops, EditOp::Retain(prefix));
+    for op in myers(a_middle, b_middle) {
+        push_op(&mut ops, op);
+    }
+    push_op(&mut ops, EditOp::Retain(suffix));
+    ops
+}
@@ -0,0 +1,398 @@
+//! A chunked sequence: a sequence stored as a tree of small arrays of values,
+//! to make a tree competitive with a [`Vec`] for medium sizes.
+//!
+//! Every value in the tree is a chunk, a wide value holding up to `N` of the sequence's values
+//! inline, in an [`ArrayVec`], along with their cached summary. Compared to storing a value
+//! per node, this reduces the number of nodes, allocations and pointers chased by a factor
+//! of about `N`.
+//!
+//! Queries evaluate the chunks at the ends of the queried segment partially, value by value,
+//! so they don't change the tree. Acting on a segment splits the chunks at its ends,
+//! like any other wide value (see [`SplitValue`]), and then acts on whole chunks lazily.
+//! An action applied to a whole chunk is applied to all of its values at once, reversing them
+//! if needed, and passing the correct offsets to indexed actions (see [`Action::is_indexed`]).
+//! Inserting or removing a single value takes `O(N + log(n / N))` time.
+
+use crate::trees::treap::Treap;
+use crate::*;
+use arrayvec::ArrayVec;
+use std::marker::PhantomData;
+use std::ops::{Add, Range};
+
+/// Up to `N` consecutive values of the sequence, stored inline, which also caches their summary.
+pub(super) struct Chunk<D: Data, const N: usize> {
+    values: ArrayVec<D::Value, N>,
+    summary: D::Summary,
+}
+
+impl<D: Data, const N: usize> Chunk<D, N> {
+    fn new(values: ArrayVec<D::Value, N>) -> Self {
+        let summary = summarize::<D>(&values);
+        Chunk { values, summary }
+    }
+
+    fn update_summary(&mut self) {
+        self.summary = summarize::<D>(&self.values);
+    }
+}
+
+/// Returns the summary of the given values, in order.
+pub(super) fn summarize<D: Data>(values: &[D::Value]) -> D::Summary {
+    values.iter().fold(D::Summary::default(), |summary, value| {
+        summary + value.to_summary()
+    })
+}
+
+/// The user's summary, of a segment of chunks.
+#[derive(Clone, Copy, Default)]
+pub(super) struct ChunkSummary<S>(S);
+
+impl<S: Add<Output = S>> Add for ChunkSummary<S> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        ChunkSummary(self.0 + other.0)
+    }
+}
+
+impl<S: SizedSummary> SizedSummary for ChunkSummary<S> {
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+}
+
+impl<D: Data, const N: usize> ToSummary<ChunkSummary<D::Summary>> for Chunk<D, N> {
+    fn to_summary(&self) -> ChunkSummary<D::Summary> {
+        ChunkSummary(self.summary.clone())
+    }
+}
+
+/// Splits the chunk by the index of a value.
+impl<D: Data, const N: usize> SplitValue for Chunk<D, N> {
+    fn split_off(&mut self, index: usize) -> Self {
+        let rest = Chunk::new(self.values.drain(index..).collect());
+        self.update_summary();
+        rest
+    }
+}
+
+/// The user's action, acting on chunks and their summaries.
+#[derive(Clone, Copy, Default)]
+pub(super) struct ChunkAction<A>(A);
+
+impl<A: Action> Add for ChunkAction<A> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        ChunkAction(self.0 + other.0)
+    }
+}
+
+impl<A: Action> Action for ChunkAction<A> {
+    const REVERSIBLE: bool = A::REVERSIBLE;
+
+    fn is_identity(&self) -> bool {
+        self.0.is_identity()
+    }
+
+    fn to_reverse(&self) -> bool {
+        self.0.to_reverse()
+    }
+
+    fn is_indexed(&self) -> bool {
+        self.0.is_indexed()
+    }
+}
+
+/// Applies the action to a segment of consecutive values, stored in order.
+/// The values are reversed if needed, and indexed actions get the correct offset for every value.
+pub(super) fn act_values<D: Data>(action: &D::Action, values: &mut [D::Value]) {
+    assert!(
+        D::Action::REVERSIBLE || !action.to_reverse(),
+        "Actions that aren't `Action::REVERSIBLE` must not reverse"
+    );
+    let reverse = D::Action::REVERSIBLE && action.to_reverse();
+    if reverse {
+        values.reverse();
+    }
+    if action.is_indexed() {
+        // every value skips the values before it, in the new order,
+        // whose summary is computed in their original order.
+        let mut skipped = D::Summary::default();
+        for value in values.iter_mut() {
+            let summary: D::Summary = (*value).to_summary();
+            action.skip(&skipped).act_inplace(value);
+            skipped = if reverse {
+                summary + skipped
+            } else {
+                skipped + summary
+            };
+        }
+    } else {
+        for value in values.iter_mut() {
+            action.act_inplace(value);
+        }
+    }
+}
+
+impl<D: Data, const N: usize> Acts<Chunk<D, N>> for ChunkAction<D::Action> {
+    fn act_inplace(&self, chunk: &mut Chunk<D, N>) {
+        act_values::<D>(&self.0, &mut chunk.values);
+        self.0.act_inplace(&mut chunk.summary);
+    }
+}
+
+impl<A: Acts<S> + Clone, S> Acts<ChunkSummary<S>> for ChunkAction<A> {
+    fn act_inplace(&self, summary: &mut ChunkSummary<S>) {
+        self.0.act_inplace(&mut summary.0);
+    }
+
+    fn skip(&self, prefix: &ChunkSummary<S>) -> Self {
+        ChunkAction(self.0.skip(&prefix.0))
+    }
+}
+
+/// The [`Data`] of the tree of chunks, for the user's [`Data`] `D`.
+pub(super) struct ChunkData<D, const N: usize>(PhantomData<D>);
+
+impl<D: Data, const N: usize> Data for ChunkData<D, N> {
+    type Value = Chunk<D, N>;
+    type Summary = ChunkSummary<D::Summary>;
+    type Action = ChunkAction<D::Action>;
+}
+
+/// A sequence of values, stored as a tree of chunks of up to `N` values.
+/// See the [module documentation](self).
+///
+/// The summaries have to implement [`SizedSummary`], so that the values can be indexed.
+///```
+/// use grove::collections::ChunkedVec;
+/// use grove::example_data::{PolyNum, ProgressionAction};
+///
+/// type D = (i32, PolyNum<2>, ProgressionAction);
+/// let mut vec: ChunkedVec<D, 4> = (0..10).map(|_| 0).collect();
+/// assert_eq!(vec.num_chunks(), 3);
+///
+/// vec.act_segment(ProgressionAction { add: 1, step: 2 }, 3..7);
+/// vec.insert(5, 100);
+/// assert_eq!(vec.remove(0), 0);
+/// assert_eq!(vec.iter().cloned().collect::<Vec<_>>(), vec![0, 0, 1, 3, 100, 5, 7, 0, 0, 0]);
+/// assert_eq!(vec.segment_summary(1..6).moments[0], 109);
+/// assert_eq!(vec.get(4), Some(100));
+///```
+pub struct ChunkedVec<D: Data, const N: usize = 64> {
+    tree: Treap<ChunkData<D, N>>,
+}
+
+impl<D: Data, const N: usize> Default for ChunkedVec<D, N> {
+    fn default() -> Self {
+        ChunkedVec { tree: Treap::new() }
+    }
+}
+
+impl<D: Data, const N: usize> ChunkedVec<D, N>
+where
+    D::Summary: SizedSummary,
+{
+    /// Creates a new empty sequence.
+    pub fn new() -> Self {
+        assert!(N > 0, "chunks must be able to hold values");
+        Self::default()
+    }
+
+    /// Returns the length of the sequence.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size()
+    }
+
+    /// Returns `true` if the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of chunks that the sequence is stored as,
+    /// which is the number of nodes in the tree.
+    pub fn num_chunks(&mut self) -> usize {
+        self.tree.iter().count()
+    }
+
+    /// Iterates over the values of the sequence, in order.
+    pub fn iter(&mut self) -> impl Iterator<Item = &D::Value> {
+        self.tree.iter().flat_map(|chunk| chunk.values.iter())
+    }
+
+    /// Returns the value at the given index, or [`None`] if the index is out of bounds.
+    pub fn get(&mut self, index: usize) -> Option<D::Value>
+    where
+        D::Value: Clone,
+    {
+        let walker = self.tree.search(index);
+        let start = walker.left_summary().size();
+        let chunk = walker.value()?;
+        Some(chunk.values[index - start].clone())
+    }
+
+    /// Sets the value at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: usize, value: D::Value) {
+        let len = self.len();
+        assert!(index < len, "index {} out of bounds ({})", index, len);
+        let mut walker = self.tree.search(index);
+        let start = walker.left_summary().size();
+        walker
+            .with_value(|chunk| {
+                chunk.values[index - start] = value;
+                chunk.update_summary();
+            })
+            .expect("Expected nonempty position");
+    }
+
+    /// Inserts the value at the given index.
+    /// If its chunk is full, it is split in two halves.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: D::Value) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        if len == 0 {
+            self.tree = std::iter::once(Chunk::new(std::iter::once(value).collect())).collect();
+            return;
+        }
+        // the value is inserted into the chunk containing the previous value, if the index
+        // is at the end of the sequence.
+        let mut walker = self.tree.search(index.min(len - 1));
+        let start = walker.left_summary().size();
+        let rest = walker
+            .with_value(|chunk| {
+                let offset = index - start;
+                // the first half of the `N + 1` values stays in the chunk
+                let half = N.div_ceil(2);
+                let rest = if !chunk.values.is_full() {
+                    chunk.values.insert(offset, value);
+                    None
+                } else if offset < half {
+                    let rest = chunk.values.drain(half - 1..).collect();
+                    chunk.values.insert(offset, value);
+                    Some(rest)
+                } else {
+                    let mut rest: ArrayVec<_, N> = chunk.values.drain(half..).collect();
+                    rest.insert(offset - half, value);
+                    Some(rest)
+                };
+                chunk.update_summary();
+                rest
+            })
+            .expect("Expected nonempty position");
+        if let Some(rest) = rest {
+            walker.next_empty().expect("Expected nonempty position");
+            walker
+                .insert(Chunk::new(rest))
+                .expect("Expected empty position");
+        }
+    }
+
+    /// Inserts the value at the end of the sequence.
+    pub fn push(&mut self, value: D::Value) {
+        self.insert(self.len(), value);
+    }
+
+    /// Removes the value at the given index, and returns it.
+    /// If its chunk becomes empty, the chunk is removed.
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> D::Value {
+        let len = self.len();
+        assert!(index < len, "index {} out of bounds ({})", index, len);
+        let mut walker = self.tree.search(index);
+        let start = walker.left_summary().size();
+        let (value, is_empty) = walker
+            .with_value(|chunk| {
+                let value = chunk.values.remove(index - start);
+                chunk.update_summary();
+                (value, chunk.values.is_empty())
+            })
+            .expect("Expected nonempty position");
+        if is_empty {
+            walker.delete();
+        }
+        value
+    }
+
+    /// Applies the action to every value in the given range of indices.
+    /// The chunks at the ends of the range are split, if the range's ends fall inside them.
+    /// Panics if the range is out of bounds.
+    pub fn act_segment(&mut self, action: D::Action, range: Range<usize>) {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        if range.start == range.end {
+            return;
+        }
+        self.tree.split_value_at(range.start);
+        self.tree.split_value_at(range.end);
+        self.tree.act_segment(ChunkAction(action), range);
+    }
+
+    /// Returns the summary of the values in the given range of indices.
+    /// The chunks at the ends of the range are evaluated value by value, without splitting them.
+    /// Panics if the range is out of bounds.
+    pub fn segment_summary(&mut self, range: Range<usize>) -> D::Summary {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        if range.start == range.end {
+            return D::Summary::default();
+        }
+        let (first_end, first) = {
+            let walker = self.tree.search(range.start);
+            let start = walker.left_summary().size();
+            let chunk = walker.value().expect("Expected nonempty position");
+            let end = start + chunk.values.len();
+            if range.end <= end {
+                return summarize::<D>(&chunk.values[range.start - start..range.end - start]);
+            }
+            (end, summarize::<D>(&chunk.values[range.start - start..]))
+        };
+        let (last_start, last) = {
+            let walker = self.tree.search(range.end - 1);
+            let start = walker.left_summary().size();
+            let chunk = walker.value().expect("Expected nonempty position");
+            (start, summarize::<D>(&chunk.values[..range.end - start]))
+        };
+        let middle = self.tree.segment_summary(first_end..last_start).0;
+        first + middle + last
+    }
+}
+
+impl<D: Data, const N: usize> std::iter::FromIterator<D::Value> for ChunkedVec<D, N>
+where
+    D::Summary: SizedSummary,
+{
+    /// Creates a sequence holding the values, in full chunks of `N` values.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        assert!(N > 0, "chunks must be able to hold values");
+        let mut chunks = vec![];
+        let mut values = ArrayVec::new();
+        for value in iter {
+            values.push(value);
+            if values.is_full() {
+                chunks.push(Chunk::new(std::mem::take(&mut values)));
+            }
+        }
+        if !values.is_empty() {
+            chunks.push(Chunk::new(values));
+        }
+        ChunkedVec {
+            tree: chunks.into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,514 @@
+//! Drop-in replacements for standard collections, for migrating existing code to Grove.
+//!
+//! The [`BTreeMap`] here has the same methods as [`std::collections::BTreeMap`],
+//! taking the same kinds of references, so code can switch to it by changing an import.
+//! It also has [`BTreeMap::index_of`] and [`BTreeMap::get_index`], which take `O(log n)` time.
+//! Code that needs summaries and actions over ranges of keys can then move on to
+//! [`OrchardMap`](super::OrchardMap).
+//!```
+//! use grove::collections::compat::BTreeMap;
+//!
+//! let mut counts: BTreeMap<&str, i32> = BTreeMap::new();
+//! for word in "the cat and the hat and the bat".split(' ') {
+//!     *counts.entry(word).or_insert(0) += 1;
+//! }
+//! assert_eq!(counts.get("the"), Some(&3));
+//! assert_eq!(counts.keys().cloned().collect::<Vec<_>>(), vec!["and", "bat", "cat", "hat", "the"]);
+//!
+//! counts.retain(|_, count| *count > 1);
+//! assert_eq!(counts.range("b".."z").collect::<Vec<_>>(), vec![(&"the", &3)]);
+//! assert_eq!(counts.index_of("the"), Some(1));
+//!```
+
+use super::TreeVec;
+use crate::locators::LocResult;
+use crate::trees::snapshot;
+use crate::*;
+use example_data::Size;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+
+/// A sorted map with the interface of [`std::collections::BTreeMap`],
+/// stored as a [`TreeVec`] of its entries, sorted by key.
+/// See the [module documentation](self).
+///
+/// Unlike the standard map, the entries can also be accessed by their index in sorted order,
+/// in `O(log n)` time.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BTreeMap<K, V> {
+    entries: TreeVec<(K, V)>,
+}
+
+impl<K, V> BTreeMap<K, V> {
+    /// Creates a new empty map.
+    pub fn new() -> Self {
+        BTreeMap {
+            entries: TreeVec::new(),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all of the entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the entry at the given index in sorted order, or [`None`] if it is out of bounds.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+
+    /// Returns the first entry, with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// Returns the last entry, with the biggest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.get_index(self.len().checked_sub(1)?)
+    }
+
+    /// Removes the first entry, with the smallest key, and returns it.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.entries.remove(0))
+    }
+
+    /// Removes the last entry, with the biggest key, and returns it.
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.entries.pop()
+    }
+
+    /// Iterates over the entries, in sorted order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Iterates over the entries, with mutable references to the values, in sorted order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.entries.iter_mut().map(|(key, value)| (&*key, value))
+    }
+
+    /// Iterates over the keys, in sorted order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterates over the values, in the sorted order of their keys.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Iterates over mutable references to the values, in the sorted order of their keys.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Returns the keys, in sorted order.
+    pub fn into_keys(self) -> impl Iterator<Item = K> {
+        self.into_iter().map(|(key, _)| key)
+    }
+
+    /// Returns the values, in the sorted order of their keys.
+    pub fn into_values(self) -> impl Iterator<Item = V> {
+        self.into_iter().map(|(_, value)| value)
+    }
+
+    /// Keeps only the entries for which `f` returns `true`, in `O(n)` time.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let entries = std::mem::take(&mut self.entries);
+        self.entries = entries
+            .into_iter()
+            .filter_map(|(key, mut value)| f(&key, &mut value).then_some((key, value)))
+            .collect();
+    }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+    /// Returns the index of the first entry whose key isn't smaller than the given key,
+    /// and whether its key is equal to the given key.
+    fn search<Q>(&self, key: &Q) -> (usize, bool)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // there are no actions, so the entries can be read without pushing anything down
+        let mut tree = self.entries.tree().inner();
+        let mut index = 0;
+        let mut found = false;
+        while let Some(node) = tree.node() {
+            match key.cmp(node.node_value.0.borrow()) {
+                Ordering::Greater => {
+                    index += node.left.subtree_summary().size + 1;
+                    tree = &node.right;
+                }
+                ordering => {
+                    found |= ordering == Ordering::Equal;
+                    tree = &node.left;
+                }
+            }
+        }
+        (index, found)
+    }
+
+    /// Returns the index of the key in sorted order, if it is in the map.
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.search(key) {
+            (index, true) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    /// Returns the key-value pair corresponding to the key.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_index(self.index_of(key)?)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.index_of(key)?;
+        self.entries.get_mut(index).map(|(_, value)| value)
+    }
+
+    /// Returns `true` if the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).1
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// If the map already had this key, its value is replaced, and the old value is returned.
+    /// The key itself isn't replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// Removes a key from the map, and returns its value if it was in the map.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the map, and returns the key-value pair if it was in the map.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.index_of(key)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Returns the entry of the key, for inserting or modifying its value in place.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.search(&key) {
+            (index, true) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            (index, false) => Entry::Vacant(VacantEntry {
+                map: self,
+                index,
+                key,
+            }),
+        }
+    }
+
+    /// Iterates over the entries whose keys are in the given range, in sorted order.
+    pub fn range<Q, R>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let tree = self.entries.tree().inner();
+        let locator = |_: Size, (key, _): &(K, V), _: Size| {
+            let is_before = match range.start_bound() {
+                Bound::Included(start) => key.borrow() < start,
+                Bound::Excluded(start) => key.borrow() <= start,
+                Bound::Unbounded => false,
+            };
+            let is_after = match range.end_bound() {
+                Bound::Included(end) => key.borrow() > end,
+                Bound::Excluded(end) => key.borrow() >= end,
+                Bound::Unbounded => false,
+            };
+            if is_before {
+                LocResult::GoRight
+            } else if is_after {
+                LocResult::GoLeft
+            } else {
+                LocResult::Accept
+            }
+        };
+        snapshot::Iter::segment(tree, locator).map(|(key, value)| (key, value))
+    }
+
+    /// Moves all of the entries of `other` into this map, leaving `other` empty.
+    /// Entries of `other` replace the entries of this map with the same keys.
+    pub fn append(&mut self, other: &mut Self) {
+        for (key, value) in std::mem::take(other) {
+            self.insert(key, value);
+        }
+    }
+
+    /// Splits the map in two at the given key.
+    /// Returns the entries whose keys are at least the given key, as a new map.
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (index, _) = self.search(key);
+        BTreeMap {
+            entries: self.entries.split_off(index),
+        }
+    }
+}
+
+/// An entry in a [`BTreeMap`], which might be vacant. Created by [`BTreeMap::entry`].
+pub enum Entry<'a, K, V> {
+    /// An entry whose key is in the map.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// An entry whose key isn't in the map.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// An entry of a [`BTreeMap`], whose key is in the map.
+pub struct OccupiedEntry<'a, K, V> {
+    map: &'a mut BTreeMap<K, V>,
+    index: usize,
+}
+
+/// An entry of a [`BTreeMap`], whose key isn't in the map.
+pub struct VacantEntry<'a, K, V> {
+    map: &'a mut BTreeMap<K, V>,
+    // The index where the key should be inserted.
+    index: usize,
+    key: K,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the value if the entry is vacant, and returns a mutable reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Inserts the result of `default` on the key if the entry is vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Inserts the default value if the entry is vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Applies `f` to the value if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    fn entry(&self) -> &(K, V) {
+        self.map.entries.get(self.index).expect("Expected an entry")
+    }
+
+    fn entry_mut(&mut self) -> &mut (K, V) {
+        self.map
+            .entries
+            .get_mut(self.index)
+            .expect("Expected an entry")
+    }
+
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        &self.entry().0
+    }
+
+    /// Returns a reference to the entry's value.
+    pub fn get(&self) -> &V {
+        &self.entry().1
+    }
+
+    /// Returns a mutable reference to the entry's value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.entry_mut().1
+    }
+
+    /// Returns a mutable reference to the entry's value, that lives as long as the map's borrow.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self
+            .map
+            .entries
+            .get_mut(self.index)
+            .expect("Expected an entry")
+            .1
+    }
+
+    /// Replaces the entry's value, and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map, and returns its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry from the map, and returns its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.entries.remove(self.index)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the entry's key, without inserting it.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the key with the value, and returns a mutable reference to the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.entries.insert(self.index, (self.key, value));
+        &mut self
+            .map
+            .entries
+            .get_mut(self.index)
+            .expect("Expected an entry")
+            .1
+    }
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> std::iter::FromIterator<(K, V)> for BTreeMap<K, V> {
+    /// Later values replace earlier values with the same key.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BTreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for BTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = <TreeVec<(K, V)> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K, Q, V> std::ops::Index<&Q> for BTreeMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in the map")
+    }
+}
+
+impl<K: std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for BTreeMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
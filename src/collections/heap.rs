@@ -0,0 +1,138 @@
+//! A mergeable priority queue, similar to [`std::collections::BinaryHeap`], which can also
+//! meld two heaps together.
+
+use crate::trees::basic_tree::BasicTree;
+use crate::trees::treap::Treap;
+use crate::*;
+use example_data::SizeData;
+
+/// A double-ended priority queue of values of type `T`, stored as a sorted [`Treap`].
+///
+/// Pushing and popping take `O(log n)` expected time, and peeking at the minimum or the maximum
+/// takes `O(log n)` time through a shared reference.
+/// Unlike a [`std::collections::BinaryHeap`], two heaps can be melded efficiently,
+/// using [`Treap::union`]: melding heaps of sizes `m <= n` takes `O(m log(n/m + 1))` expected time,
+/// which is `O(log n)` when one of the heaps is small, and never more than `O(m log n)`.
+/// Equal values are kept, like in a multiset.
+///```
+/// use grove::collections::TreeHeap;
+///
+/// let mut heap: TreeHeap<i32> = vec![5, 1, 8].into_iter().collect();
+/// heap.push(3);
+/// let other: TreeHeap<i32> = vec![7, 1, 2].into_iter().collect();
+/// heap.meld(other);
+///
+/// assert_eq!(heap.len(), 7);
+/// assert_eq!(heap.peek_min(), Some(&1));
+/// assert_eq!(heap.pop_max(), Some(8));
+/// let popped: Vec<_> = std::iter::from_fn(|| heap.pop_min()).collect();
+/// assert_eq!(popped, vec![1, 1, 2, 3, 5, 7]);
+///```
+pub struct TreeHeap<T> {
+    tree: Treap<SizeData<T>>,
+}
+
+impl<T: Ord> TreeHeap<T> {
+    /// Creates a new empty heap.
+    pub fn new() -> Self {
+        TreeHeap { tree: Treap::new() }
+    }
+
+    /// Returns the number of values in the heap.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size
+    }
+
+    /// Returns `true` if the heap has no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts a value into the heap.
+    pub fn push(&mut self, value: T) {
+        self.tree.insert_sorted(value);
+    }
+
+    /// Returns the minimal value, or [`None`] if the heap is empty.
+    pub fn peek_min(&self) -> Option<&T> {
+        Self::peek(self.tree.inner(), Side::Left)
+    }
+
+    /// Returns the maximal value, or [`None`] if the heap is empty.
+    pub fn peek_max(&self) -> Option<&T> {
+        Self::peek(self.tree.inner(), Side::Right)
+    }
+
+    // Returns the value at the far end of the given side.
+    fn peek(mut tree: &BasicTree<SizeData<T>, u64>, side: Side) -> Option<&T> {
+        // there are no actions, so the values can be read without pushing anything down
+        let mut res = None;
+        while let Some(node) = tree.node() {
+            res = Some(&node.node_value);
+            tree = match side {
+                Side::Left => &node.left,
+                Side::Right => &node.right,
+            };
+        }
+        res
+    }
+
+    /// Removes the minimal value and returns it, or [`None`] if the heap is empty.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.tree.slice(0..1).delete()
+    }
+
+    /// Removes the maximal value and returns it, or [`None`] if the heap is empty.
+    pub fn pop_max(&mut self) -> Option<T> {
+        let len = self.len();
+        self.tree.slice(len.checked_sub(1)?..len).delete()
+    }
+
+    /// Moves all of the values of the other heap into this heap.
+    /// Takes `O(m log(n/m + 1))` expected time, where `m` is the size of the smaller heap.
+    pub fn meld(&mut self, other: TreeHeap<T>) {
+        self.tree.union(other.tree);
+    }
+
+    /// Iterates over the values of the heap, in sorted order.
+    pub fn iter(&mut self) -> impl DoubleEndedIterator<Item = &T> {
+        self.tree.iter()
+    }
+
+    /// Returns a reference to the underlying tree.
+    pub fn tree(&self) -> &Treap<SizeData<T>> {
+        &self.tree
+    }
+}
+
+impl<T: Ord> Default for TreeHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> std::iter::FromIterator<T> for TreeHeap<T> {
+    /// Creates a heap holding the values, in `O(n log n)` time.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut values: Vec<T> = iter.into_iter().collect();
+        values.sort();
+        TreeHeap {
+            tree: values.into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for TreeHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.meld(iter.into_iter().collect());
+    }
+}
+
+impl<T: Ord> IntoIterator for TreeHeap<T> {
+    type Item = T;
+    type IntoIter = <Treap<SizeData<T>> as IntoIterator>::IntoIter;
+    /// Iterates over the values of the heap, in sorted order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
@@ -0,0 +1,208 @@
+//! A sorted map, similar to [`std::collections::BTreeMap`], which additionally supports
+//! summaries and actions over ranges of keys.
+
+use crate::locators::ByKey;
+use crate::trees::treap::Treap;
+use crate::*;
+use example_data::PlainData;
+use std::marker::PhantomData;
+
+/// A sorted map from keys of type `K` to values of type `V`, which is stored in a tree
+/// of type `T` with the [`Data`] instance `D`. By default, the map uses a [`Treap`] without
+/// summaries or actions.
+///
+/// The tree stores `(K, V)` pairs, sorted by key, with no repeated keys.
+/// By choosing `D`, you can query summaries of the pairs in a range of keys, and apply
+/// actions on them. The actions mustn't change the keys.
+///
+/// Since some trees restructure themselves on every access, most methods take `&mut self`.
+///```
+/// use grove::SomeTree;
+/// use grove::collections::OrchardMap;
+///
+/// let mut map: OrchardMap<i32, &str> = OrchardMap::new();
+/// assert_eq!(map.insert(3, "three"), None);
+/// assert_eq!(map.insert(1, "one"), None);
+/// assert_eq!(map.insert(3, "drei"), Some("three"));
+///
+/// assert_eq!(map.get(&3), Some(&"drei"));
+/// assert_eq!(map.get(&2), None);
+/// assert_eq!(map.remove(&1), Some("one"));
+/// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&3, &"drei")]);
+/// # map.tree().assert_correctness();
+///```
+///
+/// With summaries and actions:
+///```
+/// use grove::*;
+/// use grove::collections::OrchardMap;
+/// use grove::avl::AVLTree;
+/// use grove::example_data::{Size, Unit};
+///
+/// type D = ((i32, char), Size, Unit);
+/// let mut map: OrchardMap<i32, char, D, AVLTree<D>> =
+///     (0..20).map(|x| (x * 3, 'a')).collect();
+///
+/// // the number of keys in the range `10..30`
+/// assert_eq!(map.segment_summary(&10..&30).size, 6);
+/// # map.tree().assert_correctness();
+///```
+pub struct OrchardMap<K, V, D = PlainData<(K, V)>, T = Treap<D>> {
+    tree: T,
+    phantom: PhantomData<(K, V, D)>,
+}
+
+impl<K, V, D, T> OrchardMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    T: SomeTree<D>,
+{
+    /// Creates a new empty map.
+    pub fn new() -> Self {
+        OrchardMap {
+            tree: T::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the map has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// If the map already had this key, its value is replaced, and the old value is returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut walker = self.tree.search(ByKey((&key,)));
+        if walker.is_empty() {
+            walker
+                .insert((key, value))
+                .expect("Expected empty position");
+            None
+        } else {
+            walker.with_value(|pair| std::mem::replace(&mut pair.1, value))
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.tree.get_by_key(key).map(|(_, value)| value)
+    }
+
+    /// Applies `f` to the value corresponding to the key, and returns its result.
+    /// Returns [`None`] if the key isn't in the map.
+    ///
+    /// This method receives a function instead of returning a mutable reference, because
+    /// the summaries have to be recomputed after the value is modified.
+    pub fn get_mut<F, R>(&mut self, key: &K, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut V) -> R,
+    {
+        let mut walker = self.tree.search(ByKey((key,)));
+        walker.with_value(|pair| f(&mut pair.1))
+    }
+
+    /// Returns `true` if the map contains the key.
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.tree.contains_key(key)
+    }
+
+    /// Removes a key from the map, and returns its value if it was in the map.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.tree.remove_by_key(key).map(|(_, value)| value)
+    }
+
+    /// Iterates over the entries of the map whose keys are in the given range, in sorted order.
+    /// The range should be a range of references to keys, e.g., `&3..&7`.
+    ///```
+    /// use grove::collections::OrchardMap;
+    ///
+    /// let mut map: OrchardMap<i32, i32> = (0..10).map(|x| (x, x * x)).collect();
+    /// let values: Vec<i32> = map.range(&3..=&5).map(|(_, value)| *value).collect();
+    /// assert_eq!(values, vec![9, 16, 25]);
+    ///```
+    pub fn range<R>(&mut self, range: R) -> impl DoubleEndedIterator<Item = (&K, &V)>
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree
+            .iter_locator(ByKey(range))
+            .map(|(key, value)| (key, value))
+    }
+
+    /// Iterates over the entries of the map, in sorted order.
+    pub fn iter(&mut self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.tree.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Returns the summary of all the entries whose keys are in the given range.
+    /// The range should be a range of references to keys, e.g., `&3..&7`.
+    pub fn segment_summary<R>(&mut self, range: R) -> D::Summary
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.segment_summary(ByKey(range))
+    }
+
+    /// Applies an action on all the entries whose keys are in the given range.
+    /// The range should be a range of references to keys, e.g., `&3..&7`.
+    ///
+    /// The action must not change the keys, or reverse the segment.
+    pub fn act_segment<R>(&mut self, action: D::Action, range: R)
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.act_segment(action, ByKey(range))
+    }
+
+    /// Returns a reference to the underlying tree.
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the underlying tree.
+    pub fn into_tree(self) -> T {
+        self.tree
+    }
+}
+
+impl<K, V, D, T> Default for OrchardMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    T: SomeTree<D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, D, T> std::iter::FromIterator<(K, V)> for OrchardMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    T: SomeTree<D>,
+{
+    /// Later values replace earlier values with the same key.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<K, V, D, T> IntoIterator for OrchardMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    T: SomeTree<D>,
+{
+    type Item = (K, V);
+    type IntoIter = T::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
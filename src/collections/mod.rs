@@ -0,0 +1,36 @@
+//! Collection types built on top of the trees, with a more familiar interface.
+//!
+//! The maps and sets wrap any of the trees in [`crate::trees`], keep their values sorted by key,
+//! and forward the segment operations over key ranges to the underlying tree.
+//! The [`Rope`] stores text in a tree of wide values, for text editing, and the [`OrderList`]
+//! keeps its elements' labels in a tree, for order comparisons in `O(1)` time.
+//! The [`TreeVec`] has the interface of a [`Vec`], for sequences with many insertions and removals,
+//! and the [`TreeHeap`] is a priority queue that can be melded with other heaps.
+//! The [`SparseSegmentTree`] stores a huge sequence as a tree of runs of equal values,
+//! and the [`RleVec`] does the same for sequences that values can be inserted into and removed from.
+//! The [`ChunkedVec`] stores small arrays of values in every node, to compete with a [`Vec`].
+//! The [`SmallTree`] stores short sequences in a vector, and only builds a tree for long ones.
+//! The [`compat`] module has drop-in replacements for the standard collections.
+
+pub mod chunked;
+pub mod compat;
+pub mod heap;
+pub mod map;
+pub mod order;
+pub mod rle;
+pub mod rope;
+pub mod set;
+pub mod small;
+pub mod sparse;
+pub mod tree_vec;
+
+pub use chunked::ChunkedVec;
+pub use heap::TreeHeap;
+pub use map::OrchardMap;
+pub use order::OrderList;
+pub use rle::RleVec;
+pub use rope::Rope;
+pub use set::{OrchardMultiSet, OrchardSet};
+pub use small::SmallTree;
+pub use sparse::SparseSegmentTree;
+pub use tree_vec::TreeVec;
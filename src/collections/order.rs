@@ -0,0 +1,246 @@
+//! An order-maintenance list: a list of elements, whose order can be compared in `O(1)` time.
+//!
+//! Every element of an [`OrderList`] is represented by a [`Handle`], which holds an integer label.
+//! The labels increase along the list, so comparing two handles only compares their labels.
+//! When there is no free label for a new element, the labels of a small window of elements
+//! around it are spread out evenly. The window is the smallest aligned range of labels
+//! that is sparse enough, as in the algorithm of Bender et al. ("Two simplified algorithms
+//! for maintaining order in a list"). Finding and counting the elements in a window uses
+//! the list's tree, which is sorted by labels and summarizes sizes.
+
+use crate::locators::LocResult;
+use crate::trees::treap::Treap;
+use crate::*;
+use example_data::{Size, SizeData};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Controls how sparse a window of labels needs to be in order to be relabeled:
+/// a window of `2^i` labels is relabeled only if it holds at most `(2 / DENSITY)^i` elements.
+/// Must be strictly between `1` and `2`. Smaller values relabel fewer elements at a time,
+/// but support fewer elements in total.
+const DENSITY: f64 = 1.3;
+
+/// A handle to an element of an [`OrderList`].
+///
+/// Handles are compared by their position in the list, in `O(1)` time.
+/// Comparing handles of different lists, or of elements that were removed, gives meaningless results.
+#[derive(Clone)]
+pub struct Handle {
+    label: Rc<Cell<u64>>,
+}
+
+impl Handle {
+    fn new(label: u64) -> Handle {
+        Handle {
+            label: Rc::new(Cell::new(label)),
+        }
+    }
+
+    fn label(&self) -> u64 {
+        self.label.get()
+    }
+}
+
+impl PartialEq for Handle {
+    fn eq(&self, other: &Handle) -> bool {
+        self.label() == other.label()
+    }
+}
+
+impl Eq for Handle {}
+
+impl PartialOrd for Handle {
+    fn partial_cmp(&self, other: &Handle) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Handle {
+    fn cmp(&self, other: &Handle) -> std::cmp::Ordering {
+        self.label().cmp(&other.label())
+    }
+}
+
+impl std::fmt::Debug for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Handle").field(&self.label()).finish()
+    }
+}
+
+/// Locates the element with the given label.
+fn at_label(label: u64) -> impl Locator<SizeData<Handle>> {
+    move |_left: Size, handle: &Handle, _right: Size| match handle.label().cmp(&label) {
+        std::cmp::Ordering::Less => LocResult::GoRight,
+        std::cmp::Ordering::Equal => LocResult::Accept,
+        std::cmp::Ordering::Greater => LocResult::GoLeft,
+    }
+}
+
+/// Locates the elements with labels in the range `start..end`.
+/// The range is given in `u128`, so that it can contain all of the labels.
+fn in_labels(start: u128, end: u128) -> impl Locator<SizeData<Handle>> {
+    move |_left: Size, handle: &Handle, _right: Size| {
+        let label = handle.label() as u128;
+        if label < start {
+            LocResult::GoRight
+        } else if label >= end {
+            LocResult::GoLeft
+        } else {
+            LocResult::Accept
+        }
+    }
+}
+
+/// A list of elements, represented by [`Handle`]s that can be compared by their
+/// order in the list in `O(1)` time. See the [module documentation](self).
+///
+/// Insertions and removals take `O(log n)` time, and relabeling takes an additional
+/// `O(log^2 n)` amortized time per insertion.
+///```
+/// use grove::collections::OrderList;
+///
+/// let mut list = OrderList::new();
+/// let b = list.push_back();
+/// let d = list.push_back();
+/// let a = list.insert_before(&b);
+/// let c = list.insert_after(&b);
+/// assert!(a < b && b < c && c < d);
+///
+/// list.remove(&c);
+/// let e = list.push_front();
+/// assert!(e < a && a < d);
+/// assert_eq!(list.len(), 4);
+/// # list.assert_correctness();
+///```
+pub struct OrderList {
+    // Sorted by labels. Always starts with a hidden sentinel element with the label `0`,
+    // so every new element is inserted after an existing element.
+    tree: Treap<SizeData<Handle>>,
+    sentinel: Handle,
+}
+
+impl OrderList {
+    /// Creates a new empty list.
+    pub fn new() -> Self {
+        let sentinel = Handle::new(0);
+        OrderList {
+            tree: std::iter::once(sentinel.clone()).collect(),
+            sentinel,
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size - 1
+    }
+
+    /// Returns `true` if the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts a new element at the start of the list.
+    pub fn push_front(&mut self) -> Handle {
+        let sentinel = self.sentinel.clone();
+        self.insert_after(&sentinel)
+    }
+
+    /// Inserts a new element at the end of the list.
+    pub fn push_back(&mut self) -> Handle {
+        let len = self.tree.subtree_summary().size;
+        let last = self.tree.search(len - 1).value().unwrap().clone();
+        self.insert_after(&last)
+    }
+
+    /// Inserts a new element right before the given element.
+    /// Panics if the handle isn't in the list.
+    pub fn insert_before(&mut self, handle: &Handle) -> Handle {
+        let mut walker = self.tree.search(at_label(handle.label()));
+        assert!(!walker.is_empty(), "Handle isn't in the list");
+        walker.previous_filled().expect("Handle isn't in the list");
+        let previous = walker.value().unwrap().clone();
+        drop(walker);
+        self.insert_after(&previous)
+    }
+
+    /// Inserts a new element right after the given element.
+    /// Panics if the handle isn't in the list.
+    pub fn insert_after(&mut self, handle: &Handle) -> Handle {
+        let label = handle.label();
+        let mut walker = self.tree.search(at_label(label));
+        assert!(!walker.is_empty(), "Handle isn't in the list");
+        let next = match walker.next_filled() {
+            Ok(()) => walker.value().unwrap().label() as u128,
+            Err(_) => 1 << 64,
+        };
+        drop(walker);
+
+        let new_label = if next - label as u128 > 1 {
+            (label as u128 + (next - label as u128) / 2) as u64
+        } else {
+            self.relabel_around(label)
+        };
+        let new = Handle::new(new_label);
+        let mut walker = self.tree.search(at_label(new_label));
+        walker.insert(new.clone()).expect("Expected empty position");
+        new
+    }
+
+    /// Spreads out the labels of the smallest sparse enough window around the element with
+    /// the given label, leaving a free label right after it. Returns the free label.
+    fn relabel_around(&mut self, label: u64) -> u64 {
+        for i in 1..=64 {
+            let start = (label as u128 >> i) << i;
+            let end = start + (1 << i);
+            let count = self.tree.segment_summary(in_labels(start, end)).size;
+            if (count + 1) as f64 > (2.0 / DENSITY).powi(i) {
+                continue;
+            }
+            // the new element takes the place right after the element with the given label
+            let gap = (end - start) / (count as u128 + 1);
+            let mut new_label = None;
+            let mut next = start;
+            for handle in self.tree.iter_locator(in_labels(start, end)) {
+                let old = handle.label();
+                handle.label.set(next as u64);
+                next += gap;
+                if old == label {
+                    new_label = Some(next as u64);
+                    next += gap;
+                }
+            }
+            return new_label.expect("Expected the element to be in the window");
+        }
+        panic!("Too many elements in the list");
+    }
+
+    /// Removes the element from the list. Its handle mustn't be used with this list anymore.
+    /// Panics if the handle isn't in the list.
+    pub fn remove(&mut self, handle: &Handle) {
+        assert!(handle.label() != 0, "Handle isn't in the list");
+        self.tree
+            .search(at_label(handle.label()))
+            .delete()
+            .expect("Handle isn't in the list");
+    }
+
+    /// Iterates over the handles of the elements, in order.
+    pub fn iter(&mut self) -> impl Iterator<Item = &Handle> {
+        self.tree.iter().skip(1)
+    }
+
+    /// Checks that the labels are strictly increasing, and that the underlying tree is correct.
+    pub fn assert_correctness(&mut self) {
+        self.tree.assert_correctness();
+        let labels: Vec<u64> = self.tree.iter().map(Handle::label).collect();
+        assert_eq!(labels[0], 0);
+        assert!(labels.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
+
+impl Default for OrderList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,229 @@
+//! A run-length encoded sequence: a sequence stored as a tree of runs of equal values,
+//! for huge sequences with long constant stretches, such as tile maps.
+//!
+//! Every value in the tree is a run, a wide value holding a value and the number of times it repeats.
+//! Unlike the [`SparseSegmentTree`], whose length is fixed,
+//! values and runs can be inserted and removed anywhere.
+//! Runs are split whenever a segment boundary or a single updated index falls inside one,
+//! and adjacent runs of equal values are merged back whenever values are inserted, removed, or set.
+//! Therefore, every operation takes `O(log k)` time, where `k` is the number of runs,
+//! plus `O(log n)` additions of summaries, where `n` is the length of the sequence.
+
+use super::sparse::{segment, Run, SparseSegmentTree};
+use crate::locators::LeftEdgeOf;
+use crate::trees::treap::Treap;
+use crate::*;
+use std::ops::Range;
+
+/// A run-length encoded sequence of values. See the [module documentation](self).
+///
+/// Indices are `u64`s, so that the sequence can be much longer than the memory.
+/// The values have to be comparable, so that adjacent equal runs can be merged.
+///```
+/// use grove::collections::RleVec;
+/// use grove::example_data::{NumSummary, RevAffineAction};
+///
+/// type D = (i64, NumSummary<i64>, RevAffineAction<i64>);
+/// let mut tiles: RleVec<D> = vec![0, 0, 0, 1, 1, 0, 0].into_iter().collect();
+/// assert_eq!(tiles.num_runs(), 3);
+///
+/// // merges with the run of ones before it
+/// tiles.insert_run(5, 1, 1_000_000);
+/// assert_eq!(tiles.num_runs(), 3);
+/// assert_eq!(tiles.len(), 1_000_007);
+/// assert_eq!(tiles.segment_summary(2..1_000_006).sum, 1_000_002);
+///
+/// // splits the run of ones, and then merges it back
+/// tiles.set(100, 0);
+/// assert_eq!(tiles.num_runs(), 5);
+/// tiles.set(100, 1);
+/// assert_eq!(tiles.num_runs(), 3);
+///
+/// tiles.remove(2..1_000_006);
+/// assert_eq!(tiles.num_runs(), 1);
+/// tiles.act_segment(RevAffineAction { to_reverse: false, mul: 1, add: 2 }, 1..2);
+/// let runs: Vec<(i64, u64)> = tiles.runs().map(|(value, count)| (*value, count)).collect();
+/// assert_eq!(runs, vec![(0, 1), (2, 1), (0, 1)]);
+///```
+pub struct RleVec<D: Data> {
+    runs: SparseSegmentTree<D>,
+}
+
+impl<D: Data> Default for RleVec<D> {
+    fn default() -> Self {
+        RleVec {
+            runs: SparseSegmentTree { tree: Treap::new() },
+        }
+    }
+}
+
+impl<D: Data> RleVec<D>
+where
+    D::Value: Clone + PartialEq,
+{
+    /// Creates a new empty sequence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the length of the sequence.
+    pub fn len(&self) -> u64 {
+        self.runs.len()
+    }
+
+    /// Returns `true` if the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Returns the number of runs of equal values that the sequence is stored as,
+    /// which is the number of nodes in the tree.
+    pub fn num_runs(&self) -> usize {
+        self.runs.num_runs()
+    }
+
+    /// Iterates over the runs of the sequence, in order.
+    /// Every run is yielded as its value and the number of times it repeats.
+    pub fn runs(&mut self) -> impl Iterator<Item = (&D::Value, u64)> {
+        self.runs.tree.iter().map(|run| (&run.value, run.count))
+    }
+
+    /// Returns the value at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn get(&mut self, index: u64) -> D::Value {
+        self.runs.get(index)
+    }
+
+    /// Inserts `count` copies of the value, so that the first one of them ends up at index `index`.
+    /// Panics if `index > len`.
+    pub fn insert_run(&mut self, index: u64, value: D::Value, count: u64) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        if count == 0 {
+            return;
+        }
+        self.runs.split_at(index);
+        self.runs
+            .tree
+            .slice(LeftEdgeOf(segment::<D>(index..index + 1)))
+            .insert(Run::new(value, count))
+            .expect("Expected empty position");
+        self.merge_at(index + count);
+        self.merge_at(index);
+    }
+
+    /// Inserts the value at the given index.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: u64, value: D::Value) {
+        self.insert_run(index, value, 1);
+    }
+
+    /// Removes the values in the given range of indices.
+    /// Panics if the range is out of bounds.
+    pub fn remove(&mut self, range: Range<u64>) {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        if range.start == range.end {
+            return;
+        }
+        self.runs.split_at(range.start);
+        self.runs.split_at(range.end);
+        self.runs.tree.delete_segment(segment::<D>(range.clone()));
+        self.merge_at(range.start);
+    }
+
+    /// Sets the value at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: u64, value: D::Value) {
+        self.runs.set(index, value);
+        self.merge_at(index + 1);
+        self.merge_at(index);
+    }
+
+    /// Applies the action to every value in the given range of indices.
+    /// The runs at the ends of the range are merged with their neighbors if they become equal,
+    /// but runs inside the range aren't merged with each other.
+    /// Panics if the range is out of bounds, or if the action depends on the index.
+    pub fn act_segment(&mut self, action: D::Action, range: Range<u64>) {
+        self.runs.act_segment(action, range.clone());
+        self.merge_at(range.end);
+        self.merge_at(range.start);
+    }
+
+    /// Returns the summary of the values in the given range of indices.
+    /// Panics if the range is out of bounds.
+    pub fn segment_summary(&mut self, range: Range<u64>) -> D::Summary {
+        self.runs.segment_summary(range)
+    }
+
+    /// Merges the runs on both sides of the given index, if there is a boundary between runs
+    /// at the index, and their values are equal.
+    fn merge_at(&mut self, index: u64) {
+        if index == 0 || index >= self.len() {
+            return;
+        }
+        let tree = &mut self.runs.tree;
+        let right_value = {
+            let walker = tree.search(segment::<D>(index..index + 1));
+            if walker.left_summary().len != index {
+                return;
+            }
+            let run = walker.value().expect("Expected nonempty position");
+            run.value.clone()
+        };
+        let equal = tree
+            .search(segment::<D>(index - 1..index))
+            .with_value(|run| run.value == right_value)
+            .expect("Expected nonempty position");
+        if !equal {
+            return;
+        }
+        let right = tree
+            .slice(segment::<D>(index..index + 1))
+            .delete()
+            .expect("Expected nonempty position");
+        tree.search(segment::<D>(index - 1..index))
+            .with_value(|run| {
+                run.count += right.count;
+                run.summary = std::mem::take(&mut run.summary) + right.summary;
+            })
+            .expect("Expected nonempty position");
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for RleVec<D>
+where
+    D::Value: Clone + PartialEq,
+{
+    /// Creates a sequence holding the values, merging adjacent equal values into runs.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut runs = vec![];
+        let mut current: Option<(D::Value, u64)> = None;
+        for value in iter {
+            current = match current {
+                Some((run_value, count)) if run_value == value => Some((run_value, count + 1)),
+                Some((run_value, count)) => {
+                    runs.push(Run::new(run_value, count));
+                    Some((value, 1))
+                }
+                None => Some((value, 1)),
+            };
+        }
+        runs.extend(current.map(|(value, count)| Run::new(value, count)));
+        RleVec {
+            runs: SparseSegmentTree {
+                tree: runs.into_iter().collect(),
+            },
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! A rope: a string stored as a balanced tree of chunks, for efficient text editing.
+//!
+//! Every value in the tree is a [`Chunk`], a wide value holding up to [`MAX_CHUNK`] bytes of text.
+//! The summaries count the bytes, chars and newlines in every subtree, so that the rope can be
+//! indexed by chars, by bytes, or by lines. Inserting, removing, splitting and concatenating
+//! all take `O(log n)` time, plus the length of the inserted text.
+
+use crate::locators::LocResult;
+use crate::trees::treap::Treap;
+use crate::*;
+pub use example_data::TextSummary;
+use example_data::Unit;
+use std::ops::Range;
+
+/// The maximal number of bytes in a [`Chunk`].
+pub const MAX_CHUNK: usize = 256;
+
+/// A piece of the text stored in a [`Rope`], which also caches its own summary.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Chunk {
+    text: String,
+    summary: TextSummary,
+}
+
+impl Chunk {
+    fn new(text: String) -> Chunk {
+        let summary = text.to_summary();
+        Chunk { text, summary }
+    }
+
+    /// Returns the text stored in the chunk.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    // Returns the byte index of the char at the given char index.
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+        self.summary = self.summary + text.to_summary();
+    }
+}
+
+impl ToSummary<TextSummary> for Chunk {
+    fn to_summary(&self) -> TextSummary {
+        self.summary
+    }
+}
+
+/// Splits the chunk by char index.
+impl SplitValue for Chunk {
+    fn split_off(&mut self, index: usize) -> Chunk {
+        let rest = self.text.split_off(self.byte_index(index));
+        *self = Chunk::new(std::mem::take(&mut self.text));
+        Chunk::new(rest)
+    }
+}
+
+/// The [`Data`] instance of the tree stored in a [`Rope`].
+pub type RopeData = (Chunk, TextSummary, Unit);
+
+/// Splits the text into chunks of at most [`MAX_CHUNK`] bytes, at char boundaries.
+fn to_chunks(mut text: &str) -> Vec<Chunk> {
+    let mut chunks = vec![];
+    while !text.is_empty() {
+        let mut end = text.len().min(MAX_CHUNK);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, rest) = text.split_at(end);
+        chunks.push(Chunk::new(chunk.to_string()));
+        text = rest;
+    }
+    chunks
+}
+
+/// A string stored as a balanced tree of chunks. See the [module documentation](self).
+///
+/// Unless stated otherwise, indices are char indices, and lines are numbered from `0`.
+/// Since the tree restructures itself on accesses, queries take `&mut self` as well.
+///```
+/// use grove::SomeTree;
+/// use grove::collections::Rope;
+///
+/// let mut rope = Rope::from("hello\nworld");
+/// rope.insert(5, ", dear");
+/// rope.insert_at_byte(rope.len_bytes(), "\n¡adiós!");
+/// assert_eq!(rope.len_lines(), 3);
+/// assert_eq!(rope.line(1), "world\n");
+/// assert_eq!(rope.char_to_byte(rope.len_chars()), rope.len_bytes());
+///
+/// rope.remove(0..7);
+/// let second_line = rope.line_to_char(1);
+/// let tail = rope.split_off(second_line);
+/// assert_eq!(String::from(rope.clone()), "dear\n");
+/// rope.append(tail);
+/// assert_eq!(rope.slice(5..), "world\n¡adiós!");
+/// # rope.tree().assert_correctness();
+///```
+#[derive(Clone, Default)]
+pub struct Rope {
+    tree: Treap<RopeData>,
+}
+
+impl Rope {
+    /// Creates a new empty rope.
+    pub fn new() -> Self {
+        Rope { tree: Treap::new() }
+    }
+
+    /// Returns `true` if the rope holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the summary of the whole text.
+    pub fn summary(&self) -> TextSummary {
+        self.tree.subtree_summary()
+    }
+
+    /// Returns the length of the text, in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.summary().bytes
+    }
+
+    /// Returns the length of the text, in chars.
+    pub fn len_chars(&self) -> usize {
+        self.summary().chars
+    }
+
+    /// Returns the number of lines in the text, which is one more than the number of newlines.
+    pub fn len_lines(&self) -> usize {
+        self.summary().newlines + 1
+    }
+
+    /// Returns the char index of the given byte index.
+    /// Panics if the byte index is out of bounds, or isn't at a char boundary.
+    pub fn byte_to_char(&mut self, byte_index: usize) -> usize {
+        assert!(
+            byte_index <= self.len_bytes(),
+            "byte index {} out of bounds",
+            byte_index
+        );
+        let locator = move |left: TextSummary, chunk: &Chunk, _right: TextSummary| {
+            if byte_index < left.bytes {
+                LocResult::GoLeft
+            } else if byte_index < left.bytes + chunk.summary.bytes {
+                LocResult::Accept
+            } else {
+                LocResult::GoRight
+            }
+        };
+        let walker = self.tree.search(locator);
+        let left = walker.left_summary();
+        match walker.value() {
+            None => left.chars,
+            Some(chunk) => {
+                let offset = byte_index - left.bytes;
+                assert!(
+                    chunk.text.is_char_boundary(offset),
+                    "byte index {} isn't at a char boundary",
+                    byte_index
+                );
+                left.chars + chunk.text[..offset].chars().count()
+            }
+        }
+    }
+
+    /// Returns the byte index of the given char index.
+    /// Panics if the char index is out of bounds.
+    pub fn char_to_byte(&mut self, char_index: usize) -> usize {
+        assert!(
+            char_index <= self.len_chars(),
+            "char index {} out of bounds",
+            char_index
+        );
+        let walker = self.tree.search(char_index);
+        let left = walker.left_summary();
+        match walker.value() {
+            None => left.bytes,
+            Some(chunk) => left.bytes + chunk.byte_index(char_index - left.chars),
+        }
+    }
+
+    /// Returns the index of the line containing the given char index.
+    /// Panics if the char index is out of bounds.
+    pub fn char_to_line(&mut self, char_index: usize) -> usize {
+        assert!(
+            char_index <= self.len_chars(),
+            "char index {} out of bounds",
+            char_index
+        );
+        let walker = self.tree.search(char_index);
+        let left = walker.left_summary();
+        match walker.value() {
+            None => left.newlines,
+            Some(chunk) => {
+                let offset = chunk.byte_index(char_index - left.chars);
+                left.newlines + chunk.text[..offset].matches('\n').count()
+            }
+        }
+    }
+
+    /// Returns the char index at which the given line starts.
+    /// Panics if the line index is out of bounds.
+    pub fn line_to_char(&mut self, line: usize) -> usize {
+        assert!(line < self.len_lines(), "line {} out of bounds", line);
+        if line == 0 {
+            return 0;
+        }
+        // look for the chunk containing the newline that ends the previous line
+        let locator = move |left: TextSummary, chunk: &Chunk, _right: TextSummary| {
+            if line <= left.newlines {
+                LocResult::GoLeft
+            } else if line <= left.newlines + chunk.summary.newlines {
+                LocResult::Accept
+            } else {
+                LocResult::GoRight
+            }
+        };
+        let walker = self.tree.search(locator);
+        let left = walker.left_summary();
+        let chunk = walker.value().expect("Expected nonempty position");
+        let (offset, _) = chunk
+            .text
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| c == '\n')
+            .nth(line - left.newlines - 1)
+            .expect("Expected the chunk to contain the newline");
+        left.chars + offset + 1
+    }
+
+    /// Returns the text of the given line, including its newline, if it has one.
+    /// Panics if the line index is out of bounds.
+    pub fn line(&mut self, line: usize) -> String {
+        let start = self.line_to_char(line);
+        let end = if line + 1 < self.len_lines() {
+            self.line_to_char(line + 1)
+        } else {
+            self.len_chars()
+        };
+        self.slice(start..end)
+    }
+
+    /// Inserts the text at the given char index.
+    /// Panics if the index is out of bounds.
+    pub fn insert(&mut self, char_index: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let right = self.split_off(char_index);
+        self.append(Rope::from(text));
+        self.append(right);
+    }
+
+    /// Inserts the text at the given byte index.
+    /// Panics if the index is out of bounds, or isn't at a char boundary.
+    pub fn insert_at_byte(&mut self, byte_index: usize, text: &str) {
+        let char_index = self.byte_to_char(byte_index);
+        self.insert(char_index, text);
+    }
+
+    /// Removes the text in the given range of chars.
+    /// Panics if the range is out of bounds.
+    pub fn remove(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end, "invalid range {:?}", range);
+        let right = self.split_off(range.end);
+        self.split_off(range.start);
+        self.append(right);
+    }
+
+    /// Removes the text in the given range of bytes.
+    /// Panics if the range is out of bounds, or its ends aren't at char boundaries.
+    pub fn remove_bytes(&mut self, range: Range<usize>) {
+        let start = self.byte_to_char(range.start);
+        let end = self.byte_to_char(range.end);
+        self.remove(start..end);
+    }
+
+    /// Returns a copy of the text in the given range of chars.
+    /// Panics if the range is out of bounds.
+    pub fn slice<R: std::ops::RangeBounds<usize>>(&mut self, range: R) -> String {
+        use std::ops::Bound;
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len_chars(),
+        };
+        assert!(start <= end, "invalid range {}..{}", start, end);
+        let right = self.split_off(end);
+        let mut middle = self.split_off(start);
+        let text = middle.chunks().collect();
+        self.append(middle);
+        self.append(right);
+        text
+    }
+
+    /// Splits the rope in two at the given char index, and returns the part after it.
+    /// Panics if the index is out of bounds.
+    pub fn split_off(&mut self, char_index: usize) -> Rope {
+        assert!(
+            char_index <= self.len_chars(),
+            "char index {} out of bounds",
+            char_index
+        );
+        self.tree.split_value_at(char_index);
+        let right = self
+            .tree
+            .slice(char_index..char_index)
+            .split_right()
+            .expect("Expected empty position");
+        Rope { tree: right }
+    }
+
+    /// Concatenates the other rope to the end of this rope.
+    ///
+    /// If the chunks at the seam are small enough, they are merged, so that chunks
+    /// don't get too fragmented by repeated edits.
+    pub fn append(&mut self, mut other: Rope) {
+        let len = self.len_chars();
+        if len > 0 && !other.is_empty() {
+            let last_bytes = self.tree.search(len - 1).node_summary().bytes;
+            let mut walker = other.tree.search(0);
+            if last_bytes + walker.node_summary().bytes <= MAX_CHUNK {
+                let first = walker.delete().expect("Expected nonempty position");
+                drop(walker);
+                self.tree
+                    .search(len - 1)
+                    .with_value(|chunk| chunk.push_str(&first.text))
+                    .expect("Expected nonempty position");
+            }
+        }
+        self.tree.concatenate_right(other.tree);
+    }
+
+    /// Iterates over the chunks of the text, in order.
+    pub fn chunks(&mut self) -> impl Iterator<Item = &str> {
+        self.tree.iter().map(Chunk::as_str)
+    }
+
+    /// Iterates over the chars of the text, in order.
+    pub fn chars(&mut self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    /// Returns a reference to the underlying tree.
+    pub fn tree(&self) -> &Treap<RopeData> {
+        &self.tree
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Rope {
+        Rope {
+            tree: to_chunks(text).into_iter().collect(),
+        }
+    }
+}
+
+impl From<Rope> for String {
+    fn from(rope: Rope) -> String {
+        rope.tree.into_iter().map(|chunk| chunk.text).collect()
+    }
+}
+
+impl std::fmt::Debug for Rope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Rope")
+            .field(&String::from(self.clone()))
+            .finish()
+    }
+}
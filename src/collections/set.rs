@@ -0,0 +1,322 @@
+//! Sorted sets and multisets, similar to [`std::collections::BTreeSet`], which additionally
+//! support summaries and actions over ranges of values.
+
+use crate::locators::ByKey;
+use crate::trees::treap::Treap;
+use crate::*;
+use example_data::{PlainData, SizeData};
+use std::marker::PhantomData;
+
+/// A sorted set of values of type `T`, which is stored in a tree of type `Tr` with the
+/// [`Data`] instance `D`. By default, the set uses a [`Treap`] without summaries or actions.
+///
+/// The values are kept sorted, with no repetitions.
+/// By choosing `D`, you can query summaries of the values in a range, and apply actions on them.
+/// The actions mustn't change the order of the values.
+///
+/// Since some trees restructure themselves on every access, most methods take `&mut self`.
+///```
+/// use grove::SomeTree;
+/// use grove::collections::OrchardSet;
+///
+/// let mut set: OrchardSet<i32> = vec![5, 3, 8, 3].into_iter().collect();
+/// assert!(set.insert(4));
+/// assert!(!set.insert(5));
+/// assert!(set.contains(&3));
+/// assert!(set.remove(&3));
+/// assert!(!set.contains(&3));
+/// assert_eq!(set.iter().cloned().collect::<Vec<_>>(), vec![4, 5, 8]);
+/// assert_eq!(set.range(&5..).cloned().collect::<Vec<_>>(), vec![5, 8]);
+/// # set.tree().assert_correctness();
+///```
+pub struct OrchardSet<T, D = PlainData<T>, Tr = Treap<D>> {
+    tree: Tr,
+    phantom: PhantomData<(T, D)>,
+}
+
+impl<T, D, Tr> OrchardSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    /// Creates a new empty set.
+    pub fn new() -> Self {
+        OrchardSet {
+            tree: Tr::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts a value into the set.
+    /// Returns `false` if the value was already in the set, in which case nothing is changed.
+    pub fn insert(&mut self, value: T) -> bool {
+        let mut walker = self.tree.search(ByKey((&value,)));
+        if walker.is_empty() {
+            walker.insert(value).expect("Expected empty position");
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the set contains the value.
+    pub fn contains(&mut self, value: &T) -> bool {
+        self.tree.contains_key(value)
+    }
+
+    /// Removes a value from the set. Returns whether the value was in the set.
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.take(value).is_some()
+    }
+
+    /// Removes a value from the set, and returns it if it was in the set.
+    pub fn take(&mut self, value: &T) -> Option<T> {
+        self.tree.remove_by_key(value)
+    }
+
+    /// Iterates over the values in the given range, in sorted order.
+    /// The range should be a range of references, e.g., `&3..&7`.
+    pub fn range<R>(&mut self, range: R) -> impl DoubleEndedIterator<Item = &T>
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.iter_locator(ByKey(range))
+    }
+
+    /// Iterates over the values of the set, in sorted order.
+    pub fn iter(&mut self) -> impl DoubleEndedIterator<Item = &T> {
+        self.tree.iter()
+    }
+
+    /// Returns the summary of all the values in the given range.
+    /// The range should be a range of references, e.g., `&3..&7`.
+    pub fn segment_summary<R>(&mut self, range: R) -> D::Summary
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.segment_summary(ByKey(range))
+    }
+
+    /// Applies an action on all the values in the given range.
+    /// The range should be a range of references, e.g., `&3..&7`.
+    ///
+    /// The action must not change the order of the values, or reverse the segment.
+    pub fn act_segment<R>(&mut self, action: D::Action, range: R)
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.act_segment(action, ByKey(range))
+    }
+
+    /// Returns a reference to the underlying tree.
+    pub fn tree(&self) -> &Tr {
+        &self.tree
+    }
+
+    /// Returns the underlying tree.
+    pub fn into_tree(self) -> Tr {
+        self.tree
+    }
+}
+
+impl<T, D, Tr> Default for OrchardSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, D, Tr> std::iter::FromIterator<T> for OrchardSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+impl<T, D, Tr> IntoIterator for OrchardSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    type Item = T;
+    type IntoIter = Tr::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
+
+/// A sorted multiset of values of type `T`, which is stored in a tree of type `Tr` with the
+/// [`Data`] instance `D`. By default, the multiset uses a [`Treap`] with size summaries,
+/// which are needed in order to count repetitions.
+///
+/// The values are kept sorted. Equal values are inserted after the existing equal values.
+///```
+/// use grove::SomeTree;
+/// use grove::collections::OrchardMultiSet;
+///
+/// let mut set: OrchardMultiSet<i32> = vec![5, 3, 8, 3].into_iter().collect();
+/// set.insert(5);
+/// assert_eq!(set.count(&3), 2);
+/// assert_eq!(set.count(&5), 2);
+/// assert_eq!(set.count(&4), 0);
+/// assert!(set.remove(&3));
+/// assert_eq!(set.count(&3), 1);
+/// assert_eq!(set.iter().cloned().collect::<Vec<_>>(), vec![3, 5, 5, 8]);
+/// assert_eq!(set.range(&4..=&5).count(), 2);
+/// # set.tree().assert_correctness();
+///```
+pub struct OrchardMultiSet<T, D = SizeData<T>, Tr = Treap<D>> {
+    tree: Tr,
+    phantom: PhantomData<(T, D)>,
+}
+
+impl<T, D, Tr> OrchardMultiSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    /// Creates a new empty multiset.
+    pub fn new() -> Self {
+        OrchardMultiSet {
+            tree: Tr::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the multiset has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts a value into the multiset, after all the values equal to it.
+    pub fn insert(&mut self, value: T) {
+        self.tree.insert_sorted::<T>(value);
+    }
+
+    /// Returns `true` if the multiset contains the value at least once.
+    pub fn contains(&mut self, value: &T) -> bool {
+        self.tree.contains_key(value)
+    }
+
+    /// Returns the number of times the value is in the multiset.
+    pub fn count(&mut self, value: &T) -> usize
+    where
+        D::Summary: SizedSummary,
+    {
+        self.tree.segment_summary(ByKey((value,))).size()
+    }
+
+    /// Removes one occurrence of the value from the multiset.
+    /// Returns whether the value was in the multiset.
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.take(value).is_some()
+    }
+
+    /// Removes one occurrence of the value from the multiset,
+    /// and returns it if the value was in the multiset.
+    pub fn take(&mut self, value: &T) -> Option<T> {
+        self.tree.remove_by_key(value)
+    }
+
+    /// Iterates over the values in the given range, in sorted order.
+    /// The range should be a range of references, e.g., `&3..&7`.
+    pub fn range<R>(&mut self, range: R) -> impl DoubleEndedIterator<Item = &T>
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.iter_locator(ByKey(range))
+    }
+
+    /// Iterates over the values of the multiset, in sorted order.
+    pub fn iter(&mut self) -> impl DoubleEndedIterator<Item = &T> {
+        self.tree.iter()
+    }
+
+    /// Returns the summary of all the values in the given range.
+    /// The range should be a range of references, e.g., `&3..&7`.
+    pub fn segment_summary<R>(&mut self, range: R) -> D::Summary
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.segment_summary(ByKey(range))
+    }
+
+    /// Applies an action on all the values in the given range.
+    /// The range should be a range of references, e.g., `&3..&7`.
+    ///
+    /// The action must not change the order of the values, or reverse the segment.
+    pub fn act_segment<R>(&mut self, action: D::Action, range: R)
+    where
+        ByKey<R>: Locator<D>,
+    {
+        self.tree.act_segment(action, ByKey(range))
+    }
+
+    /// Returns a reference to the underlying tree.
+    pub fn tree(&self) -> &Tr {
+        &self.tree
+    }
+
+    /// Returns the underlying tree.
+    pub fn into_tree(self) -> Tr {
+        self.tree
+    }
+}
+
+impl<T, D, Tr> Default for OrchardMultiSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, D, Tr> std::iter::FromIterator<T> for OrchardMultiSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        OrchardMultiSet {
+            tree: Tr::from_unsorted_iter::<T, _>(iter),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, D, Tr> IntoIterator for OrchardMultiSet<T, D, Tr>
+where
+    T: Ord,
+    D: Data<Value = T>,
+    Tr: SomeTree<D>,
+{
+    type Item = T;
+    type IntoIter = Tr::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
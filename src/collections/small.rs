@@ -0,0 +1,213 @@
+//! A sequence that is stored inline while it is small, and only becomes a tree when it grows.
+//!
+//! Many workloads keep millions of tiny sequences, e.g., the adjacency lists of a graph,
+//! where allocating a node per value dominates the memory usage and the running time.
+//! A [`SmallTree`] keeps up to `N` values inline, in an [`ArrayVec`], without allocating,
+//! and answers queries and applies actions by scanning them. Once it holds more than `N` values,
+//! the values are moved into a tree of type `T`, and all operations take logarithmic time.
+//! If it shrinks back to `N / 2` values or fewer, the values are moved back inline.
+//! The gap between the two thresholds keeps a sequence whose length hovers around `N`
+//! from moving its values back and forth.
+
+use super::chunked::{act_values, summarize};
+use crate::trees::treap::Treap;
+use crate::*;
+use arrayvec::ArrayVec;
+use std::ops::Range;
+
+/// The two ways that a [`SmallTree`] can store its values.
+enum Repr<D: Data, T, const N: usize> {
+    Inline(ArrayVec<D::Value, N>),
+    Tree(T),
+}
+
+/// A sequence of values, stored inline while it has up to `N` values,
+/// and in a tree of type `T` otherwise. See the [module documentation](self).
+///
+/// The summaries have to implement [`SizedSummary`], so that the values can be indexed.
+///```
+/// use grove::collections::small::SmallTree;
+/// use grove::example_data::{StdNum, RevAffineAction};
+/// use grove::treap::Treap;
+///
+/// let mut seq: SmallTree<StdNum, Treap<StdNum>, 4> = (0..4).collect();
+/// assert!(seq.is_inline());
+/// seq.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 10 }, 1..4);
+/// assert_eq!(seq.segment_summary(0..2).sum, 13);
+///
+/// // the fifth value moves the values into a tree
+/// seq.push(20);
+/// assert!(!seq.is_inline());
+/// assert_eq!(seq.iter().cloned().collect::<Vec<_>>(), vec![0, 13, 12, 11, 20]);
+///
+/// // and removing three values moves them back inline
+/// for _ in 0..3 {
+///     seq.remove(0);
+/// }
+/// assert!(seq.is_inline());
+/// assert_eq!(seq.get(1), Some(20));
+///```
+pub struct SmallTree<D: Data, T = Treap<D>, const N: usize = 16> {
+    repr: Repr<D, T, N>,
+}
+
+impl<D: Data, T, const N: usize> Default for SmallTree<D, T, N> {
+    fn default() -> Self {
+        SmallTree {
+            repr: Repr::Inline(ArrayVec::new()),
+        }
+    }
+}
+
+impl<D: Data, T: SomeTree<D>, const N: usize> SmallTree<D, T, N>
+where
+    D::Summary: SizedSummary,
+{
+    /// Creates a new empty sequence, that doesn't allocate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the length of the sequence.
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(values) => values.len(),
+            Repr::Tree(tree) => tree.subtree_summary().size(),
+        }
+    }
+
+    /// Returns `true` if the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the values are currently stored inline, rather than in a tree.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline(_))
+    }
+
+    /// Iterates over the values of the sequence, in order.
+    pub fn iter(&mut self) -> impl Iterator<Item = &D::Value> {
+        let (values, tree) = match &mut self.repr {
+            Repr::Inline(values) => (&values[..], None),
+            Repr::Tree(tree) => (&[][..], Some(tree.iter())),
+        };
+        values.iter().chain(tree.into_iter().flatten())
+    }
+
+    /// Returns the value at the given index, or [`None`] if the index is out of bounds.
+    pub fn get(&mut self, index: usize) -> Option<D::Value>
+    where
+        D::Value: Clone,
+    {
+        match &mut self.repr {
+            Repr::Inline(values) => values.get(index).cloned(),
+            Repr::Tree(tree) => tree.search(index).value().cloned(),
+        }
+    }
+
+    /// Inserts the value at the given index.
+    /// If the sequence becomes longer than `N`, its values are moved into a tree.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: D::Value) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        match &mut self.repr {
+            Repr::Inline(values) if values.is_full() => {
+                let rest: ArrayVec<_, N> = values.drain(index..).collect();
+                let values = std::mem::take(values).into_iter();
+                self.repr = Repr::Tree(values.chain(std::iter::once(value)).chain(rest).collect());
+            }
+            Repr::Inline(values) => values.insert(index, value),
+            Repr::Tree(tree) => tree
+                .slice(index..index)
+                .insert(value)
+                .expect("Expected empty position"),
+        }
+    }
+
+    /// Inserts the value at the end of the sequence.
+    pub fn push(&mut self, value: D::Value) {
+        self.insert(self.len(), value);
+    }
+
+    /// Removes the value at the given index, and returns it.
+    /// If the sequence shrinks to `N / 2` values or fewer, its values are moved back inline.
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> D::Value {
+        let len = self.len();
+        assert!(index < len, "index {} out of bounds ({})", index, len);
+        match &mut self.repr {
+            Repr::Inline(values) => values.remove(index),
+            Repr::Tree(tree) => {
+                let value = tree
+                    .slice(index..=index)
+                    .delete()
+                    .expect("Expected nonempty position");
+                if len - 1 <= N / 2 {
+                    self.repr = Repr::Inline(std::mem::take(tree).into_iter().collect());
+                }
+                value
+            }
+        }
+    }
+
+    /// Applies the action to every value in the given range of indices.
+    /// Panics if the range is out of bounds.
+    pub fn act_segment(&mut self, action: D::Action, range: Range<usize>) {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        match &mut self.repr {
+            Repr::Inline(values) => act_values::<D>(&action, &mut values[range]),
+            Repr::Tree(tree) => tree.act_segment(action, range),
+        }
+    }
+
+    /// Returns the summary of the values in the given range of indices.
+    /// Panics if the range is out of bounds.
+    pub fn segment_summary(&mut self, range: Range<usize>) -> D::Summary {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        match &mut self.repr {
+            Repr::Inline(values) => summarize::<D>(&values[range]),
+            Repr::Tree(tree) => tree.segment_summary(range),
+        }
+    }
+}
+
+impl<D: Data, T: SomeTree<D>, const N: usize> std::iter::FromIterator<D::Value>
+    for SmallTree<D, T, N>
+{
+    /// Stores the values inline if there are at most `N` of them,
+    /// and in a tree otherwise.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        let mut values = ArrayVec::new();
+        while let Some(value) = iter.next() {
+            if let Err(error) = values.try_push(value) {
+                let first = values.into_iter().chain(std::iter::once(error.element()));
+                return SmallTree {
+                    repr: Repr::Tree(first.chain(iter).collect()),
+                };
+            }
+        }
+        SmallTree {
+            repr: Repr::Inline(values),
+        }
+    }
+}
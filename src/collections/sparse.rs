@@ -0,0 +1,304 @@
+//! A sparse segment tree: a huge sequence, e.g., indexed by the whole `u64` range,
+//! in which only the parts that were written to take up memory.
+//!
+//! The sequence is stored as a tree of runs, where every run is a segment of indices that all
+//! hold the same value. The tree starts as a single run, spanning the whole sequence.
+//! Acting on a segment splits at most two runs, at the segment's ends, and then applies the action
+//! to the runs in between, lazily, like in any other tree. Therefore, nodes are created only when
+//! writing, at most two per write, and every operation takes `O(log k)` time, where `k` is the
+//! number of runs, plus `O(log n)` additions of summaries, where `n` is the length of the sequence.
+//!
+//! The values, summaries and actions are the same as in any other tree, with any [`Data`],
+//! except that the actions can't depend on the index (see [`Action::is_indexed`]),
+//! since all of the values of a run must stay equal.
+//! Since segments can be huge, use numeric types that are big enough for their summaries.
+
+use crate::locators::{LocResult, Locator};
+use crate::trees::treap::Treap;
+use crate::*;
+use std::marker::PhantomData;
+use std::ops::{Add, Range};
+
+/// Returns the summary of `count` copies of a value with the given summary,
+/// using `O(log count)` additions.
+fn repeat<S: Clone + Default + Add<Output = S>>(summary: S, mut count: u64) -> S {
+    let mut res = S::default();
+    let mut power = summary;
+    while count > 0 {
+        if count & 1 == 1 {
+            res = res + power.clone();
+        }
+        count >>= 1;
+        if count > 0 {
+            power = power.clone() + power;
+        }
+    }
+    res
+}
+
+/// A segment of indices that all hold the same value.
+pub(super) struct Run<V, S> {
+    pub(super) value: V,
+    pub(super) count: u64,
+    /// The summary of the whole run. Cached, since it takes `O(log count)` time to compute.
+    pub(super) summary: S,
+}
+
+impl<V: ToSummary<S>, S: Clone + Default + Add<Output = S>> Run<V, S> {
+    pub(super) fn new(value: V, count: u64) -> Self {
+        let summary = repeat(value.to_summary(), count);
+        Run {
+            value,
+            count,
+            summary,
+        }
+    }
+}
+
+/// The summary of a segment of runs: its length, its number of runs, and the user's summary.
+#[derive(Clone, Copy, Default)]
+pub(super) struct RunSummary<S> {
+    pub(super) len: u64,
+    pub(super) runs: usize,
+    pub(super) summary: S,
+}
+
+impl<S: Add<Output = S>> Add for RunSummary<S> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        RunSummary {
+            len: self.len + other.len,
+            runs: self.runs + other.runs,
+            summary: self.summary + other.summary,
+        }
+    }
+}
+
+impl<V, S: Clone> ToSummary<RunSummary<S>> for Run<V, S> {
+    fn to_summary(&self) -> RunSummary<S> {
+        RunSummary {
+            len: self.count,
+            runs: 1,
+            summary: self.summary.clone(),
+        }
+    }
+}
+
+/// The user's action, acting on runs and their summaries.
+/// It doesn't forward [`Action::is_indexed`], since indexed actions are rejected before
+/// they are wrapped.
+#[derive(Clone, Copy, Default)]
+pub(super) struct RunAction<A>(A);
+
+impl<A: Action> Add for RunAction<A> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        RunAction(self.0 + other.0)
+    }
+}
+
+impl<A: Action> Action for RunAction<A> {
+    const REVERSIBLE: bool = A::REVERSIBLE;
+
+    fn is_identity(&self) -> bool {
+        self.0.is_identity()
+    }
+
+    fn to_reverse(&self) -> bool {
+        self.0.to_reverse()
+    }
+}
+
+impl<A: Acts<V> + Acts<S>, V, S> Acts<Run<V, S>> for RunAction<A> {
+    fn act_inplace(&self, run: &mut Run<V, S>) {
+        self.0.act_inplace(&mut run.value);
+        self.0.act_inplace(&mut run.summary);
+    }
+}
+
+impl<A: Acts<S>, S> Acts<RunSummary<S>> for RunAction<A> {
+    fn act_inplace(&self, summary: &mut RunSummary<S>) {
+        self.0.act_inplace(&mut summary.summary);
+    }
+}
+
+/// The [`Data`] of the tree of runs, for the user's [`Data`] `D`.
+pub(super) struct RunData<D>(PhantomData<D>);
+
+impl<D: Data> Data for RunData<D> {
+    type Value = Run<D::Value, D::Summary>;
+    type Summary = RunSummary<D::Summary>;
+    type Action = RunAction<D::Action>;
+}
+
+/// Locates the segment of runs intersecting the given range of indices.
+pub(super) fn segment<D: Data>(range: Range<u64>) -> impl Locator<RunData<D>> {
+    move |left: RunSummary<D::Summary>, run: &Run<D::Value, D::Summary>, _right| {
+        if left.len + run.count <= range.start {
+            LocResult::GoRight
+        } else if left.len >= range.end {
+            LocResult::GoLeft
+        } else {
+            LocResult::Accept
+        }
+    }
+}
+
+/// A huge sequence of values, in which only the parts that were written to take up memory.
+/// See the [module documentation](self).
+///```
+/// use grove::collections::SparseSegmentTree;
+/// use grove::example_data::{NumSummary, RevAffineAction};
+///
+/// type D = (i64, NumSummary<i64>, RevAffineAction<i64>);
+/// let mut tree: SparseSegmentTree<D> = SparseSegmentTree::new(1 << 60, 0);
+/// let add = |add| RevAffineAction { to_reverse: false, mul: 1, add };
+/// tree.act_segment(add(3), 1 << 40..1 << 50);
+/// tree.act_segment(add(-1), 1 << 45..1 << 59);
+///
+/// assert_eq!(tree.get(1 << 41), 3);
+/// assert_eq!(tree.get(1 << 46), 2);
+/// assert_eq!(tree.segment_summary(0..1 << 60).sum, 3 * (1 << 50) - 3 * (1 << 40) - ((1 << 59) - (1 << 45)));
+/// assert_eq!(tree.segment_summary(1 << 44..1 << 46).max, Some(3));
+/// assert_eq!(tree.num_runs(), 5);
+///```
+pub struct SparseSegmentTree<D: Data> {
+    pub(super) tree: Treap<RunData<D>>,
+}
+
+impl<D: Data> SparseSegmentTree<D>
+where
+    D::Value: Clone,
+{
+    /// Creates a sequence of length `len`, in which every index holds `value`.
+    /// Takes `O(log len)` time.
+    pub fn new(len: u64, value: D::Value) -> Self {
+        let tree = if len == 0 {
+            Treap::new()
+        } else {
+            std::iter::once(Run::new(value, len)).collect()
+        };
+        SparseSegmentTree { tree }
+    }
+
+    /// Returns the length of the sequence.
+    pub fn len(&self) -> u64 {
+        self.tree.subtree_summary().len
+    }
+
+    /// Returns `true` if the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of runs of equal values that the sequence is stored as,
+    /// which is the number of nodes in the tree.
+    pub fn num_runs(&self) -> usize {
+        self.tree.subtree_summary().runs
+    }
+
+    /// Returns the value at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn get(&mut self, index: u64) -> D::Value {
+        let len = self.len();
+        assert!(index < len, "index {} out of bounds ({})", index, len);
+        let walker = self.tree.search(segment::<D>(index..index + 1));
+        walker
+            .value()
+            .expect("Expected nonempty position")
+            .value
+            .clone()
+    }
+
+    /// Makes sure that there is a boundary between runs at the given index.
+    pub(super) fn split_at(&mut self, index: u64) {
+        if index == 0 || index >= self.len() {
+            return;
+        }
+        let mut walker = self.tree.search(segment::<D>(index..index + 1));
+        let start = walker.left_summary().len;
+        if start == index {
+            return;
+        }
+        let rest = walker
+            .with_value(|run| {
+                let rest = Run::new(run.value.clone(), run.count - (index - start));
+                *run = Run::new(run.value.clone(), index - start);
+                rest
+            })
+            .expect("Expected nonempty position");
+        walker.next_empty().expect("Expected nonempty position");
+        walker.insert(rest).expect("Expected empty position");
+    }
+
+    /// Applies the action to every value in the given range of indices.
+    /// Creates at most two new nodes.
+    /// Panics if the range is out of bounds, or if the action depends on the index.
+    pub fn act_segment(&mut self, action: D::Action, range: Range<u64>) {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        assert!(
+            !action.is_indexed(),
+            "Actions on a sparse segment tree must not depend on the index"
+        );
+        self.split_at(range.start);
+        self.split_at(range.end);
+        self.tree
+            .act_segment(RunAction(action), segment::<D>(range));
+    }
+
+    /// Sets the value at the given index. Creates at most two new nodes.
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: u64, value: D::Value) {
+        let len = self.len();
+        assert!(index < len, "index {} out of bounds ({})", index, len);
+        self.split_at(index);
+        self.split_at(index + 1);
+        let mut walker = self.tree.search(segment::<D>(index..index + 1));
+        walker
+            .with_value(|run| *run = Run::new(value, 1))
+            .expect("Expected nonempty position");
+    }
+
+    /// Returns the summary of the values in the given range of indices.
+    /// Doesn't create any nodes.
+    /// Panics if the range is out of bounds.
+    pub fn segment_summary(&mut self, range: Range<u64>) -> D::Summary {
+        let len = self.len();
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {:?} out of bounds ({})",
+            range,
+            len
+        );
+        if range.start == range.end {
+            return Default::default();
+        }
+        // the runs at the ends of the range might only be partially in it
+        let (_, first_end, first_summary) = self.run_at(range.start);
+        if range.end <= first_end {
+            return repeat(first_summary, range.end - range.start);
+        }
+        let (last_start, _, last_summary) = self.run_at(range.end - 1);
+        let middle = self
+            .tree
+            .segment_summary(segment::<D>(first_end..last_start))
+            .summary;
+        repeat(first_summary, first_end - range.start)
+            + middle
+            + repeat(last_summary, range.end - last_start)
+    }
+
+    /// Returns the range of the run containing the index, and the summary of a single value in it.
+    fn run_at(&mut self, index: u64) -> (u64, u64, D::Summary) {
+        let walker = self.tree.search(segment::<D>(index..index + 1));
+        let start = walker.left_summary().len;
+        let run = walker.value().expect("Expected nonempty position");
+        (start, start + run.count, run.value.to_summary())
+    }
+}
@@ -0,0 +1,331 @@
+//! A sequence with the interface of a [`Vec`], in which inserting and removing
+//! values anywhere takes `O(log n)` time.
+
+use crate::trees::avl::AVLTree;
+use crate::trees::basic_tree::{BasicNode, BasicTree};
+use crate::trees::snapshot;
+use crate::*;
+use example_data::SizeData;
+use std::ops::{Bound, RangeBounds};
+
+/// A sequence of values with the interface of a [`Vec`], stored in an [`AVLTree`].
+///
+/// Unlike a [`Vec`], inserting and removing values anywhere in the sequence takes
+/// `O(log n)` time, and so does indexing. Splitting and appending take `O(log n)` time as well.
+/// There are no walkers or locators in the interface: values are accessed by their indices.
+///
+/// Since the values aren't stored contiguously, the sequence can't be sliced into a `&[T]`.
+/// Instead, [`TreeVec::range`] iterates over a range of indices.
+///```
+/// use grove::collections::TreeVec;
+///
+/// let mut vec: TreeVec<i32> = (0..10).collect();
+/// vec.push(10);
+/// vec.insert(0, -1);
+/// assert_eq!(vec.remove(5), 4);
+/// assert_eq!(vec[5], 5);
+/// vec[5] = 50;
+///
+/// let mut tail = vec.split_off(8);
+/// assert_eq!(tail.iter().cloned().collect::<Vec<_>>(), vec![8, 9, 10]);
+/// tail.append(&mut vec);
+/// assert!(vec.is_empty());
+/// assert_eq!(tail.range(3..7).cloned().collect::<Vec<_>>(), vec![-1, 0, 1, 2]);
+/// assert_eq!(tail.last(), Some(&7));
+/// assert_eq!(tail.len(), 11);
+///```
+#[derive(Clone)]
+pub struct TreeVec<T> {
+    tree: AVLTree<SizeData<T>>,
+}
+
+impl<T> TreeVec<T> {
+    /// Creates a new empty sequence.
+    pub fn new() -> Self {
+        TreeVec {
+            tree: AVLTree::new(),
+        }
+    }
+
+    /// Returns the number of values in the sequence.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size
+    }
+
+    /// Returns `true` if the sequence has no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns a reference to the value at the given index, or [`None`] if it is out of bounds.
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        // there are no actions, so the values can be read without pushing anything down
+        let mut tree = self.tree.inner();
+        while let Some(node) = tree.node() {
+            let left = node.left.subtree_summary().size;
+            match index.cmp(&left) {
+                std::cmp::Ordering::Less => tree = &node.left,
+                std::cmp::Ordering::Equal => return Some(&node.node_value),
+                std::cmp::Ordering::Greater => {
+                    index -= left + 1;
+                    tree = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the value at the given index,
+    /// or [`None`] if it is out of bounds.
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        // the summaries only count the values, so they stay correct when the values change
+        let mut tree = self.tree.inner_mut();
+        while let Some(node) = tree.node_mut() {
+            let left = node.left.subtree_summary().size;
+            match index.cmp(&left) {
+                std::cmp::Ordering::Less => tree = &mut node.left,
+                std::cmp::Ordering::Equal => return Some(&mut node.node_value),
+                std::cmp::Ordering::Greater => {
+                    index -= left + 1;
+                    tree = &mut node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a reference to the first value, or [`None`] if the sequence is empty.
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a reference to the last value, or [`None`] if the sequence is empty.
+    pub fn last(&self) -> Option<&T> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Appends a value to the end of the sequence.
+    pub fn push(&mut self, value: T) {
+        let len = self.len();
+        self.insert(len, value);
+    }
+
+    /// Removes the last value and returns it, or [`None`] if the sequence is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len();
+        self.tree.slice(len.checked_sub(1)?..len).delete()
+    }
+
+    /// Inserts a value at the given index, shifting the values after it.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        self.tree
+            .slice(index..index)
+            .insert(value)
+            .expect("Expected empty position");
+    }
+
+    /// Removes the value at the given index and returns it, shifting the values after it.
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+        self.tree
+            .slice(index..=index)
+            .delete()
+            .expect("Expected nonempty position")
+    }
+
+    /// Removes all of the values.
+    pub fn clear(&mut self) {
+        self.tree = AVLTree::new();
+    }
+
+    /// Splits the sequence in two at the given index, and returns the values after it.
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> TreeVec<T> {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "`at` split index (is {}) should be <= len (is {})",
+            at,
+            len
+        );
+        let tree = self
+            .tree
+            .slice(at..at)
+            .split_right()
+            .expect("Expected empty position");
+        TreeVec { tree }
+    }
+
+    /// Moves all of the values of `other` to the end of this sequence, leaving `other` empty.
+    pub fn append(&mut self, other: &mut TreeVec<T>) {
+        self.tree.concatenate_right(std::mem::take(&mut other.tree));
+    }
+
+    /// Iterates over the values, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        snapshot::Iter::new(self.tree.inner(), self.len())
+    }
+
+    /// Iterates over mutable references to the values, in order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let mut iter = IterMut { stack: vec![] };
+        iter.push_left_path(self.tree.inner_mut());
+        iter
+    }
+
+    /// Iterates over the values in the given range of indices, in order.
+    /// Panics if the range is out of bounds.
+    pub fn range<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "range {}..{} out of bounds",
+            start,
+            end
+        );
+        snapshot::Iter::segment(self.tree.inner(), start..end)
+    }
+
+    /// Returns a reference to the underlying tree.
+    pub fn tree(&self) -> &AVLTree<SizeData<T>> {
+        &self.tree
+    }
+}
+
+/// A mutable iterator over the values of a [`TreeVec`]. Created by [`TreeVec::iter_mut`].
+pub struct IterMut<'a, T> {
+    // The values yet to be yielded, each with the subtree that comes after it, in reverse order.
+    stack: Vec<(&'a mut T, &'a mut BasicTree<SizeData<T>, u8>)>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    fn push_left_path(&mut self, mut tree: &'a mut BasicTree<SizeData<T>, u8>) {
+        while let Some(node) = tree.node_mut() {
+            let BasicNode {
+                node_value,
+                left,
+                right,
+                ..
+            } = node;
+            self.stack.push((node_value, right));
+            tree = left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        let (value, right) = self.stack.pop()?;
+        self.push_left_path(right);
+        Some(value)
+    }
+}
+
+impl<T> Default for TreeVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::ops::Index<usize> for TreeVec<T> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+impl<T> std::ops::IndexMut<usize> for TreeVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for TreeVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        TreeVec {
+            tree: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for TreeVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut other: TreeVec<T> = iter.into_iter().collect();
+        self.append(&mut other);
+    }
+}
+
+impl<T> IntoIterator for TreeVec<T> {
+    type Item = T;
+    type IntoIter = <AVLTree<SizeData<T>> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
+
+impl<T> From<Vec<T>> for TreeVec<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T> From<TreeVec<T>> for Vec<T> {
+    fn from(vec: TreeVec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for TreeVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for TreeVec<T> {}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for TreeVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
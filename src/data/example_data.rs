@@ -10,7 +10,7 @@ use super::*;
 /// A trait for summary instances which keep track of the size of segments.
 pub trait SizedSummary {
     /// The size of the segment
-    fn size(self) -> usize;
+    fn size(&self) -> usize;
 }
 
 /// A trait for values that are keyed by a key type `Key`. When using keyed values, we assume
@@ -36,6 +36,13 @@ impl<T: Ord> Keyed<T> for T {
     }
 }
 
+/// Key-value pairs are keyed by their first element.
+impl<K: Ord, V> Keyed<K> for (K, V) {
+    fn get_key(&self) -> &K {
+        &self.0
+    }
+}
+
 // Some common instantiations and examples
 
 /// [`Data`] instance for just plain values.
@@ -49,6 +56,22 @@ pub type SizeData<V> = (V, Size, Unit);
 /// the maximum, minimum, size and sum of a whole segment at once.
 pub type StdNum = (I, NumSummary, RevAffineAction);
 
+/// A Data marker for sequences of bits, with queries about the ones in a segment, and an action
+/// that flips all the bits in a segment. See [`BitSummary`].
+pub type BitData = (bool, BitSummary, FlipAction);
+
+/// A Data marker for numbers modulo `M`, with segment sum queries, and actions that multiply
+/// a segment by a constant and add a constant to it. See [`ModSum`].
+pub type ModNum<const M: u64> = (u64, ModSum<M>, ModAffineAction<M>);
+
+/// A Data marker for text stored in chunks, with queries about the bytes, chars and lines
+/// of a segment. See [`TextSummary`].
+pub type TextData = (String, TextSummary, Unit);
+
+/// A Data marker for numbers with segment gcd and lcm queries. Segments can be reversed or
+/// multiplied by a constant, but adding a constant is not supported. See [`GcdSummary`].
+pub type GcdNum = (I, GcdSummary, RevMulAction);
+
 // ----------------- particular summaries and actions -------------------
 // from here, each struct is packaged into its own internal module.
 // mostly in order to reduce clutter / separate the different structs.
@@ -72,8 +95,14 @@ mod unit {
     }
 
     impl Action for Unit {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+    }
+
+    impl InvertibleAction for Unit {
+        fn inverse(&self) -> Self {
+            *self
         }
     }
 
@@ -82,6 +111,17 @@ mod unit {
             Unit {}
         }
     }
+
+    impl ReverseSummary for Unit {
+        fn reverse(&mut self) {}
+    }
+
+    #[cfg(feature = "arbitrary")]
+    impl<'a> arbitrary::Arbitrary<'a> for Unit {
+        fn arbitrary(_u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+            Ok(Unit {})
+        }
+    }
 }
 
 pub use size::*;
@@ -110,7 +150,7 @@ mod size {
     }
 
     impl SizedSummary for Size {
-        fn size(self) -> usize {
+        fn size(&self) -> usize {
             self.size
         }
     }
@@ -120,6 +160,10 @@ mod size {
             Size { size: 1 }
         }
     }
+
+    impl ReverseSummary for Size {
+        fn reverse(&mut self) {}
+    }
 }
 
 pub use rev_action::*;
@@ -148,29 +192,42 @@ mod rev_action {
     }
 
     impl Action for RevAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        const REVERSIBLE: bool = true;
+
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
 
-        fn to_reverse(self) -> bool {
+        fn to_reverse(&self) -> bool {
             self.to_reverse
         }
     }
 
-    impl Acts<Unit> for RevAction {
-        fn act_inplace(&self, _val: &mut Unit) {}
+    impl InvertibleAction for RevAction {
+        fn inverse(&self) -> Self {
+            *self
+        }
     }
 
-    impl Acts<I> for RevAction {
-        fn act_inplace(&self, _val: &mut I) {}
+    impl<S: ReverseSummary> Acts<S> for RevAction {
+        fn act_inplace(&self, summary: &mut S) {
+            if self.to_reverse {
+                summary.reverse();
+            }
+        }
     }
 
-    impl Acts<Size> for RevAction {
-        fn act_inplace(&self, _val: &mut Size) {}
+    impl Acts<I> for RevAction {
+        fn act_inplace(&self, _val: &mut I) {}
     }
 
-    impl Acts<NumSummary> for RevAction {
-        fn act_inplace(&self, _val: &mut NumSummary) {}
+    #[cfg(feature = "arbitrary")]
+    impl<'a> arbitrary::Arbitrary<'a> for RevAction {
+        fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+            Ok(RevAction {
+                to_reverse: u.arbitrary()?,
+            })
+        }
     }
 }
 
@@ -178,13 +235,14 @@ pub use add_action::*;
 mod add_action {
     use super::*;
     /// An action for adding a constant to all values in a segment.
+    /// Generic over the number type, see [`Num`].
     #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
-    pub struct AddAction {
+    pub struct AddAction<T = I> {
         /// The amount to be added
-        pub add: I,
+        pub add: T,
     }
 
-    impl std::ops::Add for AddAction {
+    impl<T: Num> std::ops::Add for AddAction<T> {
         type Output = Self;
         fn add(self, other: Self) -> Self {
             AddAction {
@@ -193,15 +251,21 @@ mod add_action {
         }
     }
 
-    impl Default for AddAction {
+    impl<T: Num> Default for AddAction<T> {
         fn default() -> Self {
-            AddAction { add: 0 }
+            AddAction { add: T::from(0) }
+        }
+    }
+
+    impl<T: Num> Action for AddAction<T> {
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
     }
 
-    impl Action for AddAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+    impl<T: Num + std::ops::Neg<Output = T>> InvertibleAction for AddAction<T> {
+        fn inverse(&self) -> Self {
+            AddAction { add: -self.add }
         }
     }
 
@@ -209,40 +273,67 @@ mod add_action {
         fn act_inplace(&self, _val: &mut Unit) {}
     }
 
-    impl Acts<I> for AddAction {
-        fn act_inplace(&self, val: &mut I) {
-            *val += self.add;
+    impl Acts<Size> for AddAction {
+        fn act_inplace(&self, _val: &mut Size) {}
+    }
+
+    impl<T: Num> Acts<T> for AddAction<T> {
+        fn act_inplace(&self, val: &mut T) {
+            *val = *val + self.add;
         }
     }
 
-    impl Acts<NumSummary> for AddAction {
-        fn act_inplace(&self, summary: &mut NumSummary) {
-            summary.max = summary.max.map(|max: I| max + self.add);
-            summary.min = summary.min.map(|max: I| max + self.add);
-            summary.sum += self.add * summary.size;
+    impl<T: Num> Acts<NumSummary<T>> for AddAction<T> {
+        fn act_inplace(&self, summary: &mut NumSummary<T>) {
+            summary.max = summary.max.map(|max: T| max + self.add);
+            summary.min = summary.min.map(|min: T| min + self.add);
+            summary.sum = summary.sum + self.add * summary.size;
         }
     }
 }
 
 type I = i32;
 
+/// The number types that can be used in [`NumSummary`], [`AddAction`] and [`RevAffineAction`],
+/// e.g., `i32`, `i64`, `u128`, [`Checked`] or [`Saturating`] integers,
+/// or your own modular integer type.
+/// Numbers are converted from `u8` in order to get `0` and `1`.
+///
+/// This trait is implemented automatically for all types that satisfy its bounds.
+pub trait Num: Copy + Ord + Add<Output = Self> + std::ops::Mul<Output = Self> + From<u8> {}
+
+impl<T> Num for T where T: Copy + Ord + Add<Output = T> + std::ops::Mul<Output = T> + From<u8> {}
+
 pub use num_summary::*;
 mod num_summary {
     use super::*;
-    /// A standard numerical summary
+    /// A standard numerical summary. Generic over the number type, see [`Num`].
+    ///
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{NumSummary, RevAffineAction};
+    ///
+    /// type D = (u128, NumSummary<u128>, RevAffineAction<u128>);
+    /// let mut tree: Treap<D> = (1..=10).map(|x: u128| x << 70).collect();
+    /// tree.act_segment(RevAffineAction { to_reverse: true, mul: 3, add: 1 }, 3..7);
+    /// let summary = tree.segment_summary(..);
+    /// assert_eq!(summary.sum, (55 + 2*(4 + 5 + 6 + 7)) << 70 | 4);
+    /// assert_eq!(summary.max, Some((21 << 70) + 1));
+    /// # tree.assert_correctness();
+    ///```
     #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
-    pub struct NumSummary {
+    pub struct NumSummary<T = I> {
         /// The maximum of all values in the segment. [`None`] is the segment is empty.
-        pub max: Option<I>,
+        pub max: Option<T>,
         /// The minimum of all values in the segment. [`None`] is the segment is empty.
-        pub min: Option<I>,
+        pub min: Option<T>,
         /// The size of the segment.
-        pub size: I,
+        pub size: T,
         /// The sum of all values in the segment.
-        pub sum: I,
+        pub sum: T,
     }
 
-    impl Add for NumSummary {
+    impl<T: Num> Add for NumSummary<T> {
         type Output = Self;
         fn add(self, other: Self) -> Self {
             NumSummary {
@@ -262,35 +353,119 @@ mod num_summary {
         }
     }
 
-    impl Default for NumSummary {
-        fn default() -> NumSummary {
+    impl<T: Num> Default for NumSummary<T> {
+        fn default() -> NumSummary<T> {
             NumSummary {
                 max: None,
                 min: None,
-                size: 0,
-                sum: 0,
+                size: T::from(0),
+                sum: T::from(0),
             }
         }
     }
 
-    impl SizedSummary for NumSummary {
-        fn size(self) -> usize {
-            self.size as usize
+    impl<T: Num + TryInto<usize>> SizedSummary for NumSummary<T> {
+        fn size(&self) -> usize {
+            match self.size.try_into() {
+                Ok(size) => size,
+                Err(_) => panic!("segment size doesn't fit in usize"),
+            }
         }
     }
 
-    impl ToSummary<NumSummary> for I {
-        fn to_summary(&self) -> NumSummary {
+    impl<T> ReverseSummary for NumSummary<T> {
+        fn reverse(&mut self) {}
+    }
+
+    impl<T: Num> ToSummary<NumSummary<T>> for T {
+        fn to_summary(&self) -> NumSummary<T> {
             NumSummary {
                 max: Some(*self),
                 min: Some(*self),
-                size: 1,
+                size: T::from(1),
                 sum: *self,
             }
         }
     }
 }
 
+pub use set_or_add::*;
+mod set_or_add {
+    use super::*;
+    /// An action that either assigns a constant to all values in a segment,
+    /// or adds a constant to all of them. Generic over the number type, see [`Num`].
+    ///
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::{NumSummary, SetOrAdd};
+    ///
+    /// type D = (i64, NumSummary<i64>, SetOrAdd<i64>);
+    /// let mut tree: AVLTree<D> = (0..10).collect();
+    /// tree.act_segment(SetOrAdd::Add(10), 2..8);
+    /// tree.act_segment(SetOrAdd::Set(3), 5..);
+    /// tree.act_segment(SetOrAdd::Add(1), 4..6);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 12, 13, 15, 4, 3, 3, 3, 3]);
+    /// assert_eq!(tree.segment_summary(3..).sum, 44);
+    /// assert_eq!(tree.segment_summary(5..).max, Some(4));
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub enum SetOrAdd<T = I> {
+        /// Assign this constant to all values in the segment.
+        Set(T),
+        /// Add this constant to all values in the segment.
+        Add(T),
+    }
+    use SetOrAdd::*;
+
+    impl<T: Num> std::ops::Add for SetOrAdd<T> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            match (self, other) {
+                (Set(a), _) => Set(a),
+                (Add(a), Set(b)) => Set(b + a),
+                (Add(a), Add(b)) => Add(a + b),
+            }
+        }
+    }
+
+    impl<T: Num> Default for SetOrAdd<T> {
+        fn default() -> Self {
+            Add(T::from(0))
+        }
+    }
+
+    impl<T: Num> Action for SetOrAdd<T> {
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+    }
+
+    impl<T: Num> Acts<T> for SetOrAdd<T> {
+        fn act_inplace(&self, val: &mut T) {
+            match *self {
+                Set(a) => *val = a,
+                Add(a) => *val = *val + a,
+            }
+        }
+    }
+
+    impl<T: Num> Acts<NumSummary<T>> for SetOrAdd<T> {
+        fn act_inplace(&self, summary: &mut NumSummary<T>) {
+            match *self {
+                Set(a) => {
+                    if summary.size != T::from(0) {
+                        summary.max = Some(a);
+                        summary.min = Some(a);
+                        summary.sum = a * summary.size;
+                    }
+                }
+                Add(a) => AddAction { add: a }.act_inplace(summary),
+            }
+        }
+    }
+}
+
 pub use rev_add_action::*;
 mod rev_add_action {
     use super::*;
@@ -323,15 +498,26 @@ mod rev_add_action {
     }
 
     impl Action for RevAddAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        const REVERSIBLE: bool = true;
+
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
 
-        fn to_reverse(self) -> bool {
+        fn to_reverse(&self) -> bool {
             self.to_reverse.to_reverse()
         }
     }
 
+    impl InvertibleAction for RevAddAction {
+        fn inverse(&self) -> Self {
+            RevAddAction {
+                to_reverse: self.to_reverse.inverse(),
+                add: self.add.inverse(),
+            }
+        }
+    }
+
     impl<T> Acts<T> for RevAddAction
     where
         RevAction: Acts<T>,
@@ -348,27 +534,30 @@ pub use rev_affine_action::*;
 mod rev_affine_action {
     use super::*;
     /// Actions of reversals, adding a constant, and multiplying by a constant.
+    /// Generic over the number type, see [`Num`].
     #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
-    pub struct RevAffineAction {
+    pub struct RevAffineAction<T = I> {
         /// Whether to reverse the segment.
         pub to_reverse: bool,
         /// A constant to multiply all the values in the segment with.
-        pub mul: I,
+        pub mul: T,
         /// A constant to add to all the values in the segment.
-        pub add: I,
+        pub add: T,
     }
 
-    impl Action for RevAffineAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+    impl<T: Num> Action for RevAffineAction<T> {
+        const REVERSIBLE: bool = true;
+
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
 
-        fn to_reverse(self) -> bool {
+        fn to_reverse(&self) -> bool {
             self.to_reverse
         }
     }
 
-    impl Add for RevAffineAction {
+    impl<T: Num> Add for RevAffineAction<T> {
         type Output = Self;
         fn add(self, other: Self) -> Self {
             RevAffineAction {
@@ -379,37 +568,186 @@ mod rev_affine_action {
         }
     }
 
-    impl Default for RevAffineAction {
+    impl<T: Num> Default for RevAffineAction<T> {
         fn default() -> Self {
             RevAffineAction {
                 to_reverse: false,
-                mul: 1,
-                add: 0,
+                mul: T::from(1),
+                add: T::from(0),
             }
         }
     }
 
-    impl Acts<I> for RevAffineAction {
-        fn act_inplace(&self, val: &mut I) {
-            *val *= self.mul;
-            *val += self.add;
+    impl Acts<Size> for RevAffineAction {
+        fn act_inplace(&self, _val: &mut Size) {}
+    }
+
+    impl<T: Num> Acts<T> for RevAffineAction<T> {
+        fn act_inplace(&self, val: &mut T) {
+            *val = *val * self.mul + self.add;
         }
     }
 
-    impl Acts<NumSummary> for RevAffineAction {
-        fn act_inplace(&self, summary: &mut NumSummary) {
-            if self.mul < 0 {
+    impl<T: Num> Acts<NumSummary<T>> for RevAffineAction<T> {
+        fn act_inplace(&self, summary: &mut NumSummary<T>) {
+            if self.mul < T::from(0) {
                 std::mem::swap(&mut summary.min, &mut summary.max);
             }
-            summary.max = summary.max.map(|max: I| max * self.mul);
-            summary.min = summary.min.map(|max: I| max * self.mul);
-            summary.sum *= self.mul;
+            summary.max = summary.max.map(|max: T| max * self.mul);
+            summary.min = summary.min.map(|min: T| min * self.mul);
+            summary.sum = summary.sum * self.mul;
+
+            summary.max = summary.max.map(|max: T| max + self.add);
+            summary.min = summary.min.map(|min: T| min + self.add);
+            summary.sum = summary.sum + self.add * summary.size;
+        }
+    }
+}
+
+pub use pair::*;
+mod pair {
+    use super::*;
+    /// A pair of summaries or of actions, used in order to combine two [`Data`] instances
+    /// that share the same value type, without writing new summary and action structs.
+    /// Summaries and actions are combined componentwise.
+    ///
+    /// [`Data`] is implemented for `(D1, D2)` whenever `D1` and `D2` are [`Data`] instances
+    /// with the same value type. Its summary is `Pair<D1::Summary, D2::Summary>`, and its action
+    /// is `Pair<D1::Action, D2::Action>`. Every action acts on its own summary, but only the
+    /// first action acts on the values: the second action must leave the values unchanged,
+    /// like [`Unit`] and [`RevAction`]. Therefore, the second summary should not depend on
+    /// the changes that the first action makes to the values, e.g., [`Size`].
+    ///
+    /// The two actions must agree on whether to reverse, since each of them reverses its own
+    /// summary. Create pairs of actions with [`Pair::actions`], which checks this.
+    ///
+    /// Pairs of actions act on [`Num`] values. For your own value type `V`,
+    /// implement `Acts<V> for Pair<A1, A2>` by applying the first action.
+    ///
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{Size, StdNum, Pair, RevAction, RevAffineAction};
+    ///
+    /// type D = (StdNum, (i32, Size, RevAction));
+    /// let mut tree: Treap<D> = (1..=10).collect();
+    /// let action = Pair::actions(
+    ///     RevAffineAction { to_reverse: true, mul: 2, add: 0 },
+    ///     RevAction { to_reverse: true },
+    /// );
+    /// tree.act_segment(action, 2..5);
+    ///
+    /// let Pair(num, size) = tree.segment_summary(..4);
+    /// assert_eq!(num.sum, 1 + 2 + 10 + 8);
+    /// assert_eq!(size.size, 4);
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default, PartialOrd, Ord)]
+    pub struct Pair<S1, S2>(pub S1, pub S2);
+
+    impl<S1: Add<Output = S1>, S2: Add<Output = S2>> Add for Pair<S1, S2> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            Pair(self.0 + other.0, self.1 + other.1)
+        }
+    }
+
+    /// The size of a pair of summaries is the size of its first component.
+    impl<S1: SizedSummary, S2> SizedSummary for Pair<S1, S2> {
+        fn size(&self) -> usize {
+            self.0.size()
+        }
+    }
+
+    impl<V, S1, S2> ToSummary<Pair<S1, S2>> for V
+    where
+        V: ToSummary<S1> + ToSummary<S2>,
+    {
+        fn to_summary(&self) -> Pair<S1, S2> {
+            Pair(self.to_summary(), self.to_summary())
+        }
+    }
+
+    impl<S1: ReverseSummary, S2: ReverseSummary> ReverseSummary for Pair<S1, S2> {
+        fn reverse(&mut self) {
+            self.0.reverse();
+            self.1.reverse();
+        }
+    }
+
+    impl<A1: Acts<S1> + Clone, A2: Acts<S2> + Clone, S1, S2> Acts<Pair<S1, S2>> for Pair<A1, A2> {
+        fn act_inplace(&self, summary: &mut Pair<S1, S2>) {
+            self.0.act_inplace(&mut summary.0);
+            self.1.act_inplace(&mut summary.1);
+        }
+
+        fn skip(&self, prefix: &Pair<S1, S2>) -> Self
+        where
+            Self: Clone,
+        {
+            Pair(self.0.skip(&prefix.0), self.1.skip(&prefix.1))
+        }
+    }
 
-            summary.max = summary.max.map(|max: I| max + self.add);
-            summary.min = summary.min.map(|max: I| max + self.add);
-            summary.sum += self.add * summary.size;
+    impl<A1: Action, A2: Action> Pair<A1, A2> {
+        /// Pairs two actions.
+        ///
+        /// # Panics
+        /// Panics if the actions don't agree on whether to reverse.
+        pub fn actions(first: A1, second: A2) -> Self {
+            assert_eq!(
+                first.to_reverse(),
+                second.to_reverse(),
+                "Both actions of a pair should agree on whether to reverse"
+            );
+            Pair(first, second)
+        }
+    }
+
+    impl<T: Num, A1: Acts<T>> Acts<T> for Pair<A1, Unit> {
+        fn act_inplace(&self, value: &mut T) {
+            self.0.act_inplace(value);
+        }
+    }
+
+    impl<T: Num, A1: Acts<T>> Acts<T> for Pair<A1, RevAction> {
+        fn act_inplace(&self, value: &mut T) {
+            self.0.act_inplace(value);
+        }
+    }
+
+    impl<A1: Action, A2: Action> Action for Pair<A1, A2> {
+        const REVERSIBLE: bool = A1::REVERSIBLE && A2::REVERSIBLE;
+
+        fn is_identity(&self) -> bool {
+            self.0.is_identity() && self.1.is_identity()
+        }
+
+        fn to_reverse(&self) -> bool {
+            self.0.to_reverse()
+        }
+
+        fn is_indexed(&self) -> bool {
+            self.0.is_indexed() || self.1.is_indexed()
+        }
+    }
+
+    impl<A1: InvertibleAction, A2: InvertibleAction> InvertibleAction for Pair<A1, A2> {
+        fn inverse(&self) -> Self {
+            Pair(self.0.inverse(), self.1.inverse())
         }
     }
+
+    /// A [`Data`] implementation for a pair of [`Data`] instances with the same values.
+    /// See [`Pair`].
+    impl<D1: Data, D2: Data<Value = D1::Value>> Data for (D1, D2)
+    where
+        D1::Value: ToSummary<Pair<D1::Summary, D2::Summary>>,
+        Pair<D1::Action, D2::Action>: Acts<D1::Value>,
+    {
+        type Value = D1::Value;
+        type Summary = Pair<D1::Summary, D2::Summary>;
+        type Action = Pair<D1::Action, D2::Action>;
+    }
 }
 
 pub use poly_num::*;
@@ -495,7 +833,7 @@ mod poly_num {
     }
 
     impl<const D: usize> SizedSummary for PolyNum<D> {
-        fn size(self) -> usize {
+        fn size(&self) -> usize {
             self.size
         }
     }
@@ -546,17 +884,13 @@ mod poly_num {
         }
     }
 
-    impl<const D: usize> Acts<PolyNum<D>> for RevAction {
-        fn act_inplace(&self, summary: &mut PolyNum<D>) {
-            if !self.to_reverse {
-                return;
-            }
-
-            summary.moments = summary.shift(1 - (summary.size as I)).moments;
+    impl<const D: usize> ReverseSummary for PolyNum<D> {
+        fn reverse(&mut self) {
+            self.moments = self.shift(1 - (self.size as I)).moments;
 
             for i in 0..D {
                 if i % 2 == 1 {
-                    summary.moments[i] *= -1;
+                    self.moments[i] *= -1;
                 }
             }
         }
@@ -578,3 +912,1167 @@ mod poly_num {
         }
     }
 }
+
+pub use progression_action::*;
+mod progression_action {
+    use super::*;
+    /// An action that adds an arithmetic progression to a segment: the `i`-th value of the
+    /// segment, starting from `0`, is increased by `add + i * step`.
+    /// This action depends on the index, see [`Action::is_indexed`].
+    ///
+    /// It acts on [`PolyNum`] summaries, so these can be used to query the sums of the segments.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{PolyNum, ProgressionAction};
+    ///
+    /// let mut tree: Treap<(i32, PolyNum<2>, ProgressionAction)> = (0..6).map(|_| 0).collect();
+    /// tree.act_segment(ProgressionAction { add: 1, step: 2 }, 1..4);
+    /// tree.act_segment(ProgressionAction { add: 0, step: 1 }, 2..);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 3, 6, 2, 3]);
+    /// assert_eq!(tree.segment_summary(1..3).moments[0], 4);
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct ProgressionAction {
+        /// The amount added to the first value
+        pub add: I,
+        /// The difference between the amounts added to consecutive values
+        pub step: I,
+    }
+
+    impl Add for ProgressionAction {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            ProgressionAction {
+                add: self.add + other.add,
+                step: self.step + other.step,
+            }
+        }
+    }
+
+    impl Action for ProgressionAction {
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+
+        fn is_indexed(&self) -> bool {
+            self.step != 0
+        }
+    }
+
+    impl InvertibleAction for ProgressionAction {
+        fn inverse(&self) -> Self {
+            ProgressionAction {
+                add: -self.add,
+                step: -self.step,
+            }
+        }
+    }
+
+    /// A single value is the first value of its segment.
+    impl Acts<I> for ProgressionAction {
+        fn act_inplace(&self, val: &mut I) {
+            *val += self.add;
+        }
+    }
+
+    impl<const D: usize> Acts<PolyNum<D>> for ProgressionAction {
+        fn act_inplace(&self, summary: &mut PolyNum<D>) {
+            // compute the summaries of the segments `1, ..., 1` and `0, 1, ..., size - 1`
+            // by repeated doubling, like in the implementation for `AddAction`.
+            let mut ones = PolyNum::<D>::default();
+            let mut indices = PolyNum::<D>::default();
+            let bits = usize::BITS - summary.size().leading_zeros();
+            for j in (0..bits).rev() {
+                // the second copy of the indices is bigger by the size of the first copy.
+                let mut second_copy = indices;
+                for i in 0..D {
+                    second_copy.moments[i] += ones.size as I * ones.moments[i];
+                }
+                indices = indices + second_copy;
+                ones = ones + ones;
+                if (summary.size() >> j) & 1 == 1 {
+                    indices = indices + (ones.size as I).to_summary();
+                    ones = ones + (1 as I).to_summary();
+                }
+            }
+
+            for i in 0..D {
+                summary.moments[i] += self.add * ones.moments[i] + self.step * indices.moments[i];
+            }
+        }
+
+        fn skip(&self, prefix: &PolyNum<D>) -> Self {
+            ProgressionAction {
+                add: self.add + self.step * prefix.size as I,
+                step: self.step,
+            }
+        }
+    }
+}
+
+pub use gcd_summary::*;
+mod gcd_summary {
+    use super::*;
+    /// A summary keeping the gcd and lcm of a segment of numbers.
+    /// The gcd and lcm are always non negative. The gcd of an empty segment is `0`,
+    /// and its lcm is `1`. The lcm is [`None`] if it overflows.
+    ///
+    /// Adding a constant to the values can't be handled by this summary, since the gcd of the
+    /// new values can't be computed from the old gcd. Therefore, it is acted on by
+    /// [`RevMulAction`], which can only reverse and multiply.
+    ///
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::{GcdNum, RevMulAction};
+    ///
+    /// let mut tree: AVLTree<GcdNum> = vec![12, 18, 30, 4, 10].into_iter().collect();
+    /// assert_eq!(tree.segment_summary(0..3).gcd, 6);
+    /// assert_eq!(tree.segment_summary(..).gcd, 2);
+    /// assert_eq!(tree.segment_summary(3..).lcm, Some(20));
+    ///
+    /// tree.act_segment(RevMulAction { to_reverse: false, mul: -3 }, 1..3);
+    /// assert_eq!(tree.segment_summary(0..3).gcd, 6);
+    /// assert_eq!(tree.segment_summary(1..3).lcm, Some(270));
+    ///
+    /// // the lcm of these values doesn't fit in an `i32`
+    /// let mut tree: AVLTree<GcdNum> = (1..=30).collect();
+    /// assert_eq!(tree.segment_summary(..).gcd, 1);
+    /// assert_eq!(tree.segment_summary(..).lcm, None);
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct GcdSummary {
+        /// The gcd of all values in the segment.
+        pub gcd: I,
+        /// The lcm of all values in the segment, or [`None`] if it overflows.
+        pub lcm: Option<I>,
+        /// The size of the segment.
+        pub size: usize,
+    }
+
+    fn gcd(mut a: I, mut b: I) -> I {
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+        a.abs()
+    }
+
+    // Both numbers are non negative.
+    fn lcm(a: Option<I>, b: Option<I>) -> Option<I> {
+        let (a, b) = (a?, b?);
+        if a == 0 || b == 0 {
+            Some(0)
+        } else {
+            (a / gcd(a, b)).checked_mul(b)
+        }
+    }
+
+    impl Add for GcdSummary {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            GcdSummary {
+                gcd: gcd(self.gcd, other.gcd),
+                lcm: lcm(self.lcm, other.lcm),
+                size: self.size + other.size,
+            }
+        }
+    }
+
+    impl Default for GcdSummary {
+        fn default() -> GcdSummary {
+            GcdSummary {
+                gcd: 0,
+                lcm: Some(1),
+                size: 0,
+            }
+        }
+    }
+
+    impl SizedSummary for GcdSummary {
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    impl ToSummary<GcdSummary> for I {
+        fn to_summary(&self) -> GcdSummary {
+            GcdSummary {
+                gcd: self.abs(),
+                lcm: self.checked_abs(),
+                size: 1,
+            }
+        }
+    }
+
+    impl ReverseSummary for GcdSummary {
+        fn reverse(&mut self) {}
+    }
+
+    /// Actions that can reverse a segment and multiply it by a constant,
+    /// but not add a constant to it. Used with [`GcdSummary`].
+    ///
+    /// Multiplying happens before reversing, but it doesn't matter.
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct RevMulAction {
+        /// Whether to reverse the segment.
+        pub to_reverse: bool,
+        /// The number to multiply by.
+        pub mul: I,
+    }
+
+    impl Default for RevMulAction {
+        fn default() -> Self {
+            RevMulAction {
+                to_reverse: false,
+                mul: 1,
+            }
+        }
+    }
+
+    impl Add for RevMulAction {
+        type Output = RevMulAction;
+        fn add(self, other: RevMulAction) -> RevMulAction {
+            RevMulAction {
+                to_reverse: self.to_reverse != other.to_reverse,
+                mul: self.mul * other.mul,
+            }
+        }
+    }
+
+    impl Action for RevMulAction {
+        const REVERSIBLE: bool = true;
+
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+
+        fn to_reverse(&self) -> bool {
+            self.to_reverse
+        }
+    }
+
+    impl Acts<I> for RevMulAction {
+        fn act_inplace(&self, value: &mut I) {
+            *value *= self.mul;
+        }
+    }
+
+    impl Acts<GcdSummary> for RevMulAction {
+        fn act_inplace(&self, summary: &mut GcdSummary) {
+            if summary.size == 0 {
+                return;
+            }
+            let mul = self.mul.abs();
+            summary.gcd *= mul;
+            summary.lcm = summary.lcm.and_then(|lcm| lcm.checked_mul(mul));
+        }
+    }
+}
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+pub use hash_summary::*;
+mod hash_summary {
+    use super::*;
+    use std::hash::{Hash, Hasher};
+
+    /// A polynomial rolling hash summary, with a configurable base and modulus.
+    /// Two segments with equal values have equal summaries, and so segments can be compared
+    /// for equality by comparing their summaries. Different segments might collide, but with
+    /// low probability.
+    ///
+    /// This summary isn't commutative. In order to support reversals, it keeps both the hash
+    /// of the segment and the hash of the reversed segment.
+    ///
+    /// Any value implementing [`Hash`] can be summarized, by reducing its hash modulo `MODULUS`.
+    /// `MODULUS` should be a prime smaller than `2^63`, and `BASE` should be smaller than it.
+    ///
+    ///```
+    /// use grove::{SomeTree, splay::SplayTree};
+    /// use grove::example_data::{HashSummary, RevAction};
+    ///
+    /// type Hash = HashSummary<1_000_003, 1_000_000_007>;
+    /// let mut tree: SplayTree<(i32, Hash, RevAction)> =
+    ///     vec![1, 2, 3, 1, 2, 3, 3, 2, 1].into_iter().collect();
+    /// assert_eq!(tree.segment_summary(0..3), tree.segment_summary(3..6));
+    /// assert_ne!(tree.segment_summary(0..3), tree.segment_summary(6..9));
+    /// assert!(tree.segment_summary(3..9).is_palindrome());
+    ///
+    /// tree.act_segment(RevAction { to_reverse: true }, 6..9);
+    /// assert_eq!(tree.segment_summary(0..3), tree.segment_summary(6..9));
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct HashSummary<const BASE: u64, const MODULUS: u64> {
+        /// The size of the segment.
+        pub size: usize,
+        /// The hash of the segment.
+        pub hash: u64,
+        /// The hash of the reversed segment.
+        pub reverse_hash: u64,
+        /// `BASE` to the power of the size of the segment, modulo `MODULUS`.
+        pub power: u64,
+    }
+
+    impl<const BASE: u64, const MODULUS: u64> HashSummary<BASE, MODULUS> {
+        /// Returns whether the segment is a palindrome, i.e., equal to its reverse.
+        /// Might return false positives, with low probability.
+        pub fn is_palindrome(&self) -> bool {
+            self.hash == self.reverse_hash
+        }
+    }
+
+    impl<const BASE: u64, const MODULUS: u64> Add for HashSummary<BASE, MODULUS> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            HashSummary {
+                size: self.size + other.size,
+                hash: (mul_mod(self.hash, other.power, MODULUS) + other.hash) % MODULUS,
+                reverse_hash: (mul_mod(other.reverse_hash, self.power, MODULUS)
+                    + self.reverse_hash)
+                    % MODULUS,
+                power: mul_mod(self.power, other.power, MODULUS),
+            }
+        }
+    }
+
+    impl<const BASE: u64, const MODULUS: u64> Default for HashSummary<BASE, MODULUS> {
+        fn default() -> Self {
+            HashSummary {
+                size: 0,
+                hash: 0,
+                reverse_hash: 0,
+                power: 1 % MODULUS,
+            }
+        }
+    }
+
+    impl<const BASE: u64, const MODULUS: u64> SizedSummary for HashSummary<BASE, MODULUS> {
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    impl<V: Hash, const BASE: u64, const MODULUS: u64> ToSummary<HashSummary<BASE, MODULUS>> for V {
+        fn to_summary(&self) -> HashSummary<BASE, MODULUS> {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            self.hash(&mut hasher);
+            let hash = hasher.finish() % MODULUS;
+            HashSummary {
+                size: 1,
+                hash,
+                reverse_hash: hash,
+                power: BASE % MODULUS,
+            }
+        }
+    }
+
+    impl<const BASE: u64, const MODULUS: u64> ReverseSummary for HashSummary<BASE, MODULUS> {
+        fn reverse(&mut self) {
+            std::mem::swap(&mut self.hash, &mut self.reverse_hash);
+        }
+    }
+}
+
+pub use mod_num::*;
+mod mod_num {
+    use super::*;
+
+    /// A summary of numbers modulo `M`, keeping the size and the sum modulo `M` of a segment.
+    /// Since all of the arithmetic is done modulo `M`, sums of huge segments never overflow.
+    /// Values are `u64`s, and are reduced modulo `M` when summarized.
+    ///
+    /// `M` should be smaller than `2^63`, so that adding two numbers modulo `M` doesn't overflow.
+    ///
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::{ModAffineAction, ModNum};
+    ///
+    /// const M: u64 = 1_000_000_007;
+    /// let mut tree: AVLTree<ModNum<M>> = (1..=1000).map(|x| x * 1_000_000).collect();
+    /// // multiply the first half by `2^40`, and then subtract `1`
+    /// tree.act_segment(ModAffineAction { mul: 1 << 40, add: M - 1 }, ..500);
+    ///
+    /// let expected = (1..=1000u128)
+    ///     .map(|x| if x <= 500 { ((x * 1_000_000) << 40) - 1 } else { x * 1_000_000 })
+    ///     .sum::<u128>();
+    /// assert_eq!(tree.segment_summary(..).sum as u128, expected % M as u128);
+    /// assert_eq!(tree.segment_summary(..).size, 1000);
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct ModSum<const M: u64> {
+        /// The size of the segment.
+        pub size: usize,
+        /// The sum of all values in the segment, modulo `M`.
+        pub sum: u64,
+    }
+
+    impl<const M: u64> Add for ModSum<M> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            ModSum {
+                size: self.size + other.size,
+                sum: (self.sum + other.sum) % M,
+            }
+        }
+    }
+
+    impl<const M: u64> SizedSummary for ModSum<M> {
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    impl<const M: u64> ReverseSummary for ModSum<M> {
+        fn reverse(&mut self) {}
+    }
+
+    impl<const M: u64> ToSummary<ModSum<M>> for u64 {
+        fn to_summary(&self) -> ModSum<M> {
+            ModSum {
+                size: 1,
+                sum: self % M,
+            }
+        }
+    }
+
+    /// Actions of multiplying by a constant and then adding a constant, modulo `M`.
+    /// Values that are acted upon are reduced modulo `M`. See [`ModSum`].
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct ModAffineAction<const M: u64> {
+        /// A constant to multiply all the values in the segment with.
+        pub mul: u64,
+        /// A constant to add to all the values in the segment.
+        pub add: u64,
+    }
+
+    impl<const M: u64> Add for ModAffineAction<M> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            ModAffineAction {
+                mul: mul_mod(self.mul, other.mul, M),
+                add: (mul_mod(self.mul, other.add, M) + self.add) % M,
+            }
+        }
+    }
+
+    impl<const M: u64> Default for ModAffineAction<M> {
+        fn default() -> Self {
+            ModAffineAction { mul: 1, add: 0 }
+        }
+    }
+
+    impl<const M: u64> Action for ModAffineAction<M> {
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+    }
+
+    impl<const M: u64> Acts<u64> for ModAffineAction<M> {
+        fn act_inplace(&self, val: &mut u64) {
+            *val = (mul_mod(*val, self.mul, M) + self.add % M) % M;
+        }
+    }
+
+    impl<const M: u64> Acts<ModSum<M>> for ModAffineAction<M> {
+        fn act_inplace(&self, summary: &mut ModSum<M>) {
+            let added = mul_mod(summary.size as u64 % M, self.add % M, M);
+            summary.sum = (mul_mod(summary.sum, self.mul, M) + added) % M;
+        }
+    }
+}
+
+pub use overflow::*;
+mod overflow {
+    use super::*;
+
+    /// Primitive integer types, whose arithmetic can be checked or saturating.
+    /// Used by [`Checked`] and [`Saturating`].
+    pub trait PrimInt: Num {
+        /// Adds the numbers, or returns [`None`] on overflow.
+        fn checked_add(self, other: Self) -> Option<Self>;
+        /// Multiplies the numbers, or returns [`None`] on overflow.
+        fn checked_mul(self, other: Self) -> Option<Self>;
+        /// Adds the numbers, saturating at the numeric bounds instead of overflowing.
+        fn saturating_add(self, other: Self) -> Self;
+        /// Multiplies the numbers, saturating at the numeric bounds instead of overflowing.
+        fn saturating_mul(self, other: Self) -> Self;
+    }
+
+    macro_rules! impl_prim_int {
+        ($($t:ty),*) => {
+            $(
+                impl PrimInt for $t {
+                    fn checked_add(self, other: Self) -> Option<Self> {
+                        <$t>::checked_add(self, other)
+                    }
+                    fn checked_mul(self, other: Self) -> Option<Self> {
+                        <$t>::checked_mul(self, other)
+                    }
+                    fn saturating_add(self, other: Self) -> Self {
+                        <$t>::saturating_add(self, other)
+                    }
+                    fn saturating_mul(self, other: Self) -> Self {
+                        <$t>::saturating_mul(self, other)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_prim_int!(u8, u16, u32, u64, u128, usize, i16, i32, i64, i128, isize);
+
+    /// Actions of reversals, adding a constant, and multiplying by a constant, whose arithmetic
+    /// is checked. See [`Checked`].
+    pub type CheckedRevAffineAction<T = I> = RevAffineAction<Checked<T>>;
+
+    /// Actions of reversals, adding a constant, and multiplying by a constant, whose arithmetic
+    /// is saturating. See [`Saturating`].
+    pub type SaturatingRevAffineAction<T = I> = RevAffineAction<Saturating<T>>;
+
+    /// A number whose arithmetic is checked: a result that overflowed is recorded as
+    /// `Checked(None)`, and so is every result computed from it.
+    /// It can be used with [`NumSummary`] and [`RevAffineAction`], so that segment sums
+    /// that overflow are reported, instead of silently wrapping around.
+    ///
+    /// If the values have mixed signs, an overflow is reported whenever a partial sum
+    /// overflows, which can depend on the structure of the tree.
+    ///
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{Checked, CheckedRevAffineAction, NumSummary};
+    ///
+    /// type D = (Checked<i32>, NumSummary<Checked<i32>>, CheckedRevAffineAction<i32>);
+    /// let mut tree: Treap<D> = (0..1000).map(|x| Checked(Some(x))).collect();
+    /// assert_eq!(tree.segment_summary(..).sum, Checked(Some(499_500)));
+    ///
+    /// let action = CheckedRevAffineAction {
+    ///     to_reverse: false,
+    ///     mul: Checked(Some(10_000)),
+    ///     add: Checked(Some(0)),
+    /// };
+    /// tree.act_segment(action, 500..);
+    /// assert!(tree.segment_summary(..).sum.overflowed());
+    /// assert_eq!(tree.segment_summary(..500).sum, Checked(Some(124_750)));
+    /// assert_eq!(tree.segment_summary(500..600).sum, Checked(Some(549_500_000)));
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug)]
+    pub struct Checked<T>(pub Option<T>);
+
+    impl<T> Checked<T> {
+        /// Returns whether this number is the result of an overflow.
+        pub fn overflowed(&self) -> bool {
+            self.0.is_none()
+        }
+    }
+
+    impl<T: PrimInt> Add for Checked<T> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            Checked(self.0.zip(other.0).and_then(|(a, b)| a.checked_add(b)))
+        }
+    }
+
+    impl<T: PrimInt> std::ops::Mul for Checked<T> {
+        type Output = Self;
+        fn mul(self, other: Self) -> Self {
+            Checked(self.0.zip(other.0).and_then(|(a, b)| a.checked_mul(b)))
+        }
+    }
+
+    impl<T: From<u8>> From<u8> for Checked<T> {
+        fn from(x: u8) -> Self {
+            Checked(Some(T::from(x)))
+        }
+    }
+
+    /// Fails if the number overflowed, or if it doesn't fit in a `usize`.
+    impl<T: TryInto<usize>> TryFrom<Checked<T>> for usize {
+        type Error = ();
+        fn try_from(x: Checked<T>) -> Result<usize, ()> {
+            x.0.ok_or(())?.try_into().map_err(|_| ())
+        }
+    }
+
+    /// A number whose arithmetic is saturating: results that overflow are clamped
+    /// to the numeric bounds.
+    /// It can be used with [`NumSummary`] and [`RevAffineAction`].
+    ///
+    /// Saturating addition is only associative if all of the values have the same sign.
+    /// Otherwise, the summaries can depend on the structure of the tree.
+    ///
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::{NumSummary, Saturating, SaturatingRevAffineAction};
+    ///
+    /// type D = (Saturating<u8>, NumSummary<Saturating<u8>>, SaturatingRevAffineAction<u8>);
+    /// let mut tree: AVLTree<D> = (0..10).map(Saturating).collect();
+    /// let action = SaturatingRevAffineAction {
+    ///     to_reverse: true,
+    ///     mul: Saturating(30),
+    ///     add: Saturating(0),
+    /// };
+    /// tree.act_segment(action, 5..);
+    ///
+    /// let values: Vec<u8> = tree.iter().map(|x| x.0).collect();
+    /// assert_eq!(values, vec![0, 1, 2, 3, 4, 255, 240, 210, 180, 150]);
+    /// assert_eq!(tree.segment_summary(..).sum, Saturating(255));
+    /// assert_eq!(tree.segment_summary(..5).sum, Saturating(10));
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug, Default)]
+    pub struct Saturating<T>(pub T);
+
+    impl<T: PrimInt> Add for Saturating<T> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            Saturating(self.0.saturating_add(other.0))
+        }
+    }
+
+    impl<T: PrimInt> std::ops::Mul for Saturating<T> {
+        type Output = Self;
+        fn mul(self, other: Self) -> Self {
+            Saturating(self.0.saturating_mul(other.0))
+        }
+    }
+
+    impl<T: From<u8>> From<u8> for Saturating<T> {
+        fn from(x: u8) -> Self {
+            Saturating(T::from(x))
+        }
+    }
+
+    /// Fails if the number doesn't fit in a `usize`.
+    impl<T: TryInto<usize>> TryFrom<Saturating<T>> for usize {
+        type Error = T::Error;
+        fn try_from(x: Saturating<T>) -> Result<usize, T::Error> {
+            x.0.try_into()
+        }
+    }
+}
+
+pub use arg_summary::*;
+mod arg_summary {
+    use super::*;
+    use std::cmp::Ordering;
+
+    /// An extreme value of a segment, along with the positions where it occurs.
+    /// The positions are offsets from the start of the segment.
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct Extremum<T> {
+        /// The extreme value.
+        pub value: T,
+        /// The position of its first occurrence in the segment.
+        pub first: usize,
+        /// The position of its last occurrence in the segment.
+        pub last: usize,
+    }
+
+    /// A summary that keeps the minimum and maximum of a segment, along with their positions,
+    /// so that the position of the minimum of a segment can be found in a single query.
+    /// Generic over the number type, see [`Num`].
+    ///
+    /// The first and last positions are both kept, so that they are correct after reversals.
+    ///
+    ///```
+    /// use grove::{SomeTree, splay::SplayTree};
+    /// use grove::example_data::{ArgSummary, RevAffineAction};
+    ///
+    /// type D = (i32, ArgSummary, RevAffineAction);
+    /// let mut tree: SplayTree<D> = vec![5, 1, 4, 1, 5, 9, 2, 6, 5, 3].into_iter().collect();
+    /// let min = tree.segment_summary(2..8).min.unwrap();
+    /// assert_eq!((min.value, min.first, min.last), (1, 1, 1));
+    /// let max = tree.segment_summary(..5).max.unwrap();
+    /// assert_eq!((max.value, max.first, max.last), (5, 0, 4));
+    ///
+    /// tree.act_segment(RevAffineAction { to_reverse: true, mul: -1, add: 0 }, 2..8);
+    /// // now the values are 5, 1, -6, -2, -9, -5, -1, -4, 5, 3
+    /// let min = tree.segment_summary(..).min.unwrap();
+    /// assert_eq!((min.value, min.first), (-9, 4));
+    /// let max = tree.segment_summary(..).max.unwrap();
+    /// assert_eq!((max.value, max.first, max.last), (5, 0, 8));
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct ArgSummary<T = I> {
+        /// The size of the segment.
+        pub size: usize,
+        /// The minimum of the segment. [`None`] if the segment is empty.
+        pub min: Option<Extremum<T>>,
+        /// The maximum of the segment. [`None`] if the segment is empty.
+        pub max: Option<Extremum<T>>,
+    }
+
+    // Merges the extrema of two adjacent segments, keeping the value
+    // that compares as `keep` to the other one.
+    fn merge<T: Ord>(
+        left: Option<Extremum<T>>,
+        right: Option<Extremum<T>>,
+        offset: usize,
+        keep: Ordering,
+    ) -> Option<Extremum<T>> {
+        let right = right.map(|r| Extremum {
+            value: r.value,
+            first: r.first + offset,
+            last: r.last + offset,
+        });
+        match (left, right) {
+            (Some(l), Some(r)) => Some(match l.value.cmp(&r.value) {
+                Ordering::Equal => Extremum {
+                    value: l.value,
+                    first: l.first,
+                    last: r.last,
+                },
+                ordering if ordering == keep => l,
+                _ => r,
+            }),
+            (l, r) => l.or(r),
+        }
+    }
+
+    impl<T: Num> Add for ArgSummary<T> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            ArgSummary {
+                size: self.size + other.size,
+                min: merge(self.min, other.min, self.size, Ordering::Less),
+                max: merge(self.max, other.max, self.size, Ordering::Greater),
+            }
+        }
+    }
+
+    impl<T> Default for ArgSummary<T> {
+        fn default() -> Self {
+            ArgSummary {
+                size: 0,
+                min: None,
+                max: None,
+            }
+        }
+    }
+
+    impl<T> SizedSummary for ArgSummary<T> {
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    impl<T> ReverseSummary for ArgSummary<T> {
+        fn reverse(&mut self) {
+            let size = self.size;
+            for extremum in [&mut self.min, &mut self.max].into_iter().flatten() {
+                let first = extremum.first;
+                extremum.first = size - 1 - extremum.last;
+                extremum.last = size - 1 - first;
+            }
+        }
+    }
+
+    impl<T: Num> ToSummary<ArgSummary<T>> for T {
+        fn to_summary(&self) -> ArgSummary<T> {
+            let extremum = Extremum {
+                value: *self,
+                first: 0,
+                last: 0,
+            };
+            ArgSummary {
+                size: 1,
+                min: Some(extremum),
+                max: Some(extremum),
+            }
+        }
+    }
+
+    impl<T: Num> Acts<ArgSummary<T>> for RevAffineAction<T> {
+        fn act_inplace(&self, summary: &mut ArgSummary<T>) {
+            if self.to_reverse {
+                summary.reverse();
+            }
+            let zero = T::from(0);
+            if self.mul < zero {
+                std::mem::swap(&mut summary.min, &mut summary.max);
+            }
+            for extremum in [&mut summary.min, &mut summary.max].into_iter().flatten() {
+                extremum.value = extremum.value * self.mul + self.add;
+                if self.mul == zero {
+                    // all of the values become equal
+                    extremum.first = 0;
+                    extremum.last = summary.size - 1;
+                }
+            }
+        }
+    }
+}
+
+pub use bits::*;
+mod bits {
+    use super::*;
+    /// Information about the runs of a single bit value in a segment.
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct Runs {
+        /// The length of the longest run at the start of the segment.
+        pub prefix: usize,
+        /// The length of the longest run at the end of the segment.
+        pub suffix: usize,
+        /// The length of the longest run in the segment.
+        pub longest: usize,
+    }
+
+    impl Runs {
+        fn concat(self, self_size: usize, other: Runs, other_size: usize) -> Runs {
+            Runs {
+                prefix: if self.prefix == self_size {
+                    self_size + other.prefix
+                } else {
+                    self.prefix
+                },
+                suffix: if other.suffix == other_size {
+                    self.suffix + other_size
+                } else {
+                    other.suffix
+                },
+                longest: std::cmp::max(
+                    std::cmp::max(self.longest, other.longest),
+                    self.suffix + other.prefix,
+                ),
+            }
+        }
+
+        fn reverse(&mut self) {
+            std::mem::swap(&mut self.prefix, &mut self.suffix);
+        }
+    }
+
+    /// A summary for sequences of bits, keeping the number of ones and the runs
+    /// of ones in a segment.
+    /// The runs of zeros are kept as well, in order to support flipping.
+    ///
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{BitData, FlipAction};
+    ///
+    /// let bits = "0110111001".chars().map(|c| c == '1');
+    /// let mut tree: Treap<BitData> = bits.collect();
+    /// let summary = tree.segment_summary(..);
+    /// assert_eq!(summary.ones, 6);
+    /// assert_eq!(summary.ones_runs.longest, 3);
+    /// assert_eq!(summary.ones_runs.suffix, 1);
+    ///
+    /// tree.act_segment(FlipAction { flip: true }, 6..9);
+    /// // now the bits are 0110110111
+    /// let summary = tree.segment_summary(3..);
+    /// assert_eq!(summary.ones, 5);
+    /// assert_eq!(summary.ones_runs.longest, 3);
+    /// assert_eq!(summary.zeros_runs.longest, 1);
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct BitSummary {
+        /// The size of the segment.
+        pub size: usize,
+        /// The number of ones in the segment.
+        pub ones: usize,
+        /// The runs of ones in the segment.
+        pub ones_runs: Runs,
+        /// The runs of zeros in the segment.
+        pub zeros_runs: Runs,
+    }
+
+    impl Add for BitSummary {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            BitSummary {
+                size: self.size + other.size,
+                ones: self.ones + other.ones,
+                ones_runs: self
+                    .ones_runs
+                    .concat(self.size, other.ones_runs, other.size),
+                zeros_runs: self
+                    .zeros_runs
+                    .concat(self.size, other.zeros_runs, other.size),
+            }
+        }
+    }
+
+    impl SizedSummary for BitSummary {
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    impl ToSummary<BitSummary> for bool {
+        fn to_summary(&self) -> BitSummary {
+            let single = Runs {
+                prefix: 1,
+                suffix: 1,
+                longest: 1,
+            };
+            BitSummary {
+                size: 1,
+                ones: *self as usize,
+                ones_runs: if *self { single } else { Runs::default() },
+                zeros_runs: if *self { Runs::default() } else { single },
+            }
+        }
+    }
+
+    /// An action that flips all of the bits in a segment, or keeps them as they are.
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct FlipAction {
+        /// Whether to flip the bits in the segment.
+        pub flip: bool,
+    }
+
+    impl Add for FlipAction {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            FlipAction {
+                flip: self.flip != other.flip,
+            }
+        }
+    }
+
+    impl Action for FlipAction {
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+    }
+
+    impl InvertibleAction for FlipAction {
+        fn inverse(&self) -> Self {
+            *self
+        }
+    }
+
+    impl Acts<bool> for FlipAction {
+        fn act_inplace(&self, val: &mut bool) {
+            *val ^= self.flip;
+        }
+    }
+
+    impl Acts<BitSummary> for FlipAction {
+        fn act_inplace(&self, summary: &mut BitSummary) {
+            if self.flip {
+                summary.ones = summary.size - summary.ones;
+                std::mem::swap(&mut summary.ones_runs, &mut summary.zeros_runs);
+            }
+        }
+    }
+
+    impl Acts<bool> for RevAction {
+        fn act_inplace(&self, _val: &mut bool) {}
+    }
+
+    impl ReverseSummary for BitSummary {
+        fn reverse(&mut self) {
+            self.ones_runs.reverse();
+            self.zeros_runs.reverse();
+        }
+    }
+}
+
+pub use text::*;
+mod text {
+    use super::*;
+
+    /// A summary for text, where every value is a chunk of text, such as a [`String`].
+    /// It counts the bytes, chars and newlines in a segment, and keeps the number of chars
+    /// in the segment's last line, i.e., after its last newline.
+    ///
+    /// The counts are commutative, but the last line isn't: it depends on the order
+    /// of the chunks. Segments are indexed by chars, and chunks can be split at char indices,
+    /// so that a tree of chunks can be used as a tree of chars, whose nodes are wide.
+    ///
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::TextData;
+    ///
+    /// let chunks = vec!["hello, ", "wörld\nand", " goodbye\n", "wörld"];
+    /// let mut tree: Treap<TextData> = chunks.into_iter().map(String::from).collect();
+    /// let summary = tree.segment_summary(..);
+    /// assert_eq!(summary.chars, 30);
+    /// assert_eq!(summary.bytes, 32);
+    /// assert_eq!(summary.newlines, 2);
+    /// assert_eq!(summary.last_line_chars, 5);
+    ///
+    /// // the column of the char at index 20, which is inside of the third chunk
+    /// assert!(tree.split_value_at(20));
+    /// assert_eq!(tree.segment_summary(..20).last_line_chars, 7);
+    /// assert_eq!(tree.iter().nth(3).unwrap(), "dbye\n");
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct TextSummary {
+        /// The number of bytes in the text.
+        pub bytes: usize,
+        /// The number of chars in the text.
+        pub chars: usize,
+        /// The number of `'\n'` chars in the text.
+        pub newlines: usize,
+        /// The number of chars after the last `'\n'` in the text, or all of the chars
+        /// if there is no `'\n'`.
+        pub last_line_chars: usize,
+    }
+
+    impl Add for TextSummary {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            TextSummary {
+                bytes: self.bytes + other.bytes,
+                chars: self.chars + other.chars,
+                newlines: self.newlines + other.newlines,
+                last_line_chars: if other.newlines == 0 {
+                    self.last_line_chars + other.last_line_chars
+                } else {
+                    other.last_line_chars
+                },
+            }
+        }
+    }
+
+    /// Texts are indexed by chars.
+    impl SizedSummary for TextSummary {
+        fn size(&self) -> usize {
+            self.chars
+        }
+    }
+
+    impl ToSummary<TextSummary> for str {
+        fn to_summary(&self) -> TextSummary {
+            let chars = self.chars().count();
+            TextSummary {
+                bytes: self.len(),
+                chars,
+                newlines: self.bytes().filter(|&b| b == b'\n').count(),
+                last_line_chars: match self.rfind('\n') {
+                    Some(i) => self[i + 1..].chars().count(),
+                    None => chars,
+                },
+            }
+        }
+    }
+
+    impl ToSummary<TextSummary> for String {
+        fn to_summary(&self) -> TextSummary {
+            self.as_str().to_summary()
+        }
+    }
+
+    /// Splits the string by char index.
+    impl SplitValue for String {
+        fn split_off(&mut self, index: usize) -> String {
+            let byte_index = self
+                .char_indices()
+                .nth(index)
+                .map_or(self.len(), |(i, _)| i);
+            String::split_off(self, byte_index)
+        }
+    }
+}
+
+pub use matrix::*;
+mod matrix {
+    use super::*;
+
+    /// A 2×2 matrix, that is both a value and a summary: the summary of a segment is the product
+    /// of its matrices, in order. Generic over the number type, see [`Num`].
+    ///
+    /// This can be used for linear recurrences whose steps can be edited, such as
+    /// `x_{i+1} = a_i * x_i + c_i * x_{i-1}`: every step is the matrix `[[a_i, 1], [c_i, 0]]`,
+    /// and the product of a segment of steps maps the row vector `(x_i, x_{i-1})` to the state
+    /// after all of the steps.
+    ///
+    /// Matrix multiplication isn't commutative. Reversing a segment transposes its product,
+    /// and therefore it also transposes every matrix in the segment. Hence, reversals are only
+    /// meaningful for segments of symmetric matrices, such as the Fibonacci matrix `[[1, 1], [1, 0]]`.
+    ///
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{Matrix2, Pair, RevAction, Size};
+    ///
+    /// // the sizes are needed in order to locate steps by their indices
+    /// type D = (Matrix2, Pair<Size, Matrix2>, RevAction);
+    /// let fib = Matrix2 { entries: [[1, 1], [1, 0]] };
+    /// let mut steps = vec![fib; 10];
+    /// let mut tree: Treap<D> = steps.iter().cloned().collect();
+    /// assert_eq!(tree.segment_summary(..).1.entries, [[89, 55], [55, 34]]);
+    /// assert_eq!(tree.segment_summary(3..8).1.entries, [[8, 5], [5, 3]]);
+    ///
+    /// // change the recurrence of a single step to `x_{i+1} = x_i + 2 * x_{i-1}`
+    /// steps[5] = Matrix2 { entries: [[1, 1], [2, 0]] };
+    /// tree.slice(5..=5).delete();
+    /// tree.slice(5..5).insert(steps[5]);
+    ///
+    /// let (mut x, mut prev) = (1, 0);
+    /// for step in &steps {
+    ///     let [[a, b], [c, d]] = step.entries;
+    ///     (x, prev) = (a * x + c * prev, b * x + d * prev);
+    /// }
+    /// let product = tree.segment_summary(..).1;
+    /// assert_eq!(product.entries[0], [x, prev]);
+    ///
+    /// tree.act_segment(RevAction { to_reverse: true }, ..);
+    /// assert_eq!(tree.segment_summary(..).1, product.transpose());
+    /// # tree.assert_correctness();
+    ///```
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+    pub struct Matrix2<T = I> {
+        /// The entries of the matrix, row by row.
+        pub entries: [[T; 2]; 2],
+    }
+
+    impl<T: Num> Matrix2<T> {
+        /// Returns the transposed matrix.
+        pub fn transpose(&self) -> Self {
+            let [[a, b], [c, d]] = self.entries;
+            Matrix2 {
+                entries: [[a, c], [b, d]],
+            }
+        }
+    }
+
+    /// Matrix multiplication.
+    impl<T: Num> Add for Matrix2<T> {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            let [[a, b], [c, d]] = self.entries;
+            let [[e, f], [g, h]] = other.entries;
+            Matrix2 {
+                entries: [
+                    [a * e + b * g, a * f + b * h],
+                    [c * e + d * g, c * f + d * h],
+                ],
+            }
+        }
+    }
+
+    /// The identity matrix.
+    impl<T: Num> Default for Matrix2<T> {
+        fn default() -> Self {
+            let (zero, one) = (T::from(0), T::from(1));
+            Matrix2 {
+                entries: [[one, zero], [zero, one]],
+            }
+        }
+    }
+
+    impl<T: Num> ToSummary<Matrix2<T>> for Matrix2<T> {
+        fn to_summary(&self) -> Matrix2<T> {
+            *self
+        }
+    }
+
+    impl<T: Num> ReverseSummary for Matrix2<T> {
+        fn reverse(&mut self) {
+            *self = self.transpose();
+        }
+    }
+}
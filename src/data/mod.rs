@@ -60,9 +60,13 @@ use std::ops::Add;
 ///  > and similarly as well for [`Self::Value`].
 ///
 /// Additional requirements:
-/// * Decide whether to reverse the subsegment it is acted upon. This is done by implementing the
-/// [`Action::to_reverse()`] function. If you do not want to reverse segments, you can use the default implementation,
-/// which always returns false.
+/// * Decide whether to reverse the subsegment it is acted upon. This is done by setting
+///   [`Action::REVERSIBLE`] to `true` and implementing the [`Action::to_reverse()`] function.
+///   If you do not want to reverse segments, you can use the default implementations,
+///   and the trees will skip reversal handling altogether.
+/// * Decide whether the action depends on the indices of the elements in the subsegment,
+///   by implementing [`Action::is_indexed()`] and [`Acts::skip()`]. The default implementations
+///   are for actions that act the same on all of the elements.
 /// * Have an identity action and empty summary: These are represented by the bounds [`Self::Action`]`: `[`Default`],
 /// [`Self::Summary`]`: `[`Default`].
 /// * Test actions for being the identity. This is represented by [`Action::is_identity()`].
@@ -85,9 +89,65 @@ pub trait Data {
     type Value: ToSummary<Self::Summary>;
     /// The summaries of values over segments. When querying a segment,
     /// you get a summary of the segment, represented by a value of type `Self::Summary`.
-    type Summary: Copy + Default + Add<Output = Self::Summary>;
+    ///
+    /// Summaries don't have to be `Copy`, e.g., they can hold a `String`.
+    /// However, they are cloned often, so cloning them should be cheap.
+    type Summary: Clone + Default + Add<Output = Self::Summary>;
     /// The actions you can perform on the values
     type Action: Action + Acts<Self::Value> + Acts<Self::Summary>;
+
+    /// A hook that is called whenever a pending action is pushed down into a node,
+    /// i.e., applied to the node's value and to the summary of its subtree.
+    /// It receives the node's value and the summary of its subtree, after the action
+    /// has been applied. The default implementation does nothing.
+    ///
+    /// This can be used in order to maintain external indexes of the values,
+    /// or in order to debug actions.
+    /// In order to use the hooks, implement [`Data`] for your own marker type.
+    ///
+    /// Every node goes through the same lifecycle: before its value or its sons are read
+    /// or modified, it is accessed, which pushes its pending action down and calls this hook.
+    /// After its value or its sons were modified, it is rebuilt, which recomputes its summary
+    /// and calls [`Data::on_rebuild`]. Custom algorithms on [`BasicTree`](crate::basic_tree::BasicTree)s
+    /// go through the same lifecycle, using the public
+    /// [`BasicNode::access`](crate::basic_tree::BasicNode::access) and
+    /// [`BasicNode::rebuild`](crate::basic_tree::BasicNode::rebuild) functions.
+    ///```
+    /// use grove::{Data, SomeTree, treap::Treap};
+    /// use grove::example_data::{NumSummary, RevAffineAction};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static PUSHED: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// struct Counted;
+    /// impl Data for Counted {
+    ///     type Value = i32;
+    ///     type Summary = NumSummary;
+    ///     type Action = RevAffineAction;
+    ///
+    ///     fn on_access(_value: &i32, _summary: &NumSummary) {
+    ///         PUSHED.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let mut tree: Treap<Counted> = (1..=8).collect();
+    /// assert_eq!(PUSHED.load(Ordering::Relaxed), 0);
+    /// tree.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 3 }, ..);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (4..=11).rev().collect::<Vec<_>>());
+    /// // the action was pushed down to all of the nodes
+    /// assert!(PUSHED.load(Ordering::Relaxed) >= 8);
+    /// # tree.assert_correctness();
+    ///```
+    fn on_access(_value: &Self::Value, _summary: &Self::Summary) {}
+
+    /// A hook that is called whenever a node is rebuilt, i.e., the summary of its subtree
+    /// is recomputed from its value and its sons, e.g., after the node's value was modified,
+    /// or the tree's structure changed.
+    /// It receives the node's value and the new summary of its subtree.
+    /// The default implementation does nothing.
+    ///
+    /// See [`Data::on_access`].
+    fn on_rebuild(_value: &Self::Value, _summary: &Self::Summary) {}
 }
 
 /// A [`Data`] implementation for a generic triplet of value, summary and action types,
@@ -95,7 +155,7 @@ pub trait Data {
 impl<V, S, A> Data for (V, S, A)
 where
     V: ToSummary<S>,
-    S: Copy + Default + Add<Output = S>,
+    S: Clone + Default + Add<Output = S>,
     A: Action + Acts<V> + Acts<S>,
 {
     type Value = V;
@@ -135,12 +195,21 @@ where
 ///   updating the whole tree.
 ///   This means that the action respects the monoid structure of the summaries.
 ///
-/// * If the action reverses segments, (i.e, if `D::to_reverse(action) == true`), then it has to satisfy a 'cross'
+///   If the action depends on the index (i.e, if `action.is_indexed() == true`), the action
+///   applied to `summary2` is shifted by `summary1` instead, see [`Acts::skip`]:
+///   ```notrust
+///   action.act(summary1 + summary2) == action.act(summary1) + action.skip(&summary1).act(summary2)
+///   ```
+///
+/// * If the action reverses segments, (i.e, if `action.to_reverse() == true`, which requires
+///   [`Action::REVERSIBLE`]), then it has to satisfy a 'cross'
 ///   a cross version instead:
 ///   ```notrust
 ///   action.act(summary1 + summary2) == action.act(summary2) + action.act(summary1)
 ///   ```
 ///
+///   For non-commutative summaries this usually means reversing the summary, see [`ReverseSummary`].
+///
 ///   And it should also satisfy that composing two actions
 ///   xor's their [`Action::to_reverse()`] results:
 ///   ```notrust
@@ -152,19 +221,53 @@ where
 ///   action.act(value).to_summary() === action.act(value.to_summary())
 ///   ```
 ///
-pub trait Action: Copy + Default + Add<Output = Self> {
+pub trait Action: Clone + Default + Add<Output = Self> {
+    /// Whether actions of this type can reverse segments at all. The default is `false`.
+    ///
+    /// This marks the data as reversible at compile time: if it is `false`, the trees skip
+    /// all of their reversal handling statically, e.g., [`crate::avl::AVLTree`] always uses its
+    /// faster non-reversing algorithm for acting on segments.
+    /// If it is `false`, [`Action::to_reverse()`] must always return `false`. This is checked
+    /// whenever actions are applied to trees, which panic if such an action reverses.
+    const REVERSIBLE: bool = false;
+
     /// Test whether this action is the identity action.
-    fn is_identity(self) -> bool;
+    fn is_identity(&self) -> bool;
 
-    /// This function should be implemented if you want to be able to reverse subsegments of your tree.
+    /// This function should be implemented if you want to be able to reverse subsegments of your tree,
+    /// together with setting [`Action::REVERSIBLE`] to `true`.
     /// The default implementation always returns `false`.
     ///
     /// This function should return whether this action reverses the segment it is applied to.
-    fn to_reverse(self) -> bool {
+    fn to_reverse(&self) -> bool {
+        false
+    }
+
+    /// This function should be implemented if you want to use actions that depend on the index
+    /// of the element within the acted segment, e.g., adding `i * c` to the `i`-th element
+    /// of the segment. The default implementation always returns `false`.
+    ///
+    /// This function should return whether this action depends on the index. If it does,
+    /// the action has to implement [`Acts::skip`] for the summaries, so that the trees can
+    /// push it down to the parts of a segment.
+    fn is_indexed(&self) -> bool {
         false
     }
 }
 
+/// Actions that can be undone: every action has an inverse action, so that the actions form a group.
+/// It must obey these rules:
+/// ```notrust
+/// action.inverse() + action === action + action.inverse() === default()
+/// action.inverse().to_reverse() === action.to_reverse()
+/// ```
+/// Therefore, applying `action.inverse()` to a segment that `action` was applied to
+/// restores the segment's original values.
+pub trait InvertibleAction: Action {
+    /// Returns the inverse action.
+    fn inverse(&self) -> Self;
+}
+
 /// Trait representation actions on a type `V`. If `A: Acts<V>` that means that given any `action: A`,
 /// we can apply it to any `val: V`. This trait is used to represent the actions on
 /// values and summaries used by segment trees.
@@ -178,6 +281,68 @@ pub trait Acts<V> {
         self.act_inplace(&mut object);
         object
     }
+
+    /// For actions that depend on the index (see [`Action::is_indexed`]): returns the
+    /// action that this action applies to the rest of a segment, after a prefix of the segment
+    /// whose summary, before applying the action, is `prefix`.
+    /// The default implementation returns the action itself.
+    ///
+    /// Here `V` is the summary type. It should obey these rules:
+    /// ```notrust
+    /// action.act(summary1 + summary2) === action.act(summary1) + action.skip(&summary1).act(summary2)
+    /// action.skip(&(summary1 + summary2)) === action.skip(&summary1).skip(&summary2)
+    /// (action2 + action1).skip(&summary) === action2.skip(&action1.act(summary)) + action1.skip(&summary)
+    /// ```
+    /// If the action also reverses the segment, `prefix` is the summary of the skipped values
+    /// in their original order, before the reversal.
+    fn skip(&self, _prefix: &V) -> Self
+    where
+        Self: Clone,
+    {
+        self.clone()
+    }
+}
+
+/// A hook for reversing summaries, for summaries that support reversals.
+///
+/// `summary.reverse()` should turn the summary of a segment into the summary of the same
+/// segment in reverse order. For commutative summaries this does nothing, but non-commutative
+/// summaries usually need to swap some fields, e.g., the prefix and suffix data, or a
+/// forward and a backward hash. It should obey these rules:
+/// ```notrust
+/// (summary1 + summary2).reverse() === summary2.reverse() + summary1.reverse()
+/// value.to_summary().reverse() === value.to_summary()
+/// ```
+///
+/// Actions that reverse segments are responsible for reversing the summaries they act on,
+/// and can call this hook in order to do that. For example,
+/// [`example_data::RevAction`] acts on every summary that implements this trait.
+///
+/// # Non-commutative summaries
+///
+/// The trees never assume that summary addition is commutative: summaries are always added
+/// in the order of the segments they represent, e.g., `left + node + right`, and reversals
+/// are handled by swapping the sons of a node and acting on the summaries with the reversing
+/// action. Therefore, non-commutative summaries are safe to use, as long as the reversing actions
+/// follow the 'cross' rule in the [`Action`] documentation.
+pub trait ReverseSummary {
+    /// Reverses the summary in-place.
+    fn reverse(&mut self);
+}
+
+/// A trait for wide values, i.e., values that represent several elements at once,
+/// such as a run of equal elements, or an interval.
+///
+/// The size of a wide value, as reported by [`SizedSummary::size`] on its summary,
+/// can be bigger than `1`. The index locators already treat a wide value as occupying all of
+/// its indices. In order to act on or query a segment whose boundary falls inside a wide value,
+/// the value has to be split first, using [`crate::SomeTree::split_value_at`].
+pub trait SplitValue: Sized {
+    /// Splits the value in two: the first `index` elements are kept in `self`,
+    /// and the rest are returned.
+    ///
+    /// `index` will always be strictly between `0` and the size of the value.
+    fn split_off(&mut self, index: usize) -> Self;
 }
 
 /// This trait is implemented by Values,
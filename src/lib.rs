@@ -139,6 +139,8 @@
 #[macro_use]
 extern crate derive_destructure;
 
+pub mod algorithms;
+pub mod collections;
 pub mod data;
 pub mod locators;
 pub mod trees;
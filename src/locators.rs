@@ -9,6 +9,8 @@
 //! but leads the locator into a space between nodes, where the node will be inserted.
 
 use crate::*;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 /// This is the result type that a `locator` returns when queried about a specific node.
 /// See [`Locator`].
@@ -42,6 +44,19 @@ use LocResult::*;
 /// that might not be the case, using interior mutability.
 /// Locators must be [`Clone`], in order for usage to be comfortable. This can always be achieved
 /// by taking a reference.
+///
+/// # Call order
+/// Locators with state (using interior mutability, or [`StatefulLocator`] for `FnMut` closures)
+/// are allowed, as long as their answers don't depend on that state. The state can be used
+/// to observe the search (e.g., counting calls) or to memoize answers.
+/// The tree operations only guarantee that:
+/// * Every call receives the correct summaries and the current value of a node.
+/// * While descending, a node is queried before any of its descendants.
+/// * When a segment is split into its left and right halves, the left half is handled first.
+///
+/// Beyond that, the tree may query the same node more than once, may clone the locator,
+/// and the set of queried nodes depends on the tree's shape, which depends on the balancing
+/// algorithm (splay trees restructure themselves even on queries).
 pub trait Locator<D: Data>: Clone {
     /// Looks at a specific node's value, and its context (the summaries to the right and left),
     /// and decides whether to go left, right, or accept the node.
@@ -57,6 +72,73 @@ where
     }
 }
 
+/// A locator that wraps an `FnMut` closure, or any other mutable state.
+/// The state is shared between all clones of the locator, so that it can be
+/// inspected after the locator has been used. See the [`Locator`] documentation
+/// about the call order contract.
+///
+/// The closure must not be called re-entrantly, i.e., it must not use the same locator
+/// in a nested tree operation. Otherwise, it panics.
+///```
+/// use grove::{SomeTree, treap::Treap};
+/// use grove::locators::{StatefulLocator, LocResult};
+/// use grove::example_data::{SizeData, Size};
+///
+/// let mut tree: Treap<SizeData<i32>> = (0..100).collect();
+/// let mut calls = 0;
+/// let locator = StatefulLocator::new(|left: Size, _: &i32, _: Size| {
+///     calls += 1;
+///     if left.size < 40 { LocResult::GoRight } else if left.size >= 45 { LocResult::GoLeft }
+///     else { LocResult::Accept }
+/// });
+/// let summary = tree.segment_summary(locator.clone());
+/// assert_eq!(summary.size, 5);
+/// drop(locator);
+/// assert!(calls > 0);
+/// # tree.assert_correctness();
+///```
+pub struct StatefulLocator<F> {
+    state: Rc<RefCell<F>>,
+}
+
+impl<F> StatefulLocator<F> {
+    /// Wraps the given closure or state.
+    pub fn new(state: F) -> Self {
+        StatefulLocator {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// Gives access to the inner state, e.g., in order to read what it has recorded.
+    /// Panics if called from inside the locator itself.
+    pub fn with_state<R, G: FnOnce(&mut F) -> R>(&self, f: G) -> R {
+        f(&mut self.state.borrow_mut())
+    }
+
+    /// Returns the inner state, if this is the only clone of the locator that is left.
+    pub fn into_inner(self) -> Option<F> {
+        Rc::try_unwrap(self.state).ok().map(RefCell::into_inner)
+    }
+}
+
+// the default clone implementation requires that F: Clone, and also doesn't share the state
+impl<F> Clone for StatefulLocator<F> {
+    fn clone(&self) -> Self {
+        StatefulLocator {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<D: Data, F> Locator<D> for StatefulLocator<F>
+where
+    F: FnMut(D::Summary, &D::Value, D::Summary) -> LocResult,
+{
+    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+        (self.state.borrow_mut())(left, node, right)
+    }
+}
+
 /// Returns the result of the locator at the walker
 /// Returns None if the walker is at an empty position
 pub fn query_locator<W, D: Data, L>(walker: &mut W, locator: &L) -> Option<LocResult>
@@ -464,6 +546,153 @@ where
     }
 }
 
+/// A saved position of a walker, that can later be used as a locator to return to the same
+/// position, even after the tree was modified. Created by [`SomeWalker::bookmark`].
+///
+/// A bookmark is either at a value, or at an empty position between two values, and
+/// it records the position by its index, i.e., by the size of the summary to its left.
+/// The tree's shape isn't recorded, since trees restructure themselves even on queries.
+/// Therefore, a bookmark stays valid across any changes that don't change the number of
+/// values before its position: changing values, applying actions that don't reverse,
+/// and inserting or removing values after its position.
+///
+/// Inserting or removing values before the bookmark's position invalidates it, unless it
+/// is updated with [`Bookmark::on_insert`] or [`Bookmark::on_remove`], which is what an
+/// editor would do with its cursors after every edit. Reversing actions invalidate it too.
+///```
+/// use grove::{SomeTree, SomeWalker, ModifiableWalker, treap::Treap};
+/// use grove::locators::BookmarkError;
+/// use grove::example_data::StdNum;
+///
+/// let mut tree: Treap<StdNum> = (0..10).collect();
+/// let mut bookmark = tree.search(6).bookmark();
+///
+/// // insert two values before the bookmarked value
+/// tree.slice(2..2).insert(20).unwrap();
+/// tree.slice(3..3).insert(30).unwrap();
+/// bookmark.on_insert(2, 2);
+/// assert_eq!(tree.search(bookmark).value(), Some(&6));
+///
+/// // remove three values, including the bookmarked value
+/// for _ in 0..3 {
+///     tree.search(7).delete();
+/// }
+/// assert_eq!(bookmark.on_remove(7..10), Err(BookmarkError::ValueRemoved));
+/// // the bookmark is left at the empty position where the value was
+/// assert!(bookmark.is_empty());
+/// tree.search(bookmark).insert(6).unwrap();
+/// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 20, 30, 2, 3, 4, 6, 8, 9]);
+/// # tree.assert_correctness();
+///```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Bookmark {
+    index: usize,
+    is_empty: bool,
+}
+
+impl Bookmark {
+    /// Creates a bookmark at the value with the given index.
+    pub fn at_value(index: usize) -> Self {
+        Bookmark {
+            index,
+            is_empty: false,
+        }
+    }
+
+    /// Creates a bookmark at the empty position before the value with the given index.
+    pub fn at_empty(index: usize) -> Self {
+        Bookmark {
+            index,
+            is_empty: true,
+        }
+    }
+
+    /// Returns the index of the bookmarked value. If the bookmark is at an empty position,
+    /// returns the index of the value after it.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `true` if the bookmark is at an empty position.
+    pub fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    /// Returns the range of indices spanned by the two bookmarks: from the first position
+    /// to the last one, in either order, including the values that they are at, if any.
+    ///```
+    /// use grove::locators::Bookmark;
+    ///
+    /// assert_eq!(Bookmark::at_empty(5).range_to(&Bookmark::at_empty(2)), 2..5);
+    /// assert_eq!(Bookmark::at_value(2).range_to(&Bookmark::at_value(5)), 2..6);
+    /// assert_eq!(Bookmark::at_value(3).range_to(&Bookmark::at_empty(3)), 3..4);
+    ///```
+    pub fn range_to(&self, other: &Bookmark) -> std::ops::Range<usize> {
+        let end = |bookmark: &Bookmark| bookmark.index + !bookmark.is_empty as usize;
+        self.index.min(other.index)..end(self).max(end(other))
+    }
+
+    /// Updates the bookmark after `count` values were inserted at the given index.
+    ///
+    /// If the bookmark is at the empty position where the values were inserted,
+    /// it stays before them.
+    pub fn on_insert(&mut self, index: usize, count: usize) {
+        if self.index > index || (self.index == index && !self.is_empty) {
+            self.index += count;
+        }
+    }
+
+    /// Updates the bookmark after the values in the given range of indices were removed.
+    ///
+    /// If the bookmarked value was removed, returns [`BookmarkError::ValueRemoved`],
+    /// and moves the bookmark to the empty position where the removed values were.
+    pub fn on_remove(&mut self, range: std::ops::Range<usize>) -> Result<(), BookmarkError> {
+        if self.index >= range.end {
+            self.index -= range.len();
+        } else if self.index > range.start || (self.index == range.start && !self.is_empty) {
+            let removed = !self.is_empty;
+            *self = Bookmark::at_empty(range.start);
+            if removed {
+                return Err(BookmarkError::ValueRemoved);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reason a [`Bookmark`] couldn't keep its position.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BookmarkError {
+    /// The bookmarked value was removed.
+    ValueRemoved,
+}
+
+impl std::fmt::Display for BookmarkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            BookmarkError::ValueRemoved => "the bookmarked value was removed",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for BookmarkError {}
+
+/// Locator instance for [`Bookmark`]. Locates either the bookmarked value,
+/// or the bookmarked empty position.
+impl<D: Data> Locator<D> for Bookmark
+where
+    D::Summary: SizedSummary,
+{
+    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+        if self.is_empty {
+            Locator::<D>::locate(&(self.index..self.index), left, node, right)
+        } else {
+            Locator::<D>::locate(&self.index, left, node, right)
+        }
+    }
+}
+
 /// A Wrapper for other locators what will find exactly the left edge
 /// of the previous locator. So, this is always a splitting locator.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -519,6 +748,65 @@ impl<D: Data, L: Locator<D>> Locator<D> for RightOf<L> {
     }
 }
 
+/// A Wrapper for another locator, that represents everything outside of its segment.
+/// So, `ComplementLocator(5..8)` is the two segments `0..5` and `8..`.
+///
+/// Since the complement of a segment is generally not a single contiguous segment,
+/// this is not a [`Locator`] by itself. Instead, it is made of two pieces,
+/// [`LeftOf`] and [`RightOf`] the inner locator, either of which might be empty.
+/// See [`crate::SomeTree::segment_summary_complement`] and
+/// [`crate::SomeTree::act_segment_complement`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ComplementLocator<L>(pub L);
+
+impl<L: Clone> ComplementLocator<L> {
+    /// Returns the two pieces of the complement: the prefix to the left of the
+    /// inner segment, and the suffix to the right of it.
+    pub fn pieces(&self) -> (LeftOf<L>, RightOf<L>) {
+        (LeftOf(self.0.clone()), RightOf(self.0.clone()))
+    }
+}
+
+/// A locator that accepts the maximal prefix whose summary satisfies a predicate.
+/// The predicate must be monotone: if it holds for the summary of some prefix, it must
+/// also hold for the summaries of all shorter prefixes. See [`by_summary_bound`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BySummaryBound<F>(pub F);
+
+/// Returns a locator that accepts the maximal prefix whose summary satisfies the monotone
+/// predicate `f`. For example, the longest prefix whose sum is at most `S`, or the longest
+/// prefix with at most `k` ones.
+///```
+/// use grove::{SomeTree, treap::Treap};
+/// use grove::locators::{by_summary_bound, RightEdgeOf};
+/// use grove::example_data::{StdNum, NumSummary};
+///
+/// let mut tree: Treap<StdNum> = (1..=10).collect();
+/// let locator = by_summary_bound(|summary: NumSummary| summary.sum <= 12);
+/// assert_eq!(tree.segment_summary(locator).size, 4); // 1 + 2 + 3 + 4 <= 12
+///
+/// // split the tree right after the prefix
+/// let rest = tree.slice(RightEdgeOf(locator)).split_right().unwrap();
+/// assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+/// assert_eq!(rest.into_iter().collect::<Vec<_>>(), (5..=10).collect::<Vec<_>>());
+///```
+pub fn by_summary_bound<F>(f: F) -> BySummaryBound<F> {
+    BySummaryBound(f)
+}
+
+impl<D: Data, F> Locator<D> for BySummaryBound<F>
+where
+    F: Fn(D::Summary) -> bool + Clone,
+{
+    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+        if (self.0)(left + node.to_summary()) {
+            Accept
+        } else {
+            GoLeft
+        }
+    }
+}
+
 /// A Wrapper for two other locators, that finds the smallest segment containing both of them.
 /// For example, the Union of ranges `[3,6)` and `[8,12)` will  be `[3,12)`.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -526,7 +814,7 @@ pub struct UnionLocator<L1, L2>(pub L1, pub L2);
 
 impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for UnionLocator<L1, L2> {
     fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
-        let a = self.0.locate(left, node, right);
+        let a = self.0.locate(left.clone(), node, right.clone());
         let b = self.1.locate(left, node, right);
         if a == b {
             a
@@ -546,7 +834,7 @@ pub struct BetweenLocator<L1, L2>(pub L1, pub L2);
 
 impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for BetweenLocator<L1, L2> {
     fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
-        let a = self.0.locate(left, node, right);
+        let a = self.0.locate(left.clone(), node, right.clone());
         let b = self.1.locate(left, node, right);
         match (a, b) {
             (GoLeft, GoRight) => Accept,
@@ -556,3 +844,69 @@ impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for BetweenLocator<L1,
         }
     }
 }
+
+/// Returns a locator for the position where a value with the given key should be inserted,
+/// before all of the elements with an equal key.
+/// This is always a splitting locator.
+///```
+/// use grove::{SomeTree, treap::Treap};
+/// use grove::locators::insert_position_before_equal;
+/// use grove::example_data::SizeData;
+///
+/// let mut tree: Treap<SizeData<(i32, char)>> = [(1, 'a'), (2, 'b'), (2, 'c'), (3, 'd')]
+///     .iter().cloned().collect();
+/// // `(i32, char)` is keyed by itself, so search for the full tuple's position
+/// tree.slice(insert_position_before_equal(&(2, 'b'))).insert((2, 'b')).unwrap();
+/// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(),
+///     vec![(1, 'a'), (2, 'b'), (2, 'b'), (2, 'c'), (3, 'd')]);
+/// # tree.assert_correctness();
+///```
+pub fn insert_position_before_equal<Key>(key: &Key) -> LeftEdgeOf<ByKey<(&Key,)>> {
+    LeftEdgeOf(ByKey((key,)))
+}
+
+/// Returns a locator for the position where a value with the given key should be inserted,
+/// after all of the elements with an equal key.
+/// This is always a splitting locator.
+///```
+/// use grove::{SomeTree, SomeEntry, avl::AVLTree};
+/// use grove::locators::insert_position_after_equal;
+/// use grove::example_data::SizeData;
+///
+/// let mut tree: AVLTree<SizeData<i32>> = [1, 2, 2, 3].iter().cloned().collect();
+/// tree.slice(insert_position_after_equal(&2)).insert(2).unwrap();
+/// // the new value is the fourth element
+/// let summary = tree.segment_summary(insert_position_after_equal(&2));
+/// assert_eq!(summary.size, 0);
+/// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 2, 2, 3]);
+/// # tree.assert_correctness();
+///```
+pub fn insert_position_after_equal<Key>(key: &Key) -> RightEdgeOf<ByKey<(&Key,)>> {
+    RightEdgeOf(ByKey((key,)))
+}
+
+/// Returns a locator for inserting a value with the given key, only if no element
+/// with an equal key exists. If there is such an element, the locator accepts it, so
+/// inserting at it fails, and searching for it finds a non empty position.
+///
+/// This is useful for maintaining set semantics.
+///```
+/// use grove::{SomeTree, SomeEntry, NavError, splay::SplayTree};
+/// use grove::locators::insert_position_unique;
+/// use grove::example_data::SizeData;
+///
+/// let mut tree: SplayTree<SizeData<i32>> = [1, 3, 5].iter().cloned().collect();
+/// assert_eq!(tree.slice(insert_position_unique(&3)).insert(3), Err(NavError::OccupiedPosition));
+/// assert_eq!(tree.slice(insert_position_unique(&4)).insert(4), Ok(()));
+///
+/// // the search reports whether the key already exists
+/// let walker = tree.slice(insert_position_unique(&5)).search();
+/// assert!(!walker.is_empty());
+/// # drop(walker);
+///
+/// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 3, 4, 5]);
+/// # tree.assert_correctness();
+///```
+pub fn insert_position_unique<Key>(key: &Key) -> ByKey<(&Key,)> {
+    ByKey((key,))
+}
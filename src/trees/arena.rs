@@ -0,0 +1,1383 @@
+//! Implementation of a tree whose nodes are stored in a single vector, without any boxes.
+//!
+//! The nodes of an [`ArenaTree`] are stored in a vector that the tree owns, and the sons of
+//! every node are `u32` indices into it. Compared to trees that allocate every node separately,
+//! this improves the locality of the nodes, halves the size of the links on 64-bit platforms,
+//! and dropping the whole tree deallocates a single vector. The slots of deleted nodes are
+//! reused by the next insertions.
+//!
+//! The tree is balanced as a treap, using split and merge,
+//! like [`PersistentTree`](super::persistent::PersistentTree), so its operations take
+//! `O(log n)` expected time. Actions are applied lazily, as in the other trees.
+//!
+//! Since the nodes are linked by indices, [`ArenaTree`] doesn't use
+//! [`BasicTree`](super::basic_tree::BasicTree). It implements [`SomeTree`] with its own walker,
+//! [`ArenaWalker`], which keeps the indices of the nodes on its path. It also provides
+//! versions of insertion and deletion that take locators.
+//!
+//! Every tree owns the vector that its values are stored in, so values that move between trees
+//! are moved between vectors. Therefore, concatenating two trees, and splitting a tree,
+//! take `O(m + log n)` expected time, where `m` is the size of the smaller of the two parts,
+//! whose nodes are moved. Iterating over a segment pushes all of its pending actions down
+//! to its values when the iterator is created.
+//!```
+//! use grove::{*, arena::ArenaTree};
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut tree: ArenaTree<StdNum> = (0..10).collect();
+//! tree.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 10 }, 2..5);
+//! assert_eq!(tree.delete(0..=0), Some(0));
+//! assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 14, 13, 12, 5, 6, 7, 8, 9]);
+//! assert_eq!(tree.segment_summary(1..3).sum, 27);
+//!
+//! // the deleted node's slot is reused
+//! tree.insert(0..0, 100);
+//! assert_eq!(tree.num_slots(), 10);
+//! # tree.assert_correctness();
+//!```
+
+use super::*;
+use crate::locators::{LocResult, Locator};
+
+// convention: a bigger number should go higher up the tree.
+type Priority = u64;
+
+// The index of a node in the tree's vector of nodes.
+type Link = Option<u32>;
+
+struct Node<D: Data> {
+    // The action to be applied to the whole subtree, including this node's value.
+    // `subtree_summary` and `value` don't include it.
+    action: D::Action,
+    subtree_summary: D::Summary,
+    value: D::Value,
+    priority: Priority,
+    left: Link,
+    right: Link,
+}
+
+/// A tree whose nodes are stored in a single vector. See the [module documentation](self).
+pub struct ArenaTree<D: Data> {
+    // `None` for the slots of deleted nodes, which are listed in `free`.
+    nodes: Vec<Option<Node<D>>>,
+    free: Vec<u32>,
+    root: Link,
+}
+
+impl<D: Data> Default for ArenaTree<D> {
+    fn default() -> Self {
+        ArenaTree {
+            nodes: vec![],
+            free: vec![],
+            root: None,
+        }
+    }
+}
+
+impl<D: Data> ArenaTree<D> {
+    /// Creates an empty [`ArenaTree`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the number of slots in the tree's vector of nodes, including the slots
+    /// of deleted nodes, that will be reused.
+    pub fn num_slots(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Removes all of the values. The vector of nodes keeps its allocation.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = None;
+    }
+
+    // The number of nodes in the tree, in `O(1)` time.
+    fn num_nodes(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    fn node(&self, index: u32) -> &Node<D> {
+        self.nodes[index as usize]
+            .as_ref()
+            .expect("A link should point to a node")
+    }
+
+    fn node_mut(&mut self, index: u32) -> &mut Node<D> {
+        self.nodes[index as usize]
+            .as_mut()
+            .expect("A link should point to a node")
+    }
+
+    // Stores a new node with the value, and returns its index.
+    fn alloc(&mut self, value: D::Value) -> u32 {
+        self.store(Node {
+            action: Default::default(),
+            subtree_summary: value.to_summary(),
+            value,
+            priority: random::priority(),
+            left: None,
+            right: None,
+        })
+    }
+
+    // Stores the node in a free slot, and returns its index.
+    fn store(&mut self, node: Node<D>) -> u32 {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index as usize] = Some(node);
+                index
+            }
+            None => {
+                let index = u32::try_from(self.nodes.len()).expect("Too many nodes");
+                self.nodes.push(Some(node));
+                index
+            }
+        }
+    }
+
+    // Removes the node from its slot, and returns it.
+    fn take(&mut self, index: u32) -> Node<D> {
+        let node = self.nodes[index as usize]
+            .take()
+            .expect("A link should point to a node");
+        self.free.push(index);
+        node
+    }
+
+    // Removes the node from its slot, and returns its value.
+    fn dealloc(&mut self, index: u32) -> D::Value {
+        self.take(index).value
+    }
+
+    // Moves the nodes of the subtree into the other tree, and returns the subtree's
+    // new root there. The nodes keep their pending actions.
+    fn move_to(&mut self, link: Link, other: &mut ArenaTree<D>) -> Link {
+        let mut root = None;
+        // the subtrees left to move, along with the new index of their father,
+        // and their side relative to it
+        let mut stack = vec![(link, None)];
+        while let Some((link, father)) = stack.pop() {
+            let index = match link {
+                None => continue,
+                Some(index) => index,
+            };
+            let mut node = self.take(index);
+            let (left, right) = (node.left.take(), node.right.take());
+            let new_index = other.store(node);
+            match father {
+                None => root = Some(new_index),
+                Some((father, Side::Left)) => other.node_mut(father).left = Some(new_index),
+                Some((father, Side::Right)) => other.node_mut(father).right = Some(new_index),
+            }
+            stack.push((left, Some((new_index, Side::Left))));
+            stack.push((right, Some((new_index, Side::Right))));
+        }
+        root
+    }
+
+    // Returns true if the first subtree doesn't have more nodes than the second one.
+    // This takes time linear in the size of the smaller subtree.
+    fn is_smaller(&self, first: Link, second: Link) -> bool {
+        let mut stacks: [Vec<u32>; 2] = [first.into_iter().collect(), second.into_iter().collect()];
+        loop {
+            for (i, stack) in stacks.iter_mut().enumerate() {
+                match stack.pop() {
+                    None => return i == 0,
+                    Some(index) => {
+                        let node = self.node(index);
+                        stack.extend(node.left);
+                        stack.extend(node.right);
+                    }
+                }
+            }
+        }
+    }
+
+    // Separates the two subtrees, which are all of the nodes of this tree:
+    // returns the index of `kept` in this tree, and a tree with the nodes of `separated`.
+    // The nodes of the smaller subtree are the ones that are moved, so if `kept` is smaller,
+    // its nodes are moved, and then the two trees are swapped.
+    fn separate(&mut self, kept: Link, separated: Link) -> (Link, ArenaTree<D>) {
+        let mut other = ArenaTree::new();
+        if self.is_smaller(separated, kept) {
+            other.root = self.move_to(separated, &mut other);
+            (kept, other)
+        } else {
+            let kept = self.move_to(kept, &mut other);
+            std::mem::swap(self, &mut other);
+            other.root = separated;
+            (kept, other)
+        }
+    }
+
+    fn summary(&self, link: Link) -> D::Summary {
+        match link {
+            None => Default::default(),
+            Some(index) => {
+                let node = self.node(index);
+                node.action.act(node.subtree_summary.clone())
+            }
+        }
+    }
+
+    // Applies the action to the whole subtree, lazily.
+    fn act(&mut self, link: Link, action: D::Action) {
+        assert!(
+            D::Action::REVERSIBLE || !action.to_reverse(),
+            "Actions that aren't `Action::REVERSIBLE` must not reverse"
+        );
+        if action.is_identity() {
+            return;
+        }
+        if let Some(index) = link {
+            let node = self.node_mut(index);
+            node.action = action + std::mem::take(&mut node.action);
+        }
+    }
+
+    // Pushes the node's action down to its sons.
+    fn access(&mut self, index: u32) {
+        let action = std::mem::take(&mut self.node_mut(index).action);
+        if action.is_identity() {
+            return;
+        }
+        let (value_action, right_action) = self.inner_actions(index, &action);
+        let node = self.node_mut(index);
+        if D::Action::REVERSIBLE && action.to_reverse() {
+            std::mem::swap(&mut node.left, &mut node.right);
+        }
+        let (left, right) = (node.left, node.right);
+        action.act_inplace(&mut node.subtree_summary);
+        value_action.act_inplace(&mut node.value);
+        D::on_access(&node.value, &node.subtree_summary);
+        self.act(left, action);
+        self.act(right, right_action);
+    }
+
+    // Returns the actions that applying `action` to the subtree applies to the node's value
+    // and to the son that comes after it. See [`BasicNode::inner_actions`].
+    fn inner_actions(&self, index: u32, action: &D::Action) -> (D::Action, D::Action) {
+        if !action.is_indexed() {
+            return (action.clone(), action.clone());
+        }
+        let node = self.node(index);
+        let value_summary = node.value.to_summary();
+        let (first, skipped) = if D::Action::REVERSIBLE && action.to_reverse() {
+            let first = self.summary(node.right);
+            (first.clone(), value_summary + first)
+        } else {
+            let first = self.summary(node.left);
+            (first.clone(), first + value_summary)
+        };
+        (action.skip(&first), action.skip(&skipped))
+    }
+
+    // Like `access`, but without modifying the tree: returns the sons of the node in order,
+    // each with the action that applying `action` to the subtree applies to it
+    // (including the node's own action), and the action that it applies to the node's value.
+    #[allow(clippy::type_complexity)]
+    fn view(
+        &self,
+        index: u32,
+        action: &D::Action,
+    ) -> ((Link, D::Action), D::Action, (Link, D::Action)) {
+        let node = self.node(index);
+        let action = action.clone() + node.action.clone();
+        let (value_action, second_action) = self.inner_actions(index, &action);
+        if D::Action::REVERSIBLE && action.to_reverse() {
+            (
+                (node.right, action),
+                value_action,
+                (node.left, second_action),
+            )
+        } else {
+            (
+                (node.left, action),
+                value_action,
+                (node.right, second_action),
+            )
+        }
+    }
+
+    // Recomputes the summary of the subtree.
+    // Assumes the node's action is the identity.
+    fn rebuild(&mut self, index: u32) {
+        let node = self.node(index);
+        let summary = self.summary(node.left) + node.value.to_summary() + self.summary(node.right);
+        let node = self.node_mut(index);
+        node.subtree_summary = summary;
+        D::on_rebuild(&node.value, &node.subtree_summary);
+    }
+
+    // Splits the tree at the given edge of the locator's segment.
+    // `left` and `right` are the summaries of the values to the left and right of the subtree.
+    fn split<L: Locator<D>>(
+        &mut self,
+        link: Link,
+        locator: &L,
+        edge: Side,
+        left: D::Summary,
+        right: D::Summary,
+    ) -> (Link, Link) {
+        let index = match link {
+            None => return (None, None),
+            Some(index) => index,
+        };
+        self.access(index);
+        let node = self.node(index);
+        let (node_left, node_right) = (node.left, node.right);
+        let node_summary = node.value.to_summary();
+        let res = locator.locate(
+            left.clone() + self.summary(node_left),
+            &node.value,
+            node_summary.clone() + self.summary(node_right) + right.clone(),
+        );
+        let goes_left = matches!(
+            (res, edge),
+            (LocResult::GoRight, _) | (LocResult::Accept, Side::Right)
+        );
+        if goes_left {
+            let new_left = left + self.summary(node_left) + node_summary;
+            let (middle, rest) = self.split(node_right, locator, edge, new_left, right);
+            self.node_mut(index).right = middle;
+            self.rebuild(index);
+            (Some(index), rest)
+        } else {
+            let new_right = node_summary + self.summary(node_right) + right;
+            let (rest, middle) = self.split(node_left, locator, edge, left, new_right);
+            self.node_mut(index).left = middle;
+            self.rebuild(index);
+            (rest, Some(index))
+        }
+    }
+
+    // Splits the subtree at the empty position that the path leads to,
+    // where the path is given by the sides to go to from the subtree's root.
+    fn split_path(&mut self, link: Link, path: &[Side]) -> (Link, Link) {
+        let (index, side, rest) = match (link, path.split_first()) {
+            (None, _) => return (None, None),
+            (Some(index), Some((&side, rest))) => (index, side, rest),
+            (Some(_), None) => panic!("The path should lead to an empty position"),
+        };
+        self.access(index);
+        let node = self.node(index);
+        match side {
+            Side::Left => {
+                let (left, right) = self.split_path(node.left, rest);
+                self.node_mut(index).left = right;
+                self.rebuild(index);
+                (left, Some(index))
+            }
+            Side::Right => {
+                let (left, right) = self.split_path(node.right, rest);
+                self.node_mut(index).right = left;
+                self.rebuild(index);
+                (Some(index), right)
+            }
+        }
+    }
+
+    // Concatenates the two trees.
+    fn join(&mut self, left: Link, right: Link) -> Link {
+        match (left, right) {
+            (None, tree) | (tree, None) => tree,
+            (Some(left), Some(right)) => {
+                if self.node(left).priority > self.node(right).priority {
+                    self.access(left);
+                    let merged = self.join(self.node(left).right, Some(right));
+                    self.node_mut(left).right = merged;
+                    self.rebuild(left);
+                    Some(left)
+                } else {
+                    self.access(right);
+                    let merged = self.join(Some(left), self.node(right).left);
+                    self.node_mut(right).left = merged;
+                    self.rebuild(right);
+                    Some(right)
+                }
+            }
+        }
+    }
+
+    // Detaches the first node in the subtree, and returns its index and the rest of the subtree.
+    fn pop_first(&mut self, index: u32) -> (u32, Link) {
+        self.access(index);
+        let node = self.node(index);
+        match node.left {
+            None => (index, node.right),
+            Some(left) => {
+                let (first, rest) = self.pop_first(left);
+                self.node_mut(index).left = rest;
+                self.rebuild(index);
+                (first, Some(index))
+            }
+        }
+    }
+
+    // Splits the tree into the part before the segment, the segment, and the part after it.
+    fn split3<L: Locator<D>>(&mut self, locator: &L) -> (Link, Link, Link) {
+        let zero = Default::default;
+        let root = self.root.take();
+        let (left, rest) = self.split(root, locator, Side::Left, zero(), zero());
+        let left_summary = self.summary(left);
+        let (middle, right) = self.split(rest, locator, Side::Right, left_summary, zero());
+        (left, middle, right)
+    }
+
+    // Merges the three parts back into the tree.
+    fn join3(&mut self, left: Link, middle: Link, right: Link) {
+        let rest = self.join(middle, right);
+        self.root = self.join(left, rest);
+    }
+
+    /// Inserts the value at the left edge of the locator's segment.
+    /// For example, `tree.insert(3..3, value)` inserts the value at index 3.
+    pub fn insert<L: Locator<D>>(&mut self, locator: L, value: D::Value) {
+        let zero = Default::default;
+        let root = self.root.take();
+        let (left, right) = self.split(root, &locator, Side::Left, zero(), zero());
+        let node = self.alloc(value);
+        self.join3(left, Some(node), right);
+    }
+
+    /// Deletes the first value in the locator's segment, and returns it.
+    /// If the segment is empty, returns [`None`].
+    pub fn delete<L: Locator<D>>(&mut self, locator: L) -> Option<D::Value> {
+        let (left, middle, right) = self.split3(&locator);
+        let (first, middle) = match middle {
+            Some(middle) => self.pop_first(middle),
+            None => {
+                self.join3(left, None, right);
+                return None;
+            }
+        };
+        self.join3(left, middle, right);
+        Some(self.dealloc(first))
+    }
+
+    // Returns the index of the last node whose key is smaller than `key` if `side` is `Left`,
+    // or of the first node whose key is greater than `key` if `side` is `Right`.
+    // Pushes down the pending actions on the path to it, in a single descent.
+    fn neighbor_by_key<Key: Ord>(&mut self, key: &Key, side: Side) -> Option<u32>
+    where
+        D::Value: Keyed<Key>,
+    {
+        let mut found = None;
+        let mut link = self.root;
+        while let Some(index) = link {
+            self.access(index);
+            let node = self.node(index);
+            let ordering = node.value.get_key().cmp(key);
+            link = match (side, ordering) {
+                (Side::Left, std::cmp::Ordering::Less) => {
+                    found = Some(index);
+                    node.right
+                }
+                (Side::Left, _) => node.left,
+                (Side::Right, std::cmp::Ordering::Greater) => {
+                    found = Some(index);
+                    node.left
+                }
+                (Side::Right, _) => node.right,
+            };
+        }
+        found
+    }
+
+    // Pushes all of the pending actions in the subtree down to its values,
+    // and returns the indices of its nodes in order.
+    fn in_order(&mut self, mut link: Link) -> Vec<u32> {
+        let mut order = vec![];
+        // the nodes whose left subtrees were already pushed
+        let mut stack = vec![];
+        loop {
+            while let Some(index) = link {
+                self.access(index);
+                stack.push(index);
+                link = self.node(index).left;
+            }
+            match stack.pop() {
+                None => return order,
+                Some(index) => {
+                    order.push(index);
+                    link = self.node(index).right;
+                }
+            }
+        }
+    }
+
+    // Pushes all of the pending actions in the segment down to its values,
+    // and returns the indices of its nodes in order.
+    fn segment_in_order<L: Locator<D>>(&mut self, locator: &L) -> Vec<u32> {
+        let (left, middle, right) = self.split3(locator);
+        let order = self.in_order(middle);
+        self.join3(left, middle, right);
+        order
+    }
+
+    // Computes the summary of the part of the subtree inside the locator's segment,
+    // as if `action` was applied to the subtree, without modifying the tree.
+    // `left` and `right` are the summaries of the values to the left and right of the subtree.
+    // `open` says for each side whether the segment continues past that edge of the subtree,
+    // in which case the whole son on that side of an accepted node is inside the segment.
+    fn summary_imm<L: Locator<D>>(
+        &self,
+        link: Link,
+        action: D::Action,
+        locator: &L,
+        (left, right): (D::Summary, D::Summary),
+        open: [bool; 2],
+    ) -> D::Summary
+    where
+        D::Value: Clone,
+    {
+        let index = match link {
+            None => return Default::default(),
+            Some(index) => index,
+        };
+        let ((first, first_action), value_action, (second, second_action)) =
+            self.view(index, &action);
+        let first_summary = first_action.act(self.summary(first));
+        let second_summary = second_action.act(self.summary(second));
+        let value = value_action.act(self.node(index).value.clone());
+        let value_summary = value.to_summary();
+        let res = locator.locate(
+            left.clone() + first_summary.clone(),
+            &value,
+            value_summary.clone() + second_summary.clone() + right.clone(),
+        );
+        match res {
+            LocResult::GoLeft => {
+                let right = value_summary + second_summary + right;
+                self.summary_imm(
+                    first,
+                    first_action,
+                    locator,
+                    (left, right),
+                    [open[0], false],
+                )
+            }
+            LocResult::GoRight => {
+                let left = left + first_summary + value_summary;
+                self.summary_imm(
+                    second,
+                    second_action,
+                    locator,
+                    (left, right),
+                    [false, open[1]],
+                )
+            }
+            LocResult::Accept => {
+                let first_part = if open[0] {
+                    first_summary.clone()
+                } else {
+                    let context = (
+                        left.clone(),
+                        value_summary.clone() + second_summary.clone() + right.clone(),
+                    );
+                    self.summary_imm(first, first_action, locator, context, [false, true])
+                };
+                let second_part = if open[1] {
+                    second_summary
+                } else {
+                    let context = (left + first_summary + value_summary.clone(), right);
+                    self.summary_imm(second, second_action, locator, context, [true, false])
+                };
+                first_part + value_summary + second_part
+            }
+        }
+    }
+
+    // Checks the node's summary and the priorities of its sons.
+    fn assert_node_locally(&self, index: u32)
+    where
+        D::Summary: Eq,
+    {
+        let node = self.node(index);
+        let expected = self.summary(node.left) + node.value.to_summary() + self.summary(node.right);
+        assert!(
+            node.subtree_summary == expected,
+            "Incorrect summaries found."
+        );
+        for son in node.left.into_iter().chain(node.right) {
+            assert!(
+                node.priority >= self.node(son).priority,
+                "Incorrect priorities found."
+            );
+        }
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for ArenaTree<D> {
+    /// This takes `O(n)` time.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut tree = ArenaTree::new();
+        // Every node in the stack is the right son of the node before it, which isn't
+        // attached yet. Their right sons aren't final yet, so they are left empty.
+        let mut stack: Vec<u32> = vec![];
+        for value in iter {
+            let index = tree.alloc(value);
+            let left = tree.pop_lower(&mut stack, Some(tree.node(index).priority));
+            tree.node_mut(index).left = left;
+            stack.push(index);
+        }
+        tree.root = tree.pop_lower(&mut stack, None);
+        tree
+    }
+}
+
+impl<D: Data> ArenaTree<D> {
+    // Pops all the nodes with priority lower than `priority` (or all of them if it is `None`)
+    // off the stack, attaching each one as the right son of the one below it,
+    // and returns the tree of the last popped node.
+    fn pop_lower(&mut self, stack: &mut Vec<u32>, priority: Option<Priority>) -> Link {
+        let mut tree = None;
+        while let Some(&top) = stack.last() {
+            if priority.is_some_and(|priority| self.node(top).priority >= priority) {
+                break;
+            }
+            stack.pop();
+            self.node_mut(top).right = tree;
+            self.rebuild(top);
+            tree = Some(top);
+        }
+        tree
+    }
+}
+
+impl<D: Data> Extend<D::Value> for ArenaTree<D> {
+    /// Inserts the values at the end of the tree, one by one.
+    fn extend<I: IntoIterator<Item = D::Value>>(&mut self, iter: I) {
+        for value in iter {
+            let node = self.alloc(value);
+            let root = self.root.take();
+            self.root = self.join(root, Some(node));
+        }
+    }
+}
+
+impl<D: Data> IntoIterator for ArenaTree<D> {
+    type Item = D::Value;
+    type IntoIter = std::vec::IntoIter<D::Value>;
+
+    /// Moves the values out of the tree, in order, in `O(n)` time.
+    fn into_iter(mut self) -> Self::IntoIter {
+        let order = self.in_order(self.root);
+        order
+            .into_iter()
+            .map(|index| self.dealloc(index))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<D: Data> SomeTree<D> for ArenaTree<D> {
+    type Walker<'a>
+        = ArenaWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn walker(&mut self) -> Self::Walker<'_> {
+        let link = self.root;
+        if let Some(index) = link {
+            self.access(index);
+        }
+        ArenaWalker {
+            tree: self,
+            link,
+            path: vec![],
+            frames: vec![Default::default()],
+        }
+    }
+
+    fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
+    where
+        L: Locator<D>,
+        D::Value: Clone,
+    {
+        let context = Default::default();
+        self.summary_imm(
+            self.root,
+            Default::default(),
+            &locator,
+            context,
+            [false, false],
+        )
+    }
+
+    fn segment_summary<L>(&mut self, locator: L) -> D::Summary
+    where
+        L: Locator<D>,
+    {
+        let (left, middle, right) = self.split3(&locator);
+        let summary = self.summary(middle);
+        self.join3(left, middle, right);
+        summary
+    }
+
+    fn act_segment<L>(&mut self, action: D::Action, locator: L)
+    where
+        L: Locator<D>,
+    {
+        let (left, middle, right) = self.split3(&locator);
+        self.act(middle, action);
+        self.join3(left, middle, right);
+    }
+
+    /// Inserts the values of the smaller tree into the larger one, like the default
+    /// implementation, but finds the smaller tree in `O(1)` time.
+    fn merge<Key: Ord>(self, other: Self) -> Self
+    where
+        D::Value: Keyed<Key>,
+    {
+        let (mut larger, smaller) = if self.num_nodes() < other.num_nodes() {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        for value in smaller {
+            larger.insert_sorted::<Key>(value);
+        }
+        larger
+    }
+
+    /// Finds the value in a single descent, unlike [`SomeTree::iter_locator`],
+    /// which pushes down the pending actions of all of the values before it.
+    fn predecessor<'a, Key: Ord>(&'a mut self, key: &Key) -> Option<&'a D::Value>
+    where
+        D: 'a,
+        D::Value: Keyed<Key>,
+    {
+        let index = self.neighbor_by_key(key, Side::Left)?;
+        Some(&self.node(index).value)
+    }
+
+    /// Finds the value in a single descent, unlike [`SomeTree::iter_locator`],
+    /// which pushes down the pending actions of all of the values after it.
+    fn successor<'a, Key: Ord>(&'a mut self, key: &Key) -> Option<&'a D::Value>
+    where
+        D: 'a,
+        D::Value: Keyed<Key>,
+    {
+        let index = self.neighbor_by_key(key, Side::Right)?;
+        Some(&self.node(index).value)
+    }
+
+    type IterLocator<'a, L: Locator<D>>
+        = Iter<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+    type IterLocatorMut<'a, L: Locator<D>>
+        = IterMutGuard<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+    type IntoIterSegment<L: Locator<D>> = std::vec::IntoIter<D::Value>;
+
+    /// Pushes all of the pending actions in the segment down to its values,
+    /// in time linear in the size of the segment, and iterates over them.
+    /// This happens before the first value is returned, so even `tree.iter().next()`
+    /// takes linear time: returning references to values while pushing actions down
+    /// to other values would need unsafe code.
+    fn iter_locator<'a, L: Locator<D>>(&'a mut self, locator: L) -> Self::IterLocator<'a, L> {
+        let order = self.segment_in_order(&locator);
+        Iter {
+            tree: self,
+            order: order.into_iter(),
+        }
+    }
+
+    /// Moves the nodes of the segment out of the tree while they are iterated,
+    /// and puts them back when the guard is dropped.
+    fn iter_locator_mut<'a, L: Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> Self::IterLocatorMut<'a, L> {
+        let (left, middle, right) = self.split3(&locator);
+        let indices = self.in_order(middle);
+        let nodes = indices.iter().map(|&index| self.take(index)).collect();
+        IterMutGuard {
+            tree: self,
+            parts: (left, middle, right),
+            indices,
+            nodes,
+        }
+    }
+
+    fn into_iter_segment<L: Locator<D>>(mut self, locator: L) -> Self::IntoIterSegment<L> {
+        let (_, middle, _) = self.split3(&locator);
+        let order = self.in_order(middle);
+        order
+            .into_iter()
+            .map(|index| self.dealloc(index))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Checks that every node's summary is the sum of the summaries of its children,
+    /// that the priorities are ordered, and that every node is reachable exactly once.
+    /// If it finds any violation, it panics.
+    fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        let mut reached = 0;
+        let mut stack: Vec<u32> = self.root.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            reached += 1;
+            self.assert_node_locally(index);
+            let node = self.node(index);
+            stack.extend(node.left.into_iter().chain(node.right));
+        }
+        assert_eq!(
+            reached + self.free.len(),
+            self.nodes.len(),
+            "Unreachable nodes found."
+        );
+    }
+}
+
+impl<D: Data> SomeEntry<D> for ArenaTree<D> {
+    fn with_value<F, R>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut D::Value) -> R,
+    {
+        let index = self.root?;
+        self.access(index);
+        let res = f(&mut self.node_mut(index).value);
+        self.rebuild(index);
+        Some(res)
+    }
+
+    fn node_summary(&self) -> D::Summary {
+        match self.root {
+            None => Default::default(),
+            Some(index) => {
+                let (_, value_action, _) = self.view(index, &Default::default());
+                value_action.act(self.node(index).value.to_summary())
+            }
+        }
+    }
+
+    fn subtree_summary(&self) -> D::Summary {
+        self.summary(self.root)
+    }
+
+    fn left_subtree_summary(&self) -> Option<D::Summary> {
+        let index = self.root?;
+        let ((left, action), _, _) = self.view(index, &Default::default());
+        Some(action.act(self.summary(left)))
+    }
+
+    fn right_subtree_summary(&self) -> Option<D::Summary> {
+        let index = self.root?;
+        let (_, _, (right, action)) = self.view(index, &Default::default());
+        Some(action.act(self.summary(right)))
+    }
+
+    fn act_node(&mut self, action: D::Action) -> Option<()> {
+        let index = self.root?;
+        self.access(index);
+        action.act_inplace(&mut self.node_mut(index).value);
+        self.rebuild(index);
+        Some(())
+    }
+
+    fn act_subtree(&mut self, action: D::Action) {
+        self.act(self.root, action);
+    }
+
+    fn act_left_subtree(&mut self, action: D::Action) -> Option<()> {
+        let index = self.root?;
+        self.access(index);
+        self.act(self.node(index).left, action);
+        self.rebuild(index);
+        Some(())
+    }
+
+    fn act_right_subtree(&mut self, action: D::Action) -> Option<()> {
+        let index = self.root?;
+        self.access(index);
+        self.act(self.node(index).right, action);
+        self.rebuild(index);
+        Some(())
+    }
+
+    fn assert_correctness_locally(&self)
+    where
+        D::Summary: Eq,
+    {
+        if let Some(index) = self.root {
+            self.assert_node_locally(index);
+        }
+    }
+}
+
+/// Unlike the other trees in this crate, concatenating arena trees takes time linear in
+/// the size of the smaller tree, since its nodes are moved to the other tree's vector.
+impl<D: Data> ConcatenableTree<D> for ArenaTree<D> {
+    /// Moves the nodes of the smaller tree into the larger tree's vector,
+    /// and then merges them, in `O(m + log n)` expected time,
+    /// where `m` is the size of the smaller tree.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, arena::ArenaTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: ArenaTree<StdNum> = (17..=89).collect();
+    /// let tree2: ArenaTree<StdNum> = (13..=25).collect();
+    /// tree.concatenate_right(tree2);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(13..=25).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn concatenate_right(&mut self, mut other: Self) {
+        if other.num_nodes() <= self.num_nodes() {
+            let right = other.move_to(other.root, self);
+            let left = self.root.take();
+            self.root = self.join(left, right);
+        } else {
+            let root = self.root.take();
+            let left = self.move_to(root, &mut other);
+            let right = other.root.take();
+            other.root = other.join(left, right);
+            *self = other;
+        }
+    }
+}
+
+/// Unlike the other trees in this crate, splitting an arena tree takes time linear in
+/// the size of the smaller part, since its nodes are moved to a new vector.
+/// See [`ArenaWalker::split_right`](SplittableWalker::split_right).
+impl<D: Data> SplittableTree<D> for ArenaTree<D> {
+    type SplittableWalker<'a>
+        = ArenaWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn splittable_walker(&mut self) -> Self::SplittableWalker<'_> {
+        self.walker()
+    }
+}
+
+/// An iterator over the values of a segment of an [`ArenaTree`],
+/// returned by [`SomeTree::iter_locator`].
+pub struct Iter<'a, D: Data> {
+    tree: &'a ArenaTree<D>,
+    order: std::vec::IntoIter<u32>,
+}
+
+impl<'a, D: Data> Iterator for Iter<'a, D> {
+    type Item = &'a D::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        self.order.next().map(|index| &tree.node(index).value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+impl<'a, D: Data> DoubleEndedIterator for Iter<'a, D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        self.order.next_back().map(|index| &tree.node(index).value)
+    }
+}
+
+/// A guard for iterating mutably over a segment of an [`ArenaTree`],
+/// returned by [`SomeTree::iter_locator_mut`]. The nodes of the segment are kept outside
+/// of the tree while the guard exists, and are rebuilt and put back when it is dropped.
+pub struct IterMutGuard<'a, D: Data> {
+    tree: &'a mut ArenaTree<D>,
+    // the parts before the segment, the segment, and after it
+    parts: (Link, Link, Link),
+    // the nodes of the segment in order, and their indices in the tree
+    indices: Vec<u32>,
+    nodes: Vec<Node<D>>,
+}
+
+impl<'a, D: Data> IterGuard<D> for IterMutGuard<'a, D> {
+    type IterMut<'b>
+        = IterMut<'b, D>
+    where
+        Self: 'b,
+        D: 'b;
+
+    fn iter(&mut self) -> Self::IterMut<'_> {
+        IterMut {
+            nodes: self.nodes.iter_mut(),
+        }
+    }
+}
+
+impl<'a, D: Data> Drop for IterMutGuard<'a, D> {
+    fn drop(&mut self) {
+        for (&index, node) in self.indices.iter().zip(self.nodes.drain(..)) {
+            self.tree.nodes[index as usize] = Some(node);
+        }
+        self.tree
+            .free
+            .truncate(self.tree.free.len() - self.indices.len());
+        let (left, middle, right) = self.parts;
+        // every node comes before its sons in pre-order, so rebuilding the nodes
+        // in the reverse order rebuilds the sons before their fathers.
+        let mut pre_order = vec![];
+        let mut stack: Vec<u32> = middle.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            pre_order.push(index);
+            let node = self.tree.node(index);
+            stack.extend(node.left.into_iter().chain(node.right));
+        }
+        for &index in pre_order.iter().rev() {
+            self.tree.rebuild(index);
+        }
+        self.tree.join3(left, middle, right);
+    }
+}
+
+/// A mutable iterator over the values of a segment of an [`ArenaTree`],
+/// returned by [`IterMutGuard::iter`](IterGuard::iter).
+pub struct IterMut<'b, D: Data> {
+    nodes: std::slice::IterMut<'b, Node<D>>,
+}
+
+impl<'b, D: Data> Iterator for IterMut<'b, D> {
+    type Item = &'b mut D::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &mut node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<'b, D: Data> DoubleEndedIterator for IterMut<'b, D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.nodes.next_back().map(|node| &mut node.value)
+    }
+}
+
+/// A walker for an [`ArenaTree`]. It keeps the indices of the nodes on the path
+/// from the root to its position, and updates the tree's root when it is dropped.
+///
+/// Inserting a value at an empty position splits the subtree around it,
+/// so that the new node gets its priority's place in the tree, as in treaps.
+/// Deleting a value merges its sons in its place.
+pub struct ArenaWalker<'a, D: Data> {
+    tree: &'a mut ArenaTree<D>,
+    // The current position. Its node, and all the nodes on the path, have no pending actions.
+    link: Link,
+    // The nodes on the path from the root, and the side that the path goes to from each of them.
+    path: Vec<(u32, Side)>,
+    // The summaries of the values to the left and to the right of the subtree
+    // of every position on the path, including the current one.
+    frames: Vec<(D::Summary, D::Summary)>,
+}
+
+impl<'a, D: Data> ArenaWalker<'a, D> {
+    fn go_to_son(&mut self, side: Side) -> Result<(), NavError> {
+        let index = self.link.ok_or(NavError::EmptyPosition)?;
+        let node = self.tree.node(index);
+        let value_summary = node.value.to_summary();
+        let (left, right) = self
+            .frames
+            .last()
+            .expect("A walker should have a frame")
+            .clone();
+        let (son, frame) = match side {
+            Side::Left => (
+                node.left,
+                (left, value_summary + self.tree.summary(node.right) + right),
+            ),
+            Side::Right => (
+                node.right,
+                (left + self.tree.summary(node.left) + value_summary, right),
+            ),
+        };
+        if let Some(son) = son {
+            self.tree.access(son);
+        }
+        self.path.push((index, side));
+        self.frames.push(frame);
+        self.link = son;
+        Ok(())
+    }
+
+    // Goes up to the root, and returns the sides from the root to the current position.
+    fn go_to_root_recording(&mut self) -> Vec<Side> {
+        let mut sides = vec![];
+        while let Ok(side) = self.go_up() {
+            sides.push(side);
+        }
+        sides.reverse();
+        sides
+    }
+}
+
+impl<'a, D: Data> Drop for ArenaWalker<'a, D> {
+    fn drop(&mut self) {
+        self.go_to_root();
+        self.tree.root = self.link;
+    }
+}
+
+impl<'a, D: Data> SomeWalker<D> for ArenaWalker<'a, D> {
+    fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    fn value(&self) -> Option<&D::Value> {
+        Some(&self.tree.node(self.link?).value)
+    }
+
+    fn peek_left(&mut self) -> Option<&D::Value> {
+        let son = self.tree.node(self.link?).left?;
+        self.tree.access(son);
+        Some(&self.tree.node(son).value)
+    }
+
+    fn peek_right(&mut self) -> Option<&D::Value> {
+        let son = self.tree.node(self.link?).right?;
+        self.tree.access(son);
+        Some(&self.tree.node(son).value)
+    }
+
+    fn go_left(&mut self) -> Result<(), NavError> {
+        self.go_to_son(Side::Left)
+    }
+
+    fn go_right(&mut self) -> Result<(), NavError> {
+        self.go_to_son(Side::Right)
+    }
+
+    fn go_up(&mut self) -> Result<Side, NavError> {
+        let (index, side) = self.path.pop().ok_or(NavError::AtRoot)?;
+        self.frames.pop();
+        let node = self.tree.node_mut(index);
+        match side {
+            Side::Left => node.left = self.link,
+            Side::Right => node.right = self.link,
+        }
+        self.tree.rebuild(index);
+        self.link = Some(index);
+        Ok(side)
+    }
+
+    fn far_left_summary(&self) -> D::Summary {
+        self.frames
+            .last()
+            .expect("A walker should have a frame")
+            .0
+            .clone()
+    }
+
+    fn far_right_summary(&self) -> D::Summary {
+        self.frames
+            .last()
+            .expect("A walker should have a frame")
+            .1
+            .clone()
+    }
+}
+
+impl<'a, D: Data> SomeEntry<D> for ArenaWalker<'a, D> {
+    fn with_value<F, R>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut D::Value) -> R,
+    {
+        let index = self.link?;
+        let res = f(&mut self.tree.node_mut(index).value);
+        self.tree.rebuild(index);
+        Some(res)
+    }
+
+    fn node_summary(&self) -> D::Summary {
+        match self.link {
+            None => Default::default(),
+            Some(index) => self.tree.node(index).value.to_summary(),
+        }
+    }
+
+    fn subtree_summary(&self) -> D::Summary {
+        self.tree.summary(self.link)
+    }
+
+    fn left_subtree_summary(&self) -> Option<D::Summary> {
+        Some(self.tree.summary(self.tree.node(self.link?).left))
+    }
+
+    fn right_subtree_summary(&self) -> Option<D::Summary> {
+        Some(self.tree.summary(self.tree.node(self.link?).right))
+    }
+
+    fn act_node(&mut self, action: D::Action) -> Option<()> {
+        let index = self.link?;
+        action.act_inplace(&mut self.tree.node_mut(index).value);
+        self.tree.rebuild(index);
+        Some(())
+    }
+
+    fn act_subtree(&mut self, action: D::Action) {
+        self.tree.act(self.link, action);
+        if let Some(index) = self.link {
+            self.tree.access(index);
+        }
+    }
+
+    fn act_left_subtree(&mut self, action: D::Action) -> Option<()> {
+        let index = self.link?;
+        self.tree.act(self.tree.node(index).left, action);
+        self.tree.rebuild(index);
+        Some(())
+    }
+
+    fn act_right_subtree(&mut self, action: D::Action) -> Option<()> {
+        let index = self.link?;
+        self.tree.act(self.tree.node(index).right, action);
+        self.tree.rebuild(index);
+        Some(())
+    }
+
+    fn assert_correctness_locally(&self)
+    where
+        D::Summary: Eq,
+    {
+        if let Some(index) = self.link {
+            self.tree.assert_node_locally(index);
+        }
+    }
+}
+
+impl<'a, D: Data> ModifiableWalker<D> for ArenaWalker<'a, D> {
+    /// Inserts the value at the current position, if it is empty.
+    /// The walker goes up to the place where the new node's priority puts it,
+    /// and splits the subtree there around the empty position.
+    /// The walker ends up at the inserted value.
+    fn insert(&mut self, value: D::Value) -> Result<(), NavError> {
+        if self.link.is_some() {
+            return Err(NavError::OccupiedPosition);
+        }
+        let index = self.tree.alloc(value);
+        let priority = self.tree.node(index).priority;
+        let mut sides = vec![];
+        while let Some(&(father, _)) = self.path.last() {
+            if self.tree.node(father).priority > priority {
+                break;
+            }
+            sides.push(self.go_up().expect("The walker shouldn't be at the root"));
+        }
+        sides.reverse();
+        let (left, right) = self.tree.split_path(self.link, &sides);
+        let node = self.tree.node_mut(index);
+        node.left = left;
+        node.right = right;
+        self.tree.rebuild(index);
+        self.link = Some(index);
+        Ok(())
+    }
+
+    /// Deletes the current value, and merges its sons in its place.
+    /// The walker stays at the same position.
+    fn delete(&mut self) -> Option<D::Value> {
+        let index = self.link?;
+        let node = self.tree.node(index);
+        self.link = self.tree.join(node.left, node.right);
+        if let Some(new_index) = self.link {
+            self.tree.access(new_index);
+        }
+        Some(self.tree.dealloc(index))
+    }
+}
+
+impl<'a, D: Data> SplittableWalker<D> for ArenaWalker<'a, D> {
+    type T = ArenaTree<D>;
+
+    /// If the current position is empty, splits the tree there, and returns the part
+    /// to its right. The nodes of the smaller part are moved to a new vector, so this takes
+    /// `O(m + log n)` expected time, where `m` is the size of the smaller part.
+    /// The walker ends up at the root.
+    ///```
+    /// use grove::{SomeTree, SplittableWalker, arena::ArenaTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: ArenaTree<StdNum> = (17..88).collect();
+    /// let mut tree2 = tree.slice(7..7).split_right().unwrap();
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..24).collect::<Vec<_>>());
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (24..88).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    /// # tree2.assert_correctness();
+    ///```
+    fn split_right(&mut self) -> Option<ArenaTree<D>> {
+        if self.link.is_some() {
+            return None;
+        }
+        let sides = self.go_to_root_recording();
+        let (left, right) = self.tree.split_path(self.link, &sides);
+        let (left, right) = self.tree.separate(left, right);
+        self.link = left;
+        if let Some(index) = self.link {
+            self.tree.access(index);
+        }
+        Some(right)
+    }
+
+    /// If the current position is empty, splits the tree there, and returns the part
+    /// to its left. The nodes of the smaller part are moved to a new vector, as in
+    /// [`SplittableWalker::split_right`].
+    /// The walker ends up at the root.
+    fn split_left(&mut self) -> Option<ArenaTree<D>> {
+        let mut right = self.split_right()?;
+        self.tree.root = self.link;
+        std::mem::swap(self.tree, &mut right);
+        self.link = self.tree.root;
+        if let Some(index) = self.link {
+            self.tree.access(index);
+        }
+        Some(right)
+    }
+
+    /// Moves the nodes of the current subtree to a new tree, in time linear in its size.
+    /// The walker stays at the same position, which becomes empty.
+    fn take_subtree(&mut self) -> ArenaTree<D> {
+        let mut subtree = ArenaTree::new();
+        let link = self.link.take();
+        subtree.root = self.tree.move_to(link, &mut subtree);
+        subtree
+    }
+
+    /// Splits the tree at the current position, and merges the three parts.
+    /// The nodes of the smaller of the two trees are moved into the other one's vector,
+    /// so this takes time linear in the size of the smaller tree.
+    /// The walker ends up at the root.
+    fn put_subtree(&mut self, mut subtree: ArenaTree<D>) -> Result<(), ArenaTree<D>> {
+        if self.link.is_some() {
+            return Err(subtree);
+        }
+        let sides = self.go_to_root_recording();
+        let (left, right) = self.tree.split_path(self.link, &sides);
+        let (left, middle, right) = if subtree.num_nodes() <= self.tree.num_nodes() {
+            (left, subtree.move_to(subtree.root, self.tree), right)
+        } else {
+            let left = self.tree.move_to(left, &mut subtree);
+            let right = self.tree.move_to(right, &mut subtree);
+            std::mem::swap(self.tree, &mut subtree);
+            (left, self.tree.root, right)
+        };
+        let rest = self.tree.join(middle, right);
+        self.link = self.tree.join(left, rest);
+        if let Some(index) = self.link {
+            self.tree.access(index);
+        }
+        Ok(())
+    }
+}
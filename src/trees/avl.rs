@@ -17,6 +17,8 @@ type TD = i8;
 /// Algorithm that has a small memory overhead per node.
 pub struct AVLTree<D: Data> {
     tree: BasicTree<D, T>,
+    // the counts of the operations performed through the tree's walkers
+    stats: Counters,
 }
 
 /// For implementing `rank`, `rank_diff` and `rebuild_ranks` for
@@ -65,7 +67,7 @@ impl<D: Data> Rankable for BasicNode<D, T> {
     /// Returns `right.rank() - left.rank()`
     fn rank_diff(&self) -> TD {
         let diff = self.right.rank() as TD - self.left.rank() as TD;
-        if self.action().to_reverse() {
+        if D::Action::REVERSIBLE && self.action().to_reverse() {
             -diff
         } else {
             diff
@@ -85,6 +87,37 @@ impl<D: Data> AVLTree<D> {
     pub fn new() -> Self {
         AVLTree {
             tree: BasicTree::Empty,
+            stats: Default::default(),
+        }
+    }
+
+    /// Creates a read-only walker for the tree, that only needs a shared reference.
+    /// See [`ImmBasicWalker`].
+    pub fn imm_walker(&self) -> ImmBasicWalker<'_, D, T> {
+        self.tree.imm_walker()
+    }
+
+    /// Returns the underlying [`BasicTree`].
+    pub(crate) fn inner(&self) -> &BasicTree<D, T> {
+        &self.tree
+    }
+
+    /// Returns the underlying [`BasicTree`]. The ranks mustn't be changed.
+    pub(crate) fn inner_mut(&mut self) -> &mut BasicTree<D, T> {
+        &mut self.tree
+    }
+
+    /// Creates a walker that reuses the vectors of the given buffer instead of allocating
+    /// new ones. See [`WalkerBuffer`].
+    pub fn walker_with_buffer<'a>(
+        &'a mut self,
+        buffer: &'a mut WalkerBuffer<D>,
+    ) -> AVLWalker<'a, D> {
+        AVLWalker {
+            walker: self
+                .tree
+                .walker_with_buffer(buffer)
+                .count_into(&mut self.stats),
         }
     }
 
@@ -108,6 +141,25 @@ impl<D: Data> AVLTree<D> {
         self.tree
             .assert_correctness_with(Self::assert_ranks_locally_internal);
     }
+
+    fn check_ranks_locally(node: &BasicNode<D, T>) -> Result<(), Violation> {
+        let (rank, left, right) = (node.rank(), node.left.rank(), node.right.rank());
+        if rank == std::cmp::max(left, right) + 1 && left.abs_diff(right) <= 1 {
+            Ok(())
+        } else {
+            Err(Violation::Rank { rank, left, right })
+        }
+    }
+}
+
+impl<D: Data> ValidateTree<D> for AVLTree<D> {
+    /// Checks the summaries and the ranks.
+    fn validate(&self) -> Result<(), InvariantError>
+    where
+        D::Summary: Eq,
+    {
+        self.tree.validate_with(Self::check_ranks_locally)
+    }
 }
 
 impl<D: Data> Rankable for AVLTree<D> {
@@ -131,7 +183,92 @@ impl<D: Data> Default for AVLTree<D> {
     }
 }
 
+/// Clones the tree structurally, in `O(n)` time. See [`BasicTree`]'s `Clone` implementation.
+impl<D: Data> Clone for AVLTree<D>
+where
+    D::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        AVLTree {
+            tree: self.tree.clone(),
+            stats: Default::default(),
+        }
+    }
+}
+
+/// Compares the values of the trees in order, in `O(n)` time.
+/// See [`BasicTree`]'s `PartialEq` implementation.
+impl<D: Data> PartialEq for AVLTree<D>
+where
+    D::Value: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.tree == other.tree
+    }
+}
+
+impl<D: Data> Eq for AVLTree<D> where D::Value: Clone + Eq {}
+
+/// Compares the values of the trees lexicographically.
+/// See [`BasicTree`]'s `PartialOrd` implementation.
+impl<D: Data> PartialOrd for AVLTree<D>
+where
+    D::Value: Clone + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.tree.partial_cmp(&other.tree)
+    }
+}
+
+/// Compares the values of the trees lexicographically.
+/// See [`BasicTree`]'s `Ord` implementation.
+impl<D: Data> Ord for AVLTree<D>
+where
+    D::Value: Clone + Ord,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.tree.cmp(&other.tree)
+    }
+}
+
+/// Hashes the values of the tree in order, in `O(n)` time.
+/// See [`BasicTree`]'s `Hash` implementation.
+impl<D: Data> std::hash::Hash for AVLTree<D>
+where
+    D::Value: Clone + std::hash::Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tree.hash(state)
+    }
+}
+
+/// Prints the structure of the tree, in the same format as [`BasicTree`]'s
+/// `Debug` implementation.
+/// The algorithm-specific data of every node is its rank.
+impl<D: Data> std::fmt::Debug for AVLTree<D>
+where
+    D::Value: std::fmt::Debug,
+    D::Summary: std::fmt::Debug,
+    D::Action: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tree.fmt(f)
+    }
+}
+
 impl<D: Data> SomeTree<D> for AVLTree<D> {
+    type Walker<'a>
+        = AVLWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn walker(&mut self) -> Self::Walker<'_> {
+        AVLWalker {
+            walker: self.tree.walker().count_into(&mut self.stats),
+        }
+    }
+
     fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
     where
         L: crate::Locator<D>,
@@ -151,7 +288,7 @@ impl<D: Data> SomeTree<D> for AVLTree<D> {
     where
         L: crate::Locator<D>,
     {
-        if !action.to_reverse() {
+        if (!D::Action::REVERSIBLE || !action.to_reverse()) && !action.is_indexed() {
             segment_algorithms::act_segment(self, action, locator)
         } else {
             // split out the middle
@@ -180,43 +317,56 @@ impl<D: Data> SomeTree<D> for AVLTree<D> {
         }
     }
 
-    type TreeData = u8;
+    type IterLocator<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterLocator<'a, D, L, u8>
+    where
+        Self: 'a,
+        D: 'a;
+    type IterLocatorMut<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterMutGuard<'a, D, L, u8>
+    where
+        Self: 'a,
+        D: 'a;
+    type IntoIterSegment<L: locators::Locator<D>> = basic_tree::iterators::IntoIter<D, L, u8>;
+
     fn iter_locator<'a, L: locators::Locator<D>>(
         &'a mut self,
         locator: L,
-    ) -> basic_tree::iterators::IterLocator<'a, D, L, u8> {
+    ) -> Self::IterLocator<'a, L> {
         iterators::IterLocator::new(&mut self.tree, locator)
     }
 
+    fn iter_locator_mut<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> Self::IterLocatorMut<'a, L> {
+        iterators::IterMutGuard::new(&mut self.tree, locator)
+    }
+
+    fn into_iter_segment<L: locators::Locator<D>>(self, locator: L) -> Self::IntoIterSegment<L> {
+        iterators::IntoIter::new(self.tree, locator)
+    }
+
     fn assert_correctness(&self)
     where
         D::Summary: Eq,
     {
-        self.tree.assert_correctness_with(|node| {
-            node.assert_correctness_locally();
-            Self::assert_ranks_locally_internal(node);
-        });
-    }
-}
-
-impl<'a, D: Data> SomeTreeRef<D> for &'a mut AVLTree<D> {
-    type Walker = AVLWalker<'a, D>;
-
-    fn walker(self) -> Self::Walker {
-        AVLWalker {
-            walker: self.tree.walker(),
+        if let Err(err) = self.validate() {
+            panic!("{}", err);
         }
     }
 }
 
-impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut AVLTree<D> {
-    type ModifiableWalker = AVLWalker<'a, D>;
-}
-
-impl<'a, D: Data> SplittableTreeRef<D> for &'a mut AVLTree<D> {
-    type T = AVLTree<D>;
+impl<D: Data> SplittableTree<D> for AVLTree<D> {
+    type SplittableWalker<'a>
+        = AVLWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
 
-    type SplittableWalker = AVLWalker<'a, D>;
+    fn splittable_walker(&mut self) -> Self::SplittableWalker<'_> {
+        self.walker()
+    }
 }
 
 derive_SomeEntry! {tree, T,
@@ -246,13 +396,23 @@ impl<D: Data> std::iter::FromIterator<D::Value> for AVLTree<D> {
             // note: this relies on the assumption, that after we insert a node, the new position of the locator
             // will be an ancestor of the location where the value was inserted.
             while walker.go_right().is_ok() {}
-            walker.insert(val);
+            walker.insert(val).expect("Expected empty position");
         }
         drop(walker);
+        // building the tree isn't counted
+        tree.stats = Default::default();
         tree
     }
 }
 
+impl<D: Data> Extend<D::Value> for AVLTree<D> {
+    /// Builds a tree out of the values, and concatenates it to the right of this tree.
+    /// This takes `O(m + log n)` time.
+    fn extend<T: IntoIterator<Item = D::Value>>(&mut self, iter: T) {
+        self.concatenate_right(iter.into_iter().collect());
+    }
+}
+
 impl<D: Data> IntoIterator for AVLTree<D> {
     type Item = D::Value;
     type IntoIter = iterators::IntoIter<D, std::ops::RangeFull, T>;
@@ -262,6 +422,125 @@ impl<D: Data> IntoIterator for AVLTree<D> {
     }
 }
 
+impl<D: Data> From<Vec<D::Value>> for AVLTree<D> {
+    /// Builds a balanced tree out of the values, in `O(n)` time.
+    /// Same as collecting the values.
+    fn from(values: Vec<D::Value>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<D: Data> From<AVLTree<D>> for Vec<D::Value> {
+    /// Returns the tree's values in order, in `O(n)` time.
+    /// Same as collecting the tree's values.
+    fn from(tree: AVLTree<D>) -> Self {
+        tree.into_iter().collect()
+    }
+}
+
+impl<D: Data> From<super::splay::SplayTree<D>> for AVLTree<D> {
+    /// Converts a splay tree into a balanced [`AVLTree`], in `O(n)` time.
+    /// See [`SomeTree::convert`].
+    fn from(tree: super::splay::SplayTree<D>) -> Self {
+        tree.convert()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: Data> rayon::iter::IntoParallelIterator for AVLTree<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    type Iter = basic_tree::parallel::IntoParIter<D, T>;
+    type Item = D::Value;
+
+    fn into_par_iter(self) -> Self::Iter {
+        basic_tree::parallel::IntoParIter::new(self.tree)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: Data> rayon::iter::FromParallelIterator<D::Value> for AVLTree<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    /// Builds a perfectly balanced [`AVLTree`], building subtrees in parallel.
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = D::Value>,
+    {
+        use rayon::iter::ParallelIterator;
+        let values = par_iter.into_par_iter().collect();
+        AVLTree {
+            tree: basic_tree::parallel::build_balanced(values, |left, right| {
+                std::cmp::max(left.rank(), right.rank()) + 1
+            }),
+            stats: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<D: Data> serde::Serialize for AVLTree<D>
+where
+    D::Value: serde::Serialize + Clone,
+{
+    /// Serializes the values in order, each paired with its depth.
+    /// See [`basic_tree::serialization`].
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.tree.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, D: Data> serde::Deserialize<'de> for AVLTree<D>
+where
+    D::Value: serde::Deserialize<'de>,
+{
+    /// Reconstructs the exact shape of the serialized tree, recomputing the ranks.
+    /// Returns an error if the tree isn't balanced.
+    fn deserialize<De: serde::Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let tree = basic_tree::serialization::deserialize_tree(deserializer, |node| {
+            node.rebuild_ranks();
+            if node.rank_diff().abs() > 1 {
+                Err("The deserialized tree is not balanced")
+            } else {
+                Ok(())
+            }
+        })?;
+        Ok(AVLTree {
+            tree,
+            stats: Default::default(),
+        })
+    }
+}
+
+#[cfg(feature = "instrument")]
+impl<D: Data> instrument::Instrumented for AVLTree<D> {
+    fn stats(&self) -> instrument::Stats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = Default::default();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, D: Data> arbitrary::Arbitrary<'a> for AVLTree<D>
+where
+    D::Value: arbitrary::Arbitrary<'a>,
+{
+    /// Generates a balanced tree out of an arbitrary sequence of values.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+}
+
 /// A walker struct for [`AVLTree`].
 pub struct AVLWalker<'a, D: Data> {
     walker: BasicWalker<'a, D, T>,
@@ -273,9 +552,21 @@ impl<'a, D: Data> std::ops::Drop for AVLWalker<'a, D> {
     }
 }
 
+/// Prints the walker in the same format as [`BasicWalker`]'s `Debug` implementation.
+impl<'a, D: Data> std::fmt::Debug for AVLWalker<'a, D>
+where
+    D::Value: std::fmt::Debug,
+    D::Summary: std::fmt::Debug,
+    D::Action: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.walker.fmt(f)
+    }
+}
+
 derive_SomeWalker! {walker,
     impl<'a, D: Data> SomeWalker<D> for AVLWalker<'a, D> {
-        fn go_up(&mut self) -> Result<Side, ()> {
+        fn go_up(&mut self) -> Result<Side, NavError> {
             let res = self.walker.go_up()?;
             let changed = self.inner_mut().rebuild_ranks();
             assert!(!changed); // it shouldn't have changed without being rebalanced already
@@ -460,14 +751,15 @@ impl<'a, D: Data> AVLWalker<'a, D> {
 
 impl<'a, D: Data> ModifiableWalker<D> for AVLWalker<'a, D> {
     /// Inserts the value into the tree at the current empty position.
-    /// If the current position is not empty, return [`None`].
+    /// If the current position is not empty, returns `Err(NavError::OccupiedPosition)`.
     /// When the function returns, the walker will be at a position which is an ancestor of the
     /// newly inserted node.
-    fn insert(&mut self, val: D::Value) -> Option<()> {
+    fn insert(&mut self, val: D::Value) -> Result<(), NavError> {
         self.walker
-            .insert_with_alg_data(val, 1 /* rank of a node with no sons */)?;
+            .insert_with_alg_data(val, 1 /* rank of a node with no sons */)
+            .ok_or(NavError::OccupiedPosition)?;
         self.rebalance();
-        Some(())
+        Ok(())
     }
 
     /// The walker reorganizes the current subtree in order to delete the current node,
@@ -513,13 +805,19 @@ impl<'a, D: Data> SplittableWalker<D> for AVLWalker<'a, D> {
             match side {
                 Side::Left => {
                     assert!(node.left.is_empty());
-                    let auxiliary_right = AVLTree { tree: node.right };
+                    let auxiliary_right = AVLTree {
+                        tree: node.right,
+                        stats: Default::default(),
+                    };
                     node.right = BasicTree::Empty;
                     AVLTree::concatenate_boxed_middle_right(&mut right, node, auxiliary_right);
                 }
                 Side::Right => {
                     assert!(node.right.is_empty());
-                    let auxiliary_left = AVLTree { tree: node.left };
+                    let auxiliary_left = AVLTree {
+                        tree: node.left,
+                        stats: Default::default(),
+                    };
                     node.left = BasicTree::Empty;
                     AVLTree::concatenate_boxed_middle_left(&mut left, auxiliary_left, node);
                 }
@@ -556,6 +854,43 @@ impl<'a, D: Data> SplittableWalker<D> for AVLWalker<'a, D> {
         std::mem::swap(&mut right.tree, self.inner_mut());
         Some(right)
     }
+
+    /// Takes the current subtree out of the tree. Since the heights along the path to the root
+    /// may now be unbalanced by more than one, the rest of the tree is rebalanced by splitting it
+    /// at the now empty position and concatenating the two parts back, in `O(log n)` time.
+    /// The walker ends up at the root.
+    fn take_subtree(&mut self) -> Self::T {
+        let subtree = AVLTree {
+            tree: self.walker.take_subtree(),
+            stats: Default::default(),
+        };
+        let right = self.split_right().unwrap();
+        let mut rest = AVLTree {
+            tree: self.walker.take_subtree(),
+            stats: Default::default(),
+        };
+        rest.concatenate_right(right);
+        self.walker.put_subtree(rest.tree).unwrap();
+        subtree
+    }
+
+    /// Puts the subtree at the current empty position, by splitting the tree there,
+    /// and concatenating the three parts, in `O(log n)` time.
+    /// The walker ends up at the root.
+    fn put_subtree(&mut self, subtree: Self::T) -> Result<(), Self::T> {
+        if !self.is_empty() {
+            return Err(subtree);
+        }
+        let right = self.split_right().unwrap();
+        let mut tree = AVLTree {
+            tree: self.walker.take_subtree(),
+            stats: Default::default(),
+        };
+        tree.concatenate_right(subtree);
+        tree.concatenate_right(right);
+        self.walker.put_subtree(tree.tree).unwrap();
+        Ok(())
+    }
 }
 
 impl<D: Data> AVLTree<D> {
@@ -0,0 +1,113 @@
+// This is a private module, so no documentation for it directly.
+// Instead look at the `Debug` implementations of `BasicTree`, `BasicNode` and `BasicWalker`.
+
+use super::*;
+use std::fmt::{self, Debug, Formatter};
+
+// Writes the subtree rooted at `node`, one node per line, with each son indented
+// one level deeper than its father. Empty sons aren't printed.
+// The nodes are printed exactly as they are stored: pending actions are not pushed down,
+// so nodes whose action contains a reversal will still have their sons in the
+// pre-reversal order.
+// This uses an explicit stack instead of recursion, since the tree might be deep.
+fn fmt_node<D: Data, T: Debug>(
+    node: &BasicNode<D, T>,
+    indent: usize,
+    f: &mut Formatter<'_>,
+) -> fmt::Result
+where
+    D::Value: Debug,
+    D::Summary: Debug,
+    D::Action: Debug,
+{
+    // the nodes that are yet to be printed, in reverse order
+    let mut stack = vec![(node, "", indent)];
+    while let Some((node, label, indent)) = stack.pop() {
+        write!(
+            f,
+            "{:indent$}{}value: {:?}, summary: {:?}, alg_data: {:?}",
+            "",
+            label,
+            node.node_value,
+            node.subtree_summary,
+            node.alg_data,
+            indent = indent
+        )?;
+        if !node.action.is_identity() {
+            write!(f, ", pending action: {:?}", node.action)?;
+        }
+        for (son, label) in [(&node.right, "R: "), (&node.left, "L: ")] {
+            if let Some(son) = son.node() {
+                stack.push((son, label, indent + 2));
+            }
+        }
+        if !stack.is_empty() {
+            writeln!(f)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the structure of the tree, one node per line, indenting every node's sons
+/// under it. Every node is printed with its value, the summary of its subtree and its
+/// algorithm-specific data, as stored, and with its action if it isn't the identity.
+///```
+/// use grove::{*, basic_tree::*};
+/// use grove::example_data::StdNum;
+///
+/// let tree: BasicTree<StdNum> = (1..=3).collect();
+/// let printed = format!("{:?}", tree);
+/// assert_eq!(printed.lines().count(), 3);
+/// assert!(printed.starts_with("value: 2"));
+/// # tree.assert_correctness();
+///```
+impl<D: Data, T: Debug> Debug for BasicTree<D, T>
+where
+    D::Value: Debug,
+    D::Summary: Debug,
+    D::Action: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.node() {
+            None => write!(f, "Empty"),
+            Some(node) => node.fmt(f),
+        }
+    }
+}
+
+/// Prints the structure of the node's subtree, in the same format as [`BasicTree`]'s
+/// `Debug` implementation.
+impl<D: Data, T: Debug> Debug for BasicNode<D, T>
+where
+    D::Value: Debug,
+    D::Summary: Debug,
+    D::Action: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_node(self, 0, f)
+    }
+}
+
+/// Prints the path from the root to the walker's current position, the summaries
+/// to the left and right of the current position, and then the structure of the
+/// current subtree.
+impl<'a, D: Data, T: Debug> Debug for BasicWalker<'a, D, T>
+where
+    D::Value: Debug,
+    D::Summary: Debug,
+    D::Action: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "path: {:?}", self.is_left)?;
+        writeln!(f, "far left summary: {:?}", self.far_left_summary())?;
+        writeln!(f, "far right summary: {:?}", self.far_right_summary())?;
+        write!(f, "subtree:")?;
+        match self.inner().node() {
+            None => write!(f, " Empty"),
+            Some(node) => {
+                writeln!(f)?;
+                fmt_node(node, 2, f)
+            }
+        }
+    }
+}
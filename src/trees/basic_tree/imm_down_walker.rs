@@ -2,7 +2,6 @@ use crate::*;
 use trees::basic_tree::BasicTree;
 
 /// A BasicWalker version that is immutable, and can only go down.
-#[derive(Copy)]
 pub(crate) struct ImmDownBasicWalker<'a, D: Data, T = ()> {
     tree: &'a BasicTree<D, T>,
 
@@ -22,7 +21,12 @@ pub(crate) struct ImmDownBasicWalker<'a, D: Data, T = ()> {
 /// `D: Clone` and `T: Clone`.
 impl<'a, D: Data, T> Clone for ImmDownBasicWalker<'a, D, T> {
     fn clone(&self) -> Self {
-        ImmDownBasicWalker { ..*self }
+        ImmDownBasicWalker {
+            tree: self.tree,
+            current_action: self.current_action.clone(),
+            far_left_summary: self.far_left_summary.clone(),
+            far_right_summary: self.far_right_summary.clone(),
+        }
     }
 }
 
@@ -72,11 +76,12 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
             std::mem::swap(&mut left, &mut right);
         }
 
-        let extra = self.current_action.act(node.node_value.to_summary())
-            + self.current_action.act(right.subtree_summary());
-        self.far_right_summary = extra + self.far_right_summary;
+        let (value_action, right_action) = node.inner_actions(&self.current_action);
+        let extra = value_action.act(node.node_value.to_summary())
+            + right_action.act(right.subtree_summary());
+        self.far_right_summary = extra.clone() + std::mem::take(&mut self.far_right_summary);
         self.tree = left;
-        self.current_action = self.current_action + left.action();
+        self.current_action = std::mem::take(&mut self.current_action) + left.action();
         Some(extra)
     }
 
@@ -101,11 +106,12 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
             std::mem::swap(&mut left, &mut right);
         }
 
+        let (value_action, right_action) = node.inner_actions(&self.current_action);
         let extra = self.current_action.act(left.subtree_summary())
-            + self.current_action.act(node.node_value.to_summary());
-        self.far_left_summary = self.far_left_summary + extra;
+            + value_action.act(node.node_value.to_summary());
+        self.far_left_summary = std::mem::take(&mut self.far_left_summary) + extra.clone();
         self.tree = right;
-        self.current_action = self.current_action + right.action();
+        self.current_action = right_action + right.action();
         Some(extra)
     }
 
@@ -114,18 +120,16 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
     where
         D::Value: Clone,
     {
-        Some(
-            self.current_action
-                .act(self.tree.node()?.node_value.clone()),
-        )
+        let node = self.tree.node()?;
+        let (value_action, _) = node.inner_actions(&self.current_action);
+        Some(value_action.act(node.node_value.clone()))
     }
 
     /// Returns the summary of just this node.
     pub fn node_summary(&self) -> Option<D::Summary> {
-        Some(
-            self.current_action
-                .act(self.tree.node()?.node_value.to_summary()),
-        )
+        let node = self.tree.node()?;
+        let (value_action, _) = node.inner_actions(&self.current_action);
+        Some(value_action.act(node.node_value.to_summary()))
     }
 
     pub fn left_summary(&self) -> D::Summary {
@@ -135,9 +139,9 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
             } else {
                 &node.left
             };
-            self.far_left_summary + self.current_action.act(left.subtree_summary())
+            self.far_left_summary.clone() + self.current_action.act(left.subtree_summary())
         } else {
-            self.far_left_summary
+            self.far_left_summary.clone()
         }
     }
 
@@ -148,13 +152,38 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
             } else {
                 &node.right
             };
-            self.current_action.act(right.subtree_summary()) + self.far_right_summary
+            let (_, right_action) = node.inner_actions(&self.current_action);
+            right_action.act(right.subtree_summary()) + self.far_right_summary.clone()
         } else {
-            self.far_right_summary
+            self.far_right_summary.clone()
         }
     }
 
-    pub fn alg_data(&self) -> Option<&T> {
+    /// Returns the summary of everything to the left of the current subtree.
+    pub fn far_left_summary(&self) -> D::Summary {
+        self.far_left_summary.clone()
+    }
+
+    /// Returns the summary of everything to the right of the current subtree.
+    pub fn far_right_summary(&self) -> D::Summary {
+        self.far_right_summary.clone()
+    }
+
+    /// Returns the summary of the current subtree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        match self.tree.node() {
+            // `current_action` already contains the node's own action
+            Some(node) => self.current_action.act(node.subtree_summary.clone()),
+            None => Default::default(),
+        }
+    }
+
+    /// Returns true if at an empty position.
+    pub fn is_empty(&self) -> bool {
+        self.tree.node().is_none()
+    }
+
+    pub fn alg_data(&self) -> Option<&'a T> {
         self.tree.alg_data()
     }
 
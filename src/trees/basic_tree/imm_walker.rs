@@ -0,0 +1,167 @@
+// This is a private module, so no documentation for it directly.
+// instead look for documentation of the `ImmBasicWalker` struct.
+
+use super::*;
+
+/// A read-only walker for the [`BasicTree`] type, that only needs a shared reference to the tree.
+///
+/// Just like [`BasicWalker`], it can walk up and down the tree, and keeps the summaries of
+/// everything to the left and to the right of the current position.
+/// Instead of the [`recursive_reference::RecRef`] that [`BasicWalker`] uses, it keeps
+/// a stack of shared references to all the subtrees from the root to the current position,
+/// which doesn't upset the borrow checker since shared references can be freely copied.
+///
+/// Since the walker can't modify the tree, it can't push actions down to the nodes
+/// it visits. Instead, it keeps the composition of all of the actions from the root to every
+/// subtree in the stack, and applies it to the values and summaries it returns.
+/// Therefore, values are returned by value, and require `D::Value: Clone`.
+///```
+/// use grove::{*, basic_tree::*};
+/// use grove::example_data::{StdNum, RevAffineAction};
+///
+/// let mut tree: BasicTree<StdNum> = (0..7).collect();
+/// tree.node_mut().unwrap().act(RevAffineAction { to_reverse: true, mul: 1, add: 0 });
+///
+/// let tree = &tree;
+/// let mut walker = tree.imm_walker();
+/// walker.go_left().unwrap();
+/// assert_eq!(walker.value(), Some(5));
+/// walker.go_right().unwrap();
+/// assert_eq!(walker.value(), Some(4));
+/// assert_eq!(walker.far_left_summary().sum, 6 + 5);
+/// assert_eq!(walker.go_up(), Ok(Side::Right));
+/// assert_eq!(walker.go_up(), Ok(Side::Left));
+/// assert_eq!(walker.go_up(), Err(NavError::AtRoot));
+/// # tree.assert_correctness();
+///```
+pub struct ImmBasicWalker<'a, D: Data, T = ()> {
+    /// Holds an immutable down-walker for every subtree from the root to the current position.
+    /// Each one holds the shared reference to its subtree, the action to be applied to it,
+    /// and the summaries to the left and to the right of it.
+    /// It is never empty.
+    stack: Vec<ImmDownBasicWalker<'a, D, T>>,
+
+    /// This array holds for every node, whether the next subtree in the walker
+    /// is its left son or the right son.
+    /// This array is always one shorter than [`ImmBasicWalker::stack`].
+    is_left: Vec<Side>,
+}
+
+impl<'a, D: Data, T> ImmBasicWalker<'a, D, T> {
+    /// Creates a new walker that walks on the given tree.
+    pub fn new(tree: &'a BasicTree<D, T>) -> Self {
+        ImmBasicWalker {
+            stack: vec![ImmDownBasicWalker::new(tree)],
+            is_left: vec![],
+        }
+    }
+
+    fn current(&self) -> &ImmDownBasicWalker<'a, D, T> {
+        self.stack
+            .last()
+            .expect("invariant violated: the stack can't be empty")
+    }
+
+    /// Goes to the left son.
+    /// If at an empty position, returns `Err(NavError::EmptyPosition)`.
+    pub fn go_left(&mut self) -> Result<(), NavError> {
+        let mut walker = self.current().clone();
+        walker.go_left().ok_or(NavError::EmptyPosition)?;
+        self.stack.push(walker);
+        self.is_left.push(Side::Left);
+        Ok(())
+    }
+
+    /// Goes to the right son.
+    /// If at an empty position, returns `Err(NavError::EmptyPosition)`.
+    pub fn go_right(&mut self) -> Result<(), NavError> {
+        let mut walker = self.current().clone();
+        walker.go_right().ok_or(NavError::EmptyPosition)?;
+        self.stack.push(walker);
+        self.is_left.push(Side::Right);
+        Ok(())
+    }
+
+    /// Goes up to the father of the current position.
+    /// If at the root, returns `Err(NavError::AtRoot)`.
+    /// Otherwise, returns which son the walker was at.
+    pub fn go_up(&mut self) -> Result<Side, NavError> {
+        let side = self.is_left.pop().ok_or(NavError::AtRoot)?;
+        self.stack.pop();
+        Ok(side)
+    }
+
+    /// Goes up all the way to the root.
+    pub fn go_to_root(&mut self) {
+        self.stack.truncate(1);
+        self.is_left.clear();
+    }
+
+    /// Returns the current depth in the tree.
+    /// The convention is, that the root is at depth zero
+    pub fn depth(&self) -> usize {
+        self.is_left.len()
+    }
+
+    /// Returns true if at an empty position.
+    pub fn is_empty(&self) -> bool {
+        self.current().is_empty()
+    }
+
+    /// If the current position is the left son of a node, returns [`Some(Left)`].
+    /// If the current position is the right son of a node, returns [`Some(Right)`].
+    /// If at the root, returns [`None`].
+    pub fn is_left_son(&self) -> Option<Side> {
+        self.is_left.last().cloned()
+    }
+
+    /// Returns the value at the current node, with all of the actions above it applied.
+    pub fn value(&self) -> Option<D::Value>
+    where
+        D::Value: Clone,
+    {
+        self.current().value()
+    }
+
+    /// Returns the summary of just the current node.
+    pub fn node_summary(&self) -> Option<D::Summary> {
+        self.current().node_summary()
+    }
+
+    /// Returns the summary of the current subtree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        self.current().subtree_summary()
+    }
+
+    /// Returns the summary of all values to the left of the current subtree.
+    pub fn far_left_summary(&self) -> D::Summary {
+        self.current().far_left_summary()
+    }
+
+    /// Returns the summary of all values to the right of the current subtree.
+    pub fn far_right_summary(&self) -> D::Summary {
+        self.current().far_right_summary()
+    }
+
+    /// Returns the summary of all values to the left of the current node.
+    pub fn left_summary(&self) -> D::Summary {
+        self.current().left_summary()
+    }
+
+    /// Returns the summary of all values to the right of the current node.
+    pub fn right_summary(&self) -> D::Summary {
+        self.current().right_summary()
+    }
+
+    /// Returns the algorithm-specific data of the current node.
+    pub fn alg_data(&self) -> Option<&'a T> {
+        self.current().alg_data()
+    }
+}
+
+impl<D: Data, T> BasicTree<D, T> {
+    /// Creates a read-only walker for the tree. See [`ImmBasicWalker`].
+    pub fn imm_walker(&self) -> ImmBasicWalker<'_, D, T> {
+        ImmBasicWalker::new(self)
+    }
+}
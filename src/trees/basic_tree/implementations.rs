@@ -9,6 +9,16 @@ use recursive_reference::RecRef;
 const NO_VALUE_ERROR: &str = "invariant violated: RecRef can't be empty";
 
 impl<D: Data> SomeTree<D> for BasicTree<D> {
+    type Walker<'a>
+        = BasicWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn walker(&mut self) -> Self::Walker<'_> {
+        BasicWalker::new(self)
+    }
+
     fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
     where
         L: Locator<D>,
@@ -31,14 +41,36 @@ impl<D: Data> SomeTree<D> for BasicTree<D> {
         segment_algorithms::act_segment(self, action, locator);
     }
 
-    type TreeData = ();
+    type IterLocator<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterLocator<'a, D, L, ()>
+    where
+        Self: 'a,
+        D: 'a;
+    type IterLocatorMut<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterMutGuard<'a, D, L, ()>
+    where
+        Self: 'a,
+        D: 'a;
+    type IntoIterSegment<L: locators::Locator<D>> = basic_tree::iterators::IntoIter<D, L, ()>;
+
     fn iter_locator<'a, L: locators::Locator<D>>(
         &'a mut self,
         locator: L,
-    ) -> basic_tree::iterators::IterLocator<'a, D, L> {
+    ) -> Self::IterLocator<'a, L> {
         iterators::IterLocator::new(self, locator)
     }
 
+    fn iter_locator_mut<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> Self::IterLocatorMut<'a, L> {
+        iterators::IterMutGuard::new(self, locator)
+    }
+
+    fn into_iter_segment<L: locators::Locator<D>>(self, locator: L) -> Self::IntoIterSegment<L> {
+        iterators::IntoIter::new(self, locator)
+    }
+
     /// Checks that invariants remain correct. i.e., that every node's summary
     /// is the sum of the summaries of its children.
     /// If it is not, panics.
@@ -46,10 +78,8 @@ impl<D: Data> SomeTree<D> for BasicTree<D> {
     where
         D::Summary: Eq,
     {
-        self.assert_correctness_locally();
-        if let Root(node) = self {
-            node.left.assert_correctness();
-            node.right.assert_correctness();
+        if let Err(err) = self.validate() {
+            panic!("{}", err);
         }
     }
 }
@@ -97,6 +127,14 @@ impl<D: Data> std::iter::FromIterator<D::Value> for BasicTree<D> {
     }
 }
 
+impl<D: Data> Extend<D::Value> for BasicTree<D> {
+    /// Since [`BasicTree`] can't concatenate, the whole tree is rebuilt with the new values,
+    /// in `O(n + m)` time.
+    fn extend<T: IntoIterator<Item = D::Value>>(&mut self, iter: T) {
+        *self = std::mem::take(self).into_iter().chain(iter).collect();
+    }
+}
+
 impl<D: Data> IntoIterator for BasicTree<D> {
     type Item = D::Value;
     type IntoIter = iterators::IntoIter<D, std::ops::RangeFull>;
@@ -105,59 +143,201 @@ impl<D: Data> IntoIterator for BasicTree<D> {
     }
 }
 
-impl<'a, D: Data, T> SomeTreeRef<D> for &'a mut BasicTree<D, T> {
-    type Walker = BasicWalker<'a, D, T>;
+impl<D: Data> From<Vec<D::Value>> for BasicTree<D> {
+    /// Builds a balanced tree out of the values, in `O(n)` time.
+    /// Same as collecting the values.
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree = BasicTree::<StdNum>::from(vec![1, 2, 3, 4, 5]);
+    /// tree.act_segment(RevAffineAction { to_reverse: false, mul: 10, add: 0 }, 1..3);
+    /// # tree.assert_correctness();
+    /// assert_eq!(Vec::from(tree), vec![1, 20, 30, 4, 5]);
+    ///```
+    fn from(values: Vec<D::Value>) -> Self {
+        values.into_iter().collect()
+    }
+}
 
-    fn walker(self) -> Self::Walker {
-        BasicWalker::new(self)
+impl<D: Data> From<BasicTree<D>> for Vec<D::Value> {
+    /// Returns the tree's values in order, in `O(n)` time.
+    /// Same as collecting the tree's values.
+    fn from(tree: BasicTree<D>) -> Self {
+        tree.into_iter().collect()
+    }
+}
+
+/// Clones the tree structurally: the clone has the same shape, and the same summaries,
+/// pending actions and algorithm-specific data in every node. This takes `O(n)` time,
+/// and doesn't use recursion, since the tree might be very deep.
+///
+/// For trees that can be cloned in `O(1)` time, see [`PersistentTree`](crate::persistent::PersistentTree).
+///```
+/// use grove::{SomeTree, basic_tree::BasicTree};
+/// use grove::example_data::{StdNum, RevAffineAction};
+///
+/// let mut tree: BasicTree<StdNum> = (0..5).collect();
+/// let copy = tree.clone();
+/// tree.act_segment(RevAffineAction { to_reverse: false, mul: 10, add: 0 }, 1..3);
+/// assert_eq!(Vec::from(tree), vec![0, 10, 20, 3, 4]);
+/// # copy.assert_correctness();
+/// assert_eq!(Vec::from(copy), vec![0, 1, 2, 3, 4]);
+///```
+impl<D: Data, T: Clone> Clone for BasicTree<D, T>
+where
+    D::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        // A post-order traversal. `clones` holds the clones of the finished subtrees
+        // whose father isn't finished yet.
+        let mut clones: Vec<BasicTree<D, T>> = vec![];
+        let mut stack = vec![(self, false)];
+        while let Some((tree, sons_done)) = stack.pop() {
+            match tree.node() {
+                None => clones.push(Empty),
+                Some(node) if sons_done => {
+                    let right = clones.pop().unwrap();
+                    let left = clones.pop().unwrap();
+                    clones.push(BasicTree::from_node(BasicNode {
+                        action: node.action.clone(),
+                        subtree_summary: node.subtree_summary.clone(),
+                        node_value: node.node_value.clone(),
+                        left,
+                        right,
+                        alg_data: node.alg_data.clone(),
+                    }));
+                }
+                Some(node) => {
+                    stack.push((tree, true));
+                    stack.push((&node.right, false));
+                    stack.push((&node.left, false));
+                }
+            }
+        }
+        clones.pop().unwrap()
+    }
+}
+
+/// Compares the values of the trees in order, like slices are compared, in `O(n)` time.
+/// The shapes of the trees, and the pending actions in their nodes, don't matter:
+/// the values are compared after the pending actions are applied to them.
+///```
+/// use grove::{SomeTree, basic_tree::BasicTree};
+/// use grove::example_data::{StdNum, RevAffineAction};
+///
+/// let mut tree: BasicTree<StdNum> = (0..5).collect();
+/// tree.act_segment(RevAffineAction { to_reverse: false, mul: 1, add: 10 }, 1..4);
+/// let other: BasicTree<StdNum> = vec![0, 11, 12, 13, 4].into_iter().collect();
+/// assert!(tree == other);
+/// # tree.assert_correctness();
+///```
+impl<D: Data, T> PartialEq for BasicTree<D, T>
+where
+    D::Value: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let values = |tree| iterators::ClonedValues::new(tree).map(|(_, value)| value);
+        values(self).eq(values(other))
+    }
+}
+
+impl<D: Data, T> Eq for BasicTree<D, T> where D::Value: Clone + Eq {}
+
+/// Compares the values of the trees lexicographically, like slices are compared.
+/// Stops at the first pair of values that differ.
+impl<D: Data, T> PartialOrd for BasicTree<D, T>
+where
+    D::Value: Clone + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        let values = |tree| iterators::ClonedValues::new(tree).map(|(_, value)| value);
+        values(self).partial_cmp(values(other))
+    }
+}
+
+/// Compares the values of the trees lexicographically, like slices are compared.
+/// Stops at the first pair of values that differ.
+impl<D: Data, T> Ord for BasicTree<D, T>
+where
+    D::Value: Clone + Ord,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let values = |tree| iterators::ClonedValues::new(tree).map(|(_, value)| value);
+        values(self).cmp(values(other))
+    }
+}
+
+/// Hashes the values of the tree in order, in `O(n)` time.
+/// Equal trees have equal hashes, regardless of their shapes.
+impl<D: Data, T> std::hash::Hash for BasicTree<D, T>
+where
+    D::Value: Clone + std::hash::Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for (_, value) in iterators::ClonedValues::new(self) {
+            value.hash(state);
+            len += 1;
+        }
+        // so that a sequence of values doesn't have the same hash as a prefix of it
+        state.write_usize(len);
     }
 }
 
 impl<'a, D: Data, T> SomeWalker<D> for BasicWalker<'a, D, T> {
-    fn go_left(&mut self) -> Result<(), ()> {
+    fn go_left(&mut self) -> Result<(), NavError> {
         let mut frame = self.vals.last().expect(NO_VALUE_ERROR).clone();
         let res = RecRef::extend_result(&mut self.rec_ref, |tree| {
             if let Some(node) = tree.node_mut() {
                 // update values
-                frame.right = node.node_summary() + node.right.subtree_summary() + frame.right;
+                frame.right = node.node_summary()
+                    + node.right.subtree_summary()
+                    + std::mem::take(&mut frame.right);
                 node.left.access();
                 Ok(&mut node.left)
             } else {
-                Err(())
+                Err(NavError::EmptyPosition)
             }
         });
         // push side information
         if res.is_ok() {
+            #[cfg(feature = "instrument")]
+            crate::instrument::record(|stats| stats.descents += 1);
             self.is_left.push(Side::Left); // went left
             self.vals.push(frame);
         }
         res
     }
 
-    fn go_right(&mut self) -> Result<(), ()> {
+    fn go_right(&mut self) -> Result<(), NavError> {
         let mut frame = self.vals.last().expect(NO_VALUE_ERROR).clone();
         let res = RecRef::extend_result(&mut self.rec_ref, |tree| {
             if let Some(node) = tree.node_mut() {
                 // update values
-                frame.left = frame.left + node.left.subtree_summary() + node.node_summary();
+                frame.left = std::mem::take(&mut frame.left)
+                    + node.left.subtree_summary()
+                    + node.node_summary();
 
                 node.right.access();
                 Ok(&mut node.right)
             } else {
-                Err(())
+                Err(NavError::EmptyPosition)
             }
         });
         // push side information
         if res.is_ok() {
+            #[cfg(feature = "instrument")]
+            crate::instrument::record(|stats| stats.descents += 1);
             self.is_left.push(Side::Right); // went right
             self.vals.push(frame);
         }
         res
     }
 
-    fn go_up(&mut self) -> Result<Side, ()> {
+    fn go_up(&mut self) -> Result<Side, NavError> {
         match self.is_left.pop() {
-            None => Err(()),
+            None => Err(NavError::AtRoot),
             Some(b) => {
                 RecRef::pop(&mut self.rec_ref).expect(NO_VALUE_ERROR);
                 self.vals.pop().expect(NO_VALUE_ERROR);
@@ -172,10 +352,10 @@ impl<'a, D: Data, T> SomeWalker<D> for BasicWalker<'a, D, T> {
     }
 
     fn far_left_summary(&self) -> D::Summary {
-        self.vals.last().expect(NO_VALUE_ERROR).left
+        self.vals.last().expect(NO_VALUE_ERROR).left.clone()
     }
     fn far_right_summary(&self) -> D::Summary {
-        self.vals.last().expect(NO_VALUE_ERROR).right
+        self.vals.last().expect(NO_VALUE_ERROR).right.clone()
     }
 
     // fn inner(&self) -> &BasicTree<A> {
@@ -186,6 +366,18 @@ impl<'a, D: Data, T> SomeWalker<D> for BasicWalker<'a, D, T> {
         let value = self.rec_ref.node()?.node_value_clean();
         Some(value)
     }
+
+    fn peek_left(&mut self) -> Option<&D::Value> {
+        let left = &mut self.rec_ref.node_mut()?.left;
+        left.access();
+        Some(left.node()?.node_value_clean())
+    }
+
+    fn peek_right(&mut self) -> Option<&D::Value> {
+        let right = &mut self.rec_ref.node_mut()?.right;
+        right.access();
+        Some(right.node()?.node_value_clean())
+    }
 }
 
 impl<D: Data, T> SomeEntry<D> for BasicTree<D, T> {
@@ -262,7 +454,9 @@ impl<D: Data, T> SomeEntry<D> for BasicTree<D, T> {
             BasicNode::assert_correctness_locally(node);
         }
     }
+}
 
+impl<D: Data, T> BasicBacked<D> for BasicTree<D, T> {
     #[cfg(debug_assertions)]
     type EntryTreeData = T;
 
@@ -334,7 +528,9 @@ impl<'a, D: Data, T> SomeEntry<D> for BasicWalker<'a, D, T> {
     {
         self.inner().assert_correctness_locally();
     }
+}
 
+impl<'a, D: Data, T> BasicBacked<D> for BasicWalker<'a, D, T> {
     #[cfg(debug_assertions)]
     type EntryTreeData = T;
 
@@ -347,17 +543,14 @@ impl<'a, D: Data, T> SomeEntry<D> for BasicWalker<'a, D, T> {
     }
 }
 
-impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut BasicTree<D> {
-    type ModifiableWalker = BasicWalker<'a, D>;
-}
-
 impl<'a, D: Data> ModifiableWalker<D> for BasicWalker<'a, D> {
     /// Inserts the value into the tree at the current empty position.
-    /// If the current position is not empty, return [`None`].
+    /// If the current position is not empty, returns `Err(NavError::OccupiedPosition)`.
     /// When the function returns, the walker will be at the position the node
     /// was inserted.
-    fn insert(&mut self, value: D::Value) -> Option<()> {
+    fn insert(&mut self, value: D::Value) -> Result<(), NavError> {
         self.insert_with_alg_data(value, ())
+            .ok_or(NavError::OccupiedPosition)
     }
 
     /// Removes the current value from the tree, and returns it.
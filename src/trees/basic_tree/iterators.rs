@@ -1,10 +1,88 @@
 use crate::*;
 use basic_tree::*;
+use example_data::SizedSummary;
 use locators::LocResult;
+use recursive_reference::RecRef;
+use std::collections::VecDeque;
 
+/// A fragment of the iterated segment.
+/// A value fragment also stores the summary of everything to its left.
+/// A node fragment also stores the summaries of everything to its left and to its right,
+/// and the slot in which it should be recorded once it is expanded.
 enum Fragment<'a, D: Data, T = ()> {
-    Value(&'a mut D::Value),
-    Node(&'a mut BasicNode<D, T>),
+    Value(&'a mut D::Value, D::Summary),
+    Node(&'a mut BasicNode<D, T>, D::Summary, D::Summary, Slot),
+}
+
+/// Where a node should be recorded in [`Visited`]: the index of its parent's record,
+/// and the side of the node relative to its parent. `None` for the root of the tree.
+type Slot = Option<(usize, Side)>;
+
+/// Records the nodes expanded by mutable iterators, so that they can be rebuilt afterwards.
+/// The expanded nodes of a single iteration form a subtree containing the root of the tree.
+#[derive(Default)]
+struct Visited {
+    /// The records of the roots of the iterations.
+    roots: Vec<usize>,
+    /// For every expanded node, the records of its expanded children.
+    children: Vec<(Option<usize>, Option<usize>)>,
+}
+
+impl Visited {
+    /// Records a newly expanded node, and returns the index of its record.
+    fn record(&mut self, slot: Slot) -> usize {
+        let index = self.children.len();
+        self.children.push((None, None));
+        match slot {
+            None => self.roots.push(index),
+            Some((parent, Side::Left)) => self.children[parent].0 = Some(index),
+            Some((parent, Side::Right)) => self.children[parent].1 = Some(index),
+        }
+        index
+    }
+
+    /// Rebuilds all of the recorded nodes, from the bottom up.
+    /// Uses an explicit stack, since the tree might be deep.
+    fn rebuild<D: Data, T>(&self, tree: &mut BasicTree<D, T>) {
+        for &root in self.roots.iter() {
+            let mut rec_ref = RecRef::new(&mut *tree);
+            // for every node in the current path, its record and the next child to visit
+            let mut path = vec![(root, Some(Side::Left))];
+            while let Some((index, next)) = path.last_mut() {
+                let (left, right) = self.children[*index];
+                let child = match *next {
+                    Some(Side::Left) => {
+                        *next = Some(Side::Right);
+                        left.map(|child| (child, Side::Left))
+                    }
+                    Some(Side::Right) => {
+                        *next = None;
+                        right.map(|child| (child, Side::Right))
+                    }
+                    None => {
+                        rec_ref.rebuild();
+                        path.pop();
+                        if !path.is_empty() {
+                            RecRef::pop(&mut rec_ref);
+                        }
+                        continue;
+                    }
+                };
+                if let Some((child, side)) = child {
+                    RecRef::extend(&mut rec_ref, |tree| {
+                        let node = tree
+                            .node_mut()
+                            .expect("A recorded node should have recorded children");
+                        match side {
+                            Side::Left => &mut node.left,
+                            Side::Right => &mut node.right,
+                        }
+                    });
+                    path.push((child, Some(Side::Left)));
+                }
+            }
+        }
+    }
 }
 
 /// Mutable iterator iterating over a segment of the tree. Since it is a mutable
@@ -33,68 +111,101 @@ enum Fragment<'a, D: Data, T = ()> {
 ///   * The `RefMut::split` function can only split a `RefMut` in two, but not in three. Even though
 ///     this could be trivially implemented in the standard library.
 ///
-/// Therefore, this type isn't exposed - it can't be used productively.
-/// Instead, this type is wrapped inside the `IterLocator` type, which is exported.
+/// Therefore, this type isn't exposed directly.
+/// Instead, this type is wrapped inside the `IterLocator` type, which is exported,
+/// and inside the `IterMut` type, which borrows an [`IterMutGuard`] that rebuilds the
+/// nodes when it is dropped.
 struct IterLocatorMut<'a, D: Data, L, T = ()> {
-    left: D::Summary,
-    // a stack of the fragments, and for every fragment,
-    // the summary of everything to its right
-    stack: Vec<(Fragment<'a, D, T>, D::Summary)>,
+    // a deque of the fragments, in order
+    deque: VecDeque<Fragment<'a, D, T>>,
     locator: L,
+    // if present, every expanded node is recorded here
+    visited: Option<&'a mut Visited>,
 }
 
 impl<'a, D: Data, L, T> IterLocatorMut<'a, D, L, T> {
-    pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
-        let mut res = IterLocatorMut {
-            left: Default::default(),
-            stack: vec![],
-            locator,
-        };
-        res.push(tree, Default::default());
-        res
+    /// Internal method: pushes a fragment to the back of the deque if `back` is true,
+    /// and to the front otherwise.
+    fn push_frag(&mut self, frag: Fragment<'a, D, T>, back: bool) {
+        if back {
+            self.deque.push_back(frag);
+        } else {
+            self.deque.push_front(frag);
+        }
     }
 
-    /// Internal method: same as stack.push(...), but deals with the [`Empty`] case.
+    /// Internal method: pops a fragment from the back of the deque if `back` is true,
+    /// and from the front otherwise.
+    fn pop_frag(&mut self, back: bool) -> Option<Fragment<'a, D, T>> {
+        if back {
+            self.deque.pop_back()
+        } else {
+            self.deque.pop_front()
+        }
+    }
+
+    /// Internal method: same as `push_frag`, but deals with the [`Empty`] case.
     /// If empty, do nothing.
-    fn push(&mut self, tree: &'a mut BasicTree<D, T>, summary: D::Summary) {
+    fn push(
+        &mut self,
+        tree: &'a mut BasicTree<D, T>,
+        left: D::Summary,
+        right: D::Summary,
+        slot: Slot,
+        back: bool,
+    ) {
         if let Some(node) = tree.node_mut() {
-            self.stack.push((Fragment::Node(node), summary));
+            self.push_frag(Fragment::Node(node, left, right, slot), back);
         }
     }
 }
 
-impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocatorMut<'a, D, L, T> {
-    type Item = &'a mut D::Value;
+impl<'a, D: Data, L: Locator<D>, T> IterLocatorMut<'a, D, L, T> {
+    pub fn new(
+        tree: &'a mut BasicTree<D, T>,
+        locator: L,
+        visited: Option<&'a mut Visited>,
+    ) -> Self {
+        let mut res = IterLocatorMut {
+            deque: VecDeque::new(),
+            locator,
+            visited,
+        };
+        res.push(tree, Default::default(), Default::default(), None, false);
+        res.settle();
+        res
+    }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        // Iterator is empty
-        if self.stack.is_empty() {
-            (0, Some(0))
-        } else {
-            // We know that every stack fragment contains at least one element.
-            // We don't know any upper bound.
-            // If we could specialize for `D: SizedData`, we could know the exact size,
-            // but we can't.
-            (self.stack.len(), None)
-        }
+    /// Internal method: expands the node fragments at the edges of the deque,
+    /// until both of its edges are value fragments.
+    ///
+    /// The locator has accepted every value fragment, and there are no two adjacent node fragments.
+    /// Therefore, afterwards, every node fragment lies between two accepted values,
+    /// and its whole subtree is contained in the segment.
+    fn settle(&mut self) {
+        self.settle_edge(false);
+        self.settle_edge(true);
     }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Internal method: expands the node fragments at the back of the deque if `back` is true,
+    /// and at the front otherwise, until that edge is a value fragment.
+    fn settle_edge(&mut self, back: bool) {
         loop {
-            let (frag, summary) = match self.stack.pop() {
-                None => return None,
-                Some(x) => x,
+            let edge = if back {
+                self.deque.back()
+            } else {
+                self.deque.front()
             };
-
-            let node = match frag {
-                // if value has been inserted to the stack, the locator has already been called
-                // on it and returned `Accept`.
-                Fragment::Value(val) => {
-                    self.left = self.left + (*val).to_summary();
-                    return Some(val);
-                }
-                Fragment::Node(node) => node,
+            if !matches!(edge, Some(Fragment::Node(..))) {
+                return;
+            }
+            let (node, left, right, slot) = match self.pop_frag(back) {
+                Some(Fragment::Node(node, left, right, slot)) => (node, left, right, slot),
+                _ => unreachable!(),
             };
+            let index = self.visited.as_mut().map(|visited| visited.record(slot));
+            let left_slot = index.map(|index| (index, Side::Left));
+            let right_slot = index.map(|index| (index, Side::Right));
 
             node.access();
             let value = &mut node.node_value;
@@ -102,32 +213,95 @@ impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocatorMut<'a, D, L, T> {
             let left_node = &mut node.left;
 
             let value_summary = (*value).to_summary();
-            let near_left_summary: D::Summary = self.left + left_node.subtree_summary();
-            let near_right_summary: D::Summary = right_node.subtree_summary() + summary;
+            let near_left_summary: D::Summary = left.clone() + left_node.subtree_summary();
+            let near_right_summary: D::Summary = right_node.subtree_summary() + right.clone();
 
-            let dir = self
-                .locator
-                .locate(near_left_summary, value, near_right_summary);
+            let dir =
+                self.locator
+                    .locate(near_left_summary.clone(), value, near_right_summary.clone());
             match dir {
                 LocResult::GoLeft => {
-                    if !self.stack.is_empty() {
+                    if !back && !self.deque.is_empty() {
                         panic!("GoLeft received in the middle of a segment");
                     }
-                    self.push(left_node, value_summary + near_right_summary);
+                    self.push(
+                        left_node,
+                        left,
+                        value_summary + near_right_summary,
+                        left_slot,
+                        back,
+                    );
                 }
                 LocResult::GoRight => {
-                    self.push(right_node, summary);
-                    self.left = near_left_summary + value_summary;
+                    if back && !self.deque.is_empty() {
+                        panic!("GoRight received in the middle of a segment");
+                    }
+                    self.push(
+                        right_node,
+                        near_left_summary + value_summary,
+                        right,
+                        right_slot,
+                        back,
+                    );
                 }
                 LocResult::Accept => {
-                    self.push(right_node, summary);
-                    self.stack
-                        .push((Fragment::Value(value), near_right_summary));
-                    self.push(left_node, value_summary + near_right_summary);
+                    let left_right_summary = value_summary.clone() + near_right_summary;
+                    let right_left_summary = near_left_summary.clone() + value_summary;
+                    // push the far side first, so that the near side ends up at the edge
+                    if back {
+                        self.push(left_node, left, left_right_summary, left_slot, back);
+                        self.push_frag(Fragment::Value(value, near_left_summary), back);
+                        self.push(right_node, right_left_summary, right, right_slot, back);
+                    } else {
+                        self.push(right_node, right_left_summary, right, right_slot, back);
+                        self.push_frag(Fragment::Value(value, near_left_summary), back);
+                        self.push(left_node, left, left_right_summary, left_slot, back);
+                    }
                 }
             }
         }
     }
+
+    /// Internal method: yields the next value from the back if `back` is true,
+    /// and from the front otherwise.
+    /// Also returns the summary of everything to the left of the value.
+    fn next_from(&mut self, back: bool) -> Option<(D::Summary, &'a mut D::Value)> {
+        match self.pop_frag(back)? {
+            // if value has been inserted to the deque, the locator has already been called
+            // on it and returned `Accept`.
+            Fragment::Value(val, left) => {
+                self.settle_edge(back);
+                Some((left, val))
+            }
+            Fragment::Node(..) => unreachable!("The edges of the deque should be values"),
+        }
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocatorMut<'a, D, L, T> {
+    type Item = &'a mut D::Value;
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Iterator is empty
+        if self.deque.is_empty() {
+            (0, Some(0))
+        } else {
+            // We know that every fragment contains at least one value.
+            // We don't know any upper bound. Even with `D::Summary: SizedSummary`,
+            // the sizes don't count the values, since a wide value can have a size bigger than 1.
+            (self.deque.len(), None)
+        }
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_from(false)?.1)
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterLocatorMut<'a, D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(self.next_from(true)?.1)
+    }
 }
 
 /// Immutable iterator.
@@ -148,7 +322,31 @@ impl<'a, D: Data, L: Locator<D>, T> IterLocator<'a, D, L, T> {
     /// Creates a new immutable iterator for a segment of the given tree.
     pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
         IterLocator {
-            mut_iter: IterLocatorMut::new(tree, locator),
+            mut_iter: IterLocatorMut::new(tree, locator, None),
+        }
+    }
+
+    /// Converts this iterator into an iterator that also yields the index of every value
+    /// in the whole tree. The indices are computed from the summaries,
+    /// and not by counting the values.
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (20..80).collect();
+    /// let mut segment_iter = tree.iter_locator(3..13).indexed();
+    ///
+    /// assert_eq!(segment_iter.next(), Some((3, &23)));
+    /// assert_eq!(segment_iter.next_back(), Some((12, &32)));
+    /// # drop(segment_iter);
+    /// # tree.assert_correctness();
+    ///```
+    pub fn indexed(self) -> IterIndexed<'a, D, L, T>
+    where
+        D::Summary: SizedSummary,
+    {
+        IterIndexed {
+            mut_iter: self.mut_iter,
         }
     }
 }
@@ -166,72 +364,220 @@ impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocator<'a, D, L, T> {
     }
 }
 
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterLocator<'a, D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(&*self.mut_iter.next_back()?)
+    }
+}
+
+/// Immutable iterator that also yields the index of every value.
+/// Created by [`IterLocator::indexed`].
+pub struct IterIndexed<'a, D: Data, L, T = ()> {
+    mut_iter: IterLocatorMut<'a, D, L, T>,
+}
+
+impl<'a, D: Data, L: Locator<D>, T> Iterator for IterIndexed<'a, D, L, T>
+where
+    D::Summary: SizedSummary,
+{
+    type Item = (usize, &'a D::Value);
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.mut_iter.size_hint()
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (left, value) = self.mut_iter.next_from(false)?;
+        Some((left.size(), &*value))
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterIndexed<'a, D, L, T>
+where
+    D::Summary: SizedSummary,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (left, value) = self.mut_iter.next_from(true)?;
+        Some((left.size(), &*value))
+    }
+}
+
+/// A guard for mutable iteration over a segment of the tree.
+///
+/// Since rust's iterators are not streaming iterators, the nodes can't be rebuilt while iterating.
+/// Instead, the iterators returned by [`IterMutGuard::iter`] borrow the guard,
+/// and record the nodes they visit. When the guard is dropped, all of the visited nodes
+/// are rebuilt, and the tree is in a legal state again.
+///
+/// Modifying the values should not change the segment selected by the locator,
+/// nor the order of the keys, if the tree is keyed.
+///```
+/// use grove::{SomeTree, basic_tree::BasicTree};
+/// use grove::example_data::StdNum;
+///
+/// let mut tree: BasicTree<StdNum> = (0..10).collect();
+/// for value in tree.iter_locator_mut(3..6).iter() {
+///     *value *= 10;
+/// }
+/// // the guard has been dropped, and the tree has been rebuilt
+/// assert_eq!(tree.segment_summary(..).sum, 1 + 2 + 30 + 40 + 50 + 6 + 7 + 8 + 9);
+/// # tree.assert_correctness();
+///```
+pub struct IterMutGuard<'a, D: Data, L, T = ()> {
+    tree: &'a mut BasicTree<D, T>,
+    locator: L,
+    visited: Visited,
+}
+
+impl<'a, D: Data, L: Locator<D>, T> IterMutGuard<'a, D, L, T> {
+    /// Creates a new mutable iteration guard for a segment of the given tree.
+    pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
+        IterMutGuard {
+            tree,
+            locator,
+            visited: Default::default(),
+        }
+    }
+
+    /// Iterates mutably over the segment. The visited nodes are rebuilt when the guard is dropped.
+    pub fn iter(&mut self) -> IterMut<'_, D, L, T> {
+        IterMut {
+            mut_iter: IterLocatorMut::new(self.tree, self.locator.clone(), Some(&mut self.visited)),
+        }
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> IterGuard<D> for IterMutGuard<'a, D, L, T> {
+    type IterMut<'b>
+        = IterMut<'b, D, L, T>
+    where
+        Self: 'b,
+        D: 'b;
+
+    fn iter(&mut self) -> IterMut<'_, D, L, T> {
+        IterMutGuard::iter(self)
+    }
+}
+
+impl<'a, 'b, D: Data, L: Locator<D>, T> IntoIterator for &'b mut IterMutGuard<'a, D, L, T> {
+    type Item = &'b mut D::Value;
+    type IntoIter = IterMut<'b, D, L, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, D: Data, L, T> Drop for IterMutGuard<'a, D, L, T> {
+    fn drop(&mut self) {
+        self.visited.rebuild(self.tree);
+    }
+}
+
+/// Mutable iterator, borrowed from an [`IterMutGuard`].
+pub struct IterMut<'a, D: Data, L, T = ()> {
+    mut_iter: IterLocatorMut<'a, D, L, T>,
+}
+
+impl<'a, D: Data, L: Locator<D>, T> Iterator for IterMut<'a, D, L, T> {
+    type Item = &'a mut D::Value;
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.mut_iter.size_hint()
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.mut_iter.next()
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterMut<'a, D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.mut_iter.next_back()
+    }
+}
+
 /// Owning fragment
+/// A node fragment also stores the summaries of everything to its left and to its right.
 enum OFragment<D: Data, T = ()> {
     Value(D::Value),
-    Node(Box<BasicNode<D, T>>),
+    Node(Box<BasicNode<D, T>>, D::Summary, D::Summary),
 }
 /// Owning iterator iterating over a segment of the tree.
 pub struct IntoIter<D: Data, L, T = ()> {
-    left: D::Summary,
-    // a stack of the fragments, and for every fragment,
-    // the summary of everything to its right
-    stack: Vec<(OFragment<D, T>, D::Summary)>,
+    // a deque of the fragments, in order
+    deque: VecDeque<OFragment<D, T>>,
     locator: L,
 }
 
 impl<D: Data, L, T> IntoIter<D, L, T> {
-    /// Creates a new owning iterator for a segment of the given tree.
-    pub fn new(tree: BasicTree<D, T>, locator: L) -> Self {
-        let mut res = IntoIter {
-            left: Default::default(),
-            stack: vec![],
-            locator,
-        };
-        res.push(tree, Default::default());
-        res
+    /// Internal method: pushes a fragment to the back of the deque if `back` is true,
+    /// and to the front otherwise.
+    fn push_frag(&mut self, frag: OFragment<D, T>, back: bool) {
+        if back {
+            self.deque.push_back(frag);
+        } else {
+            self.deque.push_front(frag);
+        }
+    }
+
+    /// Internal method: pops a fragment from the back of the deque if `back` is true,
+    /// and from the front otherwise.
+    fn pop_frag(&mut self, back: bool) -> Option<OFragment<D, T>> {
+        if back {
+            self.deque.pop_back()
+        } else {
+            self.deque.pop_front()
+        }
     }
 
-    /// Internal method: same as stack.push(...), but deals with the [`Empty`] case.
+    /// Internal method: same as `push_frag`, but deals with the [`Empty`] case.
     /// If empty, do nothing.
-    fn push(&mut self, tree: BasicTree<D, T>, summary: D::Summary) {
+    fn push(&mut self, tree: BasicTree<D, T>, left: D::Summary, right: D::Summary, back: bool) {
         if let Some(boxed_node) = tree.into_node_boxed() {
-            self.stack.push((OFragment::Node(boxed_node), summary));
+            self.push_frag(OFragment::Node(boxed_node, left, right), back);
         }
     }
 }
 
-impl<D: Data, L: Locator<D>, T> Iterator for IntoIter<D, L, T> {
-    type Item = D::Value;
+impl<D: Data, L: Locator<D>, T> IntoIter<D, L, T> {
+    /// Creates a new owning iterator for a segment of the given tree.
+    pub fn new(tree: BasicTree<D, T>, locator: L) -> Self {
+        let mut res = IntoIter {
+            deque: VecDeque::new(),
+            locator,
+        };
+        res.push(tree, Default::default(), Default::default(), false);
+        res.settle();
+        res
+    }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        // Iterator is empty
-        if self.stack.is_empty() {
-            (0, Some(0))
-        } else {
-            // We know that every stack fragment contains at least one element.
-            // We don't know any upper bound.
-            // If we could specialize for `D: SizedData`, we could know the exact size,
-            // but we can't.
-            (self.stack.len(), None)
-        }
+    /// Internal method: expands the node fragments at the edges of the deque,
+    /// until both of its edges are value fragments.
+    ///
+    /// The locator has accepted every value fragment, and there are no two adjacent node fragments.
+    /// Therefore, afterwards, every node fragment lies between two accepted values,
+    /// and its whole subtree is contained in the segment.
+    fn settle(&mut self) {
+        self.settle_edge(false);
+        self.settle_edge(true);
     }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Internal method: expands the node fragments at the back of the deque if `back` is true,
+    /// and at the front otherwise, until that edge is a value fragment.
+    fn settle_edge(&mut self, back: bool) {
         loop {
-            let (frag, summary) = match self.stack.pop() {
-                None => return None,
-                Some(x) => x,
+            let edge = if back {
+                self.deque.back()
+            } else {
+                self.deque.front()
             };
-
-            let mut node = match frag {
-                // if value has been inserted to the stack, the locator has already been called
-                // on it and returned `Accept`.
-                OFragment::Value(val) => {
-                    self.left = self.left + val.to_summary();
-                    return Some(val);
-                }
-                OFragment::Node(node) => node,
+            if !matches!(edge, Some(OFragment::Node(..))) {
+                return;
+            }
+            let (mut node, left, right) = match self.pop_frag(back) {
+                Some(OFragment::Node(node, left, right)) => (node, left, right),
+                _ => unreachable!(),
             };
 
             node.access();
@@ -240,30 +586,139 @@ impl<D: Data, L: Locator<D>, T> Iterator for IntoIter<D, L, T> {
             let left_node = node.left;
 
             let value_summary = value.to_summary();
-            let near_left_summary: D::Summary = self.left + left_node.subtree_summary();
-            let near_right_summary: D::Summary = right_node.subtree_summary() + summary;
+            let near_left_summary: D::Summary = left.clone() + left_node.subtree_summary();
+            let near_right_summary: D::Summary = right_node.subtree_summary() + right.clone();
 
-            let dir = self
-                .locator
-                .locate(near_left_summary, &value, near_right_summary);
+            let dir = self.locator.locate(
+                near_left_summary.clone(),
+                &value,
+                near_right_summary.clone(),
+            );
             match dir {
                 LocResult::GoLeft => {
-                    if !self.stack.is_empty() {
+                    if !back && !self.deque.is_empty() {
                         panic!("GoLeft received in the middle of a segment");
                     }
-                    self.push(left_node, value_summary + near_right_summary);
+                    self.push(left_node, left, value_summary + near_right_summary, back);
                 }
                 LocResult::GoRight => {
-                    self.push(right_node, summary);
-                    self.left = near_left_summary + value_summary;
+                    if back && !self.deque.is_empty() {
+                        panic!("GoRight received in the middle of a segment");
+                    }
+                    self.push(right_node, near_left_summary + value_summary, right, back);
                 }
                 LocResult::Accept => {
-                    self.push(right_node, summary);
-                    self.stack
-                        .push((OFragment::Value(value), near_right_summary));
-                    self.push(left_node, value_summary + near_right_summary);
+                    let left_right_summary = value_summary.clone() + near_right_summary;
+                    let right_left_summary = near_left_summary + value_summary;
+                    // push the far side first, so that the near side ends up at the edge
+                    if back {
+                        self.push(left_node, left, left_right_summary, back);
+                        self.push_frag(OFragment::Value(value), back);
+                        self.push(right_node, right_left_summary, right, back);
+                    } else {
+                        self.push(right_node, right_left_summary, right, back);
+                        self.push_frag(OFragment::Value(value), back);
+                        self.push(left_node, left, left_right_summary, back);
+                    }
                 }
             }
         }
     }
+
+    /// Internal method: yields the next value from the back if `back` is true,
+    /// and from the front otherwise.
+    fn next_from(&mut self, back: bool) -> Option<D::Value> {
+        match self.pop_frag(back)? {
+            // if value has been inserted to the deque, the locator has already been called
+            // on it and returned `Accept`.
+            OFragment::Value(val) => {
+                self.settle_edge(back);
+                Some(val)
+            }
+            OFragment::Node(..) => unreachable!("The edges of the deque should be values"),
+        }
+    }
+}
+
+impl<D: Data, L: Locator<D>, T> Iterator for IntoIter<D, L, T> {
+    type Item = D::Value;
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Iterator is empty
+        if self.deque.is_empty() {
+            (0, Some(0))
+        } else {
+            // We know that every fragment contains at least one value.
+            // We don't know any upper bound. Even with `D::Summary: SizedSummary`,
+            // the sizes don't count the values, since a wide value can have a size bigger than 1.
+            (self.deque.len(), None)
+        }
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_from(false)
+    }
+}
+
+impl<D: Data, L: Locator<D>, T> DoubleEndedIterator for IntoIter<D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.next_from(true)
+    }
+}
+
+/// An iterator over clones of the values of a tree, through a shared reference,
+/// with all pending actions applied. Every value is yielded along with its depth in the tree.
+pub(crate) struct ClonedValues<'a, D: Data, T> {
+    // The nodes whose values and right subtrees are yet to be yielded, in reverse order,
+    // along with the actions that should be applied to their subtrees, and their depths.
+    stack: Vec<(&'a BasicNode<D, T>, D::Action, usize)>,
+}
+
+impl<'a, D: Data, T> ClonedValues<'a, D, T> {
+    pub(crate) fn new(tree: &'a BasicTree<D, T>) -> Self {
+        let mut iter = ClonedValues { stack: vec![] };
+        iter.push_left_spine(tree, Default::default(), 0);
+        iter
+    }
+
+    /// Pushes the nodes on the left spine of the tree to the stack, together with the actions
+    /// that should be applied to their subtrees, and their depths.
+    /// `action` is the action that should be applied to the whole tree, without the root's own action.
+    fn push_left_spine(
+        &mut self,
+        mut tree: &'a BasicTree<D, T>,
+        mut action: D::Action,
+        mut depth: usize,
+    ) {
+        while let Some(node) = tree.node() {
+            action = action + node.action.clone();
+            self.stack.push((node, action.clone(), depth));
+            // deal with reversals
+            tree = if D::Action::REVERSIBLE && action.to_reverse() {
+                &node.right
+            } else {
+                &node.left
+            };
+            depth += 1;
+        }
+    }
+}
+
+impl<'a, D: Data, T> Iterator for ClonedValues<'a, D, T>
+where
+    D::Value: Clone,
+{
+    type Item = (usize, D::Value);
+
+    fn next(&mut self) -> Option<(usize, D::Value)> {
+        let (node, action, depth) = self.stack.pop()?;
+        let (value_action, right_action) = node.inner_actions(&action);
+        let right = if D::Action::REVERSIBLE && action.to_reverse() {
+            &node.left
+        } else {
+            &node.right
+        };
+        self.push_left_spine(right, right_action, depth + 1);
+        Some((depth, value_action.act(node.node_value.clone())))
+    }
 }
@@ -18,12 +18,23 @@ pub(crate) use imm_down_walker::ImmDownBasicWalker;
 mod walker;
 pub use walker::*;
 
+mod imm_walker;
+pub use imm_walker::*;
+
 mod implementations;
 pub use implementations::*;
 
 /// Iterators for [`BasicTree`]
 pub mod iterators;
 
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+#[cfg(feature = "serde")]
+pub mod serialization;
+
+mod debug;
+
 mod iterative_deallocator;
 pub use iterative_deallocator::deallocate_iteratively;
 
@@ -51,7 +62,7 @@ impl<D: Data, T> BasicTree<D, T> {
     /// Returns `default()` if the tree is empty, and `self.node().action` otherwise
     pub fn action(&self) -> D::Action {
         match self.node() {
-            Some(node) => node.action,
+            Some(node) => node.action.clone(),
             None => Default::default(),
         }
     }
@@ -76,7 +87,8 @@ impl<D: Data, T> BasicTree<D, T> {
     /// For example, after inserting a new node, all of the nodes from it to the root
     /// must be rebuilt, in order for the segment values accumulated over the whole
     /// subtree to be accurate.
-    pub(crate) fn rebuild(&mut self) {
+    /// Does nothing if the tree is empty. See [`BasicNode::rebuild`].
+    pub fn rebuild(&mut self) {
         if let Root(node) = self {
             node.rebuild()
         }
@@ -86,7 +98,8 @@ impl<D: Data, T> BasicTree<D, T> {
     /// Actions stored in nodes are supposed to be eventually applied to its
     /// whole subtree. Therefore, in order to access a node cleanly, without
     /// the still-unapplied-function complicating things, you must `access()` the node.
-    pub(crate) fn access(&mut self) {
+    /// Does nothing if the tree is empty. See [`BasicNode::access`].
+    pub fn access(&mut self) {
         if let Root(node) = self {
             node.access()
         }
@@ -167,6 +180,8 @@ pub struct BasicNode<D: ?Sized + Data, T = ()> {
 impl<D: Data> BasicNode<D> {
     /// Creates a node with a single value.
     pub fn new(value: D::Value) -> BasicNode<D> {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.nodes_created += 1);
         let subtree_summary = value.to_summary();
         BasicNode {
             action: Default::default(),
@@ -182,6 +197,8 @@ impl<D: Data> BasicNode<D> {
 impl<D: Data, T> BasicNode<D, T> {
     /// Creates a node with a single value, and the algorithm specific data.
     pub fn new_alg(value: D::Value, alg_data: T) -> BasicNode<D, T> {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.nodes_created += 1);
         let subtree_summary = value.to_summary();
         BasicNode {
             action: Default::default(),
@@ -205,14 +222,36 @@ impl<D: Data, T> BasicNode<D, T> {
     /// Returns the summary of all values in this node's subtree.
     /// Same as [`BasicTree::subtree_summary`].
     pub fn subtree_summary(&self) -> D::Summary {
-        self.action.act(self.subtree_summary)
+        self.action.act(self.subtree_summary.clone())
     }
 
     /// Returns a summary for the value in this node specifically,
     /// and not the subtree.
     pub fn node_summary(&self) -> D::Summary {
         let summary = self.node_value.to_summary();
-        self.action.act(summary)
+        let (value_action, _) = self.inner_actions(&self.action);
+        value_action.act(summary)
+    }
+
+    /// Returns the actions that applying `action` to this node's subtree applies to the node's
+    /// value and to the son that comes after it, i.e., the right son, or the left son if
+    /// `action` reverses. The son that comes first gets `action` itself.
+    ///
+    /// These are different from `action` only for actions that depend on the index.
+    pub(crate) fn inner_actions(&self, action: &D::Action) -> (D::Action, D::Action) {
+        if !action.is_indexed() {
+            return (action.clone(), action.clone());
+        }
+        let value_summary = self.node_value.to_summary();
+        // the skipped values are summed up in their original order
+        let (first, skipped) = if D::Action::REVERSIBLE && action.to_reverse() {
+            let first = self.right.subtree_summary();
+            (first.clone(), value_summary + first)
+        } else {
+            let first = self.left.subtree_summary();
+            (first.clone(), first + value_summary)
+        };
+        (action.skip(&first), action.skip(&skipped))
     }
 
     /// Returns a reference to the value stored in this node specifically.
@@ -240,18 +279,26 @@ impl<D: Data, T> BasicNode<D, T> {
     /// Actions stored in nodes are supposed to be eventually applied to its
     /// whole subtree. Therefore, in order to access a node cleanly, without
     /// the still-unapplied-function complicating things, you must `access()` the node.
-    pub(crate) fn access(&mut self) {
+    ///
+    /// This is the first half of the node lifecycle: custom algorithms that work on the nodes
+    /// directly must access a node before reading or modifying its value or its sons,
+    /// and [`BasicNode::rebuild`] it afterwards. If the node had a pending action,
+    /// [`Data::on_access`] is called.
+    pub fn access(&mut self) {
         // reversing
-        // for data that doesn't implement reversing, this becomes a no-op
-        // and hopefully optimized away
-        if self.action.to_reverse() {
+        // for data that doesn't implement reversing, this is skipped at compile time
+        let (value_action, right_action) = self.inner_actions(&self.action);
+        if D::Action::REVERSIBLE && self.action.to_reverse() {
             std::mem::swap(&mut self.left, &mut self.right);
         }
 
-        self.left.act_subtree(self.action);
-        self.right.act_subtree(self.action);
+        self.left.act_subtree(self.action.clone());
+        self.right.act_subtree(right_action);
         self.action.act_inplace(&mut self.subtree_summary);
-        self.action.act_inplace(&mut self.node_value);
+        value_action.act_inplace(&mut self.node_value);
+        if !self.action.is_identity() {
+            D::on_access(&self.node_value, &self.subtree_summary);
+        }
         self.action = Default::default();
     }
 
@@ -260,10 +307,33 @@ impl<D: Data, T> BasicNode<D, T> {
     /// For example, after inserting a new node, all of the nodes from it to the root
     /// must be rebuilt, in order for the segment values accumulated over the whole
     /// subtree to be accurate.
-    pub(crate) fn rebuild(&mut self) {
+    ///
+    /// This is the second half of the node lifecycle, after [`BasicNode::access`].
+    /// It calls [`Data::on_rebuild`], so that extra invariants can be maintained
+    /// whenever a node's subtree changes.
+    /// Panics if the node has a pending action, i.e., if it wasn't accessed.
+    ///```
+    /// use grove::{*, basic_tree::*};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: BasicTree<StdNum> = (1..=8).collect();
+    /// tree.act_subtree(RevAffineAction { to_reverse: false, mul: 1, add: 10 });
+    /// let node: &mut BasicNode<StdNum> = tree.node_mut().unwrap();
+    /// // `node_value_mut` accesses the node
+    /// let old = std::mem::replace(node.node_value_mut(), 100);
+    /// // the value changed, so the node's summary must be rebuilt
+    /// node.rebuild();
+    ///
+    /// assert_eq!(tree.subtree_summary().sum, (11..=18).sum::<i32>() - old + 100);
+    /// # tree.assert_correctness();
+    ///```
+    pub fn rebuild(&mut self) {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.rebuilds += 1);
         assert!(self.action.is_identity());
         let temp = self.node_value.to_summary();
         self.subtree_summary = self.left.subtree_summary() + temp + self.right.subtree_summary();
+        D::on_rebuild(&self.node_value, &self.subtree_summary);
     }
 
     /// This function applies the given action to its whole subtree.
@@ -284,12 +354,20 @@ impl<D: Data, T> BasicNode<D, T> {
     /// # tree.assert_correctness();
     ///```
     pub fn act(&mut self, action: D::Action) {
-        self.action = action + self.action;
+        assert!(
+            D::Action::REVERSIBLE || !action.to_reverse(),
+            "Actions that aren't `Action::REVERSIBLE` must not reverse"
+        );
+        self.action = action + std::mem::take(&mut self.action);
     }
 
     /// This function applies the given action only to the current value in this node.
     /// Same as [`SomeEntry::act_node`].
     pub fn act_value(&mut self, action: D::Action) {
+        assert!(
+            D::Action::REVERSIBLE || !action.to_reverse(),
+            "Actions that aren't `Action::REVERSIBLE` must not reverse"
+        );
         self.access();
         action.act_inplace(&mut self.node_value);
     }
@@ -318,16 +396,24 @@ impl<D: Data, T> BasicNode<D, T> {
         format!("{} {} {} {}", shebang, alg_print(self), left, right)
     }
 
-    /// Asserts that the summaries were calculated correctly at the current node.
-    /// Otherwise, panics.
-    pub fn assert_correctness_locally(&self)
+    /// Returns whether the summaries were calculated correctly at the current node.
+    pub fn is_correct_locally(&self) -> bool
     where
         D::Summary: Eq,
     {
-        let ns = self.subtree_summary;
+        let ns = self.subtree_summary.clone();
         let os: D::Summary = self.left.subtree_summary()
             + self.node_value.to_summary()
             + self.right.subtree_summary();
-        assert!(ns == os, "Incorrect summaries found.");
+        ns == os
+    }
+
+    /// Asserts that the summaries were calculated correctly at the current node.
+    /// Otherwise, panics.
+    pub fn assert_correctness_locally(&self)
+    where
+        D::Summary: Eq,
+    {
+        assert!(self.is_correct_locally(), "Incorrect summaries found.");
     }
 }
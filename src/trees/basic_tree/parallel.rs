@@ -0,0 +1,169 @@
+//! Parallel iteration and parallel building of trees, using [`rayon`].
+//! Only available with the `rayon` feature.
+//!
+//! The trees implement [`IntoParallelIterator`], and [`FromParallelIterator`], so
+//! `tree.into_par_iter()` and `par_iter.collect::<AVLTree<_>>()` both work.
+//! When building a tree, the left and right subtrees of every node are built in parallel.
+
+use super::*;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::{FlatMapIter, ParallelIterator, Split};
+
+/// Subtrees with less values than this are built sequentially.
+const SEQUENTIAL_THRESHOLD: usize = 1 << 12;
+
+/// A piece of a tree that is iterated sequentially: an optional value,
+/// followed by the values of a subtree.
+type Piece<D, T> = (Option<<D as Data>::Value>, BasicTree<D, T>);
+
+/// The iterator over the values of a [`Piece`].
+type PieceIter<D, T> = std::iter::Chain<
+    std::option::IntoIter<<D as Data>::Value>,
+    iterators::IntoIter<D, std::ops::RangeFull, T>,
+>;
+
+type Splitter<D, T> = fn(Piece<D, T>) -> (Piece<D, T>, Option<Piece<D, T>>);
+type Flattener<D, T> = fn(Piece<D, T>) -> PieceIter<D, T>;
+type Pieces<D, T> = Split<Piece<D, T>, Splitter<D, T>>;
+
+/// Splits a piece in two at its root, if it has one.
+fn split_piece<D: Data, T>((value, tree): Piece<D, T>) -> (Piece<D, T>, Option<Piece<D, T>>) {
+    match tree.into_node_boxed() {
+        None => ((value, BasicTree::Empty), None),
+        Some(mut node) => {
+            node.access();
+            let node = *node;
+            (
+                (value, node.left),
+                Some((Some(node.node_value), node.right)),
+            )
+        }
+    }
+}
+
+fn piece_iter<D: Data, T>((value, tree): Piece<D, T>) -> PieceIter<D, T> {
+    value.into_iter().chain(iterators::IntoIter::new(tree, ..))
+}
+
+/// Owning parallel iterator over the values of a tree.
+/// The tree is split at its nodes, and the resulting subtrees are iterated in parallel.
+pub struct IntoParIter<D: Data, T = ()> {
+    inner: FlatMapIter<Pieces<D, T>, Flattener<D, T>>,
+}
+
+impl<D: Data, T> IntoParIter<D, T>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+    T: Send,
+{
+    /// Creates a new owning parallel iterator over the given tree.
+    pub fn new(tree: BasicTree<D, T>) -> Self {
+        let splitter: Splitter<D, T> = split_piece;
+        let flattener: Flattener<D, T> = piece_iter;
+        IntoParIter {
+            inner: rayon::iter::split((None, tree), splitter).flat_map_iter(flattener),
+        }
+    }
+}
+
+impl<D: Data, T> ParallelIterator for IntoParIter<D, T>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+    T: Send,
+{
+    type Item = D::Value;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner.drive_unindexed(consumer)
+    }
+}
+
+/// Builds a perfectly balanced tree from the values, in order, building the
+/// left and right subtrees of every node in parallel.
+/// The `alg_data` function computes the algorithm-specific data of every node,
+/// given its left and right subtrees.
+pub(crate) fn build_balanced<D: Data, T>(
+    values: Vec<D::Value>,
+    alg_data: fn(&BasicTree<D, T>, &BasicTree<D, T>) -> T,
+) -> BasicTree<D, T>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+    T: Send,
+{
+    let mut values: Vec<Option<D::Value>> = values.into_iter().map(Some).collect();
+    build_balanced_slice(&mut values, alg_data)
+}
+
+fn build_balanced_slice<D: Data, T>(
+    values: &mut [Option<D::Value>],
+    alg_data: fn(&BasicTree<D, T>, &BasicTree<D, T>) -> T,
+) -> BasicTree<D, T>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+    T: Send,
+{
+    let len = values.len();
+    if len == 0 {
+        return BasicTree::Empty;
+    }
+    let (left_values, rest) = values.split_at_mut(len / 2);
+    let (value, right_values) = rest.split_first_mut().unwrap();
+    let (left, right) = if len >= SEQUENTIAL_THRESHOLD {
+        rayon::join(
+            || build_balanced_slice(left_values, alg_data),
+            || build_balanced_slice(right_values, alg_data),
+        )
+    } else {
+        (
+            build_balanced_slice(left_values, alg_data),
+            build_balanced_slice(right_values, alg_data),
+        )
+    };
+
+    let alg_data = alg_data(&left, &right);
+    let mut node = BasicNode::new_alg(value.take().unwrap(), alg_data);
+    node.left = left;
+    node.right = right;
+    node.rebuild();
+    BasicTree::from_node(node)
+}
+
+impl<D: Data> rayon::iter::IntoParallelIterator for BasicTree<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    type Iter = IntoParIter<D>;
+    type Item = D::Value;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter::new(self)
+    }
+}
+
+impl<D: Data> rayon::iter::FromParallelIterator<D::Value> for BasicTree<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    /// Builds a perfectly balanced [`BasicTree`], building subtrees in parallel.
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = D::Value>,
+    {
+        build_balanced(par_iter.into_par_iter().collect(), |_, _| ())
+    }
+}
@@ -0,0 +1,136 @@
+//! Serialization of trees, using [`serde`]. Only available with the `serde` feature.
+//!
+//! A tree is serialized as the sequence of its values in order, each paired with its depth in the tree.
+//! This determines the exact shape of the tree, since between any two nodes of the same depth,
+//! there is a node of a smaller depth. Therefore, the tree can be reconstructed in `O(n)` time,
+//! without rebalancing it.
+//!
+//! When deserializing, the summaries and the algorithm-specific data are recomputed,
+//! and the shape of the tree is validated.
+
+use super::*;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::marker::PhantomData;
+
+/// A function that is called on every deserialized node, after its subtree has been built.
+/// It should recompute the algorithm-specific data, and validate the node.
+pub(crate) type Finisher<D, T> = fn(&mut BasicNode<D, T>) -> Result<(), &'static str>;
+
+impl<D: Data, T> Serialize for BasicTree<D, T>
+where
+    D::Value: Serialize + Clone,
+{
+    /// Serializes the values in order, each paired with its depth.
+    /// The values are serialized with all of the pending actions applied to them.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        // the iterator uses an explicit stack, since the tree might be deep
+        for (depth, value) in iterators::ClonedValues::new(self) {
+            seq.serialize_element(&(depth, value))?;
+        }
+        seq.end()
+    }
+}
+
+/// Internal function: pops all of the nodes in the stack that are deeper than `depth`,
+/// and connects them into a single tree. Returns the tree and the depth of its root.
+///
+/// The stack contains the right spine of the tree built so far, so that every node is
+/// the right son of the node below it. Their depths are strictly increasing.
+fn pop_deeper<D: Data, T>(
+    stack: &mut Vec<(Box<BasicNode<D, T>>, usize)>,
+    depth: Option<usize>,
+    finish: Finisher<D, T>,
+) -> Result<(BasicTree<D, T>, Option<usize>), &'static str> {
+    let mut tree = BasicTree::Empty;
+    let mut tree_depth = None;
+    while let Some((mut node, node_depth)) = stack.pop() {
+        if depth.is_some_and(|depth| node_depth <= depth) {
+            stack.push((node, node_depth));
+            break;
+        }
+        if tree_depth.is_some_and(|tree_depth| tree_depth != node_depth + 1) {
+            return Err("Invalid tree structure: the depth of a right son is wrong");
+        }
+        node.right = tree;
+        node.rebuild();
+        finish(&mut node)?;
+        tree = BasicTree::from_boxed_node(node);
+        tree_depth = Some(node_depth);
+    }
+    Ok((tree, tree_depth))
+}
+
+/// Internal function: builds a tree from its values in order, each paired with its depth.
+/// Returns an error if the depths don't describe a valid tree, or if `finish` returns an error.
+pub(crate) fn deserialize_tree<'de, D: Data, T: Default, De>(
+    deserializer: De,
+    finish: Finisher<D, T>,
+) -> Result<BasicTree<D, T>, De::Error>
+where
+    D::Value: Deserialize<'de>,
+    De: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(TreeVisitor {
+        finish,
+        phantom: PhantomData,
+    })
+}
+
+struct TreeVisitor<D: Data, T> {
+    finish: Finisher<D, T>,
+    phantom: PhantomData<BasicTree<D, T>>,
+}
+
+impl<'de, D: Data, T: Default> Visitor<'de> for TreeVisitor<D, T>
+where
+    D::Value: Deserialize<'de>,
+{
+    type Value = BasicTree<D, T>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence of values paired with their depths")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut stack: Vec<(Box<BasicNode<D, T>>, usize)> = vec![];
+        while let Some((depth, value)) = seq.next_element::<(usize, D::Value)>()? {
+            let (left, left_depth) =
+                pop_deeper(&mut stack, Some(depth), self.finish).map_err(de::Error::custom)?;
+            if left_depth.is_some_and(|left_depth| left_depth != depth + 1) {
+                return Err(de::Error::custom(
+                    "Invalid tree structure: the depth of a left son is wrong",
+                ));
+            }
+            if matches!(stack.last(), Some((_, top_depth)) if *top_depth == depth) {
+                return Err(de::Error::custom(
+                    "Invalid tree structure: two adjacent nodes have the same depth",
+                ));
+            }
+            let mut node = Box::new(BasicNode::new_alg(value, T::default()));
+            node.left = left;
+            stack.push((node, depth));
+        }
+
+        let (tree, root_depth) =
+            pop_deeper(&mut stack, None, self.finish).map_err(de::Error::custom)?;
+        if root_depth.is_some_and(|root_depth| root_depth != 0) {
+            return Err(de::Error::custom(
+                "Invalid tree structure: the depth of the root is not zero",
+            ));
+        }
+        Ok(tree)
+    }
+}
+
+impl<'de, D: Data> Deserialize<'de> for BasicTree<D>
+where
+    D::Value: Deserialize<'de>,
+{
+    /// Reconstructs the exact shape of the serialized tree.
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        deserialize_tree(deserializer, |_| Ok(()))
+    }
+}
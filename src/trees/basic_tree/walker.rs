@@ -66,6 +66,91 @@ pub struct BasicWalker<'a, D: Data, T = ()> {
     /// This array is always one shorter than [`BasicWalker::rec_ref`] and [`BasicWalker::vals`],
     /// because the last node has no son in the walker.
     pub(super) is_left: Vec<Side>,
+
+    /// If the walker was created with a [`WalkerBuffer`], the buffer that
+    /// [`BasicWalker::vals`] and [`BasicWalker::is_left`] should be given back to.
+    pub(super) buffer: Option<&'a mut WalkerBuffer<D>>,
+
+    /// If the walker was created by an instrumented tree, counts the walker's
+    /// operations for the tree. See [`crate::instrument`].
+    #[cfg(feature = "instrument")]
+    pub(super) scope: Option<crate::instrument::Scope<'a>>,
+}
+
+/// The counters that instrumented trees keep, and count their walkers' operations into.
+/// Without the `instrument` feature, nothing is counted. See [`crate::instrument`].
+#[cfg(feature = "instrument")]
+pub(crate) type Counters = crate::instrument::Stats;
+/// The counters that instrumented trees keep, and count their walkers' operations into.
+/// Without the `instrument` feature, nothing is counted.
+#[cfg(not(feature = "instrument"))]
+pub(crate) type Counters = ();
+
+/// A reusable buffer for the paths that walkers keep.
+///
+/// Every walker keeps a few vectors describing the path from the root to its current position.
+/// A walker created with a buffer (e.g., by [`BasicTree::walker_with_buffer`]) takes the vectors
+/// from the buffer instead of allocating new ones, and gives them back when it is dropped.
+/// Therefore, reusing the same buffer for many walkers avoids allocating for every walker.
+///
+/// Note that the [`RecRef`] inside the walker still keeps its own vector, which isn't reused.
+///```
+/// use grove::{*, avl::AVLTree, basic_tree::WalkerBuffer};
+/// use grove::example_data::StdNum;
+///
+/// let mut tree: AVLTree<StdNum> = (0..1000).collect();
+/// let mut buffer = WalkerBuffer::new();
+/// for i in 0..1000 {
+///     let mut walker = tree.walker_with_buffer(&mut buffer);
+///     walker.search_subtree(i);
+///     assert_eq!(walker.value(), Some(&(i as i32)));
+/// }
+/// # tree.assert_correctness();
+///```
+pub struct WalkerBuffer<D: Data> {
+    vals: Vec<Frame<D>>,
+    is_left: Vec<Side>,
+}
+
+impl<D: Data> WalkerBuffer<D> {
+    /// Creates a new, empty buffer.
+    pub fn new() -> Self {
+        WalkerBuffer {
+            vals: vec![],
+            is_left: vec![],
+        }
+    }
+
+    /// Gives the vectors back to the buffer, if there is one.
+    fn give_back(buffer: Option<&mut Self>, vals: Vec<Frame<D>>, is_left: Vec<Side>) {
+        if let Some(buffer) = buffer {
+            buffer.vals = vals;
+            buffer.is_left = is_left;
+        }
+    }
+}
+
+impl<D: Data> Default for WalkerBuffer<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: Data, T> BasicTree<D, T> {
+    /// Creates a walker for the tree.
+    /// Unlike [`SomeTree::walker`], this also works when `T` is the data of a balancing algorithm.
+    pub fn walker(&mut self) -> BasicWalker<'_, D, T> {
+        BasicWalker::new(self)
+    }
+
+    /// Creates a walker that reuses the vectors of the given buffer instead of allocating
+    /// new ones, and gives them back when it is dropped. See [`WalkerBuffer`].
+    pub fn walker_with_buffer<'a>(
+        &'a mut self,
+        buffer: &'a mut WalkerBuffer<D>,
+    ) -> BasicWalker<'a, D, T> {
+        BasicWalker::new_with_buffer(self, buffer)
+    }
 }
 
 impl<'a, D: Data, T> BasicWalker<'a, D, T> {
@@ -76,9 +161,45 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
             rec_ref: RecRef::new(tree),
             vals: vec![Frame::empty()],
             is_left: vec![],
+            buffer: None,
+            #[cfg(feature = "instrument")]
+            scope: None,
         }
     }
 
+    /// Creates a new walker that walks on the given tree, and reuses the vectors of the given
+    /// buffer instead of allocating new ones. See [`WalkerBuffer`].
+    pub fn new_with_buffer(
+        tree: &'a mut BasicTree<D, T>,
+        buffer: &'a mut WalkerBuffer<D>,
+    ) -> BasicWalker<'a, D, T> {
+        tree.access();
+        let mut vals = std::mem::take(&mut buffer.vals);
+        vals.clear();
+        vals.push(Frame::empty());
+        let mut is_left = std::mem::take(&mut buffer.is_left);
+        is_left.clear();
+        BasicWalker {
+            rec_ref: RecRef::new(tree),
+            vals,
+            is_left,
+            buffer: Some(buffer),
+            #[cfg(feature = "instrument")]
+            scope: None,
+        }
+    }
+
+    /// Counts the walker's operations into the given counters of its tree,
+    /// until the walker is dropped. Does nothing without the `instrument` feature.
+    #[cfg_attr(not(feature = "instrument"), allow(unused_mut, unused_variables))]
+    pub(crate) fn count_into(mut self, stats: &'a mut Counters) -> Self {
+        #[cfg(feature = "instrument")]
+        {
+            self.scope = Some(crate::instrument::Scope::enter(stats));
+        }
+        self
+    }
+
     /// Returns a new walker at the root of the tree, but treats it as if it started in the
     /// of a larger tree, where the summaries to the left and right are
     /// `left_summary` and `right_summary`.
@@ -95,6 +216,9 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
                 right: right_summary,
             }],
             is_left: vec![],
+            buffer: None,
+            #[cfg(feature = "instrument")]
+            scope: None,
         }
     }
 
@@ -116,6 +240,39 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         self.is_left.last().cloned()
     }
 
+    /// Returns the path from the root to the current position:
+    /// for every node on the way, whether the walker went to its left son or its right son.
+    pub fn path(&self) -> &[Side] {
+        &self.is_left
+    }
+
+    /// Iterates over the frames of the walker, from the root to the current position.
+    /// For every subtree on the path, yields the summary of all values to its left,
+    /// and the summary of all values to its right.
+    ///
+    /// The nodes on the path themselves can't be accessed without going up, because the
+    /// walker only holds a usable reference to the current position.
+    ///```
+    /// use grove::{*, basic_tree::*};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (0..7).collect();
+    /// let mut walker = tree.walker();
+    /// walker.go_left().unwrap();
+    /// walker.go_right().unwrap();
+    ///
+    /// assert_eq!(walker.path(), &[Side::Left, Side::Right]);
+    /// let sums: Vec<_> = walker.frames().map(|(left, right)| (left.sum, right.sum)).collect();
+    /// assert_eq!(sums, vec![(0, 0), (0, 3 + 4 + 5 + 6), (0 + 1, 3 + 4 + 5 + 6)]);
+    /// # drop(walker);
+    /// # tree.assert_correctness();
+    ///```
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = (D::Summary, D::Summary)> + '_ {
+        self.vals
+            .iter()
+            .map(|frame| (frame.left.clone(), frame.right.clone()))
+    }
+
     /// Returns the right son of the current node.
     /// Returns `None` if currently at an empty node.
     pub fn right(&self) -> Option<&BasicTree<D, T>> {
@@ -189,6 +346,8 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         &mut self,
         mut rebuilder: F,
     ) -> Option<()> {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.rotations += 1);
         let owned_tree = std::mem::replace(&mut *self.rec_ref, BasicTree::Empty);
 
         let mut bn1: Box<BasicNode<D, T>> = owned_tree.into_node_boxed()?;
@@ -198,7 +357,7 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         bn2.access();
 
         bn1.right = bn2.left;
-        bn2.subtree_summary = bn1.subtree_summary; // this is insetad of bn2.rebuild(), since we already know the result
+        bn2.subtree_summary = std::mem::take(&mut bn1.subtree_summary); // this is insetad of bn2.rebuild(), since we already know the result
         bn1.rebuild();
         rebuilder(&mut *bn1);
         bn2.left = BasicTree::from_boxed_node(bn1);
@@ -223,6 +382,8 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         &mut self,
         mut rebuilder: F,
     ) -> Option<()> {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.rotations += 1);
         let owned_tree = std::mem::replace(&mut *self.rec_ref, BasicTree::Empty);
 
         let mut bn1: Box<BasicNode<D, T>> = owned_tree.into_node_boxed()?;
@@ -232,7 +393,7 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         bn2.access();
 
         bn1.left = bn2.right;
-        bn2.subtree_summary = bn1.subtree_summary; // this is insetad of bn2.rebuild(), since we already know the result
+        bn2.subtree_summary = std::mem::take(&mut bn1.subtree_summary); // this is insetad of bn2.rebuild(), since we already know the result
         bn1.rebuild();
         rebuilder(&mut *bn1);
         bn2.right = BasicTree::from_boxed_node(bn1);
@@ -298,7 +459,12 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
     pub fn root_into_ref(mut self) -> &'a mut BasicTree<D, T> {
         // go to the root
         self.go_to_root();
-        let (tel, _, _) = self.destructure();
+        #[cfg(not(feature = "instrument"))]
+        let (tel, vals, is_left, buffer) = self.destructure();
+        // the scope stops counting here
+        #[cfg(feature = "instrument")]
+        let (tel, vals, is_left, buffer, _scope) = self.destructure();
+        WalkerBuffer::give_back(buffer, vals, is_left);
         RecRef::into_ref(tel)
     }
 
@@ -331,9 +497,13 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
 
     /// If the current position is empty, puts the given value there instead.
     /// Intended to help writing tree algorithms.
+    ///
+    /// The new subtree is accessed, in order to keep the invariant that the current position
+    /// is always clean.
     pub(in super::super) fn put_subtree(&mut self, new: BasicTree<D, T>) -> Option<()> {
         if self.rec_ref.is_empty() {
             *self.rec_ref = new;
+            self.rec_ref.access();
             Some(())
         } else {
             None
@@ -385,9 +555,13 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
 }
 
 /// This implementation exists in order to rebuild the nodes
-/// when the walker gets dropped
+/// when the walker gets dropped, and to give back the walker's vectors
+/// if it was created with a [`WalkerBuffer`].
 impl<'a, D: Data, T> Drop for BasicWalker<'a, D, T> {
     fn drop(&mut self) {
         self.go_to_root();
+        let vals = std::mem::take(&mut self.vals);
+        let is_left = std::mem::take(&mut self.is_left);
+        WalkerBuffer::give_back(self.buffer.take(), vals, is_left);
     }
 }
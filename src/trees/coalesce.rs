@@ -0,0 +1,97 @@
+//! Coalescing consecutive actions on the same segment.
+//!
+//! Streaming workloads often update the same window of a tree many times between queries,
+//! e.g., adding to every value in a sliding window on every tick. An [`ActBuffer`] wraps a tree,
+//! and holds back the last action. If the next action is applied to the same segment,
+//! i.e., with an equal locator, the two actions are composed, and the tree isn't touched.
+//! The buffered action is applied to the tree before anything else is done with it.
+//!
+//! Since the actions are composed before they are applied, this is only correct if applying
+//! two actions one after the other to a segment is the same as applying their composition once,
+//! which is one of the rules of [`Action`]. The locator has to locate the same segment
+//! after the first action was applied, e.g., a range of indices.
+//!```
+//! use grove::{*, coalesce::ActBuffer, treap::Treap};
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let tree: Treap<StdNum> = (0..100).collect();
+//! let mut buffer = ActBuffer::new(tree);
+//! for _ in 0..1000 {
+//!     buffer.act_segment(RevAffineAction { to_reverse: false, mul: 1, add: 1 }, 10..20);
+//! }
+//! // all of the actions were composed into one
+//! assert_eq!(buffer.num_applied(), 0);
+//! assert_eq!(buffer.segment_summary(10..11).sum, 1010);
+//! assert_eq!(buffer.num_applied(), 1);
+//!
+//! let mut tree = buffer.into_inner();
+//! assert_eq!(tree.segment_summary(..).sum, (0..100).sum::<i32>() + 10 * 1000);
+//! # tree.assert_correctness();
+//!```
+
+use super::*;
+
+/// A tree, along with the last action applied to it, that wasn't applied to the tree yet.
+/// See the [module documentation](self).
+pub struct ActBuffer<D: Data, T, L> {
+    tree: T,
+    pending: Option<(L, D::Action)>,
+    applied: usize,
+}
+
+impl<D: Data, T: SomeTree<D>, L: locators::Locator<D> + PartialEq> ActBuffer<D, T, L> {
+    /// Wraps the tree, with no buffered action.
+    pub fn new(tree: T) -> Self {
+        ActBuffer {
+            tree,
+            pending: None,
+            applied: 0,
+        }
+    }
+
+    /// Applies the action to the locator's segment.
+    /// If the buffered action was applied to the same segment, the actions are composed.
+    /// Otherwise, the buffered action is applied to the tree, and this action is buffered instead.
+    pub fn act_segment(&mut self, action: D::Action, locator: L) {
+        match &mut self.pending {
+            Some((pending_locator, pending)) if *pending_locator == locator => {
+                *pending = action + std::mem::take(pending);
+            }
+            _ => {
+                self.flush();
+                self.pending = Some((locator, action));
+            }
+        }
+    }
+
+    /// Applies the buffered action to the tree, if there is one.
+    pub fn flush(&mut self) {
+        if let Some((locator, action)) = self.pending.take() {
+            self.tree.act_segment(action, locator);
+            self.applied += 1;
+        }
+    }
+
+    /// Returns the number of times that buffered actions were applied to the tree.
+    pub fn num_applied(&self) -> usize {
+        self.applied
+    }
+
+    /// Computes the summary of the segment, after applying the buffered action.
+    pub fn segment_summary<L2: locators::Locator<D>>(&mut self, locator: L2) -> D::Summary {
+        self.flush();
+        self.tree.segment_summary(locator)
+    }
+
+    /// Returns the tree, after applying the buffered action.
+    pub fn tree_mut(&mut self) -> &mut T {
+        self.flush();
+        &mut self.tree
+    }
+
+    /// Returns the tree, after applying the buffered action.
+    pub fn into_inner(mut self) -> T {
+        self.flush();
+        self.tree
+    }
+}
@@ -0,0 +1,195 @@
+//! Integration with the [`arbitrary`] crate, for structured fuzzing of trees.
+//!
+//! This module is only available with the `arbitrary` feature.
+//!
+//! All of the tree types implement [`Arbitrary`]. [`BasicTree`] and [`SplayTree`](crate::splay::SplayTree)
+//! are generated in arbitrary shapes, since any shape is valid for them. [`AVLTree`](crate::avl::AVLTree)
+//! and [`Treap`](crate::treap::Treap) are generated from an arbitrary sequence of values,
+//! since their shape is dictated by their balancing invariants.
+//!
+//! Random operation sequences are generated with the [`Operation`] type, and can be applied to
+//! any tree with [`Operation::apply`]. Applying the same operations to two different tree types
+//! and comparing the [`Outcome`]s gives a differential test:
+//!```
+//! use grove::{*, fuzzing::*, avl::AVLTree, splay::SplayTree};
+//! use grove::example_data::{Size, RevAction};
+//! use arbitrary::{Arbitrary, Unstructured};
+//!
+//! type D = (i32, Size, RevAction);
+//!
+//! let bytes: Vec<u8> = (0..1000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+//! let mut u = Unstructured::new(&bytes);
+//! let values: Vec<i32> = Arbitrary::arbitrary(&mut u).unwrap();
+//! let ops: Vec<Operation<i32, RevAction>> = Arbitrary::arbitrary(&mut u).unwrap();
+//!
+//! let mut avl: AVLTree<D> = values.iter().cloned().collect();
+//! let mut splay: SplayTree<D> = values.iter().cloned().collect();
+//! for op in ops {
+//!     assert_eq!(op.clone().apply(&mut avl), op.apply(&mut splay));
+//! }
+//! # avl.assert_correctness();
+//! # splay.assert_correctness();
+//!```
+
+use super::basic_tree::*;
+use super::*;
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// An operation to perform on a tree.
+///
+/// Indices are arbitrary `usize`s, and are only reduced into the tree's bounds when the
+/// operation is applied, so that the same operations can be applied to trees of any length.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Operation<V, A> {
+    /// Apply an action to a segment
+    Act {
+        /// One end of the segment
+        start: usize,
+        /// The other end of the segment
+        end: usize,
+        /// The action to apply
+        action: A,
+    },
+    /// Query the summary of a segment
+    Query {
+        /// One end of the segment
+        start: usize,
+        /// The other end of the segment
+        end: usize,
+    },
+    /// Insert a value
+    Insert {
+        /// The index to insert at
+        index: usize,
+        /// The value to insert
+        value: V,
+    },
+    /// Delete a value. Does nothing if the tree is empty.
+    Delete {
+        /// The index of the deleted value
+        index: usize,
+    },
+}
+
+/// The result of applying an [`Operation`] to a tree.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Outcome<V, S> {
+    /// The operation returned nothing, i.e., an act, an insert, or a delete on an empty tree.
+    Nothing,
+    /// The summary returned by a query.
+    Summary(S),
+    /// The value returned by a delete.
+    Value(V),
+}
+
+impl<V, A> Operation<V, A> {
+    /// Applies the operation to the tree.
+    /// Indices are reduced modulo the number of possible indices in the tree.
+    pub fn apply<D, T>(self, tree: &mut T) -> Outcome<V, D::Summary>
+    where
+        D: Data<Value = V, Action = A>,
+        D::Summary: SizedSummary,
+        T: SomeTree<D>,
+    {
+        let len = tree.subtree_summary().size();
+        let segment = |start: usize, end: usize| {
+            let (start, end) = (start % (len + 1), end % (len + 1));
+            start.min(end)..start.max(end)
+        };
+        match self {
+            Operation::Act { start, end, action } => {
+                tree.act_segment(action, segment(start, end));
+                Outcome::Nothing
+            }
+            Operation::Query { start, end } => {
+                Outcome::Summary(tree.segment_summary(segment(start, end)))
+            }
+            Operation::Insert { index, value } => {
+                let index = index % (len + 1);
+                tree.slice(index..index)
+                    .insert(value)
+                    .expect("Expected empty position");
+                Outcome::Nothing
+            }
+            Operation::Delete { .. } if len == 0 => Outcome::Nothing,
+            Operation::Delete { index } => {
+                let index = index % len;
+                match tree.slice(index..=index).delete() {
+                    Some(value) => Outcome::Value(value),
+                    None => panic!("Deleting an existing value failed"),
+                }
+            }
+        }
+    }
+}
+
+impl<'a, V: Arbitrary<'a>, A: Arbitrary<'a>> Arbitrary<'a> for Operation<V, A> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => Operation::Act {
+                start: u.arbitrary()?,
+                end: u.arbitrary()?,
+                action: u.arbitrary()?,
+            },
+            1 => Operation::Query {
+                start: u.arbitrary()?,
+                end: u.arbitrary()?,
+            },
+            2 => Operation::Insert {
+                index: u.arbitrary()?,
+                value: u.arbitrary()?,
+            },
+            _ => Operation::Delete {
+                index: u.arbitrary()?,
+            },
+        })
+    }
+}
+
+/// Builds a tree in an arbitrary shape, out of an arbitrary sequence of values.
+///
+/// The shape is chosen by drawing an arbitrary priority for every value, and building the
+/// cartesian tree of the priorities (higher priorities go on top, ties are broken to the left).
+/// This takes `O(n)` time, and doesn't use recursion, since the tree might be very deep.
+pub(crate) fn arbitrary_shape<'a, D: Data>(u: &mut Unstructured<'a>) -> Result<BasicTree<D>>
+where
+    D::Value: Arbitrary<'a>,
+{
+    // Every node in the stack is the right son of the node before it, which isn't
+    // attached yet. Their right sons aren't final yet, so they are left empty.
+    let mut stack: Vec<(u8, BasicNode<D>)> = vec![];
+    for pair in u.arbitrary_iter::<(u8, D::Value)>()? {
+        let (priority, value) = pair?;
+        let mut node = BasicNode::new(value);
+        node.left = pop_lower(&mut stack, Some(priority));
+        node.rebuild();
+        stack.push((priority, node));
+    }
+    Ok(pop_lower(&mut stack, None))
+}
+
+// Pops all the nodes with priority lower than `priority` (or all of them if it is `None`)
+// off the stack, attaching each one as the right son of the one below it,
+// and returns the tree of the last popped node.
+fn pop_lower<D: Data>(stack: &mut Vec<(u8, BasicNode<D>)>, priority: Option<u8>) -> BasicTree<D> {
+    let mut tree = BasicTree::Empty;
+    while let Some((top, _)) = stack.last() {
+        if priority.is_some_and(|priority| *top >= priority) {
+            break;
+        }
+        let (_, mut node) = stack.pop().unwrap();
+        node.right = tree;
+        node.rebuild();
+        tree = BasicTree::from_node(node);
+    }
+    tree
+}
+
+impl<'a, D: Data> Arbitrary<'a> for BasicTree<D>
+where
+    D::Value: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_shape(u)
+    }
+}
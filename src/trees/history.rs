@@ -0,0 +1,206 @@
+//! Undo and redo of edits to a tree, without taking snapshots.
+//!
+//! A [`History`] wraps a tree, and logs every edit made through it. Every edit can be undone
+//! by a single inverse edit: an inserted value is deleted, a deleted value is inserted back,
+//! and an action is undone by applying its [`InvertibleAction::inverse`] to the same segment.
+//! Therefore, undoing or redoing an edit takes `O(log n)` time, just like the edit itself.
+//!
+//! Edits are logged by indices, so the summaries must implement [`SizedSummary`].
+//!```
+//! use grove::{*, history::{History, HistoryError}, avl::AVLTree};
+//! use grove::example_data::{Size, RevAddAction, RevAction, AddAction};
+//!
+//! type D = (i32, Size, RevAddAction);
+//! let tree: AVLTree<D> = (0..5).collect();
+//! let mut history = History::new(tree);
+//!
+//! let action = RevAddAction { to_reverse: RevAction { to_reverse: true }, add: AddAction { add: 10 } };
+//! history.act_segment(action, 1..4);
+//! history.insert(0, -1);
+//! assert_eq!(history.delete(3), Some(12));
+//! assert_eq!(history.tree().iter().cloned().collect::<Vec<_>>(), vec![-1, 0, 13, 11, 4]);
+//!
+//! history.undo().unwrap();
+//! history.undo().unwrap();
+//! assert_eq!(history.tree().iter().cloned().collect::<Vec<_>>(), vec![0, 13, 12, 11, 4]);
+//! history.redo().unwrap();
+//! history.undo().unwrap();
+//! history.undo().unwrap();
+//! assert_eq!(history.tree().iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+//! assert_eq!(history.undo(), Err(HistoryError::NothingToUndo));
+//! # history.tree().assert_correctness();
+//!```
+
+use super::*;
+
+/// A single edit to a tree.
+enum Edit<D: Data> {
+    Act {
+        range: std::ops::Range<usize>,
+        action: D::Action,
+    },
+    Insert {
+        index: usize,
+        value: D::Value,
+    },
+    Delete {
+        index: usize,
+    },
+}
+
+/// The reason an edit couldn't be undone or redone.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HistoryError {
+    /// There is no edit to undo.
+    NothingToUndo,
+    /// There is no undone edit to redo, either because nothing was undone,
+    /// or because a new edit was made since.
+    NothingToRedo,
+}
+
+impl std::fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            HistoryError::NothingToUndo => "there is no edit to undo",
+            HistoryError::NothingToRedo => "there is no undone edit to redo",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// A tree, along with the history of the edits made to it, that can be undone and redone.
+/// See the [module documentation](self).
+pub struct History<D: Data, T> {
+    tree: T,
+    // The inverses of the edits that can be undone, with the most recent one last.
+    undo: Vec<Edit<D>>,
+    // The inverses of the edits that can be redone, with the most recently undone one last.
+    redo: Vec<Edit<D>>,
+}
+
+impl<D: Data, T> History<D, T>
+where
+    D::Summary: SizedSummary,
+    D::Action: InvertibleAction,
+    T: SomeTree<D>,
+{
+    /// Starts logging the edits to the tree. The tree's current state can't be undone.
+    pub fn new(tree: T) -> Self {
+        History {
+            tree,
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+
+    /// Returns the tree.
+    pub fn tree(&mut self) -> &mut T {
+        &mut self.tree
+    }
+
+    /// Returns the tree, discarding its history.
+    pub fn into_inner(self) -> T {
+        self.tree
+    }
+
+    /// Returns the number of edits that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns the number of edits that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Discards all of the history.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // Performs the edit, and returns its inverse.
+    fn apply(&mut self, edit: Edit<D>) -> Edit<D> {
+        match edit {
+            Edit::Act { range, action } => {
+                let inverse = action.inverse();
+                self.tree.act_segment(action, range.clone());
+                Edit::Act {
+                    range,
+                    action: inverse,
+                }
+            }
+            Edit::Insert { index, value } => {
+                self.tree
+                    .slice(index..index)
+                    .insert(value)
+                    .expect("Expected empty position");
+                Edit::Delete { index }
+            }
+            Edit::Delete { index } => {
+                let value = self
+                    .tree
+                    .slice(index..=index)
+                    .delete()
+                    .expect("the history doesn't match the tree");
+                Edit::Insert { index, value }
+            }
+        }
+    }
+
+    // Performs a new edit, which discards the edits that could be redone.
+    fn edit(&mut self, edit: Edit<D>) {
+        let inverse = self.apply(edit);
+        self.undo.push(inverse);
+        self.redo.clear();
+    }
+
+    /// Applies the action to the segment of the given indices.
+    pub fn act_segment(&mut self, action: D::Action, range: std::ops::Range<usize>) {
+        self.edit(Edit::Act { range, action });
+    }
+
+    /// Inserts the value at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn insert(&mut self, index: usize, value: D::Value) {
+        let len = self.tree.subtree_summary().size();
+        assert!(index <= len, "index {} out of bounds ({})", index, len);
+        self.edit(Edit::Insert { index, value });
+    }
+
+    /// Deletes the value at the given index, and returns it.
+    /// If the index is out of bounds, returns [`None`], and nothing is logged.
+    pub fn delete(&mut self, index: usize) -> Option<D::Value>
+    where
+        D::Value: Clone,
+    {
+        let value = self.tree.slice(index..=index).delete()?;
+        self.undo.push(Edit::Insert {
+            index,
+            value: value.clone(),
+        });
+        self.redo.clear();
+        Some(value)
+    }
+
+    /// Undoes the most recent edit that wasn't undone.
+    /// If there is no such edit, returns [`HistoryError::NothingToUndo`].
+    pub fn undo(&mut self) -> Result<(), HistoryError> {
+        let edit = self.undo.pop().ok_or(HistoryError::NothingToUndo)?;
+        let inverse = self.apply(edit);
+        self.redo.push(inverse);
+        Ok(())
+    }
+
+    /// Redoes the most recently undone edit.
+    /// If there is no such edit, or if a new edit was made since it was undone,
+    /// returns [`HistoryError::NothingToRedo`].
+    pub fn redo(&mut self) -> Result<(), HistoryError> {
+        let edit = self.redo.pop().ok_or(HistoryError::NothingToRedo)?;
+        let inverse = self.apply(edit);
+        self.undo.push(inverse);
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! Counters of the basic operations performed by the trees, for measuring and comparing
+//! the trees' performance on a specific workload.
+//!
+//! This module is only available with the `instrument` feature. When the feature is off,
+//! nothing is counted, and there is no runtime cost.
+//!
+//! Every [`SplayTree`], [`AVLTree`](crate::avl::AVLTree) and [`Treap`](crate::treap::Treap)
+//! keeps its own counters, which are returned by [`Instrumented::stats`]. The operations
+//! performed through the tree's walkers are counted, which includes all of the
+//! [`SomeTree`](crate::SomeTree) methods that take `&mut self`, except for iteration.
+//! A [`PersistentTree`](crate::persistent::PersistentTree) counts the operations of its methods
+//! that take `&mut self`. Building a tree isn't counted.
+//!
+//! Nodes don't know which tree they belong to, so while a walker is alive, the operations
+//! performed by the current thread are counted for the tree of the walker that was created last.
+//! Therefore, when using walkers of several trees at the same time, the counts might be split
+//! between the trees differently than expected.
+//!```
+//! use grove::{*, instrument::{self, Instrumented}, splay::SplayTree, avl::AVLTree};
+//! use grove::example_data::StdNum;
+//!
+//! let mut splay: SplayTree<StdNum> = (0..100).collect();
+//! splay.slice(100..100).insert(100);
+//! assert_eq!(splay.stats().nodes_created, 1);
+//! let ((), splay_stats) = instrument::measure(&mut splay, |splay| {
+//!     splay.search(50);
+//! });
+//!
+//! let mut avl: AVLTree<StdNum> = (0..100).collect();
+//! let ((), avl_stats) = instrument::measure(&mut avl, |avl| {
+//!     avl.search(50);
+//! });
+//! // searching in an AVL tree doesn't rotate it, but searching in a splay tree does
+//! assert_eq!(avl_stats.rotations, 0);
+//! assert!(splay_stats.rotations > 0);
+//! assert!(splay_stats.splay_steps > 0);
+//! # splay.assert_correctness();
+//! # avl.assert_correctness();
+//!```
+
+use crate::{splay::SplayTree, Data};
+use std::cell::{Cell, RefCell};
+
+/// Counts of the basic operations performed by the trees.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Stats {
+    /// The number of steps taken down the tree by walkers.
+    pub descents: usize,
+    /// The number of rotations performed.
+    pub rotations: usize,
+    /// The number of times a node was splayed in a splay tree.
+    pub splays: usize,
+    /// The number of splay steps performed by splay trees.
+    /// Every splay step consists of one or two rotations.
+    pub splay_steps: usize,
+    /// The number of nodes allocated.
+    pub nodes_created: usize,
+    /// The number of times a node's subtree summary was recomputed from its sons.
+    pub rebuilds: usize,
+}
+
+impl Stats {
+    /// Subtracts the counts, stopping at zero instead of overflowing.
+    /// This is useful for taking the difference between two readings of the counters,
+    /// that might have been reset in between.
+    pub fn saturating_sub(self, other: Stats) -> Stats {
+        Stats {
+            descents: self.descents.saturating_sub(other.descents),
+            rotations: self.rotations.saturating_sub(other.rotations),
+            splays: self.splays.saturating_sub(other.splays),
+            splay_steps: self.splay_steps.saturating_sub(other.splay_steps),
+            nodes_created: self.nodes_created.saturating_sub(other.nodes_created),
+            rebuilds: self.rebuilds.saturating_sub(other.rebuilds),
+        }
+    }
+}
+
+impl std::ops::Add for Stats {
+    type Output = Stats;
+    fn add(self, other: Stats) -> Stats {
+        Stats {
+            descents: self.descents + other.descents,
+            rotations: self.rotations + other.rotations,
+            splays: self.splays + other.splays,
+            splay_steps: self.splay_steps + other.splay_steps,
+            nodes_created: self.nodes_created + other.nodes_created,
+            rebuilds: self.rebuilds + other.rebuilds,
+        }
+    }
+}
+
+impl std::ops::Sub for Stats {
+    type Output = Stats;
+    fn sub(self, other: Stats) -> Stats {
+        Stats {
+            descents: self.descents - other.descents,
+            rotations: self.rotations - other.rotations,
+            splays: self.splays - other.splays,
+            splay_steps: self.splay_steps - other.splay_steps,
+            nodes_created: self.nodes_created - other.nodes_created,
+            rebuilds: self.rebuilds - other.rebuilds,
+        }
+    }
+}
+
+/// A tree that counts the operations performed on it. See the [module documentation](self).
+pub trait Instrumented {
+    /// Returns the counts of the operations performed on this tree since it was created,
+    /// or since the last call to [`Instrumented::reset_stats`].
+    fn stats(&self) -> Stats;
+
+    /// Resets all of the tree's counters to zero.
+    fn reset_stats(&mut self);
+}
+
+/// Runs the given function on the tree, and returns its result together with the counts
+/// of the operations it performed on the tree. Doesn't reset the counters.
+pub fn measure<T: Instrumented, R, F: FnOnce(&mut T) -> R>(tree: &mut T, f: F) -> (R, Stats) {
+    let before = tree.stats();
+    let res = f(tree);
+    // `f` might have reset the counters
+    (res, tree.stats().saturating_sub(before))
+}
+
+thread_local! {
+    // The counts of the walkers that are alive in the current thread, with their ids,
+    // in the order they were created. The operations are counted in the last one.
+    static FRAMES: RefCell<Vec<(usize, Stats)>> = const { RefCell::new(vec![]) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the operations performed while it is alive, and adds them to a tree's counters
+/// when it is dropped. Walkers hold one for the tree they were created from.
+pub(crate) struct Scope<'a> {
+    stats: &'a mut Stats,
+    id: usize,
+}
+
+impl<'a> Scope<'a> {
+    /// Starts counting the operations of the current thread for the tree whose counters
+    /// are `stats`, until the scope is dropped, or until another scope is entered.
+    pub(crate) fn enter(stats: &'a mut Stats) -> Self {
+        let id = NEXT_ID.with(|next_id| next_id.replace(next_id.get() + 1));
+        FRAMES.with(|frames| frames.borrow_mut().push((id, Stats::default())));
+        Scope { stats, id }
+    }
+}
+
+impl<'a> Drop for Scope<'a> {
+    fn drop(&mut self) {
+        // the scope might be dropped on another thread, in which case its counts are lost
+        let counts = FRAMES.with(|frames| {
+            let mut frames = frames.borrow_mut();
+            let index = frames.iter().rposition(|&(id, _)| id == self.id)?;
+            Some(frames.remove(index).1)
+        });
+        if let Some(counts) = counts {
+            *self.stats = *self.stats + counts;
+        }
+    }
+}
+
+/// Updates the counters of the current scope, if there is one.
+pub(crate) fn record<F: FnOnce(&mut Stats)>(f: F) {
+    FRAMES.with(|frames| {
+        if let Some((_, stats)) = frames.borrow_mut().last_mut() {
+            f(stats);
+        }
+    })
+}
+
+/// Checks the amortized complexity of a splay tree over a run of operations,
+/// by comparing the actual work done with the bound given by the Sleator-Tarjan potential.
+///
+/// The work is measured as the number of steps taken down the tree by walkers.
+/// Splaying a node that is `d` steps down takes `d` rotations, and the amortized number of
+/// rotations of a splay in a tree of `n` values is at most `3 log2(n) + 1`. One more step
+/// is allowed for searches that end at an empty position, since its father is splayed instead.
+/// Therefore, over a run of `m` splays,
+/// `work <= m * (3 log2(n) + 2) + initial potential - final potential`.
+/// Walking down the tree without splaying afterwards isn't paid for, so it breaks the bound
+/// on long enough runs.
+///
+/// The bound assumes that the tree's size doesn't change, so the run shouldn't insert or delete
+/// values.
+///```
+/// use grove::{*, instrument::PotentialTracker, splay::SplayTree};
+/// use grove::example_data::StdNum;
+///
+/// let mut tree: SplayTree<StdNum> = (0..1000).collect();
+/// let tracker = PotentialTracker::new(&tree);
+/// for i in 0..1000 {
+///     tree.search((i * 7) % 1000);
+///     tree.segment_summary(i / 2..i);
+/// }
+/// assert!(tracker.splays(&tree) >= 1000);
+/// tracker.assert_bound(&tree);
+/// # tree.assert_correctness();
+///```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PotentialTracker {
+    start: Stats,
+    initial_potential: f64,
+}
+
+impl PotentialTracker {
+    /// Starts tracking a run of operations on the tree. This takes `O(n)` time.
+    pub fn new<D: Data>(tree: &SplayTree<D>) -> Self {
+        PotentialTracker {
+            start: tree.stats(),
+            initial_potential: tree.potential(),
+        }
+    }
+
+    /// Returns the work done on the tree since the tracker was created.
+    pub fn work<D: Data>(&self, tree: &SplayTree<D>) -> usize {
+        tree.stats().saturating_sub(self.start).descents
+    }
+
+    /// Returns the number of splays performed on the tree since the tracker was created.
+    pub fn splays<D: Data>(&self, tree: &SplayTree<D>) -> usize {
+        tree.stats().saturating_sub(self.start).splays
+    }
+
+    /// Returns the amortized bound for the work done since the tracker was created.
+    /// This takes `O(n)` time.
+    pub fn amortized_bound<D: Data>(&self, tree: &SplayTree<D>) -> f64 {
+        let (size, potential) = tree.size_and_potential();
+        let per_splay = 3. * (size.max(1) as f64).log2() + 2.;
+        self.splays(tree) as f64 * per_splay + self.initial_potential - potential
+    }
+
+    /// Panics if the work done since the tracker was created exceeds the amortized bound.
+    /// This takes `O(n)` time.
+    pub fn assert_bound<D: Data>(&self, tree: &SplayTree<D>) {
+        let (work, bound) = (self.work(tree), self.amortized_bound(tree));
+        // leave some slack for floating point errors
+        assert!(
+            work as f64 <= bound + 1e-6 * bound.abs() + 1e-6,
+            "splay work {} exceeds the amortized bound {}",
+            work,
+            bound
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Link-cut trees, for maintaining a forest of rooted trees under linking and cutting,
+//! with summaries and actions on paths.
+//!
+//! The forest is represented as in Sleator and Tarjan's link-cut trees: every tree of the forest
+//! is partitioned into preferred paths, and every preferred path is stored in a splay tree,
+//! ordered from the top of the path to its bottom. Every operation takes `O(log n)` amortized time.
+//!
+//! The splay trees here use the same values, summaries and actions as the rest of the crate,
+//! but they don't reuse [`SplayTree`](crate::splay::SplayTree) or
+//! [`BasicNode`](crate::basic_tree::BasicNode): the nodes are stored in an arena indexed by
+//! their vertex, and every node points to its father.
+//! Link-cut trees need both: an operation on a vertex starts at the vertex's own node, and goes up
+//! from it, through the splay trees, to the root of the forest's tree.
+//! A [`BasicNode`](crate::basic_tree::BasicNode) owns its sons through boxes, and can only be
+//! reached by going down from the root of its tree, so without unsafe code, a vertex's node
+//! can't be found in constant time. Keeping the fathers in a side table wouldn't help either,
+//! since the walkers' rotations don't report the nodes that they move, so the table couldn't
+//! be kept up to date.
+//!
+//! Changing the root of a tree (which [`LinkCutForest::link`], [`LinkCutForest::cut`] and the path
+//! operations do) reverses a path, so the summaries have to implement [`ReverseSummary`].
+//! Actions are applied to paths without changing the order of the path, so they mustn't reverse.
+//!```
+//! use grove::link_cut::LinkCutForest;
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut forest: LinkCutForest<StdNum> = (0..6).collect();
+//! // the path 0 - 1 - 2 - 3, with 4 and 5 hanging from 1
+//! for (u, v) in [(1, 0), (2, 1), (3, 2), (4, 1), (5, 4)] {
+//!     forest.link(u, v).unwrap();
+//! }
+//! assert_eq!(forest.path_summary(3, 5).unwrap().sum, 3 + 2 + 1 + 4 + 5);
+//!
+//! forest.act_path(0, 5, RevAffineAction { to_reverse: false, mul: 1, add: 10 }).unwrap();
+//! assert_eq!(forest.path_summary(3, 5).unwrap().sum, 3 + 2 + 11 + 14 + 15);
+//!
+//! forest.cut(1, 4).unwrap();
+//! assert!(!forest.connected(3, 5));
+//! assert_eq!(forest.find_root(5), 4);
+//! assert_eq!(forest.path_summary(3, 5), None);
+//! # forest.assert_correctness();
+//!```
+
+use super::*;
+
+/// A node of a splay tree, representing a vertex.
+struct Node<D: Data> {
+    value: D::Value,
+    /// The summary of the node's splay subtree.
+    /// The node's pending action and reversal were already applied to it.
+    summary: D::Summary,
+    /// An action that should be applied to the node's sons. Already applied to the node's value.
+    action: D::Action,
+    /// Whether the node's sons' subtrees should be reversed. The node's sons were already swapped.
+    reversed: bool,
+    sons: [Option<usize>; 2],
+    /// The node's father in its splay tree, or if it is the root of its splay tree,
+    /// the father of the top vertex of its path in the forest.
+    father: Option<usize>,
+}
+
+/// The reason a link-cut operation failed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LinkCutError {
+    /// The two vertices are already in the same tree, so linking them would make a cycle.
+    AlreadyConnected,
+    /// There is no edge between the two vertices.
+    NoSuchEdge,
+    /// The two vertices are in different trees, so there is no path between them.
+    NotConnected,
+}
+
+impl std::fmt::Display for LinkCutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            LinkCutError::AlreadyConnected => "the vertices are already in the same tree",
+            LinkCutError::NoSuchEdge => "there is no edge between the vertices",
+            LinkCutError::NotConnected => "the vertices are in different trees",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for LinkCutError {}
+
+/// A forest of rooted trees, whose vertices hold values, and which supports
+/// summaries and actions on paths. See the [module documentation](self).
+///
+/// The vertices are numbered by the order in which they were added, starting at `0`.
+/// Since splay trees restructure themselves on accesses, queries take `&mut self` as well.
+pub struct LinkCutForest<D: Data> {
+    nodes: Vec<Node<D>>,
+}
+
+impl<D: Data> Default for LinkCutForest<D> {
+    fn default() -> Self {
+        LinkCutForest { nodes: vec![] }
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for LinkCutForest<D>
+where
+    D::Summary: ReverseSummary,
+{
+    /// Creates a forest of isolated vertices, holding the values.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut forest = LinkCutForest::new();
+        for value in iter {
+            forest.add_vertex(value);
+        }
+        forest
+    }
+}
+
+impl<D: Data> LinkCutForest<D>
+where
+    D::Summary: ReverseSummary,
+{
+    /// Creates an empty forest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of vertices in the forest.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the forest has no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a new isolated vertex holding the value, and returns it.
+    pub fn add_vertex(&mut self, value: D::Value) -> usize {
+        self.nodes.push(Node {
+            summary: value.to_summary(),
+            value,
+            action: Default::default(),
+            reversed: false,
+            sons: [None, None],
+            father: None,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Returns the value of the vertex.
+    pub fn value(&mut self, vertex: usize) -> &D::Value {
+        self.splay(vertex);
+        &self.nodes[vertex].value
+    }
+
+    /// Changes the value of the vertex with the given function.
+    pub fn with_value<F, R>(&mut self, vertex: usize, f: F) -> R
+    where
+        F: FnOnce(&mut D::Value) -> R,
+    {
+        self.splay(vertex);
+        let res = f(&mut self.nodes[vertex].value);
+        self.rebuild(vertex);
+        res
+    }
+
+    /// Returns the root of the vertex's tree.
+    pub fn find_root(&mut self, vertex: usize) -> usize {
+        self.access(vertex);
+        let mut root = vertex;
+        while let Some(son) = self.nodes[root].sons[0] {
+            self.push(son);
+            root = son;
+        }
+        self.splay(root);
+        root
+    }
+
+    /// Returns `true` if the two vertices are in the same tree.
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.find_root(u) == self.find_root(v)
+    }
+
+    /// Makes the vertex the root of its tree.
+    pub fn make_root(&mut self, vertex: usize) {
+        self.access(vertex);
+        self.reverse(vertex);
+    }
+
+    /// Adds an edge between `u` and `v`, so that `u`'s tree hangs from `v`.
+    /// `u` becomes the root of its tree before linking, so `v` becomes `u`'s father.
+    ///
+    /// If they are already in the same tree, returns [`LinkCutError::AlreadyConnected`],
+    /// and nothing is changed: in particular, `u`'s tree keeps its root.
+    pub fn link(&mut self, u: usize, v: usize) -> Result<(), LinkCutError> {
+        if self.connected(u, v) {
+            return Err(LinkCutError::AlreadyConnected);
+        }
+        self.make_root(u);
+        self.nodes[u].father = Some(v);
+        Ok(())
+    }
+
+    /// Removes the edge between `u` and `v`. `u` becomes the root of its tree.
+    ///
+    /// If there is no such edge, returns [`LinkCutError::NoSuchEdge`], and no edge is removed,
+    /// but `u` still becomes the root of its tree.
+    pub fn cut(&mut self, u: usize, v: usize) -> Result<(), LinkCutError> {
+        self.make_root(u);
+        self.access(v);
+        // if the edge exists, the path from `u` to `v` is only `u` and `v`
+        if self.nodes[v].sons[0] != Some(u) || self.nodes[u].sons[1].is_some() {
+            return Err(LinkCutError::NoSuchEdge);
+        }
+        self.nodes[v].sons[0] = None;
+        self.nodes[u].father = None;
+        self.rebuild(v);
+        Ok(())
+    }
+
+    /// Returns the summary of the path between the two vertices, in order from `u` to `v`,
+    /// or [`None`] if they are in different trees. `u` becomes the root of its tree.
+    pub fn path_summary(&mut self, u: usize, v: usize) -> Option<D::Summary> {
+        self.expose_path(u, v)?;
+        Some(self.nodes[v].summary.clone())
+    }
+
+    /// Applies the action on every vertex in the path between the two vertices.
+    /// The action must not reverse, and must not depend on the index.
+    /// `u` becomes the root of its tree.
+    ///
+    /// If they are in different trees, returns [`LinkCutError::NotConnected`],
+    /// and no value is changed, but `u` still becomes the root of its tree.
+    pub fn act_path(&mut self, u: usize, v: usize, action: D::Action) -> Result<(), LinkCutError> {
+        assert!(
+            !action.to_reverse(),
+            "Actions on paths of a link-cut tree must not reverse"
+        );
+        assert!(
+            !action.is_indexed(),
+            "Actions on paths of a link-cut tree must not depend on the index"
+        );
+        self.expose_path(u, v).ok_or(LinkCutError::NotConnected)?;
+        self.apply(v, action);
+        Ok(())
+    }
+
+    /// Makes the path between the two vertices into a single splay tree, rooted at `v`.
+    fn expose_path(&mut self, u: usize, v: usize) -> Option<()> {
+        self.make_root(u);
+        if self.find_root(v) != u {
+            return None;
+        }
+        self.access(v);
+        Some(())
+    }
+
+    /// Makes the path from the root of the vertex's tree to the vertex preferred,
+    /// so that it is a single splay tree rooted at the vertex, with no other vertices.
+    fn access(&mut self, vertex: usize) {
+        let mut last = None;
+        let mut current = Some(vertex);
+        while let Some(node) = current {
+            self.splay(node);
+            self.nodes[node].sons[1] = last;
+            self.rebuild(node);
+            last = Some(node);
+            current = self.nodes[node].father;
+        }
+        self.splay(vertex);
+    }
+
+    /// Returns the side of the node in its father, if it isn't the root of its splay tree.
+    fn side(&self, node: usize) -> Option<usize> {
+        let father = self.nodes[node].father?;
+        self.nodes[father]
+            .sons
+            .iter()
+            .position(|&son| son == Some(node))
+    }
+
+    fn reverse(&mut self, node: usize) {
+        let node = &mut self.nodes[node];
+        node.sons.swap(0, 1);
+        node.summary.reverse();
+        node.reversed ^= true;
+    }
+
+    fn apply(&mut self, node: usize, action: D::Action) {
+        let node = &mut self.nodes[node];
+        action.act_inplace(&mut node.value);
+        action.act_inplace(&mut node.summary);
+        node.action = action + std::mem::take(&mut node.action);
+        D::on_access(&node.value, &node.summary);
+    }
+
+    /// Pushes the node's pending action and reversal down to its sons.
+    fn push(&mut self, node: usize) {
+        let (sons, action, reversed) = {
+            let node = &mut self.nodes[node];
+            let res = (node.sons, std::mem::take(&mut node.action), node.reversed);
+            node.reversed = false;
+            res
+        };
+        for son in sons.iter().flatten() {
+            if reversed {
+                self.reverse(*son);
+            }
+            if !action.is_identity() {
+                self.apply(*son, action.clone());
+            }
+        }
+    }
+
+    /// Recomputes the node's summary from its sons.
+    fn rebuild(&mut self, node: usize) {
+        let summary_of = |son: Option<usize>| {
+            son.map_or_else(Default::default, |son| self.nodes[son].summary.clone())
+        };
+        let [left, right] = self.nodes[node].sons;
+        let summary = summary_of(left) + self.nodes[node].value.to_summary() + summary_of(right);
+        self.nodes[node].summary = summary;
+        D::on_rebuild(&self.nodes[node].value, &self.nodes[node].summary);
+    }
+
+    /// Rotates the node above its father. The node must have a father in its splay tree.
+    fn rotate(&mut self, node: usize) {
+        let side = self
+            .side(node)
+            .expect("Expected a father in the splay tree");
+        let father = self.nodes[node].father.unwrap();
+        let grandfather_side = self.side(father);
+        let grandfather = self.nodes[father].father;
+
+        let middle = self.nodes[node].sons[1 - side];
+        self.nodes[father].sons[side] = middle;
+        if let Some(middle) = middle {
+            self.nodes[middle].father = Some(father);
+        }
+        self.nodes[node].sons[1 - side] = Some(father);
+        self.nodes[father].father = Some(node);
+        self.nodes[node].father = grandfather;
+        if let (Some(grandfather), Some(grandfather_side)) = (grandfather, grandfather_side) {
+            self.nodes[grandfather].sons[grandfather_side] = Some(node);
+        }
+        self.rebuild(father);
+        self.rebuild(node);
+    }
+
+    /// Splays the node to the root of its splay tree.
+    fn splay(&mut self, node: usize) {
+        // push the pending actions down the path from the root of the splay tree, top-down
+        let mut path = vec![node];
+        let mut current = node;
+        while self.side(current).is_some() {
+            current = self.nodes[current].father.unwrap();
+            path.push(current);
+        }
+        for &ancestor in path.iter().rev() {
+            self.push(ancestor);
+        }
+
+        while let Some(side) = self.side(node) {
+            let father = self.nodes[node].father.unwrap();
+            match self.side(father) {
+                None => self.rotate(node),
+                Some(father_side) if father_side == side => {
+                    self.rotate(father);
+                    self.rotate(node);
+                }
+                Some(_) => {
+                    self.rotate(node);
+                    self.rotate(node);
+                }
+            }
+        }
+    }
+
+    /// Checks that the splay trees' links and summaries are consistent.
+    /// Takes linear time.
+    pub fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        for (index, node) in self.nodes.iter().enumerate() {
+            for son in node.sons.iter().flatten() {
+                assert_eq!(self.nodes[*son].father, Some(index));
+            }
+            if !node.action.is_identity() || node.reversed {
+                // the sons' summaries aren't up to date, so the summary can't be checked here
+                continue;
+            }
+            let summary_of = |son: Option<usize>| {
+                son.map_or_else(Default::default, |son| self.nodes[son].summary.clone())
+            };
+            assert!(
+                node.summary
+                    == summary_of(node.sons[0])
+                        + node.value.to_summary()
+                        + summary_of(node.sons[1]),
+                "incorrect summary at vertex {}",
+                index
+            );
+        }
+    }
+}
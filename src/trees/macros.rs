@@ -3,7 +3,7 @@
 ///```
 /// derive_SomeWalker!{walker,
 ///     impl<'a, D: Data> SomeWalker<D> for TreapWalker<'a, D> {
-///         fn go_up(&mut self) -> Result<Side, ()> {
+///         fn go_up(&mut self) -> Result<Side, NavError> {
 ///             ...
 ///         }
 ///     }
@@ -15,11 +15,11 @@ macro_rules! derive_SomeWalker {
         { $($token:tt)* }
     ) => {
         impl<$lifetime, $data: Data> SomeWalker<$data> for $self {
-            fn go_left(&mut self) -> Result<(), ()> {
+            fn go_left(&mut self) -> Result<(), NavError> {
                 self.$accessor.go_left()
             }
 
-            fn go_right(&mut self) -> Result<(), ()> {
+            fn go_right(&mut self) -> Result<(), NavError> {
                 self.$accessor.go_right()
             }
 
@@ -39,11 +39,19 @@ macro_rules! derive_SomeWalker {
                 self.$accessor.value()
             }
 
+            fn peek_left(&mut self) -> Option<& $data::Value> {
+                self.$accessor.peek_left()
+            }
+
+            fn peek_right(&mut self) -> Option<& $data::Value> {
+                self.$accessor.peek_right()
+            }
+
             $($token)*
         }
     }
 }
-/// deriving SomeWalker by an inner walker
+/// deriving SomeEntry and BasicBacked by an inner entry
 /// format is:
 ///```
 /// derive_SomeEntry!{walker,
@@ -102,6 +110,10 @@ macro_rules! derive_SomeEntry {
                 self.$accessor.act_right_subtree(action)
             }
 
+            $($token)*
+        }
+
+        impl<$($lifetime,)? $data : Data> BasicBacked<$data> for $self {
             #[cfg(debug_assertions)]
             type EntryTreeData = $alg_data;
 
@@ -112,8 +124,6 @@ macro_rules! derive_SomeEntry {
             {
                 self.$accessor.representation(alg_print, to_reverse)
             }
-
-            $($token)*
         }
     }
 }
@@ -6,16 +6,50 @@
 //! The [`SomeWalker`] trait implements traversing a tree. This includes dealing with the borrow
 //! checking problems of recursive structures (using [`recursive_reference`]), and rebalancing the tree.
 //! Therefore, walkers can't guarantee that the tree won't change as you walk through them.
+//!
+//! # Thread safety
+//!
+//! The crate has no unsafe code, so it never implements [`Send`] and [`Sync`] manually.
+//! All of the trees own their nodes through [`Box`]es (or [`Arc`](std::sync::Arc)s, for
+//! [`persistent::PersistentTree`], or a [`Vec`], for [`arena::ArenaTree`]),
+//! with no interior mutability, so they are [`Send`] and [`Sync`]
+//! exactly when their values, summaries and actions are. Therefore, trees can be moved to
+//! other threads, and shared between threads for immutable queries, like
+//! [`SomeTree::segment_summary_imm`].
+//!
+//! [`basic_tree::ImmBasicWalker`] only holds shared references, so it is [`Send`] and [`Sync`]
+//! whenever the tree is [`Sync`]. The other walkers, [`basic_tree::BasicWalker`],
+//! [`splay::SplayWalker`], [`avl::AVLWalker`] and [`treap::TreapWalker`], hold the tree through
+//! a [`recursive_reference::RecRef`], which is [`Send`] and [`Sync`] exactly when a mutable
+//! reference to the tree would be. Therefore, they are [`Send`] and [`Sync`] exactly
+//! when the tree is.
 
 #[macro_use]
 mod macros;
+mod random;
 mod segment_algorithms;
 
+pub mod arena;
 pub mod avl;
 pub mod basic_tree;
+pub mod coalesce;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+pub mod history;
+#[cfg(feature = "instrument")]
+pub mod instrument;
+pub mod link_cut;
+pub mod persistent;
 pub mod slice;
+pub mod snapshot;
 pub mod splay;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+pub mod trace;
 pub mod treap;
+mod validate;
+pub mod versioned;
+pub use validate::*;
 
 use crate::data::*;
 use crate::locators;
@@ -38,15 +72,79 @@ impl Side {
     }
 }
 
+/// The reason a walker couldn't move, or couldn't modify the tree at its position.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum NavError {
+    /// Tried to go up from the root.
+    AtRoot,
+    /// Tried to go down from an empty position, or to do anything else that needs a value.
+    EmptyPosition,
+    /// Tried to insert at a position that isn't empty.
+    OccupiedPosition,
+    /// Tried to go up to a depth that is deeper than the walker.
+    TooShallow,
+    /// There is no value or position to go to: e.g., there is no next value,
+    /// or the searched index is out of bounds.
+    OutOfBounds,
+    /// There is no value with the searched key.
+    KeyNotFound,
+}
+
+impl std::fmt::Display for NavError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            NavError::AtRoot => "the walker is at the root",
+            NavError::EmptyPosition => "the walker is at an empty position",
+            NavError::OccupiedPosition => "the walker isn't at an empty position",
+            NavError::TooShallow => "the walker is shallower than the requested depth",
+            NavError::OutOfBounds => "there is no position to go to",
+            NavError::KeyNotFound => "there is no value with the searched key",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for NavError {}
+
+/// Lets `?` convert walker errors in functions that return `Result<_, ()>`,
+/// which is what the walker methods used to return.
+impl From<NavError> for () {
+    fn from(_: NavError) {}
+}
+
 /// This trait is the top-level trait that the different trees implement.
 /// Every tree that implements this trait can be used directly by the functions
 /// immediately in this trait.
 /// More advanced use can be achieved by using walkers, which must be implemented.
 pub trait SomeTree<D: Data>:
-    SomeEntry<D> + std::iter::FromIterator<D::Value> + IntoIterator<Item = D::Value> + Default
-where
-    for<'a> &'a mut Self: SomeTreeRef<D>,
+    SomeEntry<D>
+    + std::iter::FromIterator<D::Value>
+    + IntoIterator<Item = D::Value, IntoIter: DoubleEndedIterator>
+    + Default
 {
+    /// The walker type associated with this tree.
+    /// For example, if `Self = AVLTree<D>` then `Self::Walker<'a> = AVLWalker<'a, D>`.
+    /// The walker's lifetime is the lifetime of the tree's borrow.
+    type Walker<'a>: ModifiableWalker<D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    /// Creates a walker for the given tree.
+    fn walker(&mut self) -> Self::Walker<'_>;
+
+    /// Finds any node that the locator `Accept`s.
+    /// If there isn't any, it finds the empty location where that node would be instead.
+    /// Returns a walker at the wanted position.
+    fn search<L>(&mut self, locator: L) -> Self::Walker<'_>
+    where
+        L: locators::Locator<D>,
+    {
+        let mut walker = self.walker();
+        walker.search_subtree(locator);
+        walker
+    }
+
     /// Compute the summary of a subsegment.
     /// Requires `D::Value: Clone`.
     ///
@@ -65,10 +163,513 @@ where
         L: locators::Locator<D>;
 
     /// Apply an action on a subsegment.
+    /// Actions that depend on the index (see [`Action::is_indexed`]) count the indices
+    /// from the start of the subsegment.
     fn act_segment<L>(&mut self, action: D::Action, locator: L)
     where
         L: locators::Locator<D>;
 
+    /// Undo an action on a subsegment, by applying its inverse action on it.
+    /// See [`InvertibleAction`].
+    ///
+    /// The locator should locate the same segment that the action was applied to,
+    /// e.g., the same range of indices.
+    ///```
+    /// use grove::{SomeTree, splay::SplayTree};
+    /// use grove::example_data::{NumSummary, RevAction, AddAction, RevAddAction};
+    ///
+    /// let mut tree: SplayTree<(i32, NumSummary, RevAddAction)> = (1..=8).collect();
+    /// let action = RevAddAction { to_reverse: RevAction { to_reverse: true }, add: AddAction { add: 10 } };
+    /// tree.act_segment(action, 2..5);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 15, 14, 13, 6, 7, 8]);
+    ///
+    /// tree.unact_segment(action, 2..5);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (1..=8).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn unact_segment<L>(&mut self, action: D::Action, locator: L)
+    where
+        D::Action: InvertibleAction,
+        L: locators::Locator<D>,
+    {
+        self.act_segment(action.inverse(), locator);
+    }
+
+    /// Computes the summary of the `query` segment, as it would be if the action was applied
+    /// on the `segment` first. The tree is left unchanged: the action is applied, the summary is
+    /// computed, and then the action is undone using [`SomeTree::unact_segment`],
+    /// so the tree doesn't need to be cloned.
+    ///
+    /// The `segment` locator should locate the same segment after the action is applied,
+    /// e.g., a range of indices.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{NumSummary, RevAction, AddAction, RevAddAction};
+    ///
+    /// let mut tree: Treap<(i32, NumSummary, RevAddAction)> = (1..=8).collect();
+    /// let action = RevAddAction { to_reverse: RevAction { to_reverse: false }, add: AddAction { add: -5 } };
+    /// // what would the minimum of the whole tree be, after subtracting 5 from the last 4 values?
+    /// let summary = tree.segment_summary_after_act(action, 4..8, ..);
+    /// assert_eq!(summary.min, Some(0));
+    /// assert_eq!(tree.segment_summary(..).min, Some(1));
+    /// # tree.assert_correctness();
+    ///```
+    fn segment_summary_after_act<L1, L2>(
+        &mut self,
+        action: D::Action,
+        segment: L1,
+        query: L2,
+    ) -> D::Summary
+    where
+        D::Action: InvertibleAction,
+        L1: locators::Locator<D>,
+        L2: locators::Locator<D>,
+    {
+        self.act_segment(action.clone(), segment.clone());
+        let summary = self.segment_summary(query);
+        self.unact_segment(action, segment);
+        summary
+    }
+
+    /// Computes the summary of the segment between two positions, given as
+    /// [`Bookmark`](locators::Bookmark)s, e.g., the two ends of a selection in an editor.
+    /// The positions can be in either order, and the values that they are at are included.
+    /// See [`Bookmark::range_to`](locators::Bookmark::range_to).
+    ///```
+    /// use grove::{SomeTree, SomeWalker, splay::SplayTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: SplayTree<StdNum> = (0..20).collect();
+    /// let mut walker = tree.search(12);
+    /// walker.next_empty().unwrap();
+    /// let cursor = walker.bookmark();
+    /// drop(walker);
+    /// let anchor = tree.search(4).bookmark();
+    ///
+    /// assert_eq!(tree.summary_between(&cursor, &anchor).sum, (4..=12).sum::<i32>());
+    /// # tree.assert_correctness();
+    ///```
+    fn summary_between(&mut self, from: &locators::Bookmark, to: &locators::Bookmark) -> D::Summary
+    where
+        D::Summary: SizedSummary,
+    {
+        self.segment_summary(from.range_to(to))
+    }
+
+    /// Applies an action on the segment between two positions, given as
+    /// [`Bookmark`](locators::Bookmark)s, like [`SomeTree::summary_between`].
+    ///
+    /// The bookmarks stay valid, unless the action reverses the segment.
+    ///```
+    /// use grove::{SomeTree, SomeWalker, treap::Treap};
+    /// use grove::locators::Bookmark;
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: Treap<StdNum> = (0..10).collect();
+    /// let (from, to) = (Bookmark::at_empty(3), Bookmark::at_empty(6));
+    /// tree.act_between(&from, &to, RevAffineAction { to_reverse: false, mul: 1, add: 10 });
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 13, 14, 15, 6, 7, 8, 9]);
+    /// assert_eq!(tree.search(to).left_summary().size, 6);
+    /// # tree.assert_correctness();
+    ///```
+    fn act_between(&mut self, from: &locators::Bookmark, to: &locators::Bookmark, action: D::Action)
+    where
+        D::Summary: SizedSummary,
+    {
+        self.act_segment(action, from.range_to(to));
+    }
+
+    /// Compute the summary of everything outside of the locator's segment.
+    /// This is the summary of the prefix to the left of the segment,
+    /// added to the summary of the suffix to the right of the segment.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::locators::ComplementLocator;
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (1..=10).collect();
+    /// let summary = tree.segment_summary_complement(ComplementLocator(3..7));
+    ///
+    /// assert_eq!(summary.size, 6);
+    /// assert_eq!(summary.sum, 1 + 2 + 3 + 8 + 9 + 10);
+    /// # tree.assert_correctness();
+    ///```
+    fn segment_summary_complement<L>(
+        &mut self,
+        locator: locators::ComplementLocator<L>,
+    ) -> D::Summary
+    where
+        L: locators::Locator<D>,
+    {
+        let (prefix, suffix) = locator.pieces();
+        let left = self.segment_summary(prefix);
+        left + self.segment_summary(suffix)
+    }
+
+    /// Apply an action on everything outside of the locator's segment.
+    /// The action is applied separately on the prefix to the left of the segment,
+    /// and on the suffix to the right of the segment. In particular, a reversing action
+    /// reverses each of the two pieces in place.
+    ///
+    /// Note that the locator is queried again after the action has been applied to the prefix.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::locators::ComplementLocator;
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: AVLTree<StdNum> = (1..=8).collect();
+    /// let action = RevAffineAction { to_reverse: true, mul: 1, add: 10 };
+    /// tree.act_segment_complement(action, ComplementLocator(2..5));
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![12, 11, 3, 4, 5, 18, 17, 16]);
+    /// # tree.assert_correctness();
+    ///```
+    fn act_segment_complement<L>(
+        &mut self,
+        action: D::Action,
+        locator: locators::ComplementLocator<L>,
+    ) where
+        L: locators::Locator<D>,
+    {
+        let (prefix, suffix) = locator.pieces();
+        self.act_segment(action.clone(), prefix);
+        self.act_segment(action, suffix);
+    }
+
+    /// Makes sure that there is a boundary between values at the given index, by splitting the
+    /// wide value containing it, if there is one. See [`SplitValue`].
+    ///
+    /// Returns whether a value was split.
+    ///```
+    /// use grove::{SomeTree, SplitValue, ToSummary, Acts, treap::Treap};
+    /// use grove::example_data::{NumSummary, RevAffineAction};
+    ///
+    /// /// `count` copies of `value`.
+    /// #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    /// struct Run { value: i32, count: usize }
+    ///
+    /// impl ToSummary<NumSummary> for Run {
+    ///     fn to_summary(&self) -> NumSummary {
+    ///         let size = self.count as i32;
+    ///         NumSummary { max: Some(self.value), min: Some(self.value), size, sum: self.value * size }
+    ///     }
+    /// }
+    ///
+    /// impl Acts<Run> for RevAffineAction {
+    ///     fn act_inplace(&self, run: &mut Run) {
+    ///         self.act_inplace(&mut run.value);
+    ///     }
+    /// }
+    ///
+    /// impl SplitValue for Run {
+    ///     fn split_off(&mut self, index: usize) -> Run {
+    ///         let rest = Run { value: self.value, count: self.count - index };
+    ///         self.count = index;
+    ///         rest
+    ///     }
+    /// }
+    ///
+    /// let runs = vec![Run { value: 1, count: 5 }, Run { value: 2, count: 5 }];
+    /// let mut tree: Treap<(Run, NumSummary, RevAffineAction)> = runs.into_iter().collect();
+    ///
+    /// // add 10 to the elements at indices 3..7
+    /// assert!(tree.split_value_at(3));
+    /// assert!(tree.split_value_at(7));
+    /// assert!(!tree.split_value_at(5));
+    /// tree.act_segment(RevAffineAction { to_reverse: false, mul: 1, add: 10 }, 3..7);
+    ///
+    /// assert_eq!(tree.segment_summary(..).sum, 15 + 4 * 10);
+    /// let runs: Vec<_> = tree.iter().map(|run| (run.value, run.count)).collect();
+    /// assert_eq!(runs, vec![(1, 3), (11, 2), (12, 2), (2, 3)]);
+    /// # tree.assert_correctness();
+    ///```
+    fn split_value_at(&mut self, index: usize) -> bool
+    where
+        D::Summary: SizedSummary,
+        D::Value: SplitValue,
+    {
+        let mut walker = self.search(index);
+        let start = walker.left_summary().size();
+        if walker.is_empty() || start == index {
+            return false;
+        }
+        let rest = walker
+            .with_value(|value| value.split_off(index - start))
+            .expect("Expected nonempty position");
+        walker.next_empty().expect("Expected nonempty position");
+        walker.insert(rest).expect("Expected empty position");
+        true
+    }
+
+    /// Returns `true` if the tree contains a value with the given key.
+    /// The values should be sorted by their keys.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: Treap<PlainData<(i32, char)>> = vec![(1, 'a'), (4, 'b'), (9, 'c')].into_iter().collect();
+    /// assert!(tree.contains_key(&4));
+    /// assert!(!tree.contains_key(&5));
+    /// # tree.assert_correctness();
+    ///```
+    fn contains_key<Key: Ord>(&mut self, key: &Key) -> bool
+    where
+        D::Value: Keyed<Key>,
+    {
+        !self.search(locators::ByKey((key,))).is_empty()
+    }
+
+    /// Returns a reference to a value with the given key, if there is one.
+    /// The values should be sorted by their keys.
+    ///```
+    /// use grove::{SomeTree, splay::SplayTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: SplayTree<PlainData<(i32, char)>> = vec![(1, 'a'), (4, 'b'), (9, 'c')].into_iter().collect();
+    /// assert_eq!(tree.get_by_key(&9), Some(&(9, 'c')));
+    /// assert_eq!(tree.get_by_key(&2), None);
+    /// # tree.assert_correctness();
+    ///```
+    fn get_by_key<'a, Key: Ord>(&'a mut self, key: &Key) -> Option<&'a D::Value>
+    where
+        D: 'a,
+        D::Value: Keyed<Key>,
+    {
+        self.iter_locator(locators::ByKey((key,))).next()
+    }
+
+    /// Returns a reference to the last value whose key is strictly smaller than the given key,
+    /// if there is one. The values should be sorted by their keys.
+    /// Finds the value with [`SomeTree::iter_locator`], so it costs as much as starting
+    /// an iteration, e.g., a single descent for the trees built on [`basic_tree::BasicTree`].
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: Treap<PlainData<(i32, char)>> = vec![(1, 'a'), (4, 'b'), (4, 'c'), (9, 'd')].into_iter().collect();
+    /// assert_eq!(tree.predecessor(&4), Some(&(1, 'a')));
+    /// assert_eq!(tree.predecessor(&5), Some(&(4, 'c')));
+    /// assert_eq!(tree.predecessor(&1), None);
+    /// # tree.assert_correctness();
+    ///```
+    fn predecessor<'a, Key: Ord>(&'a mut self, key: &Key) -> Option<&'a D::Value>
+    where
+        D: 'a,
+        D::Value: Keyed<Key>,
+    {
+        self.iter_locator(locators::LeftOf(locators::ByKey((key,))))
+            .next_back()
+    }
+
+    /// Returns a reference to the first value whose key is strictly greater than the given key,
+    /// if there is one. The values should be sorted by their keys.
+    /// Finds the value with [`SomeTree::iter_locator`], so it costs as much as starting
+    /// an iteration, e.g., a single descent for the trees built on [`basic_tree::BasicTree`].
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: AVLTree<PlainData<(i32, char)>> = vec![(1, 'a'), (4, 'b'), (4, 'c'), (9, 'd')].into_iter().collect();
+    /// assert_eq!(tree.successor(&1), Some(&(4, 'b')));
+    /// assert_eq!(tree.successor(&0), Some(&(1, 'a')));
+    /// assert_eq!(tree.successor(&9), None);
+    /// # tree.assert_correctness();
+    ///```
+    fn successor<'a, Key: Ord>(&'a mut self, key: &Key) -> Option<&'a D::Value>
+    where
+        D: 'a,
+        D::Value: Keyed<Key>,
+    {
+        self.iter_locator(locators::RightOf(locators::ByKey((key,))))
+            .next()
+    }
+
+    /// Removes a value with the given key from the tree, and returns it.
+    /// Returns [`None`] if there isn't any.
+    /// The values should be sorted by their keys.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: AVLTree<PlainData<(i32, char)>> = vec![(1, 'a'), (4, 'b'), (9, 'c')].into_iter().collect();
+    /// assert_eq!(tree.remove_by_key(&4), Some((4, 'b')));
+    /// assert_eq!(tree.remove_by_key(&4), None);
+    /// assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![(1, 'a'), (9, 'c')]);
+    ///```
+    fn remove_by_key<Key: Ord>(&mut self, key: &Key) -> Option<D::Value>
+    where
+        D::Value: Keyed<Key>,
+    {
+        self.search(locators::ByKey((key,))).delete()
+    }
+
+    /// Inserts a value in its place in sorted order, after all the values with an equal key.
+    /// The values should be sorted by their keys.
+    ///
+    /// If the value has more than one kind of key, the key type has to be specified,
+    /// e.g., `tree.insert_sorted::<i32>(value)`.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: Treap<PlainData<i32>> = vec![1, 3, 7].into_iter().collect();
+    /// tree.insert_sorted(5);
+    /// tree.insert_sorted(3);
+    /// tree.insert_sorted(0);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 3, 3, 5, 7]);
+    /// # tree.assert_correctness();
+    ///```
+    fn insert_sorted<Key: Ord>(&mut self, value: D::Value)
+    where
+        D::Value: Keyed<Key>,
+    {
+        let mut walker = self.search(locators::insert_position_after_equal(value.get_key()));
+        walker.insert(value).expect("Expected empty position");
+    }
+
+    /// Creates a tree of the values, sorted by their keys.
+    /// The sort is stable, so values with equal keys keep their order.
+    ///
+    /// This is equivalent to inserting the values one by one using [`SomeTree::insert_sorted`],
+    /// but instead, the values are collected and sorted, and the tree is built from the sorted values.
+    /// Takes `O(n log n)` time, or `O(n)` time if the values are already sorted.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let values = vec![(3, 'a'), (1, 'b'), (3, 'c'), (0, 'd')];
+    /// let tree = AVLTree::<PlainData<(i32, char)>>::from_unsorted_iter::<i32, _>(values);
+    /// assert_eq!(tree.into_sorted_vec(), vec![(0, 'd'), (1, 'b'), (3, 'a'), (3, 'c')]);
+    ///```
+    fn from_unsorted_iter<Key, I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = D::Value>,
+        D::Value: Keyed<Key>,
+        Key: Ord,
+    {
+        let mut values: Vec<D::Value> = iter.into_iter().collect();
+        let is_sorted = values
+            .windows(2)
+            .all(|pair| pair[0].get_key() <= pair[1].get_key());
+        if !is_sorted {
+            values.sort_by(|a, b| a.get_key().cmp(b.get_key()));
+        }
+        values.into_iter().collect()
+    }
+
+    /// Returns the values of the tree in a vector, in order.
+    /// The values should be sorted by their keys, so the vector is sorted as well.
+    fn into_sorted_vec(self) -> Vec<D::Value> {
+        self.into_iter().collect()
+    }
+
+    /// Removes consecutive repeated values, like [`Vec::dedup`].
+    /// Takes `O(n)` time, since the values are taken out of the tree in one traversal,
+    /// and a balanced tree is built from the remaining values.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = vec![1, 1, 2, 3, 3, 3, 1, 2, 2].into_iter().collect();
+    /// tree.dedup();
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 3, 1, 2]);
+    /// assert_eq!(tree.segment_summary(..).sum, 9);
+    /// # tree.assert_correctness();
+    ///```
+    fn dedup(&mut self)
+    where
+        D::Value: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Removes all but the first of consecutive values that satisfy the given equality relation,
+    /// like [`Vec::dedup_by`]. The closure is passed each value and the last value kept before it,
+    /// in that order. Takes `O(n)` time, like [`SomeTree::dedup`].
+    fn dedup_by<F>(&mut self, same: F)
+    where
+        F: FnMut(&mut D::Value, &mut D::Value) -> bool,
+    {
+        let mut values: Vec<D::Value> = std::mem::take(self).into_iter().collect();
+        values.dedup_by(same);
+        *self = values.into_iter().collect();
+    }
+
+    /// Inserts a value in its place in sorted order, keeping the keys unique.
+    /// If there is already a value with an equal key, it is replaced, and the old value is returned.
+    /// The values should be sorted by their keys.
+    ///
+    /// If the value has more than one kind of key, the key type has to be specified,
+    /// e.g., `tree.insert_unique::<i32>(value)`.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: AVLTree<PlainData<(i32, char)>> = vec![(1, 'a'), (3, 'b')].into_iter().collect();
+    /// assert_eq!(tree.insert_unique::<i32>((2, 'c')), None);
+    /// assert_eq!(tree.insert_unique::<i32>((3, 'd')), Some((3, 'b')));
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![(1, 'a'), (2, 'c'), (3, 'd')]);
+    /// # tree.assert_correctness();
+    ///```
+    fn insert_unique<Key: Ord>(&mut self, value: D::Value) -> Option<D::Value>
+    where
+        D::Value: Keyed<Key>,
+    {
+        let mut walker = self.search(locators::insert_position_unique(value.get_key()));
+        if walker.is_empty() {
+            walker.insert(value).expect("Expected empty position");
+            None
+        } else {
+            walker.with_value(|old| std::mem::replace(old, value))
+        }
+    }
+
+    /// Merges two trees whose values are sorted by their keys into one sorted tree.
+    /// Unlike concatenation, the values of the two trees can be interleaved.
+    /// Values with equal keys are placed in an arbitrary order.
+    ///
+    /// The default implementation inserts the values of the smaller tree into the larger tree.
+    /// If the sizes of the trees are `n, m` with `m <= n`, this takes `O(m*log(n+m))` time.
+    /// Treaps use a join-based algorithm instead, see [`treap::union`].
+    ///```
+    /// use grove::{SomeTree, splay::SplayTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let tree1: SplayTree<PlainData<i32>> = vec![1, 4, 6, 9].into_iter().collect();
+    /// let tree2: SplayTree<PlainData<i32>> = vec![2, 4, 5].into_iter().collect();
+    /// let mut tree = tree1.merge::<i32>(tree2);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 4, 4, 5, 6, 9]);
+    /// # tree.assert_correctness();
+    ///```
+    fn merge<Key: Ord>(mut self, mut other: Self) -> Self
+    where
+        D::Value: Keyed<Key>,
+    {
+        // find the smaller tree, in time proportional to its size
+        let self_is_smaller = {
+            let mut iter1 = self.iter();
+            let mut iter2 = other.iter();
+            loop {
+                if iter1.next().is_none() {
+                    break true;
+                }
+                if iter2.next().is_none() {
+                    break false;
+                }
+            }
+        };
+        let (mut larger, smaller) = if self_is_smaller {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        for value in smaller {
+            larger.insert_sorted::<Key>(value);
+        }
+        larger
+    }
+
     /// Returns a value representing a specific subsegment of the tree. This gives a nicer
     /// Interface for tree operations: `tree.slice(3..50).act(action)` instead of
     /// `tree.act_segment(3..50, action)`. see [`slice::Slice`].
@@ -76,8 +677,23 @@ where
         slice::Slice::new(self, locator)
     }
 
-    /// This is here just so that the signature for iter_locator can be written out. Don't use this.
-    type TreeData;
+    /// The iterator returned by [`SomeTree::iter_locator`].
+    /// For the trees in this crate, this is [`basic_tree::iterators::IterLocator`].
+    type IterLocator<'a, L: locators::Locator<D>>: DoubleEndedIterator<Item = &'a D::Value>
+    where
+        Self: 'a,
+        D: 'a;
+
+    /// The guard returned by [`SomeTree::iter_locator_mut`].
+    /// For the trees in this crate, this is [`basic_tree::iterators::IterMutGuard`].
+    type IterLocatorMut<'a, L: locators::Locator<D>>: IterGuard<D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    /// The iterator returned by [`SomeTree::into_iter_segment`].
+    /// For the trees in this crate, this is [`basic_tree::iterators::IntoIter`].
+    type IntoIterSegment<L: locators::Locator<D>>: DoubleEndedIterator<Item = D::Value>;
 
     /// Iterating on values.
     /// This iterator assumes you won't change the values using interior mutability. If you change the values,
@@ -93,27 +709,279 @@ where
     /// let segment_iter = tree.iter_locator(3..13);
     ///
     /// assert_eq!(segment_iter.cloned().collect::<Vec<_>>(), (23..33).collect::<Vec<_>>());
+    ///
+    /// // The iterator is double-ended
+    /// let mut segment_iter = tree.iter_locator(3..13);
+    /// assert_eq!(segment_iter.next_back(), Some(&32));
+    /// assert_eq!(segment_iter.next(), Some(&23));
+    /// assert_eq!(segment_iter.rev().cloned().collect::<Vec<_>>(), (24..32).rev().collect::<Vec<_>>());
     /// # tree.assert_correctness();
     ///```
     fn iter_locator<'a, L: locators::Locator<D>>(
         &'a mut self,
         locator: L,
-    ) -> basic_tree::iterators::IterLocator<'a, D, L, Self::TreeData>;
+    ) -> Self::IterLocator<'a, L>;
+
+    /// Iterates over the whole tree, together with the index of every value.
+    /// The indices are computed from the summaries, and not by counting the values.
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (17..=89).collect();
+    ///
+    /// assert_eq!(tree.iter_indexed().rev().nth(1), Some((71, &88)));
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_indexed(&mut self) -> SizedIndexed<D, Self::IterLocator<'_, std::ops::RangeFull>>
+    where
+        D::Summary: example_data::SizedSummary,
+    {
+        let len = self.subtree_summary().size();
+        SizedIndexed::new(self.iter(), len)
+    }
+
+    /// Mutably iterates over a segment of the tree.
+    /// Returns a guard, that rebuilds the visited nodes when it is dropped.
+    /// Call [`basic_tree::iterators::IterMutGuard::iter`] to get the actual iterator.
+    ///
+    /// Modifying the values should not change the segment selected by the locator,
+    /// nor the order of the keys, if the tree is keyed.
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (20..80).collect();
+    /// tree.iter_locator_mut(3..13).iter().for_each(|value| *value = 0);
+    ///
+    /// assert_eq!(tree.segment_summary(0..15).sum, 20 + 21 + 22 + 33 + 34);
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_locator_mut<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> Self::IterLocatorMut<'a, L>;
+
+    /// Mutably iterates over the whole tree.
+    /// Returns a guard, that rebuilds the visited nodes when it is dropped.
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (17..=89).collect();
+    /// for value in tree.iter_mut().iter().rev().take(10) {
+    ///     *value = -*value;
+    /// }
+    ///
+    /// assert_eq!(tree.segment_summary(..).sum, (17..80).sum::<i32>() - (80..=89).sum::<i32>());
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_mut(&mut self) -> Self::IterLocatorMut<'_, std::ops::RangeFull> {
+        self.iter_locator_mut(..)
+    }
+
+    /// Consumes the tree, and iterates over a segment of it.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let tree: Treap<StdNum> = (20..80).collect();
+    /// let segment_iter = tree.into_iter_segment(3..13);
+    ///
+    /// assert_eq!(segment_iter.rev().collect::<Vec<_>>(), (23..33).rev().collect::<Vec<_>>());
+    ///```
+    fn into_iter_segment<L: locators::Locator<D>>(self, locator: L) -> Self::IntoIterSegment<L>;
 
     /// Iterates over the whole tree.
     ///```
     /// use grove::{SomeTree, basic_tree::BasicTree};
     /// use grove::example_data::StdNum;
     ///
-    /// let mut tree: BasicTree<StdNum> = (17..=89).collect();
+    /// let mut tree: BasicTree<StdNum> = (17..=89).collect();
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).collect::<Vec<_>>());
+    /// assert_eq!(tree.iter().rev().cloned().collect::<Vec<_>>(), (17..=89).rev().collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn iter(&mut self) -> Self::IterLocator<'_, std::ops::RangeFull> {
+        self.iter_locator(..)
+    }
+
+    /// Folds the values of a segment, in order, like [`Iterator::fold`].
+    /// This computes a one-off aggregation over a segment, without defining a new summary type.
+    ///
+    /// Pending actions are pushed down on the way, and only the nodes of the segment and
+    /// the paths to its edges are visited, so this takes `O(log n + k)` time,
+    /// where `k` is the length of the segment.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: AVLTree<StdNum> = (0..20).collect();
+    /// tree.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 1 }, 5..10);
+    ///
+    /// let odd = tree.fold_segment(3..8, vec![], |mut odd, &value| {
+    ///     if value % 2 == 1 {
+    ///         odd.push(value);
+    ///     }
+    ///     odd
+    /// });
+    /// assert_eq!(odd, vec![3, 9]);
+    /// # tree.assert_correctness();
+    ///```
+    fn fold_segment<L, B, F>(&mut self, locator: L, init: B, f: F) -> B
+    where
+        L: locators::Locator<D>,
+        F: FnMut(B, &D::Value) -> B,
+    {
+        self.iter_locator(locator).fold(init, f)
+    }
+
+    /// Pushes all of the pending actions down to the values, so that every value in the tree
+    /// is stored as it is, and the tree can be read directly, e.g., through
+    /// [`BasicTree::node`](basic_tree::BasicTree::node), without stale lazy actions.
+    /// Takes `O(n)` time.
+    ///```
+    /// use grove::{*, basic_tree::BasicTree};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: BasicTree<StdNum> = (0..20).collect();
+    /// tree.act_subtree(RevAffineAction { to_reverse: false, mul: 2, add: 0 });
+    /// assert!(!tree.action().is_identity());
+    ///
+    /// tree.flush_actions();
+    /// assert!(tree.action().is_identity());
+    /// assert_eq!(tree.node().unwrap().subtree_summary().sum, 380);
+    /// # tree.assert_correctness();
+    ///```
+    fn flush_actions(&mut self) {
+        self.flush_segment(..);
+    }
+
+    /// Pushes the pending actions down to the values of the segment,
+    /// like [`SomeTree::flush_actions`], but only for the nodes of the segment and
+    /// the paths to its edges. Takes `O(log n + k)` time, where `k` is the length of the segment.
+    fn flush_segment<L: locators::Locator<D>>(&mut self, locator: L) {
+        // iterating over the segment accesses all of its nodes
+        self.iter_locator(locator).for_each(drop);
+    }
+
+    /// Chooses a value of a segment uniformly at random, using the size summaries to descend
+    /// to the chosen value, instead of iterating over the segment.
+    /// Returns [`None`] if the segment is empty. Takes `O(log n)` time.
+    /// Requires the `rand` feature.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (0..100).collect();
+    /// let mut rng = rand::thread_rng();
+    /// for _ in 0..10 {
+    ///     let value = *tree.sample_segment(20..30, &mut rng).unwrap();
+    ///     assert!((20..30).contains(&value));
+    /// }
+    /// assert_eq!(tree.sample_segment(20..20, &mut rng), None);
+    /// # tree.assert_correctness();
+    ///```
+    #[cfg(feature = "rand")]
+    fn sample_segment<'a, L, R>(&'a mut self, locator: L, rng: &mut R) -> Option<&'a D::Value>
+    where
+        L: locators::Locator<D>,
+        R: rand::Rng + ?Sized,
+        D: 'a,
+        D::Summary: SizedSummary,
+    {
+        self.sample_segment_by(locator, rng, |summary: &D::Summary| summary.size())
+    }
+
+    /// Chooses a value of a segment at random, with probability proportional to its weight.
+    /// Returns [`None`] if the segment's total weight is `0`. Takes `O(log n)` time.
+    /// Requires the `rand` feature.
+    ///
+    /// The weight of a segment is computed from its summary, and it should be additive:
+    /// `weight(&(summary1 + summary2)) == weight(&summary1) + weight(&summary2)`.
+    ///```
+    /// use grove::{SomeTree, splay::SplayTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// // values with zero weight are never chosen
+    /// let mut tree: SplayTree<StdNum> = vec![5, 0, 0, 3, 0, 2].into_iter().collect();
+    /// let mut rng = rand::thread_rng();
+    /// for _ in 0..10 {
+    ///     let value = *tree.sample_segment_by(1..5, &mut rng, |summary| summary.sum as usize).unwrap();
+    ///     assert_eq!(value, 3);
+    /// }
+    /// assert_eq!(tree.sample_segment_by(1..3, &mut rng, |summary| summary.sum as usize), None);
+    /// # tree.assert_correctness();
+    ///```
+    #[cfg(feature = "rand")]
+    fn sample_segment_by<'a, L, R, W>(
+        &'a mut self,
+        locator: L,
+        rng: &mut R,
+        weight: W,
+    ) -> Option<&'a D::Value>
+    where
+        L: locators::Locator<D>,
+        R: rand::Rng + ?Sized,
+        W: Fn(&D::Summary) -> usize,
+        D: 'a,
+    {
+        let before = weight(
+            &self
+                .search(locators::LeftEdgeOf(locator.clone()))
+                .left_summary(),
+        );
+        let total = weight(&self.segment_summary(locator));
+        if total == 0 {
+            return None;
+        }
+        let target = before + rng.gen_range(0..total);
+        let weight = &weight;
+        let chosen = move |left: D::Summary, value: &D::Value, _right: D::Summary| {
+            let left = weight(&left);
+            if target < left {
+                locators::LocResult::GoLeft
+            } else if target < left + weight(&value.to_summary()) {
+                locators::LocResult::Accept
+            } else {
+                locators::LocResult::GoRight
+            }
+        };
+        self.iter_locator(chosen).next()
+    }
+
+    /// Converts the tree into another tree type, holding the same values in the same order.
+    /// Any pending actions are applied on the way.
+    ///
+    /// The values are consumed in order and bulk-built into the new tree,
+    /// so this takes `O(n)` time for all of the tree types in this crate.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree, splay::SplayTree};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut avl: AVLTree<StdNum> = (1..=20).collect();
+    /// avl.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 0 }, 5..15);
     ///
-    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).collect::<Vec<_>>());
-    /// # tree.assert_correctness();
+    /// let mut splay: SplayTree<StdNum> = avl.convert();
+    /// assert_eq!(splay.segment_summary(5..6).sum, 15);
+    /// # splay.assert_correctness();
     ///```
-    fn iter(
-        &mut self,
-    ) -> basic_tree::iterators::IterLocator<'_, D, std::ops::RangeFull, Self::TreeData> {
-        self.iter_locator(..)
+    fn convert<T2>(self) -> T2
+    where
+        T2: std::iter::FromIterator<D::Value>,
+    {
+        self.into_iter().collect()
+    }
+
+    /// Creates a read-only, frozen copy of the tree, that can be shared between threads
+    /// while the tree keeps being modified. This takes `O(n)` time.
+    /// See [`snapshot::Snapshot`].
+    fn snapshot(&mut self) -> snapshot::Snapshot<D>
+    where
+        D::Value: Clone,
+    {
+        self.iter().cloned().collect()
     }
 
     /// Used for testing purposes.
@@ -123,33 +991,144 @@ where
         D::Summary: Eq;
 }
 
-/// This is a workaround for not having Generic Associated Types in Rust yet.
-/// Really, the type [`Self::Walker`] should have been defined in [`SomeTree`] and
-/// should have been generic in a lifetime parameter.
-pub trait SomeTreeRef<D: Data> {
-    /// The walker type associated with this tree.
-    /// for example, if `Self = &'a AVLTreee<D>` then `Self::Walker = AVLWalker<'a>`.
-    /// The walker's lifetime comes from `Self`'s lifetime.
-    type Walker: SomeWalker<D>;
-    /// Creates a walker for the given tree.
-    fn walker(self) -> Self::Walker;
-
-    /// Finds any node that the locator `Accept`s.
-    /// If there isn't any, it finds the empty location where that node would be instead.
-    /// Returns a walker at the wanted position.
-    fn search<L>(self, locator: L) -> Self::Walker
+/// Guards for iterating mutably over a segment of a tree, returned by [`SomeTree::iter_locator_mut`].
+/// The guard makes the tree legal again when it is dropped, e.g., by rebuilding the visited nodes.
+/// See [`basic_tree::iterators::IterMutGuard`].
+pub trait IterGuard<D: Data> {
+    /// The mutable iterator, which borrows the guard.
+    type IterMut<'b>: DoubleEndedIterator<Item = &'b mut D::Value>
     where
-        L: locators::Locator<D>,
-        Self: Sized,
-    {
-        let mut walker = self.walker();
+        Self: 'b,
+        D: 'b;
+
+    /// Iterates mutably over the segment.
+    fn iter(&mut self) -> Self::IterMut<'_>;
+}
+
+/// An iterator that also yields the index of every value, created by [`SomeTree::iter_indexed`].
+/// The indices are computed from the sizes of the values, and not by counting the values.
+/// They count from the start of the wrapped iterator, unlike
+/// [`basic_tree::iterators::IterIndexed`], which reads them from the tree for any segment.
+pub struct SizedIndexed<D: Data, I> {
+    iter: I,
+    // the index of the next value from the front
+    start: usize,
+    // the index after the next value from the back
+    end: usize,
+    phantom: std::marker::PhantomData<D>,
+}
+
+impl<D: Data, I> SizedIndexed<D, I> {
+    /// Wraps an iterator over values, whose total size is `len`.
+    pub fn new(iter: I, len: usize) -> Self {
+        SizedIndexed {
+            iter,
+            start: 0,
+            end: len,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, D: Data, I> Iterator for SizedIndexed<D, I>
+where
+    D: 'a,
+    D::Summary: SizedSummary,
+    I: Iterator<Item = &'a D::Value>,
+{
+    type Item = (usize, &'a D::Value);
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next()?;
+        let index = self.start;
+        self.start += ToSummary::<D::Summary>::to_summary(value).size();
+        Some((index, value))
+    }
+}
+
+impl<'a, D: Data, I> DoubleEndedIterator for SizedIndexed<D, I>
+where
+    D: 'a,
+    D::Summary: SizedSummary,
+    I: DoubleEndedIterator<Item = &'a D::Value>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next_back()?;
+        self.end -= ToSummary::<D::Summary>::to_summary(value).size();
+        Some((self.end, value))
+    }
+}
+
+/// Returns a walker positioned at the first value whose key is greater than or equal to `key`.
+/// If there isn't any, the walker is positioned at the empty position after all of the values,
+/// where a value with this key should be inserted.
+/// The values should be sorted by their keys.
+///```
+/// use grove::{SomeTree, SomeWalker, SomeEntry, walker_lower_bound, treap::Treap};
+/// use grove::example_data::PlainData;
+///
+/// let mut tree: Treap<PlainData<i32>> = vec![1, 3, 3, 7].into_iter().collect();
+/// assert_eq!(walker_lower_bound(&mut tree, &3).value(), Some(&3));
+/// assert_eq!(walker_lower_bound(&mut tree, &4).value(), Some(&7));
+/// assert!(walker_lower_bound(&mut tree, &8).is_empty());
+/// # tree.assert_correctness();
+///```
+pub fn walker_lower_bound<'a, D, T, Key>(tree: &'a mut T, key: &Key) -> T::Walker<'a>
+where
+    D: Data,
+    D::Value: Keyed<Key>,
+    Key: Ord,
+    T: SomeTree<D>,
+{
+    walker_after_gap(tree, locators::LeftEdgeOf(locators::ByKey((key,))))
+}
+
+/// Returns a walker positioned at the first value whose key is strictly greater than `key`.
+/// If there isn't any, the walker is positioned at the empty position after all of the values,
+/// where a value with this key should be inserted.
+/// The values should be sorted by their keys.
+///```
+/// use grove::{SomeTree, SomeWalker, SomeEntry, walker_upper_bound, avl::AVLTree};
+/// use grove::example_data::PlainData;
+///
+/// let mut tree: AVLTree<PlainData<i32>> = vec![1, 3, 3, 7].into_iter().collect();
+/// assert_eq!(walker_upper_bound(&mut tree, &3).value(), Some(&7));
+/// assert_eq!(walker_upper_bound(&mut tree, &0).value(), Some(&1));
+/// assert!(walker_upper_bound(&mut tree, &7).is_empty());
+/// # tree.assert_correctness();
+///```
+pub fn walker_upper_bound<'a, D, T, Key>(tree: &'a mut T, key: &Key) -> T::Walker<'a>
+where
+    D: Data,
+    D::Value: Keyed<Key>,
+    Key: Ord,
+    T: SomeTree<D>,
+{
+    walker_after_gap(tree, locators::RightEdgeOf(locators::ByKey((key,))))
+}
+
+/// Searches for the empty position specified by the splitting locator, and moves to the next value.
+/// If there is no next value, stays at the empty position.
+fn walker_after_gap<D, T, L>(tree: &mut T, locator: L) -> T::Walker<'_>
+where
+    D: Data,
+    T: SomeTree<D>,
+    L: locators::Locator<D>,
+{
+    let mut walker = tree.search(locator.clone());
+    if walker.next_filled().is_err() {
+        // the walker is now at the root
         walker.search_subtree(locator);
-        walker
     }
+    walker
 }
 
 /// The Walker trait implements walking through a tree.
-/// This includes dealing with the borrow checking problems of recursive structures (using Telescope),
+/// This includes dealing with the borrow checking problems of recursive structures (using [`recursive_reference::RecRef`]),
 /// and rebalancing the tree.
 /// Therefore, walkers can't guarantee that the tree won't change as you walk through them.
 ///
@@ -167,13 +1146,42 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
     /// is clean.
     fn value(&self) -> Option<&D::Value>;
 
-    /// return `Err(())` if it is in an empty spot.
-    fn go_left(&mut self) -> Result<(), ()>;
-    /// returns `Err(())` if it is in an empty spot.
-    fn go_right(&mut self) -> Result<(), ()>;
+    /// Returns the value of the current node's left son, without moving the walker.
+    /// Returns [`None`] if the walker is at an empty position, or if the left son is empty.
+    ///
+    /// Together with [`SomeEntry::left_subtree_summary`] and [`SomeEntry::right_subtree_summary`],
+    /// this lets you decide where to go before going there, without going down and back up,
+    /// which could restructure the tree. Takes `&mut self`, since the son's pending actions
+    /// have to be pushed down to its own sons first. This doesn't change the tree's structure.
+    ///```
+    /// use grove::{SomeTree, SomeWalker, SomeEntry, splay::SplayTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: SplayTree<StdNum> = (0..3).collect();
+    /// let mut walker = tree.search(1);
+    /// // splay trees splay the searched node to the root
+    /// assert_eq!(walker.depth(), 0);
+    /// assert_eq!(walker.peek_left(), Some(&0));
+    /// assert_eq!(walker.peek_right(), Some(&2));
+    /// assert_eq!(walker.left_subtree_summary().unwrap().sum, 0);
+    /// assert_eq!(walker.depth(), 0);
+    /// # drop(walker);
+    /// # tree.assert_correctness();
+    ///```
+    fn peek_left(&mut self) -> Option<&D::Value>;
+
+    /// Returns the value of the current node's right son, without moving the walker.
+    /// Returns [`None`] if the walker is at an empty position, or if the right son is empty.
+    /// See [`SomeWalker::peek_left`].
+    fn peek_right(&mut self) -> Option<&D::Value>;
+
+    /// Returns `Err(NavError::EmptyPosition)` if it is in an empty spot.
+    fn go_left(&mut self) -> Result<(), NavError>;
+    /// Returns `Err(NavError::EmptyPosition)` if it is in an empty spot.
+    fn go_right(&mut self) -> Result<(), NavError>;
     /// If successful, returns whether or not the previous current value was the left son.
-    /// If already at the root of the tree, returns `Err(())`.
-    fn go_up(&mut self) -> Result<Side, ()>;
+    /// If already at the root of the tree, returns `Err(NavError::AtRoot)`.
+    fn go_up(&mut self) -> Result<Side, NavError>;
     /// Goes to the root.
     /// May restructure the tree while doing so. For example, in splay trees,
     /// this splays the current node.
@@ -181,11 +1189,52 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
         while self.go_up().is_ok() {}
     }
 
-    /// If the walker is at an empty position, return an error.
+    /// Goes up until reaching the given depth, i.e., to the ancestor at that depth.
+    /// If the walker is already shallower than `depth`, returns `Err(NavError::TooShallow)`
+    /// and doesn't move.
+    ///
+    /// Every level is still passed through separately, so this takes `O(n)` time
+    /// for going up `n` levels.
+    ///```
+    /// use grove::{*, avl::AVLTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: AVLTree<StdNum> = (0..100).collect();
+    /// let mut walker = tree.search(37);
+    /// let depth = walker.depth();
+    ///
+    /// assert_eq!(walker.go_up_to_depth(depth + 1), Err(NavError::TooShallow));
+    /// assert_eq!(walker.go_up_to_depth(1), Ok(()));
+    /// assert_eq!(walker.depth(), 1);
+    /// assert_eq!(walker.go_up_n(1), Ok(()));
+    /// assert_eq!(walker.depth(), 0);
+    /// assert_eq!(walker.go_up_n(1), Err(NavError::TooShallow));
+    /// # drop(walker);
+    /// # tree.assert_correctness();
+    ///```
+    fn go_up_to_depth(&mut self, depth: usize) -> Result<(), NavError> {
+        if depth > self.depth() {
+            return Err(NavError::TooShallow);
+        }
+        while self.depth() > depth {
+            self.go_up().expect("Expected to be below the root");
+        }
+        Ok(())
+    }
+
+    /// Goes up `n` levels.
+    /// If the walker's depth is smaller than `n`, returns `Err(NavError::TooShallow)`
+    /// and doesn't move.
+    fn go_up_n(&mut self, n: usize) -> Result<(), NavError> {
+        let depth = self.depth().checked_sub(n).ok_or(NavError::TooShallow)?;
+        self.go_up_to_depth(depth)
+    }
+
     /// Goes to the next empty position.
+    /// If there isn't any, moves to root and returns `Err(NavError::OutOfBounds)`.
     ///
     /// May restructure the tree while doing so.
-    fn next_empty(&mut self) -> Result<(), ()> {
+    fn next_empty(&mut self) -> Result<(), NavError> {
         if self.is_empty() {
             self.next_filled()?; // if already at the last empty node, returns error here.
         }
@@ -197,11 +1246,11 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
         Ok(())
     }
 
-    /// If the walker is at an empty position, return an error.
     /// Goes to the previous empty position.
+    /// If there isn't any, moves to root and returns `Err(NavError::OutOfBounds)`.
     ///
     /// May restructure the tree while doing so.
-    fn previous_empty(&mut self) -> Result<(), ()> {
+    fn previous_empty(&mut self) -> Result<(), NavError> {
         if self.is_empty() {
             self.previous_filled()?; // if already at the first empty node, returns error here.
         }
@@ -214,10 +1263,10 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
     }
 
     /// Finds the next filled node.
-    /// If there isn't any, moves to root and return Err(()).
+    /// If there isn't any, moves to root and returns `Err(NavError::OutOfBounds)`.
     ///
     /// May restructure the tree while doing so.
-    fn next_filled(&mut self) -> Result<(), ()> {
+    fn next_filled(&mut self) -> Result<(), NavError> {
         if !self.is_empty() {
             self.next_empty().unwrap();
         }
@@ -225,17 +1274,17 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
             match self.go_up() {
                 Ok(Side::Left) => break,
                 Ok(Side::Right) => (),
-                Err(_) => return Err(()), // there was no next node
+                Err(_) => return Err(NavError::OutOfBounds), // there was no next node
             }
         }
         Ok(())
     }
 
     /// Finds the previous filled node.
-    /// If there isn't any, moves to root and return Err(()).
+    /// If there isn't any, moves to root and returns `Err(NavError::OutOfBounds)`.
     ///
     /// May restructure the tree while doing so.
-    fn previous_filled(&mut self) -> Result<(), ()> {
+    fn previous_filled(&mut self) -> Result<(), NavError> {
         if !self.is_empty() {
             self.previous_empty().unwrap();
         }
@@ -243,12 +1292,83 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
             match self.go_up() {
                 Ok(Side::Right) => break,
                 Ok(Side::Left) => (),
-                Err(_) => return Err(()), // there was no next node
+                Err(_) => return Err(NavError::OutOfBounds), // there was no next node
             }
         }
         Ok(())
     }
 
+    /// Steps to the next value in the tree, and returns it.
+    /// If the walker is at an empty position, steps to the first value after it.
+    ///
+    /// If there is no next value, returns [`None`], and leaves the walker
+    /// at the empty position after the last value, so that [`SomeWalker::step_prev`]
+    /// steps back to the last value.
+    ///
+    /// Uses [`SomeWalker::next_filled`], so it restructures the tree in the same way.
+    /// Stepping through a whole tree takes `O(n)` time in trees that don't restructure
+    /// while walking, i.e., amortized `O(1)` time per step. Splay trees splay on every step.
+    ///```
+    /// use grove::{SomeTree, SomeWalker, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (0..5).collect();
+    /// let mut walker = tree.search(1..1);
+    /// assert_eq!(walker.step_next(), Some(&1));
+    /// assert_eq!(walker.step_next(), Some(&2));
+    /// assert_eq!(walker.step_prev(), Some(&1));
+    /// let mut rest = vec![];
+    /// while let Some(&value) = walker.step_next() {
+    ///     rest.push(value);
+    /// }
+    /// assert_eq!(rest, vec![2, 3, 4]);
+    /// assert_eq!(walker.step_next(), None);
+    /// assert_eq!(walker.step_prev(), Some(&4));
+    /// # drop(walker);
+    /// # tree.assert_correctness();
+    ///```
+    fn step_next(&mut self) -> Option<&D::Value> {
+        if self.next_filled().is_err() {
+            // the walker is at the root, so go to the last empty position
+            while self.go_right().is_ok() {}
+            return None;
+        }
+        self.value()
+    }
+
+    /// Steps to the previous value in the tree, and returns it.
+    /// If the walker is at an empty position, steps to the last value before it.
+    ///
+    /// If there is no previous value, returns [`None`], and leaves the walker
+    /// at the empty position before the first value, so that [`SomeWalker::step_next`]
+    /// steps back to the first value.
+    ///
+    /// Uses [`SomeWalker::previous_filled`], and has the same complexity as
+    /// [`SomeWalker::step_next`].
+    fn step_prev(&mut self) -> Option<&D::Value> {
+        if self.previous_filled().is_err() {
+            // the walker is at the root, so go to the first empty position
+            while self.go_left().is_ok() {}
+            return None;
+        }
+        self.value()
+    }
+
+    /// Saves the walker's position as a [`Bookmark`](locators::Bookmark), which can be used
+    /// as a locator to return to the same position later, even after the tree was modified.
+    /// See [`Bookmark`](locators::Bookmark) for when bookmarks stay valid.
+    fn bookmark(&self) -> locators::Bookmark
+    where
+        D::Summary: SizedSummary,
+    {
+        let index = self.left_summary().size();
+        if self.is_empty() {
+            locators::Bookmark::at_empty(index)
+        } else {
+            locators::Bookmark::at_value(index)
+        }
+    }
+
     /// Finds any node that the locator `Accept`s. Looks only inside the current subtree.
     /// If there isn't any, it finds the empty location where that node would be instead.
     /// Returns a walker at the wanted position.
@@ -263,6 +1383,106 @@ pub trait SomeWalker<D: Data>: SomeEntry<D> {
         }
     }
 
+    /// Goes to the value at the given index, starting from the current position:
+    /// goes up until the current subtree contains the index, and then searches
+    /// for it inside the subtree. If the index is out of bounds, ends at an empty position
+    /// and returns `Err(NavError::OutOfBounds)`.
+    ///
+    /// When scanning a tree in a monotone order, this takes time logarithmic in the distance
+    /// between the consecutive indices, rather than in the size of the tree (amortized,
+    /// for splay trees).
+    ///```
+    /// use grove::{SomeTree, SomeWalker, NavError, avl::AVLTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: AVLTree<StdNum> = (0..100).map(|x| x * 2).collect();
+    /// let mut walker = tree.search(10);
+    /// for index in 11..20 {
+    ///     walker.go_to_index(index).unwrap();
+    ///     assert_eq!(walker.value(), Some(&(index as i32 * 2)));
+    /// }
+    /// assert_eq!(walker.go_to_index(100), Err(NavError::OutOfBounds));
+    /// # drop(walker);
+    /// # tree.assert_correctness();
+    ///```
+    fn go_to_index(&mut self, index: usize) -> Result<(), NavError>
+    where
+        D::Summary: SizedSummary,
+    {
+        loop {
+            let start = self.far_left_summary().size();
+            if start <= index && index < start + self.subtree_summary().size() {
+                break;
+            }
+            if self.go_up().is_err() {
+                break;
+            }
+        }
+        self.search_subtree(index);
+        if self.is_empty() {
+            Err(NavError::OutOfBounds)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Goes to a value with the given key, starting from the current position,
+    /// assuming the tree is sorted by keys: goes up until reaching an ancestor that bounds
+    /// the key from the other side, and then searches for the key inside its subtree.
+    /// If there is no such value, ends at the empty position where it would be inserted,
+    /// and returns `Err(NavError::KeyNotFound)`.
+    ///
+    /// When scanning a tree in a monotone order, this takes time logarithmic in the distance
+    /// between the consecutive keys, rather than in the size of the tree (amortized,
+    /// for splay trees).
+    ///```
+    /// use grove::{SomeTree, SomeWalker, NavError, treap::Treap};
+    /// use grove::example_data::SizeData;
+    ///
+    /// let mut tree: Treap<SizeData<i32>> = (0..100).map(|x| x * 2).collect();
+    /// let mut walker = tree.search(..);
+    /// for key in (0..200).step_by(10) {
+    ///     walker.go_to_key(&key).unwrap();
+    ///     assert_eq!(walker.value(), Some(&key));
+    /// }
+    /// assert_eq!(walker.go_to_key(&33), Err(NavError::KeyNotFound));
+    /// assert_eq!(walker.left_summary().size, 17);
+    /// # drop(walker);
+    /// # tree.assert_correctness();
+    ///```
+    fn go_to_key<Key: Ord>(&mut self, key: &Key) -> Result<(), NavError>
+    where
+        D::Value: Keyed<Key>,
+    {
+        // at an empty position, start from its father
+        if self.is_empty() {
+            let _ = self.go_up();
+        }
+        if let Some(value) = self.value() {
+            let direction = value.get_key().cmp(key);
+            if direction != std::cmp::Ordering::Equal {
+                // the ancestors that bound the current subtree on the key's side
+                // are the ones we reach from this side
+                let from_side = match direction {
+                    std::cmp::Ordering::Less => Side::Left,
+                    _ => Side::Right,
+                };
+                while let Ok(side) = self.go_up() {
+                    let ancestor = self.value().expect("Expected nonempty position");
+                    if side == from_side && ancestor.get_key().cmp(key) != direction {
+                        break;
+                    }
+                }
+            }
+        }
+        self.search_subtree(locators::ByKey((key,)));
+        if self.is_empty() {
+            Err(NavError::KeyNotFound)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Returns a summary of all the values to the left of this point,
     /// That are not children of this point.
     fn far_left_summary(&self) -> D::Summary;
@@ -346,7 +1566,13 @@ pub trait SomeEntry<D: Data> {
     fn assert_correctness_locally(&self)
     where
         D::Summary: Eq;
+}
 
+/// Entries that are stored as [`basic_tree::BasicTree`]s, i.e., the trees in this crate
+/// that are made of [`basic_tree::BasicNode`]s, and their walkers.
+/// This is separate from [`SomeEntry`], so that trees with other node layouts,
+/// e.g., [`arena::ArenaTree`], can implement [`SomeEntry`] and [`SomeTree`] as well.
+pub trait BasicBacked<D: Data>: SomeEntry<D> {
     #[cfg(debug_assertions)]
     /// This has to be specified in order for the `representation` method
     /// to work
@@ -366,24 +1592,12 @@ pub trait SomeEntry<D: Data> {
         F: Fn(&basic_tree::BasicNode<D, Self::EntryTreeData>) -> String;
 }
 
-/// Trait for trees that can be modified, i.e., values can be inserted and deleted.
-///
-/// This trait is a workaround for current rust type inference limitations.
-/// It allows to write generic code for a tree type that has a modifiable walker.
-/// Intuitively it should've been enough to require
-/// `T: SomeTree<D>, for<'a> &'a mut T: SomeTreeRef<D>, for<'a> <&'a mut T as SomeTreeRef<D>>::Walker: ModifiableWalker`.
-/// However, that doesn't work. Instead, use `for<'a> &'a mut T: ModifiableTreeRef<D>`.
-pub trait ModifiableTreeRef<D: Data>: SomeTreeRef<D, Walker = Self::ModifiableWalker> {
-    /// Inner type that ideally shouldn't be used - just use `Self::Walker`.
-    type ModifiableWalker: ModifiableWalker<D>;
-}
-
 /// This is a trait for walkers that allow inserting and deleting values.
 pub trait ModifiableWalker<D: Data>: SomeWalker<D> {
     /// Inserts the value into the tree at the current empty position.
-    /// If the current position is not empty, returns [`None`].
+    /// If the current position is not empty, returns `Err(NavError::OccupiedPosition)`.
     /// May end up at any possible location, depending on the tree type.
-    fn insert(&mut self, value: D::Value) -> Option<()>;
+    fn insert(&mut self, value: D::Value) -> Result<(), NavError>;
 
     /// Removes the current value from the tree, and returns it.
     /// If currently at an empty position, returns [`None`].
@@ -394,10 +1608,11 @@ pub trait ModifiableWalker<D: Data>: SomeWalker<D> {
 /// Trait for trees that can concatenate.
 /// I wanted this to be the same trait family as SplittableWalker, but the current rustc type solver didn't let me.
 /// It's enough to only implement any one of the three methods - they're all implemented in terms of each other.
-pub trait ConcatenableTree<D: Data>: SomeTree<D>
-where
-    for<'a> &'a mut Self: SomeTreeRef<D>,
-{
+///
+/// Concatenation takes `O(log n)` time for most trees in this crate, but an
+/// [`arena::ArenaTree`] moves the values of the smaller tree into the other tree's vector,
+/// so concatenating it takes time linear in the size of the smaller tree.
+pub trait ConcatenableTree<D: Data>: SomeTree<D> {
     /// Concatenates the two inputs into one tree.
     fn concatenate(mut left: Self, right: Self) -> Self {
         left.concatenate_right(right);
@@ -416,16 +1631,320 @@ where
         let right = std::mem::take(self);
         *self = Self::concatenate(other, right);
     }
+
+    /// Moves all of the values of `other` to the end of this tree, leaving `other` empty,
+    /// like [`Vec::append`].
+    /// This takes `O(log n)` time (amortized, for splay trees), and `O(m + log n)` time
+    /// for an [`arena::ArenaTree`], where `m` is the size of the smaller tree.
+    ///```
+    /// use grove::{SomeTree, SomeEntry, ConcatenableTree, splay::SplayTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: SplayTree<StdNum> = (0..10).collect();
+    /// let mut other: SplayTree<StdNum> = (10..20).collect();
+    /// tree.append(&mut other);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+    /// assert!(other.is_empty());
+    /// # tree.assert_correctness();
+    ///```
+    fn append(&mut self, other: &mut Self) {
+        self.concatenate_right(std::mem::take(other));
+    }
+
+    /// Concatenates a sequence of trees, in order, into one tree.
+    ///
+    /// The trees are concatenated in rounds, where every round concatenates adjacent pairs,
+    /// as in a balanced binary tree, instead of concatenating them one by one from the left.
+    /// This way, every value takes part in only `O(log k)` concatenations,
+    /// where `k` is the number of trees, even when there are thousands of small trees.
+    /// For an [`arena::ArenaTree`], whose concatenations take linear time,
+    /// this takes `O(n log k)` time in total.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let pieces = (0..1000).map(|i| (i * 3..i * 3 + 3).collect::<Treap<StdNum>>());
+    /// let mut tree = Treap::concat_all(pieces);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (0..3000).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn concat_all<I: IntoIterator<Item = Self>>(trees: I) -> Self {
+        let mut trees: Vec<Self> = trees.into_iter().collect();
+        while trees.len() > 1 {
+            let mut merged = Vec::with_capacity(trees.len().div_ceil(2));
+            let mut trees_iter = trees.into_iter();
+            while let Some(left) = trees_iter.next() {
+                merged.push(match trees_iter.next() {
+                    Some(right) => Self::concatenate(left, right),
+                    None => left,
+                });
+            }
+            trees = merged;
+        }
+        trees.pop().unwrap_or_default()
+    }
+
+    /// Removes the segment from the tree, and iterates over its values.
+    /// The rest of the tree stays balanced.
+    ///
+    /// The values are removed immediately, even if the iterator isn't used.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, avl::AVLTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: AVLTree<StdNum> = (20..80).collect();
+    /// let drained: Vec<_> = tree.drain(3..13).collect();
+    ///
+    /// assert_eq!(drained, (23..33).collect::<Vec<_>>());
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (20..23).chain(33..80).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn drain<L: locators::Locator<D>>(&mut self, locator: L) -> Self::IntoIter
+    where
+        Self: SplittableTree<D>,
+    {
+        split_out_segment(self, locator).into_iter()
+    }
+
+    /// Removes the segment from the tree, and returns its summary.
+    /// The rest of the tree stays balanced.
+    ///
+    /// The segment is removed with two splits and a concatenation, which take `O(log n)` time
+    /// (amortized, for splay trees). The values aren't collected anywhere, but they are
+    /// still dropped, which takes time proportional to their number.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (20..80).collect();
+    /// let summary = tree.delete_segment(3..13);
+    ///
+    /// assert_eq!(summary.size, 10);
+    /// assert_eq!(summary.sum, (23..33).sum());
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (20..23).chain(33..80).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn delete_segment<L: locators::Locator<D>>(&mut self, locator: L) -> D::Summary
+    where
+        Self: SplittableTree<D>,
+    {
+        split_out_segment(self, locator).subtree_summary()
+    }
+
+    /// Inserts a run of values, so that the first one of them ends up at index `index`.
+    /// Panics if `index` is larger than the size of the tree.
+    ///
+    /// The run is built as a separate balanced tree, and is spliced in with a single split and
+    /// two concatenations. This takes `O(k + log n)` time (amortized, for splay trees),
+    /// instead of `O(k log n)` time for `k` separate insertions.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, avl::AVLTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: AVLTree<StdNum> = (0..10).collect();
+    /// tree.insert_run_at(3, 100..105);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (0..3).chain(100..105).chain(3..10).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn insert_run_at<I>(&mut self, index: usize, values: I)
+    where
+        I: IntoIterator<Item = D::Value>,
+        D::Summary: SizedSummary,
+        Self: SplittableTree<D>,
+    {
+        assert!(
+            index <= self.subtree_summary().size(),
+            "index is out of bounds"
+        );
+        let right = self.slice(index..index).split_right().unwrap();
+        self.concatenate_right(values.into_iter().collect());
+        self.concatenate_right(right);
+    }
+
+    /// Inserts a sorted run of values into a tree sorted by keys.
+    /// The run is inserted before any existing values with a key equal to the first value's key.
+    ///
+    /// The whole run is inserted into a single gap between existing values, so
+    /// no existing value may have a key strictly between the run's first and last keys,
+    /// otherwise the tree stops being sorted.
+    /// This is typical when appending batches of increasing keys.
+    ///
+    /// Takes `O(k + log n)` time, like [`ConcatenableTree::insert_run_at`].
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, splay::SplayTree};
+    /// use grove::example_data::PlainData;
+    ///
+    /// let mut tree: SplayTree<PlainData<i32>> = (0..10).chain(20..30).collect();
+    /// tree.insert_sorted_run(10..20);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (0..30).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn insert_sorted_run<Key, I>(&mut self, values: I)
+    where
+        I: IntoIterator<Item = D::Value>,
+        D::Value: Keyed<Key>,
+        Key: Ord,
+        Self: SplittableTree<D>,
+    {
+        let mut values = values.into_iter().peekable();
+        let right = match values.peek() {
+            None => return,
+            Some(first) => self
+                .slice(locators::LeftEdgeOf(locators::ByKey((first.get_key(),))))
+                .split_right()
+                .unwrap(),
+        };
+        self.concatenate_right(values.collect());
+        self.concatenate_right(right);
+    }
+}
+
+/// Splits the segment out of the tree, and returns it as a separate tree.
+/// The rest of the tree is concatenated back together.
+fn split_out_segment<D, T, L>(tree: &mut T, locator: L) -> T
+where
+    D: Data,
+    T: SplittableTree<D>,
+    L: locators::Locator<D>,
+{
+    let right = tree
+        .slice(locators::RightEdgeOf(locator.clone()))
+        .split_right()
+        .unwrap();
+    // the locator should see the summary of the part that was split out, as well
+    let right_summary = right.subtree_summary();
+    let mid_locator = move |left, value: &D::Value, right| {
+        locator.locate(left, value, right + right_summary.clone())
+    };
+    let mid = tree
+        .slice(locators::LeftEdgeOf(mid_locator))
+        .split_right()
+        .unwrap();
+    tree.concatenate_right(right);
+    mid
 }
+
 /// Trait for trees that can be split and concatenated.
 /// Require this kind of tree if you want to use reversal actions on segments of your tree.
-pub trait SplittableTreeRef<D: Data>:
-    SomeTreeRef<D, Walker = Self::SplittableWalker> + Sized
-{
-    /// Inner type that ideally shouldn't be used - just use the original tree type.
-    type T;
-    /// Inner type that ideally shouldn't be used - just use `Self::Walker`.
-    type SplittableWalker: SplittableWalker<D, T = Self::T>;
+///
+/// Ideally, this would have been a bound on [`SomeTree::Walker`] for every lifetime.
+/// However, such a bound currently forces the tree type to be `'static`.
+/// Instead, the walker that can split the tree is created by [`SplittableTree::splittable_walker`].
+///
+/// Splitting takes `O(log n)` time for most trees in this crate, but an [`arena::ArenaTree`]
+/// moves the values of the smaller part into a new vector, so splitting it takes time linear
+/// in the size of the smaller part.
+pub trait SplittableTree<D: Data>: ConcatenableTree<D> {
+    /// The walker type that can split the tree.
+    /// For the trees in this crate, this is the same type as [`SomeTree::Walker`].
+    type SplittableWalker<'a>: SplittableWalker<D, T = Self>
+    where
+        Self: 'a,
+        D: 'a;
+
+    /// Creates a walker that can split the tree.
+    fn splittable_walker(&mut self) -> Self::SplittableWalker<'_>;
+
+    /// Like [`SomeTree::search`], but returns a walker that can split the tree.
+    fn splittable_search<L>(&mut self, locator: L) -> Self::SplittableWalker<'_>
+    where
+        L: locators::Locator<D>,
+    {
+        let mut walker = self.splittable_walker();
+        walker.search_subtree(locator);
+        walker
+    }
+
+    /// Splits the tree in two at the given index, and returns the values after it,
+    /// like [`Vec::split_off`]. Panics if `index` is larger than the size of the tree.
+    /// This takes `O(log n)` time (amortized, for splay trees), and `O(m + log n)` time
+    /// for an [`arena::ArenaTree`], where `m` is the size of the smaller part.
+    ///```
+    /// use grove::{SomeTree, SplittableTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (0..10).collect();
+    /// let mut tail = tree.split_off(7);
+    /// assert_eq!(tail.iter().cloned().collect::<Vec<_>>(), vec![7, 8, 9]);
+    ///
+    /// tree.truncate(3);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// tree.truncate(5);
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// # tree.assert_correctness();
+    /// # tail.assert_correctness();
+    ///```
+    fn split_off(&mut self, index: usize) -> Self
+    where
+        D::Summary: SizedSummary,
+    {
+        let len = self.subtree_summary().size();
+        assert!(
+            index <= len,
+            "`at` split index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        self.slice(index..index)
+            .split_right()
+            .expect("Expected empty position")
+    }
+
+    /// Keeps the first `len` values, and drops the rest, like [`Vec::truncate`].
+    /// If `len` is at least the size of the tree, this has no effect.
+    /// Splitting the tree takes `O(log n)` time (amortized, for splay trees),
+    /// but the dropped values still take time proportional to their number.
+    fn truncate(&mut self, len: usize)
+    where
+        D::Summary: SizedSummary,
+    {
+        if len < self.subtree_summary().size() {
+            self.split_off(len);
+        }
+    }
+
+    /// Inserts all of the values of `other` as a contiguous block, at the empty position
+    /// that the locator selects, e.g., `index..index`.
+    /// This takes `O(log n)` time (amortized, for splay trees), by splitting the tree
+    /// at the position and concatenating the pieces.
+    /// For an [`arena::ArenaTree`], whose splitting and concatenation take linear time,
+    /// this takes `O(n + m)` time, where `m` is the size of `other`.
+    ///
+    /// If the locator doesn't select an empty position, returns `Err(other)`,
+    /// and the tree is left unchanged.
+    ///```
+    /// use grove::{SomeTree, SplittableTree, avl::AVLTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: AVLTree<StdNum> = (0..10).collect();
+    /// let block: AVLTree<StdNum> = (100..103).collect();
+    /// tree.insert_tree_at(4..4, block).unwrap();
+    /// assert_eq!(
+    ///     tree.iter().cloned().collect::<Vec<_>>(),
+    ///     vec![0, 1, 2, 3, 100, 101, 102, 4, 5, 6, 7, 8, 9],
+    /// );
+    ///
+    /// // the value at index 4 isn't an empty position
+    /// let block: AVLTree<StdNum> = (200..203).collect();
+    /// assert!(tree.insert_tree_at(4, block).is_err());
+    /// # tree.assert_correctness();
+    ///```
+    fn insert_tree_at<L>(&mut self, locator: L, other: Self) -> Result<(), Self>
+    where
+        L: locators::Locator<D>,
+    {
+        let right = match self.splittable_search(locator).split_right() {
+            Some(right) => right,
+            None => return Err(other),
+        };
+        self.concatenate_right(other);
+        self.concatenate_right(right);
+        Ok(())
+    }
 }
 
 /// Walkers that can split a tree into two.
@@ -440,4 +1959,42 @@ pub trait SplittableWalker<D: Data>: ModifiableWalker<D> {
     /// Split out everything to the left of the current position, if it is an empty position.
     /// Otherwise returns [`None`].
     fn split_left(&mut self) -> Option<Self::T>;
+
+    /// Takes the current subtree out of the tree, as a tree of its own,
+    /// and leaves the current position empty. If the current position is empty,
+    /// returns an empty tree.
+    ///
+    /// Both the taken subtree and the rest of the tree satisfy the tree's invariants.
+    /// Trees that need to rebalance after losing a whole subtree do so,
+    /// in which case the walker ends up at the root. See the implementations.
+    ///```
+    /// use grove::{SomeTree, SomeWalker, SomeEntry, SplittableWalker, avl::AVLTree};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: AVLTree<StdNum> = (0..100).collect();
+    /// let mut walker = tree.walker();
+    /// walker.go_left().unwrap();
+    /// let mut subtree = walker.take_subtree();
+    /// drop(walker);
+    ///
+    /// // detach, process, reattach
+    /// subtree.act_segment(RevAffineAction { to_reverse: false, mul: 1, add: 1000 }, ..);
+    /// let len = subtree.subtree_summary().size;
+    /// let mut walker = tree.search(0..0);
+    /// walker.put_subtree(subtree).unwrap();
+    /// drop(walker);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (1000..1000 + len as i32).chain(len as i32..100).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn take_subtree(&mut self) -> Self::T;
+
+    /// If the current position is empty, puts the values of the given tree there, in order.
+    /// Otherwise, returns the tree back in an `Err`.
+    ///
+    /// The tree's invariants are restored after putting the subtree, so it doesn't matter
+    /// how the given tree is balanced relative to the rest of the tree.
+    /// Trees that need to rebalance do so by splitting and concatenating,
+    /// in which case the walker ends up at the root. See the implementations.
+    fn put_subtree(&mut self, subtree: Self::T) -> Result<(), Self::T>;
 }
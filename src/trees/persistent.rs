@@ -0,0 +1,577 @@
+//! Implementation of a persistent tree, i.e., a tree whose old versions remain available
+//! after it is modified.
+//!
+//! Nodes are reference counted, and are never modified once they are shared. Instead,
+//! every operation copies the nodes on the paths it modifies, and shares all of the other nodes
+//! with the previous version. Therefore, cloning a [`PersistentTree`] takes `O(1)` time,
+//! and the clone is an independent snapshot of the tree.
+//!
+//! Actions are applied lazily, just like in the other trees: a node's action is pushed down
+//! to copies of its sons whenever the node is copied.
+//!
+//! The tree is balanced as a treap, using split and merge, so its operations take `O(log n)`
+//! expected time, and copy `O(log n)` nodes in expectation. Queries also copy nodes, since
+//! they are implemented by splitting a copy of the tree.
+//!
+//! Since [`PersistentTree`] doesn't use [`BasicTree`](super::basic_tree::BasicTree), it doesn't
+//! have walkers, and doesn't implement [`SomeTree`]. Instead, it provides its own versions
+//! of the basic operations, with locators.
+//!```
+//! use grove::{*, persistent::PersistentTree};
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut tree: PersistentTree<StdNum> = (0..10).collect();
+//! let snapshot = tree.clone();
+//!
+//! tree.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 10 }, 2..5);
+//! tree.delete(0..=0);
+//! assert_eq!(tree.iter().collect::<Vec<_>>(), vec![1, 14, 13, 12, 5, 6, 7, 8, 9]);
+//! assert_eq!(tree.segment_summary(1..3).sum, 27);
+//!
+//! // the snapshot is unchanged
+//! assert_eq!(snapshot.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+//! assert_eq!(snapshot.segment_summary(1..3).sum, 3);
+//! # tree.assert_correctness();
+//! # snapshot.assert_correctness();
+//!```
+
+use super::*;
+use crate::locators::{LocResult, Locator};
+use std::sync::Arc;
+
+// convention: a bigger number should go higher up the tree.
+type Priority = u64;
+
+type Link<D> = Option<Arc<Node<D>>>;
+
+struct Node<D: Data> {
+    // The action to be applied to the whole subtree, including this node's value.
+    // `subtree_summary` and `value` don't include it.
+    action: D::Action,
+    subtree_summary: D::Summary,
+    value: D::Value,
+    priority: Priority,
+    left: Link<D>,
+    right: Link<D>,
+}
+
+impl<D: Data> Clone for Node<D>
+where
+    D::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        Node {
+            action: self.action.clone(),
+            subtree_summary: self.subtree_summary.clone(),
+            value: self.value.clone(),
+            priority: self.priority,
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+impl<D: Data> Node<D>
+where
+    D::Value: Clone,
+{
+    fn new(value: D::Value, priority: Priority) -> Self {
+        Node {
+            action: Default::default(),
+            subtree_summary: value.to_summary(),
+            value,
+            priority,
+            left: None,
+            right: None,
+        }
+    }
+
+    // Takes the node out of the `Arc`, copying it if it is shared,
+    // and pushes its action down to its sons.
+    fn take(node: Arc<Node<D>>) -> Self {
+        let mut node = Arc::try_unwrap(node).unwrap_or_else(|node| {
+            #[cfg(feature = "instrument")]
+            crate::instrument::record(|stats| stats.nodes_created += 1);
+            (*node).clone()
+        });
+        let action = std::mem::take(&mut node.action);
+        let (value_action, right_action) = node.inner_actions(&action);
+        if D::Action::REVERSIBLE && action.to_reverse() {
+            std::mem::swap(&mut node.left, &mut node.right);
+        }
+        node.left = act(node.left.take(), action.clone());
+        node.right = act(node.right.take(), right_action);
+        action.act_inplace(&mut node.subtree_summary);
+        value_action.act_inplace(&mut node.value);
+        if !action.is_identity() {
+            D::on_access(&node.value, &node.subtree_summary);
+        }
+        node
+    }
+
+    // Returns the actions that applying `action` to the subtree applies to the node's value
+    // and to the son that comes after it. See [`BasicNode::inner_actions`].
+    fn inner_actions(&self, action: &D::Action) -> (D::Action, D::Action) {
+        if !action.is_indexed() {
+            return (action.clone(), action.clone());
+        }
+        let value_summary = self.value.to_summary();
+        let (first, skipped) = if D::Action::REVERSIBLE && action.to_reverse() {
+            let first = summary(&self.right);
+            (first.clone(), value_summary + first)
+        } else {
+            let first = summary(&self.left);
+            (first.clone(), first + value_summary)
+        };
+        (action.skip(&first), action.skip(&skipped))
+    }
+
+    // Recomputes the summary of the subtree.
+    // Assumes the node's action is the identity.
+    fn rebuild(mut self) -> Link<D> {
+        self.subtree_summary = summary(&self.left) + self.value.to_summary() + summary(&self.right);
+        D::on_rebuild(&self.value, &self.subtree_summary);
+        Some(Arc::new(self))
+    }
+}
+
+fn summary<D: Data>(link: &Link<D>) -> D::Summary {
+    match link {
+        None => Default::default(),
+        Some(node) => node.action.act(node.subtree_summary.clone()),
+    }
+}
+
+// Splits the tree into the part before the segment, the segment, and the part after it.
+fn split3<D: Data, L: Locator<D>>(root: &Link<D>, locator: &L) -> (Link<D>, Link<D>, Link<D>)
+where
+    D::Value: Clone,
+{
+    let zero = Default::default;
+    let (left, rest) = split(root.clone(), locator, Side::Left, zero(), zero());
+    let left_summary = summary(&left);
+    let (middle, right) = split(rest, locator, Side::Right, left_summary, zero());
+    (left, middle, right)
+}
+
+// Applies the action to the whole subtree, lazily.
+fn act<D: Data>(link: Link<D>, action: D::Action) -> Link<D>
+where
+    D::Value: Clone,
+{
+    assert!(
+        D::Action::REVERSIBLE || !action.to_reverse(),
+        "Actions that aren't `Action::REVERSIBLE` must not reverse"
+    );
+    if action.is_identity() {
+        return link;
+    }
+    link.map(|node| {
+        let mut node = Arc::try_unwrap(node).unwrap_or_else(|node| {
+            #[cfg(feature = "instrument")]
+            crate::instrument::record(|stats| stats.nodes_created += 1);
+            (*node).clone()
+        });
+        node.action = action + std::mem::take(&mut node.action);
+        Arc::new(node)
+    })
+}
+
+// Splits the tree at the given edge of the locator's segment.
+// `left` and `right` are the summaries of the values to the left and right of the subtree.
+fn split<D: Data, L: Locator<D>>(
+    link: Link<D>,
+    locator: &L,
+    edge: Side,
+    left: D::Summary,
+    right: D::Summary,
+) -> (Link<D>, Link<D>)
+where
+    D::Value: Clone,
+{
+    let mut node = match link {
+        None => return (None, None),
+        Some(node) => Node::take(node),
+    };
+    let node_summary = node.value.to_summary();
+    let res = locator.locate(
+        left.clone() + summary(&node.left),
+        &node.value,
+        node_summary.clone() + summary(&node.right) + right.clone(),
+    );
+    let goes_left = matches!(
+        (res, edge),
+        (LocResult::GoRight, _) | (LocResult::Accept, Side::Right)
+    );
+    if goes_left {
+        let new_left = left + summary(&node.left) + node_summary;
+        let (middle, rest) = split(node.right.take(), locator, edge, new_left, right);
+        node.right = middle;
+        (node.rebuild(), rest)
+    } else {
+        let new_right = node_summary + summary(&node.right) + right;
+        let (rest, middle) = split(node.left.take(), locator, edge, left, new_right);
+        node.left = middle;
+        (rest, node.rebuild())
+    }
+}
+
+// Concatenates the two trees.
+fn merge<D: Data>(left: Link<D>, right: Link<D>) -> Link<D>
+where
+    D::Value: Clone,
+{
+    match (left, right) {
+        (None, tree) | (tree, None) => tree,
+        (Some(left), Some(right)) => {
+            if left.priority > right.priority {
+                let mut node = Node::take(left);
+                node.right = merge(node.right.take(), Some(right));
+                node.rebuild()
+            } else {
+                let mut node = Node::take(right);
+                node.left = merge(Some(left), node.left.take());
+                node.rebuild()
+            }
+        }
+    }
+}
+
+// Removes the first value in the tree.
+fn pop_first<D: Data>(node: Arc<Node<D>>) -> (D::Value, Link<D>)
+where
+    D::Value: Clone,
+{
+    let mut node = Node::take(node);
+    match node.left.take() {
+        None => (node.value, node.right),
+        Some(left) => {
+            let (value, rest) = pop_first(left);
+            node.left = rest;
+            (value, node.rebuild())
+        }
+    }
+}
+
+/// A persistent tree. See the [module documentation](self).
+pub struct PersistentTree<D: Data> {
+    root: Link<D>,
+    // the counts of the operations performed through `&mut self` methods
+    #[cfg_attr(not(feature = "instrument"), allow(dead_code))]
+    stats: basic_tree::Counters,
+}
+
+/// Creates a snapshot of the tree in `O(1)` time.
+/// The tree and the snapshot share all of their nodes, until either one of them is modified.
+impl<D: Data> Clone for PersistentTree<D> {
+    fn clone(&self) -> Self {
+        PersistentTree {
+            root: self.root.clone(),
+            stats: Default::default(),
+        }
+    }
+}
+
+/// Compares the values of the trees in order, in `O(n)` time.
+impl<D: Data> PartialEq for PersistentTree<D>
+where
+    D::Value: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<D: Data> Eq for PersistentTree<D> where D::Value: Clone + Eq {}
+
+/// Compares the values of the trees lexicographically, like slices are compared.
+/// Stops at the first pair of values that differ.
+impl<D: Data> PartialOrd for PersistentTree<D>
+where
+    D::Value: Clone + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+/// Compares the values of the trees lexicographically, like slices are compared.
+/// Stops at the first pair of values that differ.
+impl<D: Data> Ord for PersistentTree<D>
+where
+    D::Value: Clone + Ord,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+/// Hashes the values of the tree in order, in `O(n)` time.
+/// Equal trees have equal hashes, regardless of their shapes.
+impl<D: Data> std::hash::Hash for PersistentTree<D>
+where
+    D::Value: Clone + std::hash::Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let mut len = 0;
+        for value in self.iter() {
+            value.hash(state);
+            len += 1;
+        }
+        // so that a sequence of values doesn't have the same hash as a prefix of it
+        state.write_usize(len);
+    }
+}
+
+impl<D: Data> Default for PersistentTree<D> {
+    fn default() -> Self {
+        PersistentTree {
+            root: None,
+            stats: Default::default(),
+        }
+    }
+}
+
+impl<D: Data> PersistentTree<D>
+where
+    D::Value: Clone,
+{
+    /// Creates an empty [`PersistentTree`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns true if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the summary of all of the values in the tree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        summary(&self.root)
+    }
+
+    /// Computes the summary of the segment.
+    pub fn segment_summary<L: Locator<D>>(&self, locator: L) -> D::Summary {
+        let (_, middle, _) = split3(&self.root, &locator);
+        summary(&middle)
+    }
+
+    /// Applies the action to every value in the segment.
+    pub fn act_segment<L: Locator<D>>(&mut self, action: D::Action, locator: L) {
+        #[cfg(feature = "instrument")]
+        let _scope = instrument::Scope::enter(&mut self.stats);
+        let (left, middle, right) = split3(&self.root, &locator);
+        self.root = merge(merge(left, act(middle, action)), right);
+    }
+
+    /// Inserts the value at the left edge of the locator's segment.
+    /// For example, `tree.insert(3..3, value)` inserts the value at index 3.
+    pub fn insert<L: Locator<D>>(&mut self, locator: L, value: D::Value) {
+        #[cfg(feature = "instrument")]
+        let _scope = instrument::Scope::enter(&mut self.stats);
+        let zero = Default::default;
+        let (left, right) = split(self.root.take(), &locator, Side::Left, zero(), zero());
+        let node = Some(Arc::new(Node::new(value, random::priority())));
+        self.root = merge(merge(left, node), right);
+    }
+
+    /// Deletes the first value in the locator's segment, and returns it.
+    /// If the segment is empty, returns [`None`].
+    pub fn delete<L: Locator<D>>(&mut self, locator: L) -> Option<D::Value> {
+        #[cfg(feature = "instrument")]
+        let _scope = instrument::Scope::enter(&mut self.stats);
+        let (left, middle, right) = split3(&self.root, &locator);
+        let (value, middle) = pop_first(middle?);
+        self.root = merge(merge(left, middle), right);
+        Some(value)
+    }
+
+    /// Concatenates the other tree to the right of this tree.
+    pub fn concatenate(&mut self, other: Self) {
+        #[cfg(feature = "instrument")]
+        let _scope = instrument::Scope::enter(&mut self.stats);
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    /// Moves all of the values of `other` to the end of this tree, leaving `other` empty,
+    /// like [`Vec::append`]. This takes `O(log n)` expected time.
+    pub fn append(&mut self, other: &mut Self) {
+        #[cfg(feature = "instrument")]
+        let _scope = instrument::Scope::enter(&mut self.stats);
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    /// Iterates over copies of the values in the tree, in order.
+    pub fn iter(&self) -> Iter<'_, D> {
+        let mut stack = vec![];
+        if let Some(node) = &self.root {
+            stack.push((&**node, Default::default(), false));
+        }
+        Iter { stack }
+    }
+
+    /// Returns the segment as a separate tree, in `O(log n)` expected time.
+    /// The tree is unchanged, and shares its nodes with the segment.
+    pub fn segment<L: Locator<D>>(&self, locator: L) -> Self {
+        let (_, middle, _) = split3(&self.root, &locator);
+        PersistentTree {
+            root: middle,
+            stats: Default::default(),
+        }
+    }
+
+    /// Returns an edit script that turns this tree's values into the other tree's values.
+    /// Like [`crate::algorithms::diff`], the summaries are used to skip the longest common
+    /// prefix and suffix, so they should identify the values of their segments.
+    ///```
+    /// use grove::persistent::PersistentTree;
+    /// use grove::algorithms::EditOp;
+    /// use grove::example_data::{HashSummary, Unit};
+    ///
+    /// type D = (i32, HashSummary<1_000_003, 1_000_000_007>, Unit);
+    /// let tree: PersistentTree<D> = (0..1000).collect();
+    /// let mut edited = tree.clone();
+    /// edited.delete(500..=500);
+    /// assert_eq!(tree.diff(&edited), vec![EditOp::Retain(500), EditOp::Delete(1), EditOp::Retain(499)]);
+    ///```
+    pub fn diff(&self, other: &Self) -> Vec<crate::algorithms::EditOp<D::Value>>
+    where
+        D::Value: PartialEq,
+        D::Summary: SizedSummary + PartialEq,
+    {
+        use crate::algorithms::{common_ends, edit_script};
+        let (n, m) = (
+            self.subtree_summary().size(),
+            other.subtree_summary().size(),
+        );
+        let (prefix, suffix) = common_ends(n, m, |a_range, b_range| {
+            self.segment_summary(a_range) == other.segment_summary(b_range)
+        });
+        let a_middle: Vec<D::Value> = self.segment(prefix..n - suffix).iter().collect();
+        let b_middle: Vec<D::Value> = other.segment(prefix..m - suffix).iter().collect();
+        edit_script(prefix, &a_middle, &b_middle, suffix)
+    }
+
+    /// Checks that every node's summary is the sum of the summaries of its children,
+    /// and that the priorities are ordered.
+    /// If it finds any violation, it panics.
+    pub fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        let mut stack: Vec<&Node<D>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            let expected = summary(&node.left) + node.value.to_summary() + summary(&node.right);
+            assert!(
+                node.subtree_summary == expected,
+                "Incorrect summaries found."
+            );
+            for son in node.left.iter().chain(node.right.iter()) {
+                assert!(node.priority >= son.priority, "Incorrect priorities found.");
+                stack.push(son);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "instrument")]
+impl<D: Data> instrument::Instrumented for PersistentTree<D> {
+    fn stats(&self) -> instrument::Stats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = Default::default();
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for PersistentTree<D>
+where
+    D::Value: Clone,
+{
+    /// This takes `O(n)` time.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        // Every node in the stack is the right son of the node before it, which isn't
+        // attached yet. Their right sons aren't final yet, so they are left empty.
+        let mut stack: Vec<Node<D>> = vec![];
+        for value in iter {
+            let mut node = Node::new(value, random::priority());
+            node.left = pop_lower(&mut stack, Some(node.priority));
+            stack.push(node);
+        }
+        PersistentTree {
+            root: pop_lower(&mut stack, None),
+            stats: Default::default(),
+        }
+    }
+}
+
+impl<D: Data> Extend<D::Value> for PersistentTree<D>
+where
+    D::Value: Clone,
+{
+    /// Builds a tree out of the values, and concatenates it to the right of this tree.
+    /// This takes `O(m + log n)` expected time.
+    fn extend<I: IntoIterator<Item = D::Value>>(&mut self, iter: I) {
+        self.concatenate(iter.into_iter().collect());
+    }
+}
+
+// Pops all the nodes with priority lower than `priority` (or all of them if it is `None`)
+// off the stack, attaching each one as the right son of the one below it,
+// and returns the tree of the last popped node.
+fn pop_lower<D: Data>(stack: &mut Vec<Node<D>>, priority: Option<Priority>) -> Link<D>
+where
+    D::Value: Clone,
+{
+    let mut tree = None;
+    while let Some(top) = stack.last() {
+        if priority.is_some_and(|priority| top.priority >= priority) {
+            break;
+        }
+        let mut node = stack.pop().unwrap();
+        node.right = tree;
+        tree = node.rebuild();
+    }
+    tree
+}
+
+/// An iterator over copies of the values of a [`PersistentTree`], with all pending actions applied.
+/// Created by [`PersistentTree::iter`].
+pub struct Iter<'a, D: Data> {
+    // The nodes whose subtrees are yet to be yielded, in reverse order, along with the action
+    // to be applied to them from above. Nodes marked `true` already have their sons
+    // in the stack, and their action included in the action, so only their value is left.
+    stack: Vec<(&'a Node<D>, D::Action, bool)>,
+}
+
+impl<'a, D: Data> Iterator for Iter<'a, D>
+where
+    D::Value: Clone,
+{
+    type Item = D::Value;
+
+    fn next(&mut self) -> Option<D::Value> {
+        loop {
+            let (node, action, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(action.act(node.value.clone()));
+            }
+            let action = action + node.action.clone();
+            let (value_action, second_action) = node.inner_actions(&action);
+            let (first, second) = if action.to_reverse() {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            if let Some(second) = second {
+                self.stack.push((second, second_action, false));
+            }
+            self.stack.push((node, value_action, true));
+            if let Some(first) = first {
+                self.stack.push((first, action, false));
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! The random priorities of the trees that are balanced as treaps.
+//!
+//! The priorities are generated by a small generator, so that the trees don't depend
+//! on the `rand` crate, which is only needed for sampling segments.
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+thread_local! {
+    // The state of a SplitMix64 generator, seeded by the standard library's random keys.
+    static STATE: Cell<u64> = Cell::new(RandomState::new().hash_one(0u8));
+}
+
+/// Returns a random priority for a new node.
+pub(crate) fn priority() -> u64 {
+    STATE.with(|state| {
+        let seed = state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state.set(seed);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
@@ -18,9 +18,9 @@ use trees::basic_tree::{ImmDownBasicWalker, *};
 /// because it uses go_up().
 ///
 /// Instead, use the specific [`SomeTree::segment_summary`]
-pub fn segment_summary<TR, L, D: Data>(tree: TR, locator: L) -> D::Summary
+pub fn segment_summary<T, L, D: Data>(tree: &mut T, locator: L) -> D::Summary
 where
-    TR: SomeTreeRef<D>,
+    T: SomeTree<D>,
     L: Locator<D>,
 {
     use LocResult::*;
@@ -101,18 +101,27 @@ where
 /// Do not use with splay trees - it might mess up the complexity,
 /// because it uses go_up().
 ///
-/// Don't use with actions that reverse segments. Panics otherwise.
+/// Don't use with actions that reverse segments, or that depend on the index
+/// (see [`Action::is_indexed`]). Panics otherwise.
 ///
 /// Instead, use [`SomeTree::act_segment`]
-pub fn act_segment<TR, L, D: Data>(tree: TR, action: D::Action, locator: L)
+pub fn act_segment<T, L, D: Data>(tree: &mut T, action: D::Action, locator: L)
 where
-    TR: SomeTreeRef<D>,
+    T: SomeTree<D>,
     L: Locator<D>,
 {
+    assert!(
+        D::Action::REVERSIBLE || !action.to_reverse(),
+        "Actions that aren't `Action::REVERSIBLE` must not reverse"
+    );
     assert!(
         !action.to_reverse(),
         "This tree type might not support reversals"
     );
+    assert!(
+        !action.is_indexed(),
+        "This tree type might not support actions that depend on the index"
+    );
     use LocResult::*;
 
     let mut walker = tree.walker();
@@ -123,10 +132,10 @@ where
 
             // at this point, we split into the two sides
             Accept => {
-                walker.act_node(action);
+                walker.act_node(action.clone());
                 let depth = walker.depth();
                 walker.go_left().unwrap();
-                act_on_suffix(&mut walker, action, locator.clone());
+                act_on_suffix(&mut walker, action.clone(), locator.clone());
                 // get back to the original node
                 for _ in 0..walker.depth() - depth {
                     walker.go_up().unwrap();
@@ -150,8 +159,8 @@ where
     while let Some(dir) = query_locator(walker, &locator) {
         match dir {
             Accept => {
-                walker.act_node(action);
-                walker.act_right_subtree(action).unwrap();
+                walker.act_node(action.clone());
+                walker.act_right_subtree(action.clone()).unwrap();
                 walker.go_left().unwrap();
             }
             GoRight => walker.go_right().unwrap(),
@@ -171,8 +180,8 @@ where
     while let Some(dir) = query_locator(walker, &locator) {
         match dir {
             Accept => {
-                walker.act_node(action);
-                walker.act_left_subtree(action).unwrap();
+                walker.act_node(action.clone());
+                walker.act_left_subtree(action.clone()).unwrap();
                 walker.go_right().unwrap();
             }
             GoRight => panic!("inconsistent locator"),
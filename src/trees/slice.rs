@@ -18,10 +18,7 @@ pub struct Slice<'a, D, T, L> {
     locator: L,
 }
 
-impl<'a, D: Data, T: SomeTree<D>, L: Locator<D>> Slice<'a, D, T, L>
-where
-    for<'b> &'b mut T: SomeTreeRef<D>,
-{
+impl<'a, D: Data, T: SomeTree<D>, L: Locator<D>> Slice<'a, D, T, L> {
     /// Creates a new slice that represents the locator's segment in the tree.
     pub fn new(tree: &'a mut T, locator: L) -> Self {
         Slice {
@@ -45,7 +42,7 @@ where
     /// Finds any node in the current subsegment.
     /// If there isn't any, it finds the empty location where that node would be instead.
     /// Returns a walker at the wanted position.
-    pub fn search(self) -> <&'a mut T as SomeTreeRef<D>>::Walker {
+    pub fn search(self) -> T::Walker<'a> {
         self.tree.search(self.locator)
     }
 
@@ -60,19 +57,14 @@ where
     /// On the other hand, mutable iterators can't be written because the values of the nodes must be rebuilt,
     /// but they can only be rebuilt after the iterator exits. (This is because rust iterators can't be streaming iterators).
     /// If you want a mutable iterator, use a walker instead.
-    pub fn iter(self) -> basic_tree::iterators::IterLocator<'a, D, L, T::TreeData> {
+    pub fn iter(self) -> T::IterLocator<'a, L> {
         self.tree.iter_locator(self.locator)
     }
-}
 
-impl<'a, D: Data, T: SomeTree<D>, L: Locator<D>> Slice<'a, D, T, L>
-where
-    for<'b> &'b mut T: ModifiableTreeRef<D>,
-{
     /// Assumes that the this subsegment is empty.
     /// Inserts the value into the tree into the position of this empty subsegment.
-    /// If the current subsegment is not empty, returns [`None`].
-    pub fn insert(&mut self, value: D::Value) -> Option<()> {
+    /// If the current subsegment is not empty, returns `Err(NavError::OccupiedPosition)`.
+    pub fn insert(&mut self, value: D::Value) -> Result<(), NavError> {
         let mut walker = self.tree.search(self.locator.clone());
         walker.insert(value)
     }
@@ -85,29 +77,20 @@ where
     }
 }
 
-impl<'a, D: Data, T: SomeTree<D>, L: Locator<D>> Slice<'a, D, T, L>
-where
-    for<'b> &'b mut T: SplittableTreeRef<D>,
-{
+impl<'a, D: Data, T: SplittableTree<D>, L: Locator<D>> Slice<'a, D, T, L> {
     /// Assumes that the this subsegment is empty.
     /// Split out everything to the right of this subsegment, if it is an empty subsegment.
     /// Otherwise returns [`None`].
-    pub fn split_right(
-        &mut self,
-    ) -> Option<<<&mut T as SplittableTreeRef<D>>::SplittableWalker as SplittableWalker<D>>::T>
-    {
-        let mut walker = self.tree.search(self.locator.clone());
+    pub fn split_right(&mut self) -> Option<T> {
+        let mut walker = self.tree.splittable_search(self.locator.clone());
         walker.split_right()
     }
 
     /// Assumes that the this subsegment is empty.
     /// Split out everything to the left of the this subsegment, if it is an empty subsegment.
     /// Otherwise returns [`None`].
-    pub fn split_left(
-        &mut self,
-    ) -> Option<<<&mut T as SplittableTreeRef<D>>::SplittableWalker as SplittableWalker<D>>::T>
-    {
-        let mut walker = self.tree.search(self.locator.clone());
+    pub fn split_left(&mut self) -> Option<T> {
+        let mut walker = self.tree.splittable_search(self.locator.clone());
         walker.split_left()
     }
 }
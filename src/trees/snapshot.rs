@@ -0,0 +1,246 @@
+//! Read-only snapshots of trees, that can be shared between threads.
+//!
+//! A [`Snapshot`] is a frozen copy of a tree's values, created by [`SomeTree::snapshot`].
+//! It only supports queries, which take `&self`, so it can be shared between threads freely,
+//! while the original tree keeps being modified. Cloning a snapshot only clones an [`Arc`],
+//! so every thread can hold its own handle to the same snapshot.
+//!```
+//! use grove::{*, avl::AVLTree};
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut tree: AVLTree<StdNum> = (0..100).collect();
+//! let snapshot = tree.snapshot();
+//! let reader = {
+//!     let snapshot = snapshot.clone();
+//!     std::thread::spawn(move || snapshot.segment_summary(10..20).sum)
+//! };
+//! tree.act_segment(RevAffineAction { to_reverse: false, mul: 1, add: 1 }, ..);
+//!
+//! assert_eq!(reader.join().unwrap(), (10..20).sum());
+//! assert_eq!(tree.segment_summary(10..20).sum, (11..21).sum());
+//! assert_eq!(snapshot.get(15), Some(&15));
+//! # tree.assert_correctness();
+//!```
+
+use super::basic_tree::*;
+use super::*;
+use crate::example_data::Size;
+use crate::locators::{LocResult, Locator};
+use std::sync::Arc;
+
+/// A read-only, frozen copy of a tree. See the [module documentation](self).
+///
+/// The snapshot holds a balanced tree with no pending actions, so values can be
+/// returned by reference.
+/// It is [`Send`] and [`Sync`] whenever the values, summaries and actions are.
+pub struct Snapshot<D: Data> {
+    tree: Arc<BasicTree<D>>,
+    // the number of values
+    len: usize,
+}
+
+/// Clones the handle to the snapshot, in `O(1)` time.
+impl<D: Data> Clone for Snapshot<D> {
+    fn clone(&self) -> Self {
+        Snapshot {
+            tree: self.tree.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for Snapshot<D> {
+    /// Creates a snapshot holding the values, in `O(n)` time.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut len = 0;
+        let tree = iter.into_iter().inspect(|_| len += 1).collect();
+        Snapshot {
+            tree: Arc::new(tree),
+            len,
+        }
+    }
+}
+
+impl<D: Data> Snapshot<D> {
+    /// Returns true if the snapshot holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the summary of all of the values in the snapshot.
+    pub fn subtree_summary(&self) -> D::Summary {
+        self.tree.subtree_summary()
+    }
+
+    /// Computes the summary of the segment.
+    pub fn segment_summary<L: Locator<D>>(&self, locator: L) -> D::Summary
+    where
+        D::Value: Clone,
+    {
+        segment_algorithms::segment_summary_imm(&self.tree, locator)
+    }
+
+    /// Returns a value in the locator's segment, or [`None`] if the segment is empty.
+    /// For example, `snapshot.get(3)` returns the value at index 3.
+    pub fn get<L: Locator<D>>(&self, locator: L) -> Option<&D::Value> {
+        let mut tree: &BasicTree<D> = &self.tree;
+        let (mut left, mut right) = (D::Summary::default(), D::Summary::default());
+        while let Some(node) = tree.node() {
+            let node_summary = node.node_value.to_summary();
+            let res = locator.locate(
+                left.clone() + node.left.subtree_summary(),
+                &node.node_value,
+                node_summary.clone() + node.right.subtree_summary() + right.clone(),
+            );
+            match res {
+                LocResult::Accept => return Some(&node.node_value),
+                LocResult::GoLeft => {
+                    right = node_summary + node.right.subtree_summary() + right;
+                    tree = &node.left;
+                }
+                LocResult::GoRight => {
+                    left = left + node.left.subtree_summary() + node_summary;
+                    tree = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// Iterates over the values in the snapshot, in order.
+    pub fn iter(&self) -> Iter<'_, D> {
+        Iter::new(&self.tree, self.len)
+    }
+}
+
+/// An iterator over the values of a [`Snapshot`]. Created by [`Snapshot::iter`].
+pub struct Iter<'a, D: Data, T = ()> {
+    // The nodes whose values and right subtrees are yet to be yielded, in reverse order.
+    front: Vec<&'a BasicNode<D, T>>,
+    // The nodes whose values and left subtrees are yet to be yielded from the back, in order.
+    back: Vec<&'a BasicNode<D, T>>,
+    // The number of values yet to be yielded. The stacks may reach values that were
+    // already yielded from the other end, so this is what ends the iteration.
+    len: usize,
+}
+
+impl<'a, D: Data, T> Iter<'a, D, T> {
+    /// Iterates over the values of a tree through a shared reference, given the number of values.
+    /// The values are yielded as they are stored, so the tree mustn't have any pending actions.
+    pub(crate) fn new(tree: &'a BasicTree<D, T>, len: usize) -> Self {
+        let mut iter = Iter {
+            front: vec![],
+            back: vec![],
+            len,
+        };
+        iter.push_left_path(tree);
+        iter.push_right_path(tree);
+        iter
+    }
+
+    fn push_left_path(&mut self, mut tree: &'a BasicTree<D, T>) {
+        while let Some(node) = tree.node() {
+            self.front.push(node);
+            tree = &node.left;
+        }
+    }
+
+    fn push_right_path(&mut self, mut tree: &'a BasicTree<D, T>) {
+        while let Some(node) = tree.node() {
+            self.back.push(node);
+            tree = &node.right;
+        }
+    }
+}
+
+impl<'a, D: Data<Summary = Size>, T> Iter<'a, D, T> {
+    /// Iterates over the values in the locator's segment of a tree through a shared reference.
+    /// The tree mustn't have any pending actions, as in [`Iter::new`].
+    pub(crate) fn segment<L: Locator<D>>(tree: &'a BasicTree<D, T>, locator: L) -> Self {
+        let mut iter = Iter {
+            front: vec![],
+            back: vec![],
+            len: 0,
+        };
+        let before = Self::push_segment_path(&mut iter.front, tree, &locator, false);
+        let after = Self::push_segment_path(&mut iter.back, tree, &locator, true);
+        iter.len = size(tree).saturating_sub(before + after);
+        iter
+    }
+
+    /// Pushes the path to the first value of the segment, or to the last value if `back` is true.
+    /// Returns the number of values before the segment, or after it if `back` is true.
+    fn push_segment_path<L: Locator<D>>(
+        stack: &mut Vec<&'a BasicNode<D, T>>,
+        mut tree: &'a BasicTree<D, T>,
+        locator: &L,
+        back: bool,
+    ) -> usize {
+        let (mut left, mut right) = (0, 0);
+        while let Some(node) = tree.node() {
+            let near_left = left + size(&node.left);
+            let near_right = size(&node.right) + right;
+            let res = locator.locate(
+                Size { size: near_left },
+                &node.node_value,
+                Size { size: near_right },
+            );
+            let go_right = match res {
+                LocResult::GoRight => true,
+                LocResult::GoLeft => false,
+                LocResult::Accept => {
+                    stack.push(node);
+                    back
+                }
+            };
+            if go_right {
+                left = near_left + 1;
+                tree = &node.right;
+            } else {
+                right = near_right + 1;
+                tree = &node.left;
+            }
+        }
+        if back {
+            right
+        } else {
+            left
+        }
+    }
+}
+
+fn size<D: Data<Summary = Size>, T>(tree: &BasicTree<D, T>) -> usize {
+    tree.node().map_or(0, |node| node.subtree_summary().size)
+}
+
+impl<'a, D: Data, T> Iterator for Iter<'a, D, T> {
+    type Item = &'a D::Value;
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    fn next(&mut self) -> Option<&'a D::Value> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let node = self.front.pop()?;
+        self.push_left_path(&node.right);
+        Some(&node.node_value)
+    }
+}
+
+impl<'a, D: Data, T> DoubleEndedIterator for Iter<'a, D, T> {
+    fn next_back(&mut self) -> Option<&'a D::Value> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let node = self.back.pop()?;
+        self.push_right_path(&node.left);
+        Some(&node.node_value)
+    }
+}
+
+impl<'a, D: Data, T> ExactSizeIterator for Iter<'a, D, T> {}
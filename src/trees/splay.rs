@@ -58,18 +58,35 @@ use crate::locators;
 /// the splaytree's complexity properties remain.
 pub struct SplayTree<D: Data> {
     tree: BasicTree<D>,
+    // the counts of the operations performed through the tree's walkers
+    stats: Counters,
 }
 
 impl<D: Data> SplayTree<D> {
     /// Note: using this directly may cause the tree to lose its properties as a splay tree
     pub fn basic_walker(&mut self) -> BasicWalker<D> {
-        BasicWalker::new(&mut self.tree)
+        BasicWalker::new(&mut self.tree).count_into(&mut self.stats)
+    }
+
+    /// Creates a walker that reuses the vectors of the given buffer instead of allocating
+    /// new ones. See [`WalkerBuffer`].
+    pub fn walker_with_buffer<'a>(
+        &'a mut self,
+        buffer: &'a mut WalkerBuffer<D>,
+    ) -> SplayWalker<'a, D> {
+        SplayWalker {
+            walker: self
+                .tree
+                .walker_with_buffer(buffer)
+                .count_into(&mut self.stats),
+        }
     }
 
     /// Creates a new empty [`SplayTree`].
     pub fn new() -> SplayTree<D> {
         SplayTree {
             tree: BasicTree::Empty,
+            stats: Default::default(),
         }
     }
 
@@ -83,6 +100,44 @@ impl<D: Data> SplayTree<D> {
         self.tree.assert_correctness()
     }
 
+    /// Returns the Sleator-Tarjan potential of the tree: the sum of `log2(size)` over the
+    /// subtrees of all nodes. Used for checking the amortized complexity bounds of splay trees.
+    /// See [`PotentialTracker`](crate::instrument::PotentialTracker).
+    ///
+    /// This takes `O(n)` time.
+    #[cfg(feature = "instrument")]
+    pub fn potential(&self) -> f64 {
+        self.size_and_potential().1
+    }
+
+    /// Returns the number of values in the tree and its potential.
+    #[cfg(feature = "instrument")]
+    pub(crate) fn size_and_potential(&self) -> (usize, f64) {
+        // A post-order traversal without recursion, since the tree might be very deep.
+        // `sizes` holds the sizes of the finished subtrees whose father isn't finished yet.
+        let mut potential = 0.;
+        let mut sizes: Vec<usize> = vec![];
+        let mut stack = vec![(&self.tree, false)];
+        while let Some((tree, sons_done)) = stack.pop() {
+            match tree.node() {
+                None => sizes.push(0),
+                Some(_) if sons_done => {
+                    let right = sizes.pop().unwrap();
+                    let left = sizes.pop().unwrap();
+                    let size = left + 1 + right;
+                    potential += (size as f64).log2();
+                    sizes.push(size);
+                }
+                Some(node) => {
+                    stack.push((tree, true));
+                    stack.push((&node.right, false));
+                    stack.push((&node.left, false));
+                }
+            }
+        }
+        (sizes.pop().unwrap(), potential)
+    }
+
     /// Gets the tree into a state in which the locator's segment
     /// is a single subtree, and returns a walker at that subtree.
     pub fn isolate_segment<'a, L>(&'a mut self, locator: L) -> SplayWalker<'a, D>
@@ -135,6 +190,65 @@ impl<D: Data> std::default::Default for SplayTree<D> {
     }
 }
 
+/// Clones the tree structurally, in `O(n)` time. See [`BasicTree`]'s `Clone` implementation.
+impl<D: Data> Clone for SplayTree<D>
+where
+    D::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        SplayTree {
+            tree: self.tree.clone(),
+            stats: Default::default(),
+        }
+    }
+}
+
+/// Compares the values of the trees in order, in `O(n)` time.
+/// See [`BasicTree`]'s `PartialEq` implementation.
+impl<D: Data> PartialEq for SplayTree<D>
+where
+    D::Value: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.tree == other.tree
+    }
+}
+
+impl<D: Data> Eq for SplayTree<D> where D::Value: Clone + Eq {}
+
+/// Compares the values of the trees lexicographically.
+/// See [`BasicTree`]'s `PartialOrd` implementation.
+impl<D: Data> PartialOrd for SplayTree<D>
+where
+    D::Value: Clone + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.tree.partial_cmp(&other.tree)
+    }
+}
+
+/// Compares the values of the trees lexicographically.
+/// See [`BasicTree`]'s `Ord` implementation.
+impl<D: Data> Ord for SplayTree<D>
+where
+    D::Value: Clone + Ord,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.tree.cmp(&other.tree)
+    }
+}
+
+/// Hashes the values of the tree in order, in `O(n)` time.
+/// See [`BasicTree`]'s `Hash` implementation.
+impl<D: Data> std::hash::Hash for SplayTree<D>
+where
+    D::Value: Clone + std::hash::Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tree.hash(state)
+    }
+}
+
 /// Deallocating a large splay tree can cause a stack overflow, since the tree might be unbalanced.
 /// Therefore we have an iterative deallocator.
 impl<D: Data> Drop for SplayTree<D> {
@@ -143,6 +257,19 @@ impl<D: Data> Drop for SplayTree<D> {
     }
 }
 
+/// Prints the structure of the tree, in the same format as [`BasicTree`]'s
+/// `Debug` implementation.
+impl<D: Data> std::fmt::Debug for SplayTree<D>
+where
+    D::Value: std::fmt::Debug,
+    D::Summary: std::fmt::Debug,
+    D::Action: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tree.fmt(f)
+    }
+}
+
 /// A walker for a [`SplayTree`].
 #[derive(destructure)]
 pub struct SplayWalker<'a, D: Data> {
@@ -192,9 +319,11 @@ impl<'a, D: Data> SplayWalker<'a, D> {
         }
 
         let b1 = match self.walker.go_up() {
-            Err(()) => return, // already the root
+            Err(_) => return, // already the root
             Ok(b1) => b1,
         };
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.splay_steps += 1);
 
         let b2 = match self.walker.is_left_son() {
             None => {
@@ -224,7 +353,7 @@ impl<'a, D: Data> SplayWalker<'a, D> {
         // if the walker points to an empty position,
         // we can't splay it, just go upwards once.
         if self.walker.is_empty() {
-            if let Err(()) = self.walker.go_up() {
+            if self.walker.go_up().is_err() {
                 // if already the root, exit. otherwise, go up
                 panic!(); // shouldn't happen, because if we are at the root, the previous condition would have caught it.
             };
@@ -233,8 +362,10 @@ impl<'a, D: Data> SplayWalker<'a, D> {
 
         let b1 = match self.walker.go_up() {
             Ok(b1) => b1,
-            Err(()) => panic!(), // shouldn't happen, the previous condition would have caught this
+            Err(_) => panic!(), // shouldn't happen, the previous condition would have caught this
         };
+        #[cfg(feature = "instrument")]
+        crate::instrument::record(|stats| stats.splay_steps += 1);
 
         if self.depth() <= depth {
             // zig case
@@ -273,6 +404,10 @@ impl<'a, D: Data> SplayWalker<'a, D> {
     /// See the [`splay`] function.
     pub fn splay_to_depth(&mut self, depth: usize) {
         assert!(self.depth() >= depth);
+        #[cfg(feature = "instrument")]
+        if self.depth() > depth {
+            crate::instrument::record(|stats| stats.splays += 1);
+        }
         while self.walker.depth() != depth {
             self.splay_step_depth(depth);
         }
@@ -285,7 +420,41 @@ impl<'a, D: Data> Drop for SplayWalker<'a, D> {
     }
 }
 
+/// Prints the walker in the same format as [`BasicWalker`]'s `Debug` implementation.
+impl<'a, D: Data> std::fmt::Debug for SplayWalker<'a, D>
+where
+    D::Value: std::fmt::Debug,
+    D::Summary: std::fmt::Debug,
+    D::Action: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.walker.fmt(f)
+    }
+}
+
+impl<D: Data> ValidateTree<D> for SplayTree<D> {
+    /// Splay trees have no balancing invariants, so only the summaries are checked.
+    fn validate(&self) -> Result<(), InvariantError>
+    where
+        D::Summary: Eq,
+    {
+        self.tree.validate()
+    }
+}
+
 impl<D: Data> SomeTree<D> for SplayTree<D> {
+    type Walker<'a>
+        = SplayWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn walker(&mut self) -> Self::Walker<'_> {
+        SplayWalker {
+            walker: self.basic_walker(),
+        }
+    }
+
     /// Note: calling this is inefficient
     /// and panicks if debug assertions are on.
     ///
@@ -323,15 +492,38 @@ impl<D: Data> SomeTree<D> for SplayTree<D> {
         walker.act_subtree(action);
     }
 
-    type TreeData = ();
+    type IterLocator<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterLocator<'a, D, L, ()>
+    where
+        Self: 'a,
+        D: 'a;
+    type IterLocatorMut<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterMutGuard<'a, D, L, ()>
+    where
+        Self: 'a,
+        D: 'a;
+    type IntoIterSegment<L: locators::Locator<D>> = basic_tree::iterators::IntoIter<D, L, ()>;
+
     fn iter_locator<'a, L: locators::Locator<D>>(
         &'a mut self,
         locator: L,
-    ) -> basic_tree::iterators::IterLocator<'a, D, L> {
+    ) -> Self::IterLocator<'a, L> {
         self.isolate_segment(locator.clone());
         iterators::IterLocator::new(&mut self.tree, locator)
     }
 
+    fn iter_locator_mut<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> Self::IterLocatorMut<'a, L> {
+        self.isolate_segment(locator.clone());
+        iterators::IterMutGuard::new(&mut self.tree, locator)
+    }
+
+    fn into_iter_segment<L: locators::Locator<D>>(self, locator: L) -> Self::IntoIterSegment<L> {
+        iterators::IntoIter::new(self.into_inner(), locator)
+    }
+
     fn assert_correctness(&self)
     where
         D::Summary: Eq,
@@ -351,27 +543,23 @@ derive_SomeEntry! {tree, (),
     }
 }
 
-impl<'a, D: Data> SomeTreeRef<D> for &'a mut SplayTree<D> {
-    type Walker = SplayWalker<'a, D>;
-    fn walker(self: &'a mut SplayTree<D>) -> SplayWalker<'a, D> {
-        SplayWalker {
-            walker: self.basic_walker(),
-        }
-    }
-}
-
-impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut SplayTree<D> {
-    type ModifiableWalker = Self::Walker;
-}
-
 impl<D: Data> std::iter::FromIterator<D::Value> for SplayTree<D> {
     fn from_iter<T: IntoIterator<Item = D::Value>>(iter: T) -> Self {
         SplayTree {
             tree: iter.into_iter().collect(),
+            stats: Default::default(),
         }
     }
 }
 
+impl<D: Data> Extend<D::Value> for SplayTree<D> {
+    /// Builds a tree out of the values, and concatenates it to the right of this tree.
+    /// This takes `O(m + log n)` time.
+    fn extend<T: IntoIterator<Item = D::Value>>(&mut self, iter: T) {
+        self.concatenate_right(iter.into_iter().collect());
+    }
+}
+
 impl<D: Data> IntoIterator for SplayTree<D> {
     type Item = D::Value;
     type IntoIter = <BasicTree<D> as IntoIterator>::IntoIter;
@@ -380,24 +568,133 @@ impl<D: Data> IntoIterator for SplayTree<D> {
     }
 }
 
+impl<D: Data> From<Vec<D::Value>> for SplayTree<D> {
+    /// Builds a balanced tree out of the values, in `O(n)` time.
+    /// Same as collecting the values.
+    fn from(values: Vec<D::Value>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<D: Data> From<SplayTree<D>> for Vec<D::Value> {
+    /// Returns the tree's values in order, in `O(n)` time.
+    /// Same as collecting the tree's values.
+    fn from(tree: SplayTree<D>) -> Self {
+        tree.into_iter().collect()
+    }
+}
+
+impl<D: Data> From<super::avl::AVLTree<D>> for SplayTree<D> {
+    /// Converts an AVL tree into a [`SplayTree`], in `O(n)` time.
+    /// See [`SomeTree::convert`].
+    fn from(tree: super::avl::AVLTree<D>) -> Self {
+        tree.convert()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: Data> rayon::iter::IntoParallelIterator for SplayTree<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    type Iter = basic_tree::parallel::IntoParIter<D>;
+    type Item = D::Value;
+
+    fn into_par_iter(self) -> Self::Iter {
+        basic_tree::parallel::IntoParIter::new(self.into_inner())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: Data> rayon::iter::FromParallelIterator<D::Value> for SplayTree<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    /// Builds a perfectly balanced [`SplayTree`], building subtrees in parallel.
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = D::Value>,
+    {
+        SplayTree {
+            tree: BasicTree::from_par_iter(par_iter),
+            stats: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<D: Data> serde::Serialize for SplayTree<D>
+where
+    D::Value: serde::Serialize + Clone,
+{
+    /// Serializes the values in order, each paired with its depth.
+    /// See [`basic_tree::serialization`].
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.tree.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, D: Data> serde::Deserialize<'de> for SplayTree<D>
+where
+    D::Value: serde::Deserialize<'de>,
+{
+    /// Reconstructs the exact shape of the serialized tree.
+    fn deserialize<De: serde::Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        Ok(SplayTree {
+            tree: BasicTree::deserialize(deserializer)?,
+            stats: Default::default(),
+        })
+    }
+}
+
+#[cfg(feature = "instrument")]
+impl<D: Data> instrument::Instrumented for SplayTree<D> {
+    fn stats(&self) -> instrument::Stats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = Default::default();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, D: Data> arbitrary::Arbitrary<'a> for SplayTree<D>
+where
+    D::Value: arbitrary::Arbitrary<'a>,
+{
+    /// Generates a tree in an arbitrary shape. See [`fuzzing`].
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(SplayTree {
+            tree: fuzzing::arbitrary_shape(u)?,
+            stats: Default::default(),
+        })
+    }
+}
+
 derive_SomeWalker! {walker,
     impl<'a, D: Data> SomeWalker<D> for SplayWalker<'a, D> {
         /// If successful, returns whether or not the previous current value was the left son.
-        /// If already at the root of the tree, returns `Err(())`.
+        /// If already at the root of the tree, returns `Err(NavError::AtRoot)`.
         /// You shouldn't use this method too much, or you might lose the
         /// SplayTree's complexity properties - see documentation aboud splay tree.
-        fn go_up(&mut self) -> Result<Side, ()> {
+        fn go_up(&mut self) -> Result<Side, NavError> {
             self.walker.go_up()
         }
 
         // overrides the default implementations for these methods:
 
         /// Finds the previous filled node.
-        /// If there isn't any, moves to root and return Err(()).
+        /// If there isn't any, moves to root and returns `Err(NavError::OutOfBounds)`.
         ///
         /// Restructures the tree in order to satisfy the splay tree's complexity properties.
         /// Complexity: amortized `O(log n)` time.
-        fn previous_filled(&mut self) -> Result<(), ()> {
+        fn previous_filled(&mut self) -> Result<(), NavError> {
             match self.walker.node() {
                 None => {}
                 Some(node) => {
@@ -416,7 +713,7 @@ derive_SomeWalker! {walker,
             let count = match self.walker.steps_until_sided_ancestor(Side::Right) {
                 None => {
                     self.splay();
-                    return Err(());
+                    return Err(NavError::OutOfBounds);
                 }
                 Some(count) => count,
             };
@@ -430,11 +727,11 @@ derive_SomeWalker! {walker,
         }
 
         /// Finds the next filled node.
-        /// If there isn't any, moves to root and return Err(()).
+        /// If there isn't any, moves to root and returns `Err(NavError::OutOfBounds)`.
         ///
         /// Restructures the tree in order to satisfy the splay tree's complexity properties.
         /// Complexity: amortized `O(log n)` time.
-        fn next_filled(&mut self) -> Result<(), ()> {
+        fn next_filled(&mut self) -> Result<(), NavError> {
             match self.walker.node() {
                 None => {}
                 Some(node) => {
@@ -453,7 +750,7 @@ derive_SomeWalker! {walker,
             let count = match self.walker.steps_until_sided_ancestor(Side::Left) {
                 None => {
                     self.splay();
-                    return Err(());
+                    return Err(NavError::OutOfBounds);
                 }
                 Some(count) => count,
             };
@@ -481,10 +778,10 @@ derive_SomeEntry! {walker, (),
 
 impl<'a, D: Data> ModifiableWalker<D> for SplayWalker<'a, D> {
     /// Inserts the value into the tree at the current empty position.
-    /// If the current position is not empty, return [`None`].
+    /// If the current position is not empty, returns `Err(NavError::OccupiedPosition)`.
     /// When the function returns, the walker will be at the position the node
     /// was inserted.
-    fn insert(&mut self, value: D::Value) -> Option<()> {
+    fn insert(&mut self, value: D::Value) -> Result<(), NavError> {
         self.walker.insert(value)
     }
 
@@ -538,7 +835,7 @@ impl<D: Data> ConcatenableTree<D> for SplayTree<D> {
         let mut walker = self.walker();
         while walker.go_right().is_ok() {}
         match walker.go_up() {
-            Err(()) => {
+            Err(_) => {
                 // the tree is empty; just substitute the other tree.
                 drop(walker);
                 *self = other;
@@ -555,10 +852,16 @@ impl<D: Data> ConcatenableTree<D> for SplayTree<D> {
     }
 }
 
-impl<'a, D: Data> SplittableTreeRef<D> for &'a mut SplayTree<D> {
-    type T = SplayTree<D>;
+impl<D: Data> SplittableTree<D> for SplayTree<D> {
+    type SplittableWalker<'a>
+        = SplayWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
 
-    type SplittableWalker = SplayWalker<'a, D>;
+    fn splittable_walker(&mut self) -> Self::SplittableWalker<'_> {
+        self.walker()
+    }
 }
 
 impl<'a, D: Data> SplittableWalker<D> for SplayWalker<'a, D> {
@@ -571,7 +874,7 @@ impl<'a, D: Data> SplittableWalker<D> for SplayWalker<'a, D> {
     /// The walker will be at the root after this operation, if it succeeds.
     ///
     ///```
-    /// use grove::{SomeTree, SplittableTreeRef, splay::SplayTree};
+    /// use grove::{SomeTree, splay::SplayTree};
     /// use grove::example_data::StdNum;
     ///
     /// let mut tree: SplayTree<StdNum> = (17..88).collect();
@@ -588,7 +891,7 @@ impl<'a, D: Data> SplittableWalker<D> for SplayWalker<'a, D> {
 
         // to know which side we should cut
         let side = match self.go_up() {
-            Err(()) => return Some(SplayTree::new()), // this is the empty tree
+            Err(_) => return Some(SplayTree::new()), // this is the empty tree
             Ok(b) => b,
         };
         self.splay();
@@ -601,12 +904,18 @@ impl<'a, D: Data> SplittableWalker<D> for SplayWalker<'a, D> {
                 let mut tree = std::mem::replace(&mut node.left, BasicTree::Empty);
                 node.rebuild();
                 std::mem::swap(self.inner_mut(), &mut tree);
-                Some(SplayTree { tree })
+                Some(SplayTree {
+                    tree,
+                    stats: Default::default(),
+                })
             }
             Side::Right => {
                 let tree = std::mem::replace(&mut node.right, BasicTree::Empty);
                 node.rebuild();
-                Some(SplayTree { tree })
+                Some(SplayTree {
+                    tree,
+                    stats: Default::default(),
+                })
             }
         }
     }
@@ -616,4 +925,25 @@ impl<'a, D: Data> SplittableWalker<D> for SplayWalker<'a, D> {
         std::mem::swap(self.inner_mut(), &mut right.tree);
         Some(right)
     }
+
+    /// Takes the current subtree out of the tree. The walker stays at the now empty position.
+    /// Splay trees have no balance invariants, so nothing else changes.
+    fn take_subtree(&mut self) -> Self::T {
+        SplayTree {
+            tree: self.walker.take_subtree(),
+            stats: Default::default(),
+        }
+    }
+
+    /// Puts the subtree at the current empty position. The walker ends up at the subtree's root.
+    /// Splay trees have no balance invariants, so nothing else changes.
+    fn put_subtree(&mut self, mut subtree: Self::T) -> Result<(), Self::T> {
+        if !self.is_empty() {
+            return Err(subtree);
+        }
+        self.walker
+            .put_subtree(std::mem::take(&mut subtree.tree))
+            .unwrap();
+        Ok(())
+    }
 }
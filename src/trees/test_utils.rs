@@ -0,0 +1,175 @@
+//! Differential testing of trees against a simple reference implementation.
+//!
+//! This module is only available with the `test_utils` feature, which also enables
+//! the `arbitrary` feature, since it uses the [`Operation`]s of the [`fuzzing`](super::fuzzing) module.
+//!
+//! [`ReferenceModel`] implements the same semantics as the trees, on a plain [`Vec`]:
+//! actions are applied to every value in the segment, and summaries are computed by adding up
+//! the summaries of the values one by one. Since it is straightforward, it is easy to trust.
+//! [`compare_with_reference`] replays a sequence of operations on a tree and on the reference
+//! model, and reports the first place in which they diverge. This works for any tree type,
+//! including user-defined trees.
+//!```
+//! use grove::{*, fuzzing::*, test_utils::*, splay::SplayTree};
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut tree: SplayTree<StdNum> = (0..20).collect();
+//! let ops = vec![
+//!     Operation::Act { start: 3, end: 15, action: RevAffineAction { to_reverse: true, mul: -1, add: 4 } },
+//!     Operation::Delete { index: 7 },
+//!     Operation::Query { start: 0, end: 10 },
+//! ];
+//! assert_eq!(compare_with_reference(&mut tree, ops), Ok(()));
+//! # tree.assert_correctness();
+//!```
+
+use super::fuzzing::{Operation, Outcome};
+use super::*;
+use arbitrary::{Arbitrary, Unstructured};
+
+/// A reference implementation of a sequence of values with summaries and actions,
+/// backed by a [`Vec`]. All operations take linear time.
+pub struct ReferenceModel<D: Data> {
+    values: Vec<D::Value>,
+}
+
+impl<D: Data> ReferenceModel<D> {
+    /// Creates a reference model holding the given values.
+    pub fn new(values: Vec<D::Value>) -> Self {
+        ReferenceModel { values }
+    }
+
+    /// Returns the values currently held by the model.
+    pub fn values(&self) -> &[D::Value] {
+        &self.values
+    }
+
+    /// Returns the summary of a segment of the model's values.
+    pub fn segment_summary(&self, segment: std::ops::Range<usize>) -> D::Summary {
+        self.values[segment]
+            .iter()
+            .fold(D::Summary::default(), |summary, value| {
+                summary + value.to_summary()
+            })
+    }
+
+    /// Applies the operation to the model, with the same semantics as [`Operation::apply`].
+    pub fn apply(&mut self, op: Operation<D::Value, D::Action>) -> Outcome<D::Value, D::Summary> {
+        let len = self.values.len();
+        let segment = |start: usize, end: usize| {
+            let (start, end) = (start % (len + 1), end % (len + 1));
+            start.min(end)..start.max(end)
+        };
+        match op {
+            Operation::Act { start, end, action } => {
+                let values = &mut self.values[segment(start, end)];
+                if action.to_reverse() {
+                    values.reverse();
+                }
+                // the summary of the values before the current one, in their original order
+                let mut prefix = D::Summary::default();
+                for value in values {
+                    let summary = (*value).to_summary();
+                    action.skip(&prefix).act_inplace(value);
+                    prefix = if action.to_reverse() {
+                        summary + prefix
+                    } else {
+                        prefix + summary
+                    };
+                }
+                Outcome::Nothing
+            }
+            Operation::Query { start, end } => {
+                Outcome::Summary(self.segment_summary(segment(start, end)))
+            }
+            Operation::Insert { index, value } => {
+                self.values.insert(index % (len + 1), value);
+                Outcome::Nothing
+            }
+            Operation::Delete { .. } if len == 0 => Outcome::Nothing,
+            Operation::Delete { index } => Outcome::Value(self.values.remove(index % len)),
+        }
+    }
+}
+
+/// The first difference found by [`compare_with_reference`] between a tree and the reference model.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Divergence<V, A, S> {
+    /// Applying an operation gave different outcomes.
+    Outcome {
+        /// The index of the operation in the sequence
+        step: usize,
+        /// The operation
+        operation: Operation<V, A>,
+        /// The outcome given by the reference model
+        expected: Outcome<V, S>,
+        /// The outcome given by the tree
+        found: Outcome<V, S>,
+    },
+    /// All of the operations gave the same outcomes, but at the end, the tree held different values.
+    Values {
+        /// The values held by the reference model
+        expected: Vec<V>,
+        /// The values held by the tree
+        found: Vec<V>,
+    },
+}
+
+/// The result of comparing a tree with the reference model: the first [`Divergence`], if any.
+pub type ReferenceResult<D> =
+    Result<(), Divergence<<D as Data>::Value, <D as Data>::Action, <D as Data>::Summary>>;
+
+/// Applies the operations to the tree and to a [`ReferenceModel`] holding the same values,
+/// and compares the outcomes of every operation, and then the values held at the end.
+/// Returns the first divergence found.
+pub fn compare_with_reference<D, T, I>(tree: &mut T, ops: I) -> ReferenceResult<D>
+where
+    D: Data,
+    D::Value: Clone + PartialEq,
+    D::Summary: SizedSummary + PartialEq,
+    T: SomeTree<D>,
+    I: IntoIterator<Item = Operation<D::Value, D::Action>>,
+{
+    let mut model = ReferenceModel::<D>::new(tree.iter().cloned().collect());
+    for (step, operation) in ops.into_iter().enumerate() {
+        let expected = model.apply(operation.clone());
+        let found = operation.clone().apply(tree);
+        if expected != found {
+            return Err(Divergence::Outcome {
+                step,
+                operation,
+                expected,
+                found,
+            });
+        }
+    }
+    let found: Vec<D::Value> = tree.iter().cloned().collect();
+    if found != model.values {
+        return Err(Divergence::Values {
+            expected: model.values,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Generates an arbitrary tree and an arbitrary sequence of operations from the given bytes,
+/// and compares the tree with the reference model, using [`compare_with_reference`].
+/// Suitable as the body of a fuzz target, or for random testing with random bytes.
+pub fn check_against_reference<D, T>(data: &[u8]) -> ReferenceResult<D>
+where
+    D: Data,
+    D::Value: Clone + PartialEq + for<'a> Arbitrary<'a>,
+    D::Summary: SizedSummary + PartialEq,
+    D::Action: for<'a> Arbitrary<'a>,
+    T: SomeTree<D> + for<'a> Arbitrary<'a>,
+{
+    let mut u = Unstructured::new(data);
+    // running out of bytes isn't a divergence, it just means there is nothing to test.
+    let mut tree: T = match u.arbitrary() {
+        Ok(tree) => tree,
+        Err(_) => return Ok(()),
+    };
+    let ops: Vec<Operation<D::Value, D::Action>> = u.arbitrary().unwrap_or_default();
+    compare_with_reference(&mut tree, ops)
+}
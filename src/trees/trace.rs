@@ -0,0 +1,210 @@
+//! Recording the operations made on a tree, and replaying them deterministically.
+//!
+//! A [`Recorder`] wraps a tree, and logs its initial values and every structural operation
+//! made through it into a [`Trace`]. Operations that select a segment with a locator are logged
+//! with the range of indices that the locator selected, so a trace doesn't depend on the locators,
+//! and can be printed, stored, and replayed with [`replay`] into any tree type.
+//! This is useful for reducing a failing sequence of operations into a bug report.
+//!
+//! Operations are logged by indices, so the summaries must implement [`SizedSummary`].
+//!```
+//! use grove::{*, trace::{Recorder, replay}, splay::SplayTree, treap::Treap};
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let tree: SplayTree<StdNum> = (0..10).collect();
+//! let mut recorder = Recorder::new(tree);
+//! recorder.act_segment(RevAffineAction { to_reverse: true, mul: 2, add: 0 }, 2..5);
+//! recorder.insert(0, 100);
+//! assert_eq!(recorder.delete(4), Some(6));
+//! let tail = recorder.split_off(6);
+//! recorder.append(tail);
+//!
+//! let trace = recorder.trace().clone();
+//! assert_eq!(trace.ops.len(), 5);
+//! let mut replayed: Treap<StdNum> = replay(&trace);
+//! assert_eq!(
+//!     replayed.iter().cloned().collect::<Vec<_>>(),
+//!     recorder.tree().iter().cloned().collect::<Vec<_>>(),
+//! );
+//! # replayed.assert_correctness();
+//!```
+
+use super::*;
+use std::ops::Range;
+
+/// A single operation in a [`Trace`], with its segment given by indices.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Op<V, A> {
+    /// Inserting the value at the index.
+    Insert {
+        /// The index of the inserted value.
+        index: usize,
+        /// The inserted value.
+        value: V,
+    },
+    /// Deleting the value at the index.
+    Delete {
+        /// The index of the deleted value.
+        index: usize,
+    },
+    /// Applying the action to the segment of the range of indices.
+    Act {
+        /// The range of indices that the locator selected.
+        range: Range<usize>,
+        /// The applied action.
+        action: A,
+    },
+    /// Splitting the tree at the index, keeping only the values before it.
+    SplitOff {
+        /// The index at which the tree was split.
+        index: usize,
+    },
+    /// Concatenating a tree with these values to the right of the tree.
+    Append {
+        /// The values of the concatenated tree, in order.
+        values: Vec<V>,
+    },
+}
+
+/// The initial values of a tree, and the operations that were made on it, in order.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Trace<V, A> {
+    /// The values of the tree when the recording started.
+    pub initial: Vec<V>,
+    /// The operations made on the tree, in order.
+    pub ops: Vec<Op<V, A>>,
+}
+
+/// A tree, along with a trace of the operations made on it. See the [module documentation](self).
+pub struct Recorder<D: Data, T> {
+    tree: T,
+    trace: Trace<D::Value, D::Action>,
+}
+
+impl<D: Data, T> Recorder<D, T>
+where
+    D::Value: Clone,
+    D::Summary: SizedSummary,
+    T: SplittableTree<D>,
+{
+    /// Starts recording the operations made on the tree, beginning with its current values.
+    pub fn new(mut tree: T) -> Self {
+        let initial = tree.iter().cloned().collect();
+        Recorder {
+            tree,
+            trace: Trace {
+                initial,
+                ops: vec![],
+            },
+        }
+    }
+
+    /// Returns the tree. Edits made directly to it aren't recorded,
+    /// and make the trace invalid.
+    pub fn tree(&mut self) -> &mut T {
+        &mut self.tree
+    }
+
+    /// Returns the trace of the operations recorded so far.
+    pub fn trace(&self) -> &Trace<D::Value, D::Action> {
+        &self.trace
+    }
+
+    /// Returns the tree and its trace.
+    pub fn into_inner(self) -> (T, Trace<D::Value, D::Action>) {
+        (self.tree, self.trace)
+    }
+
+    /// Inserts the value at the given index.
+    /// Panics if the index is out of bounds.
+    pub fn insert(&mut self, index: usize, value: D::Value) {
+        let len = self.tree.subtree_summary().size();
+        assert!(index <= len, "index {} out of bounds ({})", index, len);
+        self.trace.ops.push(Op::Insert {
+            index,
+            value: value.clone(),
+        });
+        apply(&mut self.tree, Op::Insert { index, value });
+    }
+
+    /// Deletes the value at the given index, and returns it.
+    /// If the index is out of bounds, returns [`None`], and nothing is recorded.
+    pub fn delete(&mut self, index: usize) -> Option<D::Value> {
+        let value = self.tree.slice(index..=index).delete()?;
+        self.trace.ops.push(Op::Delete { index });
+        Some(value)
+    }
+
+    /// Applies the action to the segment selected by the locator.
+    /// The segment is recorded as the range of indices that the locator selected.
+    pub fn act_segment<L: locators::Locator<D>>(&mut self, action: D::Action, locator: L) {
+        let start = self
+            .tree
+            .search(locators::LeftEdgeOf(locator.clone()))
+            .left_summary()
+            .size();
+        let len = self.tree.segment_summary(locator).size();
+        let range = start..start + len;
+        self.trace.ops.push(Op::Act {
+            range: range.clone(),
+            action: action.clone(),
+        });
+        apply(&mut self.tree, Op::Act { range, action });
+    }
+
+    /// Splits the tree at the given index, and returns the values from the index onwards,
+    /// as a separate tree. The returned tree isn't recorded.
+    /// Panics if the index is out of bounds.
+    pub fn split_off(&mut self, index: usize) -> T {
+        let right = self.tree.split_off(index);
+        self.trace.ops.push(Op::SplitOff { index });
+        right
+    }
+
+    /// Concatenates the other tree to the right of the tree.
+    /// The other tree's values are recorded, so that the trace can be replayed on its own.
+    pub fn append(&mut self, mut other: T) {
+        let values = other.iter().cloned().collect();
+        self.trace.ops.push(Op::Append { values });
+        self.tree.concatenate_right(other);
+    }
+}
+
+/// Applies a single operation to the tree.
+fn apply<D: Data, T>(tree: &mut T, op: Op<D::Value, D::Action>)
+where
+    D::Summary: SizedSummary,
+    T: SplittableTree<D>,
+{
+    match op {
+        Op::Insert { index, value } => tree
+            .slice(index..index)
+            .insert(value)
+            .expect("Expected empty position"),
+        Op::Delete { index } => {
+            tree.slice(index..=index)
+                .delete()
+                .expect("the trace doesn't match the tree");
+        }
+        Op::Act { range, action } => tree.act_segment(action, range),
+        Op::SplitOff { index } => tree.truncate(index),
+        Op::Append { values } => tree.concatenate_right(values.into_iter().collect()),
+    }
+}
+
+/// Builds a tree from the trace's initial values, and replays all of its operations on it,
+/// reproducing the state of the recorded tree. The tree type doesn't have to be the one
+/// that was recorded.
+/// Panics if the trace is invalid, e.g., if it deletes an index that is out of bounds.
+pub fn replay<D: Data, T>(trace: &Trace<D::Value, D::Action>) -> T
+where
+    D::Value: Clone,
+    D::Summary: SizedSummary,
+    T: SplittableTree<D>,
+{
+    let mut tree: T = trace.initial.iter().cloned().collect();
+    for op in &trace.ops {
+        apply(&mut tree, op.clone());
+    }
+    tree
+}
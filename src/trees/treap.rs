@@ -15,7 +15,6 @@ use crate::locators;
 
 use super::basic_tree::*;
 use super::*;
-use rand;
 
 // The type that is used for bookkeeping.
 // convention: a bigger number should go higher up the tree.
@@ -24,9 +23,23 @@ type T = u64;
 /// A Treap.
 pub struct Treap<D: Data> {
     tree: BasicTree<D, T>,
+    // the counts of the operations performed through the tree's walkers
+    stats: Counters,
 }
 
 impl<D: Data> SomeTree<D> for Treap<D> {
+    type Walker<'a>
+        = TreapWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn walker(&mut self) -> Self::Walker<'_> {
+        TreapWalker {
+            walker: self.tree.walker().count_into(&mut self.stats),
+        }
+    }
+
     fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
     where
         L: locators::Locator<D>,
@@ -42,11 +55,20 @@ impl<D: Data> SomeTree<D> for Treap<D> {
         segment_algorithms::segment_summary(self, locator)
     }
 
+    /// Merges the trees using the join-based union algorithm. See [`union`].
+    fn merge<Key: Ord>(mut self, other: Self) -> Self
+    where
+        D::Value: Keyed<Key>,
+    {
+        union_internal::<D, Key>(&mut self.tree, other);
+        self
+    }
+
     fn act_segment<L>(&mut self, action: D::Action, locator: L)
     where
         L: crate::Locator<D>,
     {
-        if !action.to_reverse() {
+        if (!D::Action::REVERSIBLE || !action.to_reverse()) && !action.is_indexed() {
             segment_algorithms::act_segment(self, action, locator)
         } else {
             // split out the middle
@@ -75,14 +97,36 @@ impl<D: Data> SomeTree<D> for Treap<D> {
         }
     }
 
-    type TreeData = T;
+    type IterLocator<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterLocator<'a, D, L, T>
+    where
+        Self: 'a,
+        D: 'a;
+    type IterLocatorMut<'a, L: locators::Locator<D>>
+        = basic_tree::iterators::IterMutGuard<'a, D, L, T>
+    where
+        Self: 'a,
+        D: 'a;
+    type IntoIterSegment<L: locators::Locator<D>> = basic_tree::iterators::IntoIter<D, L, T>;
+
     fn iter_locator<'a, L: locators::Locator<D>>(
         &'a mut self,
         locator: L,
-    ) -> basic_tree::iterators::IterLocator<'a, D, L, T> {
+    ) -> Self::IterLocator<'a, L> {
         iterators::IterLocator::new(&mut self.tree, locator)
     }
 
+    fn iter_locator_mut<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> Self::IterLocatorMut<'a, L> {
+        iterators::IterMutGuard::new(&mut self.tree, locator)
+    }
+
+    fn into_iter_segment<L: locators::Locator<D>>(self, locator: L) -> Self::IntoIterSegment<L> {
+        iterators::IntoIter::new(self.tree, locator)
+    }
+
     /// Checks that invariants remain correct. i.e., that every node's summary
     /// is the sum of the summaries of its children, and that the priorities are ordered.
     /// If it finds any violation, it panics.
@@ -90,10 +134,9 @@ impl<D: Data> SomeTree<D> for Treap<D> {
     where
         D::Summary: Eq,
     {
-        self.tree.assert_correctness_with(|node| {
-            Self::assert_priorities_locally_internal(node);
-            node.assert_correctness_locally();
-        });
+        if let Err(err) = self.validate() {
+            panic!("{}", err);
+        }
     }
 }
 
@@ -103,18 +146,77 @@ impl<D: Data> Default for Treap<D> {
     }
 }
 
-impl<'a, D: Data> SomeTreeRef<D> for &'a mut Treap<D> {
-    type Walker = TreapWalker<'a, D>;
-
-    fn walker(self) -> Self::Walker {
-        TreapWalker {
-            walker: self.tree.walker(),
+/// Clones the tree structurally, in `O(n)` time. See [`BasicTree`]'s `Clone` implementation.
+impl<D: Data> Clone for Treap<D>
+where
+    D::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        Treap {
+            tree: self.tree.clone(),
+            stats: Default::default(),
         }
     }
 }
 
-impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut Treap<D> {
-    type ModifiableWalker = TreapWalker<'a, D>;
+/// Compares the values of the trees in order, in `O(n)` time.
+/// See [`BasicTree`]'s `PartialEq` implementation.
+impl<D: Data> PartialEq for Treap<D>
+where
+    D::Value: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.tree == other.tree
+    }
+}
+
+impl<D: Data> Eq for Treap<D> where D::Value: Clone + Eq {}
+
+/// Compares the values of the trees lexicographically.
+/// See [`BasicTree`]'s `PartialOrd` implementation.
+impl<D: Data> PartialOrd for Treap<D>
+where
+    D::Value: Clone + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.tree.partial_cmp(&other.tree)
+    }
+}
+
+/// Compares the values of the trees lexicographically.
+/// See [`BasicTree`]'s `Ord` implementation.
+impl<D: Data> Ord for Treap<D>
+where
+    D::Value: Clone + Ord,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.tree.cmp(&other.tree)
+    }
+}
+
+/// Hashes the values of the tree in order, in `O(n)` time.
+/// See [`BasicTree`]'s `Hash` implementation.
+impl<D: Data> std::hash::Hash for Treap<D>
+where
+    D::Value: Clone + std::hash::Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tree.hash(state)
+    }
+}
+
+/// Prints the structure of the tree, in the same format as [`BasicTree`]'s
+/// `Debug` implementation.
+/// The algorithm-specific data of every node is its priority.
+impl<D: Data> std::fmt::Debug for Treap<D>
+where
+    D::Value: std::fmt::Debug,
+    D::Summary: std::fmt::Debug,
+    D::Action: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tree.fmt(f)
+    }
 }
 
 derive_SomeEntry! {tree, T,
@@ -143,6 +245,32 @@ impl<D: Data> Treap<D> {
     pub fn new() -> Treap<D> {
         Treap {
             tree: BasicTree::Empty,
+            stats: Default::default(),
+        }
+    }
+
+    /// Creates a read-only walker for the tree, that only needs a shared reference.
+    /// See [`ImmBasicWalker`].
+    pub fn imm_walker(&self) -> ImmBasicWalker<'_, D, T> {
+        self.tree.imm_walker()
+    }
+
+    /// Returns the underlying [`BasicTree`].
+    pub(crate) fn inner(&self) -> &BasicTree<D, T> {
+        &self.tree
+    }
+
+    /// Creates a walker that reuses the vectors of the given buffer instead of allocating
+    /// new ones. See [`WalkerBuffer`].
+    pub fn walker_with_buffer<'a>(
+        &'a mut self,
+        buffer: &'a mut WalkerBuffer<D>,
+    ) -> TreapWalker<'a, D> {
+        TreapWalker {
+            walker: self
+                .tree
+                .walker_with_buffer(buffer)
+                .count_into(&mut self.stats),
         }
     }
 
@@ -170,7 +298,7 @@ impl<D: Data> Treap<D> {
     where
         D::Value: Ord,
     {
-        union_internal(&mut self.tree, tree2);
+        union_internal::<D, D::Value>(&mut self.tree, tree2);
     }
 
     /// Asserts that the priorities maintain the priority invariant
@@ -197,6 +325,27 @@ impl<D: Data> Treap<D> {
         self.tree
             .assert_correctness_with(Self::assert_priorities_locally_internal);
     }
+
+    fn check_priorities_locally(node: &BasicNode<D, T>) -> Result<(), Violation> {
+        for (son, side) in [(&node.left, Side::Left), (&node.right, Side::Right)] {
+            if let Some(son) = son.node() {
+                if node.alg_data() <= son.alg_data() {
+                    return Err(Violation::Priority(side));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D: Data> ValidateTree<D> for Treap<D> {
+    /// Checks the summaries and the priorities.
+    fn validate(&self) -> Result<(), InvariantError>
+    where
+        D::Summary: Eq,
+    {
+        self.tree.validate_with(Self::check_priorities_locally)
+    }
 }
 
 impl<D: Data> std::iter::FromIterator<D::Value> for Treap<D> {
@@ -206,6 +355,7 @@ impl<D: Data> std::iter::FromIterator<D::Value> for Treap<D> {
         // because we know that we're not using all of insert's generality.
         let mut tree = Treap {
             tree: BasicTree::Empty,
+            stats: Default::default(),
         };
         let mut walker = tree.walker();
         for val in iter {
@@ -214,10 +364,20 @@ impl<D: Data> std::iter::FromIterator<D::Value> for Treap<D> {
             while let Ok(()) = walker.go_right() {}
         }
         drop(walker);
+        // building the tree isn't counted
+        tree.stats = Default::default();
         tree
     }
 }
 
+impl<D: Data> Extend<D::Value> for Treap<D> {
+    /// Builds a tree out of the values, and concatenates it to the right of this tree.
+    /// This takes `O(m + log n)` time.
+    fn extend<T: IntoIterator<Item = D::Value>>(&mut self, iter: T) {
+        self.concatenate_right(iter.into_iter().collect());
+    }
+}
+
 impl<D: Data> IntoIterator for Treap<D> {
     type Item = D::Value;
     type IntoIter = iterators::IntoIter<D, std::ops::RangeFull, T>;
@@ -227,14 +387,102 @@ impl<D: Data> IntoIterator for Treap<D> {
     }
 }
 
+impl<D: Data> From<Vec<D::Value>> for Treap<D> {
+    /// Builds a treap out of the values, in `O(n)` time.
+    /// Same as collecting the values.
+    fn from(values: Vec<D::Value>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<D: Data> From<Treap<D>> for Vec<D::Value> {
+    /// Returns the tree's values in order, in `O(n)` time.
+    /// Same as collecting the tree's values.
+    fn from(tree: Treap<D>) -> Self {
+        tree.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: Data> rayon::iter::IntoParallelIterator for Treap<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    type Iter = basic_tree::parallel::IntoParIter<D, T>;
+    type Item = D::Value;
+
+    fn into_par_iter(self) -> Self::Iter {
+        basic_tree::parallel::IntoParIter::new(self.tree)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: Data> rayon::iter::FromParallelIterator<D::Value> for Treap<D>
+where
+    D::Value: Send,
+    D::Summary: Send,
+    D::Action: Send,
+{
+    /// Collects the values in parallel. The treap itself is built sequentially,
+    /// because its shape is determined by the random priorities.
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = D::Value>,
+    {
+        use rayon::iter::ParallelIterator;
+        par_iter
+            .into_par_iter()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(feature = "instrument")]
+impl<D: Data> instrument::Instrumented for Treap<D> {
+    fn stats(&self) -> instrument::Stats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = Default::default();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, D: Data> arbitrary::Arbitrary<'a> for Treap<D>
+where
+    D::Value: arbitrary::Arbitrary<'a>,
+{
+    /// Generates a treap out of an arbitrary sequence of values.
+    /// Note that the priorities are still random, and not taken from the [`arbitrary::Unstructured`].
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+}
+
 /// A walker for a [`Treap`].
 pub struct TreapWalker<'a, D: Data> {
     walker: BasicWalker<'a, D, T>,
 }
 
+/// Prints the walker in the same format as [`BasicWalker`]'s `Debug` implementation.
+impl<'a, D: Data> std::fmt::Debug for TreapWalker<'a, D>
+where
+    D::Value: std::fmt::Debug,
+    D::Summary: std::fmt::Debug,
+    D::Action: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.walker.fmt(f)
+    }
+}
+
 derive_SomeWalker! {walker,
     impl<'a, D: Data> SomeWalker<D> for TreapWalker<'a, D> {
-        fn go_up(&mut self) -> Result<Side, ()> {
+        fn go_up(&mut self) -> Result<Side, NavError> {
             self.walker.go_up()
         }
     }
@@ -268,15 +516,15 @@ impl<'a, D: Data> TreapWalker<'a, D> {
 
 impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
     /// Inserts the value into the tree at the current empty position.
-    /// If the current position is not empty, return [`None`].
+    /// If the current position is not empty, returns `Err(NavError::OccupiedPosition)`.
     /// When the function returns, the walker will be at the position the node
     /// was inserted.
-    fn insert(&mut self, val: D::Value) -> Option<()> {
+    fn insert(&mut self, val: D::Value) -> Result<(), NavError> {
         if !self.is_empty() {
-            return None;
+            return Err(NavError::OccupiedPosition);
         }
 
-        let priority: T = rand::random();
+        let priority: T = random::priority();
         let mut temp = BasicTree::Empty;
         // in the first round, this value is irrelevent. choosing this will skip the first if.
         let mut prev_side = self.walker.is_left_son().unwrap_or(Side::Right);
@@ -320,7 +568,7 @@ impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
         }
         new.rebuild();
         *self.walker.inner_mut() = BasicTree::from_node(new);
-        Some(())
+        Ok(())
     }
 
     /// Removes the current value from the tree, and returns it.
@@ -329,8 +577,14 @@ impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
     fn delete(&mut self) -> Option<D::Value> {
         let tree = std::mem::replace(self.walker.inner_mut(), BasicTree::Empty);
         let node = tree.into_node()?;
-        let left = Treap { tree: node.left };
-        let right = Treap { tree: node.right };
+        let left = Treap {
+            tree: node.left,
+            stats: Default::default(),
+        };
+        let right = Treap {
+            tree: node.right,
+            stats: Default::default(),
+        };
         *self.walker.inner_mut() = ConcatenableTree::concatenate(left, right).tree;
         Some(node.node_value)
     }
@@ -350,9 +604,9 @@ impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
 /// This has the effect that if you start with `n` different singletone trees,
 /// and you united them together in any way whatsoever, the overall complexity would be
 /// `O(n*log(n))`.
-fn union_internal<D: Data>(tree1: &mut BasicTree<D, T>, mut tree2: Treap<D>)
+fn union_internal<D: Data, Key: Ord>(tree1: &mut BasicTree<D, T>, mut tree2: Treap<D>)
 where
-    D::Value: Ord,
+    D::Value: Keyed<Key>,
 {
     if tree2.is_empty() {
         return;
@@ -394,8 +648,8 @@ where
     drop(split_walker);
     let left = tree2;
 
-    union_internal(&mut node.left, left);
-    union_internal(&mut node.right, right);
+    union_internal::<D, Key>(&mut node.left, left);
+    union_internal::<D, Key>(&mut node.right, right);
     node.rebuild();
 }
 
@@ -477,9 +731,16 @@ impl<D: Data> ConcatenableTree<D> for Treap<D> {
     }
 }
 
-impl<'a, D: Data> SplittableTreeRef<D> for &'a mut Treap<D> {
-    type T = Treap<D>;
-    type SplittableWalker = TreapWalker<'a, D>;
+impl<D: Data> SplittableTree<D> for Treap<D> {
+    type SplittableWalker<'a>
+        = TreapWalker<'a, D>
+    where
+        Self: 'a,
+        D: 'a;
+
+    fn splittable_walker(&mut self) -> Self::SplittableWalker<'_> {
+        self.walker()
+    }
 }
 
 impl<'a, D: Data> SplittableWalker<D> for TreapWalker<'a, D> {
@@ -527,7 +788,10 @@ impl<'a, D: Data> SplittableWalker<D> for TreapWalker<'a, D> {
         if prev_side == Side::Left {
             std::mem::swap(self.walker.inner_mut(), &mut temp);
         }
-        Some(Treap { tree: temp })
+        Some(Treap {
+            tree: temp,
+            stats: Default::default(),
+        })
     }
 
     /// Will only do anything if the current position is empty.
@@ -552,4 +816,31 @@ impl<'a, D: Data> SplittableWalker<D> for TreapWalker<'a, D> {
         std::mem::swap(self.inner_mut(), &mut right.tree);
         Some(right)
     }
+
+    /// Takes the current subtree out of the tree. The walker stays at the now empty position.
+    /// Removing a subtree keeps the heap order of the priorities, so nothing else changes.
+    fn take_subtree(&mut self) -> Self::T {
+        Treap {
+            tree: self.walker.take_subtree(),
+            stats: Default::default(),
+        }
+    }
+
+    /// Puts the subtree at the current empty position, by splitting the tree there,
+    /// and concatenating the three parts, in `O(log n)` expected time.
+    /// The walker ends up at the root.
+    fn put_subtree(&mut self, subtree: Self::T) -> Result<(), Self::T> {
+        if !self.is_empty() {
+            return Err(subtree);
+        }
+        let right = self.split_right().unwrap();
+        let mut tree = Treap {
+            tree: self.walker.take_subtree(),
+            stats: Default::default(),
+        };
+        tree.concatenate_right(subtree);
+        tree.concatenate_right(right);
+        self.walker.put_subtree(tree.tree).unwrap();
+        Ok(())
+    }
 }
@@ -0,0 +1,128 @@
+// This is a private module, so no documentation for it directly.
+// Instead look for documentation of the `ValidateTree` trait.
+
+use super::basic_tree::*;
+use super::*;
+
+/// A broken invariant found by [`ValidateTree::validate`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Violation {
+    /// The node's stored summary isn't the sum of its sons' summaries and its value's summary.
+    Summary,
+    /// The node's rank isn't one more than the maximum of its sons' ranks,
+    /// or its sons' ranks differ by more than one. Used by [`AVLTree`](crate::avl::AVLTree).
+    Rank {
+        /// The rank stored in the node
+        rank: u8,
+        /// The rank of its left son
+        left: u8,
+        /// The rank of its right son
+        right: u8,
+    },
+    /// The node's son on the given side doesn't have a lower priority than it.
+    /// Used by [`Treap`](crate::treap::Treap).
+    Priority(Side),
+}
+
+/// The error returned by [`ValidateTree::validate`]: which invariant was broken, and where.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct InvariantError {
+    /// The path from the root to the offending node.
+    ///
+    /// The path is given as the tree is stored: pending actions are not pushed down,
+    /// so if an ancestor of the node has a pending reversal, the sides are in the
+    /// pre-reversal order, like in the trees' `Debug` output.
+    pub path: Vec<Side>,
+    /// The broken invariant.
+    pub violation: Violation,
+}
+
+impl std::fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.violation {
+            Violation::Summary => write!(f, "incorrect summary")?,
+            Violation::Rank { rank, left, right } => write!(
+                f,
+                "incorrect rank {} with sons of ranks {} and {}",
+                rank, left, right
+            )?,
+            Violation::Priority(side) => write!(
+                f,
+                "{:?} son's priority is not lower than its father's",
+                side
+            )?,
+        }
+        write!(f, " at node with path {:?} from the root", self.path)
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+/// Trees that can check their own invariants, and report where they are broken.
+///
+/// This is the non-panicking counterpart of [`SomeTree::assert_correctness`], which
+/// panics with the message of the first error found.
+///```
+/// use grove::{*, avl::AVLTree};
+/// use grove::example_data::StdNum;
+///
+/// let mut tree: AVLTree<StdNum> = (0..20).collect();
+/// tree.act_segment(example_data::RevAffineAction { to_reverse: false, mul: 2, add: 1 }, 3..14);
+/// assert_eq!(tree.validate(), Ok(()));
+/// # tree.assert_correctness();
+///```
+pub trait ValidateTree<D: Data> {
+    /// Checks all of the tree's invariants. If any of them is broken,
+    /// returns the first broken invariant found, in pre-order.
+    fn validate(&self) -> Result<(), InvariantError>
+    where
+        D::Summary: Eq;
+}
+
+impl<D: Data, T> BasicTree<D, T> {
+    /// Checks the summaries of every node in the tree, and the given local invariant
+    /// on every node, in pre-order.
+    /// Returns the first broken invariant found, along with the path to its node.
+    ///
+    /// This doesn't use recursion, since the tree might be very deep.
+    pub fn validate_with<F>(&self, func: F) -> Result<(), InvariantError>
+    where
+        D::Summary: Eq,
+        F: Fn(&BasicNode<D, T>) -> Result<(), Violation>,
+    {
+        let mut path = vec![];
+        // Every subtree in the stack is stored with its depth, and the side it is on,
+        // unless it is the root.
+        let mut stack = vec![(self, 0, None)];
+        while let Some((tree, depth, side)) = stack.pop() {
+            let node = match tree.node() {
+                Some(node) => node,
+                None => continue,
+            };
+            if let Some(side) = side {
+                path.truncate(depth - 1);
+                path.push(side);
+            }
+            let res = if node.is_correct_locally() {
+                func(node)
+            } else {
+                Err(Violation::Summary)
+            };
+            if let Err(violation) = res {
+                return Err(InvariantError { path, violation });
+            }
+            stack.push((&node.right, depth + 1, Some(Side::Right)));
+            stack.push((&node.left, depth + 1, Some(Side::Left)));
+        }
+        Ok(())
+    }
+}
+
+impl<D: Data, T> ValidateTree<D> for BasicTree<D, T> {
+    fn validate(&self) -> Result<(), InvariantError>
+    where
+        D::Summary: Eq,
+    {
+        self.validate_with(|_| Ok(()))
+    }
+}
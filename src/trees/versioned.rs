@@ -0,0 +1,138 @@
+//! A tree with committed versions, that can be checked out, named, and compared.
+//!
+//! A [`VersionedTree`] holds a working [`PersistentTree`], which can be edited freely,
+//! and the versions that were committed from it. Since cloning a persistent tree takes `O(1)` time,
+//! committing and checking out versions take `O(1)` time, and all of the versions share
+//! the nodes that weren't modified between them.
+//!```
+//! use grove::versioned::VersionedTree;
+//! use grove::algorithms::EditOp;
+//! use grove::example_data::{HashSummary, Unit};
+//!
+//! type D = (char, HashSummary<257, 1_000_000_007>, Unit);
+//! let mut doc: VersionedTree<D> = VersionedTree::new("hello world".chars().collect());
+//! let first = doc.commit_named("draft");
+//!
+//! doc.tree_mut().delete(0..=0);
+//! doc.tree_mut().insert(0..0, 'j');
+//! let second = doc.commit();
+//! assert_eq!(doc.diff(first, second), vec![
+//!     EditOp::Delete(1),
+//!     EditOp::Insert(vec!['j']),
+//!     EditOp::Retain(10),
+//! ]);
+//!
+//! // uncommitted changes are discarded by checking out a version
+//! doc.tree_mut().delete(0..5);
+//! doc.checkout(doc.named("draft").unwrap());
+//! assert_eq!(doc.tree().iter().collect::<String>(), "hello world");
+//! assert_eq!(doc.version(second).unwrap().iter().collect::<String>(), "jello world");
+//! # doc.tree().assert_correctness();
+//!```
+
+use super::persistent::PersistentTree;
+use super::*;
+use crate::algorithms::EditOp;
+use std::collections::HashMap;
+
+/// The identifier of a version committed to a [`VersionedTree`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct VersionId(usize);
+
+/// A persistent tree, along with its committed versions. See the [module documentation](self).
+pub struct VersionedTree<D: Data> {
+    tree: PersistentTree<D>,
+    // The committed versions, in the order they were committed.
+    versions: Vec<PersistentTree<D>>,
+    names: HashMap<String, VersionId>,
+}
+
+impl<D: Data> Default for VersionedTree<D> {
+    fn default() -> Self {
+        VersionedTree {
+            tree: Default::default(),
+            versions: vec![],
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl<D: Data> VersionedTree<D>
+where
+    D::Value: Clone,
+{
+    /// Starts with the given working tree, and no committed versions.
+    pub fn new(tree: PersistentTree<D>) -> Self {
+        VersionedTree {
+            tree,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the working tree.
+    pub fn tree(&self) -> &PersistentTree<D> {
+        &self.tree
+    }
+
+    /// Returns the working tree, for editing.
+    /// The edits are added to the next committed version.
+    pub fn tree_mut(&mut self) -> &mut PersistentTree<D> {
+        &mut self.tree
+    }
+
+    /// Returns the number of committed versions.
+    pub fn num_versions(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Commits a snapshot of the working tree as a new version, and returns its identifier.
+    /// Takes `O(1)` time.
+    pub fn commit(&mut self) -> VersionId {
+        self.versions.push(self.tree.clone());
+        VersionId(self.versions.len() - 1)
+    }
+
+    /// Commits a new version like [`VersionedTree::commit`], and names it.
+    /// If another version already had this name, the name is moved to the new version.
+    pub fn commit_named(&mut self, name: &str) -> VersionId {
+        let id = self.commit();
+        self.names.insert(name.to_string(), id);
+        id
+    }
+
+    /// Returns the version with the given name, if there is one.
+    pub fn named(&self, name: &str) -> Option<VersionId> {
+        self.names.get(name).copied()
+    }
+
+    /// Returns a committed version, or [`None`] if the identifier
+    /// wasn't returned by this versioned tree.
+    pub fn version(&self, id: VersionId) -> Option<&PersistentTree<D>> {
+        self.versions.get(id.0)
+    }
+
+    /// Replaces the working tree with a committed version, discarding any uncommitted edits.
+    /// Takes `O(1)` time.
+    /// Panics if the identifier wasn't returned by this versioned tree.
+    pub fn checkout(&mut self, id: VersionId) {
+        self.tree = self
+            .version(id)
+            .expect("the version wasn't committed to this tree")
+            .clone();
+    }
+
+    /// Returns an edit script that turns the first version into the second one.
+    /// See [`PersistentTree::diff`].
+    /// Panics if either identifier wasn't returned by this versioned tree.
+    pub fn diff(&self, from: VersionId, to: VersionId) -> Vec<EditOp<D::Value>>
+    where
+        D::Value: PartialEq,
+        D::Summary: SizedSummary + PartialEq,
+    {
+        let version = |id| {
+            self.version(id)
+                .expect("the version wasn't committed to this tree")
+        };
+        version(from).diff(version(to))
+    }
+}
@@ -10,7 +10,6 @@ where
     D: Clone + std::fmt::Debug + Eq, // useless bounds because the auto-generated clone instance for RoundAction requires it
     D::Summary: std::fmt::Debug + Eq + SizedSummary,
     T: SomeTree<D>,
-    for<'a> &'a mut T: ModifiableTreeRef<D>,
 {
     let mut rng = rand::thread_rng();
     let mut len: usize = INITIAL_SIZE;
@@ -70,3 +69,30 @@ fn bench_treap_imm(b: &mut Bencher) {
 fn bench_avl_imm(b: &mut Bencher) {
     bench_tree::<StdNum, AVLTree<_>>(b, false)
 }
+
+const SEARCH_SIZE: usize = 1000;
+
+#[bench]
+fn bench_avl_search(b: &mut Bencher) {
+    let mut tree: AVLTree<StdNum> = (0..SEARCH_SIZE as i32).collect();
+    let mut i = 0;
+    b.iter(|| {
+        let mut walker = tree.walker();
+        walker.search_subtree(i);
+        test::bench::black_box(walker.value());
+        i = (i + 1) % SEARCH_SIZE;
+    });
+}
+
+#[bench]
+fn bench_avl_search_with_buffer(b: &mut Bencher) {
+    let mut tree: AVLTree<StdNum> = (0..SEARCH_SIZE as i32).collect();
+    let mut buffer = grove::basic_tree::WalkerBuffer::new();
+    let mut i = 0;
+    b.iter(|| {
+        let mut walker = tree.walker_with_buffer(&mut buffer);
+        walker.search_subtree(i);
+        test::bench::black_box(walker.value());
+        i = (i + 1) % SEARCH_SIZE;
+    });
+}
@@ -1,7 +1,12 @@
 #[cfg(feature = "bench")]
 pub mod bench;
 
-use example_data::{RevAffineAction, StdNum};
+use basic_tree::iterators::IterLocator;
+#[cfg(feature = "arbitrary")]
+use example_data::Size;
+use example_data::{
+    NumSummary, PolyNum, ProgressionAction, RevAction, RevAffineAction, SizeData, StdNum,
+};
 use grove::*;
 use rand::{self, Rng};
 use std::ops::Range;
@@ -96,7 +101,6 @@ where
     D: Data<Value = i32, Action = RevAffineAction>,
     D::Summary: std::fmt::Debug + Eq + SizedSummary,
     T: SomeTree<D>,
-    for<'a> &'a mut T: ModifiableTreeRef<D>,
 {
     use RoundAction::*;
     use RoundResult::*;
@@ -149,9 +153,7 @@ where
     D: Clone + std::fmt::Debug + Eq, // useless bounds because the auto-generated clone instance for RoundAction requires it
     D::Summary: std::fmt::Debug + Eq + SizedSummary,
     T1: SomeTree<D>,
-    for<'a> &'a mut T1: ModifiableTreeRef<D>,
     T2: SomeTree<D>,
-    for<'a> &'a mut T2: ModifiableTreeRef<D>,
 {
     let mut rng = rand::thread_rng();
     let mut len: usize = INITIAL_SIZE;
@@ -193,7 +195,6 @@ where
 pub fn check_delete<T>()
 where
     T: SomeTree<StdNum>,
-    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
 {
     let arr: Vec<_> = (0..500).collect();
     for i in 0..arr.len() {
@@ -218,14 +219,13 @@ where
 pub fn check_insert<T>(should_walker_stay_at_inserted_value: bool)
 where
     T: SomeTree<StdNum>,
-    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
 {
     let arr: Vec<_> = (0..500).collect();
     for i in 0..=arr.len() {
         let new_val = 13;
         let mut tree: T = arr.iter().cloned().collect();
         let mut walker = tree.search(i..i);
-        walker.insert(new_val);
+        walker.insert(new_val).unwrap();
         if !should_walker_stay_at_inserted_value {
             // after inserting, the walker can move, because of rebalancing.
             // for example, in avl trees, the walker should be in an ancestor of the inserted value.
@@ -246,3 +246,2288 @@ where
         );
     }
 }
+
+/// Checks that iterating from both ends, in an arbitrary interleaving,
+/// yields the values of the segment in order.
+pub fn check_double_ended_iteration<T, TD>(num_rounds: u32)
+where
+    for<'a> T:
+        SomeTree<StdNum, IterLocator<'a, Range<usize>> = IterLocator<'a, StdNum, Range<usize>, TD>>,
+{
+    let mut rng = rand::thread_rng();
+    let arr: Vec<_> = (0..200).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let range = random_range(arr.len());
+        let mut expected = arr[range.clone()].iter();
+        let mut iter = tree.iter_locator(range.clone());
+        loop {
+            check_size_hint(&iter, expected.len());
+            let (res, expected_res) = if rng.gen() {
+                (iter.next(), expected.next())
+            } else {
+                (iter.next_back(), expected.next_back())
+            };
+            assert_eq!(res, expected_res);
+            if res.is_none() {
+                break;
+            }
+        }
+
+        let indexed: Vec<_> = tree.iter_locator(range.clone()).indexed().rev().collect();
+        let expected: Vec<_> = range.clone().zip(&arr[range]).rev().collect();
+        assert_eq!(indexed, expected);
+    }
+    tree.assert_correctness();
+
+    let mut expected = arr.into_iter();
+    let mut iter = tree.into_iter();
+    loop {
+        let (res, expected_res) = if rng.gen() {
+            (iter.next(), expected.next())
+        } else {
+            (iter.next_back(), expected.next_back())
+        };
+        assert_eq!(res, expected_res);
+        if res.is_none() {
+            break;
+        }
+    }
+}
+
+/// Checks that the iterator's size hint is consistent with the number of values left.
+fn check_size_hint<I: Iterator>(iter: &I, remaining: usize) {
+    let (lower, upper) = iter.size_hint();
+    assert!(lower <= remaining);
+    if let Some(upper) = upper {
+        assert!(upper >= remaining);
+    }
+}
+
+/// A run of equal values, that is counted as `count` elements. That is, a wide value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Run {
+    value: i32,
+    count: usize,
+}
+
+impl ToSummary<NumSummary> for Run {
+    fn to_summary(&self) -> NumSummary {
+        let size = self.count as i32;
+        NumSummary {
+            max: Some(self.value),
+            min: Some(self.value),
+            size,
+            sum: self.value * size,
+        }
+    }
+}
+
+impl Acts<Run> for RevAffineAction {
+    fn act_inplace(&self, run: &mut Run) {
+        self.act_inplace(&mut run.value);
+    }
+}
+
+/// Checks that iterating over wide values yields every value once,
+/// with size hints that don't assume that the sizes count the values.
+pub fn check_wide_value_iteration<T>()
+where
+    T: SomeTree<(Run, NumSummary, RevAffineAction)>,
+{
+    let mut rng = rand::thread_rng();
+    let runs: Vec<_> = (0..50).map(|value| Run { value, count: 5 }).collect();
+    let mut tree: T = runs.iter().cloned().collect();
+    assert_eq!(tree.subtree_summary().size, 250);
+
+    {
+        let mut expected = runs.iter();
+        let mut iter = tree.iter();
+        loop {
+            check_size_hint(&iter, expected.len());
+            let (res, expected_res) = if rng.gen() {
+                (iter.next(), expected.next())
+            } else {
+                (iter.next_back(), expected.next_back())
+            };
+            assert_eq!(res, expected_res);
+            if res.is_none() {
+                break;
+            }
+        }
+    }
+
+    let indexed: Vec<_> = tree
+        .iter_indexed()
+        .map(|(index, run)| (index, run.value))
+        .collect();
+    let expected: Vec<_> = (0..50).map(|value| (5 * value as usize, value)).collect();
+    assert_eq!(indexed, expected);
+    tree.assert_correctness();
+}
+
+/// An action that reverses segments, but forgot to set [`Action::REVERSIBLE`].
+#[derive(Clone, Copy, Default)]
+pub struct UnmarkedRevAction {
+    to_reverse: bool,
+}
+
+impl std::ops::Add for UnmarkedRevAction {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        UnmarkedRevAction {
+            to_reverse: self.to_reverse != other.to_reverse,
+        }
+    }
+}
+
+impl Action for UnmarkedRevAction {
+    fn is_identity(&self) -> bool {
+        !self.to_reverse
+    }
+
+    fn to_reverse(&self) -> bool {
+        self.to_reverse
+    }
+}
+
+impl<V> Acts<V> for UnmarkedRevAction {
+    fn act_inplace(&self, _object: &mut V) {}
+}
+
+/// Applies a reversing action that isn't [`Action::REVERSIBLE`], which should panic,
+/// since the tree can't reverse the segment.
+pub fn check_unmarked_reversal<T>()
+where
+    T: SomeTree<(i32, example_data::Size, UnmarkedRevAction)>,
+{
+    let mut tree: T = (0..10).collect();
+    tree.act_segment(UnmarkedRevAction { to_reverse: true }, 2..5);
+}
+
+/// Checks that mutating values through mutable iterators, including iterators
+/// that stop in the middle of the segment, keeps the tree's summaries correct.
+pub fn check_iter_mut<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<_> = (0..200).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let range = random_range(arr.len());
+        let len = range.len();
+        let num_front = rng.gen_range(0..len + 1);
+        let num_back = rng.gen_range(0..len - num_front + 1);
+        let add = rng.gen_range(-MAX_ADD..=MAX_ADD);
+
+        let mut guard = tree.iter_locator_mut(range.clone());
+        let mut iter = guard.iter();
+        for value in iter.by_ref().take(num_front) {
+            *value += add;
+        }
+        for value in iter.rev().take(num_back) {
+            *value += add;
+        }
+        drop(guard);
+        let touched = (range.start..range.start + num_front).chain(range.end - num_back..range.end);
+        for index in touched {
+            arr[index] += add;
+        }
+
+        tree.assert_correctness();
+        let query = random_range(arr.len());
+        assert_eq!(
+            tree.segment_summary(query.clone()).sum,
+            arr[query].iter().sum::<i32>()
+        );
+    }
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), arr);
+}
+
+/// Checks that draining segments removes exactly the segment, and keeps the tree correct.
+pub fn check_drain<T>(num_rounds: u32)
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut arr: Vec<_> = (0..1000).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let range = random_range(arr.len());
+        let drained: Vec<_> = tree.drain(range.clone()).collect();
+        assert_eq!(drained, arr.drain(range).collect::<Vec<_>>());
+        tree.assert_correctness();
+        assert_eq!(tree.segment_summary(..).size(), arr.len());
+    }
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), arr);
+}
+
+/// Checks that arbitrary trees are correct, and that applying arbitrary operations to them
+/// agrees with applying them to a vector.
+#[cfg(feature = "arbitrary")]
+pub fn check_arbitrary_operations<T>(num_rounds: u32)
+where
+    T: SomeTree<(i32, Size, RevAction)> + for<'a> arbitrary::Arbitrary<'a>,
+{
+    use fuzzing::{Operation, Outcome};
+    let mut rng = rand::thread_rng();
+    for _ in 0..num_rounds {
+        let len = rng.gen_range(0..4000);
+        let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let mut u = arbitrary::Unstructured::new(&bytes);
+        let mut tree: T = u.arbitrary().unwrap();
+        tree.assert_correctness();
+        let mut arr: Vec<i32> = tree.iter().cloned().collect();
+
+        let ops: Vec<Operation<i32, RevAction>> = u.arbitrary().unwrap();
+        for op in ops {
+            let len = arr.len();
+            let segment = |start: usize, end: usize| {
+                let (start, end) = (start % (len + 1), end % (len + 1));
+                start.min(end)..start.max(end)
+            };
+            let expected = match op.clone() {
+                Operation::Act { start, end, action } => {
+                    if action.to_reverse {
+                        arr[segment(start, end)].reverse();
+                    }
+                    Outcome::Nothing
+                }
+                Operation::Query { start, end } => Outcome::Summary(Size {
+                    size: segment(start, end).len(),
+                }),
+                Operation::Insert { index, value } => {
+                    arr.insert(index % (arr.len() + 1), value);
+                    Outcome::Nothing
+                }
+                Operation::Delete { .. } if arr.is_empty() => Outcome::Nothing,
+                Operation::Delete { index } => Outcome::Value(arr.remove(index % arr.len())),
+            };
+            assert_eq!(op.apply(&mut tree), expected);
+        }
+        tree.assert_correctness();
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    }
+}
+
+/// Checks arbitrary trees against the reference model of the `test_utils` module,
+/// using random bytes.
+#[cfg(feature = "test_utils")]
+pub fn check_reference_model<T>(num_rounds: u32)
+where
+    T: SomeTree<(i32, Size, RevAction)> + for<'a> arbitrary::Arbitrary<'a>,
+{
+    let mut rng = rand::thread_rng();
+    for _ in 0..num_rounds {
+        let len = rng.gen_range(0..4000);
+        let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        assert_eq!(
+            test_utils::check_against_reference::<_, T>(&bytes),
+            Ok(())
+        );
+    }
+}
+
+/// Checks that deleting segments returns their summaries, and keeps the tree correct.
+pub fn check_delete_segment<T>(num_rounds: u32)
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut arr: Vec<_> = (0..1000).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let range = random_range(arr.len());
+        let summary = tree.delete_segment(range.clone());
+        let deleted: Vec<_> = arr.drain(range).collect();
+        assert_eq!(summary.size(), deleted.len());
+        assert_eq!(summary.sum, deleted.iter().sum::<i32>());
+        tree.assert_correctness();
+        assert_eq!(tree.segment_summary(..).size(), arr.len());
+    }
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), arr);
+}
+
+/// Checks that inserting runs of values inserts them at the right place, and keeps the tree correct.
+pub fn check_insert_run<T>(num_rounds: u32)
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<_> = (0..100).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let index = rng.gen_range(0..=arr.len());
+        let len = rng.gen_range(0..50);
+        let run: Vec<i32> = (0..len).map(|_| rng.gen_range(-MAX_ADD..=MAX_ADD)).collect();
+        tree.insert_run_at(index, run.iter().cloned());
+        arr.splice(index..index, run);
+        tree.assert_correctness();
+        assert_eq!(tree.segment_summary(..).size(), arr.len());
+    }
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), arr);
+}
+
+/// Checks that converting from a vector and back keeps the values,
+/// including after acting on segments of the tree.
+pub fn check_vec_conversion<T>()
+where
+    T: SomeTree<StdNum> + From<Vec<i32>>,
+    Vec<i32>: From<T>,
+{
+    for len in [0, 1, 2, 100, 1000] {
+        let arr: Vec<i32> = (0..len).collect();
+        let mut tree = T::from(arr.clone());
+        tree.assert_correctness();
+        assert_eq!(tree.segment_summary(..).size(), arr.len());
+        tree.act_segment(
+            RevAffineAction {
+                to_reverse: false,
+                mul: 1,
+                add: 5,
+            },
+            ..,
+        );
+        assert_eq!(Vec::from(tree), arr.iter().map(|x| x + 5).collect::<Vec<_>>());
+    }
+}
+
+/// Checks that clones keep the values and the pending actions of the original tree,
+/// and are independent of it.
+pub fn check_clone<T>()
+where
+    T: SomeTree<StdNum> + Clone,
+{
+    let len = 10_000;
+    let mut arr: Vec<i32> = (0..len as i32).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    let action = RevAffineAction {
+        to_reverse: true,
+        mul: 2,
+        add: 0,
+    };
+    tree.act_segment(action, 100..900);
+    arr[100..900].reverse();
+    for val in &mut arr[100..900] {
+        *val = action.act(*val);
+    }
+    // accessing every value in order makes splay trees very deep
+    for i in 0..len {
+        tree.search(i);
+    }
+
+    let mut copy = tree.clone();
+    copy.assert_correctness();
+    let shift = RevAffineAction {
+        to_reverse: false,
+        mul: 1,
+        add: 1,
+    };
+    tree.act_segment(shift, ..);
+    assert_eq!(copy.iter().cloned().collect::<Vec<_>>(), arr);
+    copy.act_segment(shift, ..);
+    assert_eq!(copy.iter().cloned().collect::<Vec<_>>(), tree.iter().cloned().collect::<Vec<_>>());
+    tree.assert_correctness();
+}
+
+/// Checks that trees are equal, and have equal hashes, exactly when their values are equal,
+/// regardless of their shapes and pending actions.
+pub fn check_eq_hash<T>()
+where
+    T: SomeTree<StdNum> + Eq + std::hash::Hash,
+{
+    fn hash<T: std::hash::Hash>(tree: &T) -> u64 {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        tree.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let len = 1_000;
+    let mut arr: Vec<i32> = (0..len as i32).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    let action = RevAffineAction {
+        to_reverse: true,
+        mul: 3,
+        add: -1,
+    };
+    tree.act_segment(action, 100..700);
+    arr[100..700].reverse();
+    for val in &mut arr[100..700] {
+        *val = action.act(*val);
+    }
+
+    let mut other: T = arr.iter().cloned().collect();
+    assert!(tree == other);
+    assert_eq!(hash(&tree), hash(&other));
+
+    other.slice(500..=500).delete();
+    assert!(tree != other);
+    other.slice(500..500).insert(arr[500] + 1).unwrap();
+    assert!(tree != other);
+
+    let prefix: T = arr[..len - 1].iter().cloned().collect();
+    assert!(tree != prefix);
+    assert_ne!(hash(&tree), hash(&prefix));
+    assert!(T::default() == T::default());
+    tree.assert_correctness();
+}
+
+/// Checks that trees are ordered lexicographically by their values, like vectors.
+pub fn check_ord<T>()
+where
+    T: SomeTree<StdNum> + Ord,
+{
+    let mut rng = rand::thread_rng();
+    let mut vecs: Vec<Vec<i32>> = (0..50)
+        .map(|_| {
+            let len = rng.gen_range(0..6);
+            (0..len).map(|_| rng.gen_range(0..3)).collect()
+        })
+        .collect();
+    let mut trees: Vec<T> = vecs
+        .iter()
+        .map(|vec| {
+            // leave pending actions in the tree
+            let mut tree: T = vec.iter().rev().map(|x| x - 1).collect();
+            tree.act_segment(
+                RevAffineAction {
+                    to_reverse: true,
+                    mul: 1,
+                    add: 1,
+                },
+                ..,
+            );
+            tree
+        })
+        .collect();
+    for (vec1, tree1) in vecs.iter().zip(trees.iter()) {
+        for (vec2, tree2) in vecs.iter().zip(trees.iter()) {
+            assert_eq!(tree1.cmp(tree2), vec1.cmp(vec2));
+            assert_eq!(tree1.partial_cmp(tree2), vec1.partial_cmp(vec2));
+        }
+    }
+
+    vecs.sort();
+    trees.sort();
+    for (vec, tree) in vecs.iter().zip(trees.iter_mut()) {
+        assert_eq!(&tree.iter().cloned().collect::<Vec<_>>(), vec);
+    }
+}
+
+/// Checks that building a tree from unsorted values sorts them stably, like sorting a vector.
+pub fn check_from_unsorted_iter<T>()
+where
+    T: SomeTree<example_data::PlainData<(i32, usize)>>,
+{
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..100);
+        let mut values: Vec<(i32, usize)> = (0..len).map(|i| (rng.gen_range(0..20), i)).collect();
+        if rng.gen() {
+            values.sort();
+        }
+        let tree = T::from_unsorted_iter::<i32, _>(values.clone());
+        tree.assert_correctness();
+        values.sort_by_key(|&(key, _)| key);
+        assert_eq!(tree.into_sorted_vec(), values);
+    }
+}
+
+/// Checks that deduplicating a tree with pending actions acts like deduplicating a vector.
+pub fn check_dedup<T>()
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..100);
+        let mut arr: Vec<i32> = (0..len).map(|_| rng.gen_range(0..4)).collect();
+        let mut tree: T = arr.iter().cloned().collect();
+
+        // leave pending actions in the tree
+        let action = random_action(&mut rng);
+        let range = random_range(arr.len());
+        tree.act_segment(action, range.clone());
+        if action.to_reverse {
+            arr[range.clone()].reverse();
+        }
+        for val in &mut arr[range] {
+            *val = action.act(*val);
+        }
+
+        if rng.gen() {
+            tree.dedup();
+            arr.dedup();
+        } else {
+            tree.dedup_by(|a, b| *a / 2 == *b / 2);
+            arr.dedup_by(|a, b| *a / 2 == *b / 2);
+        }
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+        assert_eq!(tree.segment_summary(..).sum, arr.iter().sum::<i32>());
+        tree.assert_correctness();
+    }
+}
+
+/// Checks that sampling a segment only chooses values from the segment, and eventually chooses
+/// all of them, with pending actions in the tree.
+#[cfg(feature = "rand")]
+pub fn check_sample_segment<T>()
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..100).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..30 {
+        let action = random_action(&mut rng);
+        let range = random_range(arr.len());
+        tree.act_segment(action, range.clone());
+        if action.to_reverse {
+            arr[range.clone()].reverse();
+        }
+        for val in &mut arr[range] {
+            *val = action.act(*val);
+        }
+
+        let start = rng.gen_range(0..=arr.len());
+        let range = start..arr.len().min(start + rng.gen_range(0..10));
+        let mut expected: Vec<i32> = arr[range.clone()].to_vec();
+        expected.sort();
+        expected.dedup();
+        let mut sampled = vec![];
+        for _ in 0..1000 {
+            sampled.extend(tree.sample_segment(range.clone(), &mut rng).cloned());
+        }
+        sampled.sort();
+        sampled.dedup();
+        assert_eq!(sampled, expected);
+    }
+    tree.assert_correctness();
+}
+
+/// Checks [`SomeTree::predecessor`] and [`SomeTree::successor`] against a sorted vector,
+/// with repeated keys.
+pub fn check_predecessor_successor<T>()
+where
+    T: SomeTree<example_data::PlainData<(i32, usize)>>,
+{
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..50);
+        let mut values: Vec<(i32, usize)> = (0..len).map(|i| (rng.gen_range(0..30), i)).collect();
+        values.sort_by_key(|&(key, _)| key);
+        let mut tree: T = values.iter().cloned().collect();
+        for key in -1..=30 {
+            let predecessor = values.iter().rfind(|(k, _)| *k < key);
+            assert_eq!(tree.predecessor(&key), predecessor);
+            let successor = values.iter().find(|(k, _)| *k > key);
+            assert_eq!(tree.successor(&key), successor);
+        }
+        tree.assert_correctness();
+    }
+}
+
+/// Checks that [`SomeTree::flush_segment`] and [`SomeTree::flush_actions`]
+/// keep the values and the summaries of the tree.
+pub fn check_flush_actions<T>()
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..100).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..100 {
+        let action = RevAffineAction {
+            to_reverse: rng.gen(),
+            mul: rng.gen_range(-2..=2),
+            add: rng.gen_range(-10..=10),
+        };
+        let range = random_range(arr.len());
+        tree.act_segment(action, range.clone());
+        let segment = &mut arr[range];
+        if action.to_reverse {
+            segment.reverse();
+        }
+        for val in segment {
+            *val = action.act(*val);
+        }
+
+        let range = random_range(arr.len());
+        tree.flush_segment(range.clone());
+        tree.assert_correctness();
+        assert_eq!(
+            tree.segment_summary(range.clone()).sum,
+            arr[range].iter().sum::<i32>()
+        );
+    }
+    tree.flush_actions();
+    tree.assert_correctness();
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+}
+
+/// Checks [`SomeTree::summary_between`] and [`SomeTree::act_between`] against a vector,
+/// with bookmarks at values and at empty positions, in either order.
+pub fn check_between<T>()
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..100).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    let random_bookmark = || {
+        let mut rng = rand::thread_rng();
+        let index = rng.gen_range(0..100);
+        if rng.gen() {
+            locators::Bookmark::at_empty(index)
+        } else {
+            locators::Bookmark::at_value(index)
+        }
+    };
+    // the range between two bookmarks, including the values that they are at
+    let span = |from: locators::Bookmark, to: locators::Bookmark| {
+        let end = |bookmark: locators::Bookmark| bookmark.index() + !bookmark.is_empty() as usize;
+        (from.index().min(to.index()), end(from).max(end(to)))
+    };
+    for _ in 0..100 {
+        let (from, to) = (random_bookmark(), random_bookmark());
+        let (start, end) = span(from, to);
+        let summary = tree.summary_between(&from, &to);
+        assert_eq!(summary.size(), end - start);
+        assert_eq!(summary.sum, arr[start..end].iter().sum::<i32>());
+
+        let (from, to) = (random_bookmark(), random_bookmark());
+        let (start, end) = span(from, to);
+        let add = rng.gen_range(-10..=10);
+        let action = RevAffineAction {
+            to_reverse: false,
+            mul: 1,
+            add,
+        };
+        tree.act_between(&from, &to, action);
+        for val in &mut arr[start..end] {
+            *val += add;
+        }
+    }
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    tree.assert_correctness();
+}
+
+/// Checks that extending and appending trees act like extending and appending vectors.
+pub fn check_extend_append<T>()
+where
+    T: ConcatenableTree<StdNum> + Extend<i32>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = vec![];
+    let mut tree: T = T::default();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..50);
+        let values: Vec<i32> = (0..len).map(|_| rng.gen_range(-100..100)).collect();
+        if rng.gen() {
+            tree.extend(values.iter().cloned());
+        } else {
+            let mut other: T = values.iter().cloned().collect();
+            tree.append(&mut other);
+            assert!(other.is_empty());
+        }
+        arr.extend(values);
+
+        // leave pending actions in the tree
+        let action = random_action(&mut rng);
+        let range = random_range(arr.len());
+        tree.act_segment(action, range.clone());
+        if action.to_reverse {
+            arr[range.clone()].reverse();
+        }
+        for val in &mut arr[range] {
+            *val = action.act(*val);
+        }
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    }
+    tree.assert_correctness();
+}
+
+/// Checks that inserting trees at arbitrary positions acts like splicing vectors,
+/// with pending actions in both trees.
+pub fn check_insert_tree_at<T>()
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = vec![];
+    let mut tree: T = T::default();
+    for _ in 0..100 {
+        let len = rng.gen_range(0..50);
+        let mut values: Vec<i32> = (0..len).map(|_| rng.gen_range(-100..100)).collect();
+        let mut other: T = values.iter().cloned().collect();
+        let action = random_action(&mut rng);
+        other.act_segment(action, ..);
+        if action.to_reverse {
+            values.reverse();
+        }
+        for val in &mut values {
+            *val = action.act(*val);
+        }
+
+        let index = rng.gen_range(0..=arr.len());
+        assert!(tree.insert_tree_at(index..index, other).is_ok());
+        arr.splice(index..index, values);
+        if !arr.is_empty() {
+            let index = rng.gen_range(0..arr.len());
+            assert!(tree.insert_tree_at(index, T::default()).is_err());
+        }
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    }
+    tree.assert_correctness();
+}
+
+/// Checks that [`ConcatenableTree::concat_all`] concatenates many small trees in order,
+/// including empty trees and trees with pending actions.
+pub fn check_concat_all<T>()
+where
+    T: ConcatenableTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    for num_trees in [0, 1, 2, 3, 100, 1000] {
+        let mut arr: Vec<i32> = vec![];
+        let mut trees: Vec<T> = vec![];
+        for _ in 0..num_trees {
+            let len = rng.gen_range(0..5);
+            let mut values: Vec<i32> = (0..len).map(|_| rng.gen_range(-100..100)).collect();
+            let mut tree: T = values.iter().cloned().collect();
+            let action = random_action(&mut rng);
+            tree.act_segment(action, ..);
+            if action.to_reverse {
+                values.reverse();
+            }
+            for val in &mut values {
+                *val = action.act(*val);
+            }
+            arr.extend(values);
+            trees.push(tree);
+        }
+        let mut tree = T::concat_all(trees);
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+        tree.assert_correctness();
+    }
+}
+
+/// Checks that snapshots keep the tree's values while the tree is modified on another thread.
+pub fn check_snapshot<T>()
+where
+    T: SomeTree<StdNum> + Send + 'static,
+{
+    let arr: Vec<i32> = (0..1000).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    tree.act_segment(
+        RevAffineAction {
+            to_reverse: true,
+            mul: 1,
+            add: 0,
+        },
+        ..,
+    );
+    let snapshot = tree.snapshot();
+    let writer = std::thread::spawn(move || {
+        tree.act_segment(
+            RevAffineAction {
+                to_reverse: false,
+                mul: 0,
+                add: 0,
+            },
+            ..,
+        );
+        tree
+    });
+    let mut tree = writer.join().unwrap();
+    tree.assert_correctness();
+    assert_eq!(tree.segment_summary(..).sum, 0);
+
+    let reversed: Vec<i32> = arr.iter().cloned().rev().collect();
+    assert_eq!(snapshot.iter().cloned().collect::<Vec<_>>(), reversed);
+    assert_eq!(snapshot.iter().len(), arr.len());
+    assert!(snapshot.iter().rev().eq(arr.iter()));
+    for i in 0..arr.len() {
+        assert_eq!(snapshot.get(i), Some(&reversed[i]));
+        let sum: i32 = reversed[i..].iter().sum();
+        assert_eq!(snapshot.segment_summary(i..).sum, sum);
+    }
+    assert_eq!(snapshot.get(arr.len()), None);
+}
+
+/// A single letter, for testing non-commutative summaries.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Letter(u8);
+
+/// A summary holding the concatenation of all the letters in a segment.
+/// Used for testing that the order of summaries is kept, including when reversing segments.
+/// It isn't `Copy`, so it also tests that the trees only clone summaries.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Concat {
+    bytes: Vec<u8>,
+}
+
+impl Concat {
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl std::ops::Add for Concat {
+    type Output = Self;
+    fn add(mut self, other: Self) -> Self {
+        self.bytes.extend_from_slice(&other.bytes);
+        self
+    }
+}
+
+impl SizedSummary for Concat {
+    fn size(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+impl ReverseSummary for Concat {
+    fn reverse(&mut self) {
+        self.bytes.reverse();
+    }
+}
+
+impl ToSummary<Concat> for Letter {
+    fn to_summary(&self) -> Concat {
+        Concat {
+            bytes: vec![self.0],
+        }
+    }
+}
+
+impl Acts<Letter> for RevAction {
+    fn act_inplace(&self, _letter: &mut Letter) {}
+}
+
+pub type ConcatData = (Letter, Concat, RevAction);
+
+/// Checks that a tree keeps the order of non-commutative summaries, by using string
+/// concatenation as the summary, and comparing against a plain vector.
+pub fn check_string_concatenation<T>(num_rounds: u32)
+where
+    T: SomeTree<ConcatData>,
+{
+    let mut rng = rand::thread_rng();
+    let mut model: Vec<u8> = b"the quick brown fox jumps over the lazy dog".to_vec();
+    let mut tree: T = model.iter().map(|&b| Letter(b)).collect();
+
+    for _ in 0..num_rounds {
+        match rng.gen_range(0..4) {
+            // reverse a segment
+            0 => {
+                let range = random_range(model.len());
+                tree.act_segment(RevAction { to_reverse: true }, range.clone());
+                model[range].reverse();
+            }
+            // query a segment
+            1 => {
+                let range = random_range(model.len());
+                let summary = tree.segment_summary(range.clone());
+                assert_eq!(summary.as_bytes(), &model[range]);
+            }
+            // insert a letter
+            2 => {
+                let letter = rng.gen_range(b'a'..=b'z');
+                let index = rng.gen_range(0..=model.len());
+                tree.slice(index..index).insert(Letter(letter)).unwrap();
+                model.insert(index, letter);
+            }
+            // delete a letter
+            3 if !model.is_empty() => {
+                let index = rng.gen_range(0..model.len());
+                let deleted = tree.slice(index..=index).delete();
+                assert_eq!(deleted, Some(Letter(model.remove(index))));
+            }
+            _ => {}
+        }
+        assert_eq!(tree.subtree_summary().as_bytes(), &model[..]);
+        tree.assert_correctness();
+    }
+}
+
+/// Checks the operations of [`persistent::PersistentTree`] against a plain vector,
+/// and checks that snapshots taken along the way don't change.
+pub fn check_persistent_snapshots(num_rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..200).collect();
+    let mut tree: persistent::PersistentTree<StdNum> = arr.iter().cloned().collect();
+    let mut snapshots = vec![];
+    for round in 0..num_rounds {
+        let i = rng.gen_range(0..=arr.len());
+        let j = rng.gen_range(0..=arr.len());
+        let range = i.min(j)..i.max(j);
+        match rng.gen_range(0..4) {
+            0 => {
+                let action = RevAffineAction {
+                    to_reverse: rng.gen(),
+                    mul: if rng.gen() { 1 } else { -1 },
+                    add: rng.gen_range(-10..=10),
+                };
+                tree.act_segment(action, range.clone());
+                let segment = &mut arr[range];
+                if action.to_reverse {
+                    segment.reverse();
+                }
+                for val in segment {
+                    *val = action.act(*val);
+                }
+            }
+            1 => {
+                let sum: i32 = arr[range.clone()].iter().sum();
+                assert_eq!(tree.segment_summary(range).sum, sum);
+            }
+            2 => {
+                let val = rng.gen_range(-100..100);
+                tree.insert(i..i, val);
+                arr.insert(i, val);
+            }
+            _ => {
+                let expected = (i < arr.len()).then(|| arr.remove(i));
+                assert_eq!(tree.delete(i..=i), expected);
+            }
+        }
+        if round % 10 == 0 {
+            snapshots.push((tree.clone(), arr.clone()));
+        }
+    }
+    tree.assert_correctness();
+    assert_eq!(tree.iter().collect::<Vec<_>>(), arr);
+    for (snapshot, arr) in snapshots {
+        snapshot.assert_correctness();
+        assert_eq!(snapshot.iter().collect::<Vec<_>>(), arr);
+    }
+}
+
+/// Checks the operations of [`arena::ArenaTree`] against a plain vector,
+/// including indexed actions, and checks that the slots of deleted nodes are reused.
+pub fn check_arena_tree(num_rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..200).collect();
+    let mut tree: arena::ArenaTree<(i32, PolyNum<2>, ProgressionAction)> =
+        arr.iter().cloned().collect();
+    let mut max_len = arr.len();
+    for _ in 0..num_rounds {
+        let i = rng.gen_range(0..=arr.len());
+        let range = random_range(arr.len());
+        match rng.gen_range(0..4) {
+            0 => {
+                let action = ProgressionAction {
+                    add: rng.gen_range(-10..=10),
+                    step: rng.gen_range(-3..=3),
+                };
+                tree.act_segment(action, range.clone());
+                for (k, val) in arr[range].iter_mut().enumerate() {
+                    *val += action.add + action.step * k as i32;
+                }
+            }
+            1 => {
+                let expected = arr[range.clone()]
+                    .iter()
+                    .fold(PolyNum::default(), |summary, value| {
+                        summary + value.to_summary()
+                    });
+                assert_eq!(tree.segment_summary(range), expected);
+            }
+            2 => {
+                let val = rng.gen_range(-100..100);
+                tree.insert(i..i, val);
+                arr.insert(i, val);
+            }
+            _ => {
+                let expected = (i < arr.len()).then(|| arr.remove(i));
+                assert_eq!(tree.delete(i..=i), expected);
+            }
+        }
+        max_len = max_len.max(arr.len());
+        assert_eq!(tree.num_slots(), max_len);
+    }
+    tree.assert_correctness();
+
+    // splitting moves the nodes of the smaller part into a new tree
+    let summarize = |values: &[i32]| {
+        values.iter().fold(PolyNum::default(), |summary, value| {
+            summary + value.to_summary()
+        })
+    };
+    for _ in 0..num_rounds / 10 {
+        let i = rng.gen_range(0..=arr.len());
+        let to_left: bool = rng.gen();
+        let mut walker = tree.search(i..i);
+        let part = if to_left {
+            walker.split_left()
+        } else {
+            walker.split_right()
+        }
+        .unwrap();
+        drop(walker);
+        tree.assert_correctness();
+        part.assert_correctness();
+        let (left, right) = arr.split_at(i);
+        let (part_values, rest_values) = if to_left {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        assert_eq!(part.segment_summary_imm(..), summarize(part_values));
+        assert_eq!(tree.segment_summary_imm(..), summarize(rest_values));
+        if to_left {
+            tree.concatenate_left(part);
+        } else {
+            tree.concatenate_right(part);
+        }
+    }
+    tree.assert_correctness();
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), arr);
+}
+
+/// Checks that serializing a tree and deserializing it gives back a valid tree,
+/// with the same values, and in the same shape.
+#[cfg(feature = "serde")]
+pub fn check_serde_round_trip<T>()
+where
+    T: SomeTree<StdNum> + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let mut rng = rand::thread_rng();
+    let arr: Vec<i32> = (0..1000).map(|_| rng.gen_range(-1000..1000)).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..20 {
+        let range = random_range(arr.len());
+        tree.segment_summary(range.clone());
+        tree.act_segment(
+            RevAffineAction {
+                to_reverse: false,
+                mul: 1,
+                add: rng.gen_range(-10..=10),
+            },
+            range,
+        );
+    }
+    let expected: Vec<i32> = tree.iter().cloned().collect();
+
+    // the pending actions are applied to the serialized values
+    let json = serde_json::to_string(&tree).unwrap();
+    let mut copy: T = serde_json::from_str(&json).unwrap();
+    copy.assert_correctness();
+    assert_eq!(copy.iter().cloned().collect::<Vec<_>>(), expected);
+    assert_eq!(serde_json::to_string(&copy).unwrap(), json);
+}
+
+/// Checks that collecting a parallel iterator into a tree, and iterating over a tree
+/// in parallel, both keep the values in order.
+#[cfg(feature = "rayon")]
+pub fn check_par_iter<T>()
+where
+    T: SomeTree<StdNum>
+        + rayon::iter::IntoParallelIterator<Item = i32>
+        + rayon::iter::FromParallelIterator<i32>,
+{
+    use rayon::prelude::*;
+    let mut rng = rand::thread_rng();
+    // large enough that the subtrees are built in parallel
+    let arr: Vec<i32> = (0..20_000).map(|_| rng.gen_range(-1000..1000)).collect();
+    let mut tree: T = arr.par_iter().cloned().collect();
+    tree.assert_correctness();
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+
+    tree.act_segment(
+        RevAffineAction {
+            to_reverse: false,
+            mul: 1,
+            add: 3,
+        },
+        100..15_000,
+    );
+    let expected: Vec<i32> = tree.iter().cloned().collect();
+    assert_eq!(tree.into_par_iter().collect::<Vec<_>>(), expected);
+}
+
+/// Checks that a [`coalesce::ActBuffer`] gives the same results as acting on the tree directly,
+/// when most of the actions are applied to the same few segments.
+pub fn check_act_buffer<T>()
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..100).collect();
+    let windows: Vec<Range<usize>> = (0..3).map(|_| random_range(arr.len())).collect();
+    let mut buffer = coalesce::ActBuffer::new(arr.iter().cloned().collect::<T>());
+    let mut num_acts = 0;
+    for _ in 0..1000 {
+        if rng.gen_range(0..10) == 0 {
+            let range = random_range(arr.len());
+            let sum = arr[range.clone()].iter().sum::<i32>();
+            assert_eq!(buffer.segment_summary(range).sum, sum);
+            continue;
+        }
+        // repeat the same window most of the time
+        let range = if rng.gen_range(0..10) == 0 {
+            windows[rng.gen_range(0..windows.len())].clone()
+        } else {
+            windows[0].clone()
+        };
+        let action = random_action(&mut rng);
+        buffer.act_segment(action, range.clone());
+        num_acts += 1;
+        let segment = &mut arr[range];
+        if action.to_reverse {
+            segment.reverse();
+        }
+        for val in segment {
+            *val = action.act(*val);
+        }
+    }
+    assert!(buffer.num_applied() <= num_acts);
+    let mut tree = buffer.into_inner();
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    tree.assert_correctness();
+}
+
+/// Checks that undoing all of the edits made through a [`History`](history::History)
+/// restores the original values, and that redoing them restores the final values.
+pub fn check_history<T>(rounds: u32)
+where
+    T: SomeTree<(i32, example_data::Size, example_data::RevAddAction)>,
+{
+    use example_data::{AddAction, RevAddAction};
+    let mut rng = rand::thread_rng();
+    let original: Vec<i32> = (0..100).collect();
+    let mut history = history::History::new(original.iter().cloned().collect::<T>());
+    let mut len = original.len();
+    for _ in 0..rounds {
+        match rng.gen_range(0..3) {
+            0 => {
+                let action = RevAddAction {
+                    to_reverse: RevAction {
+                        to_reverse: rng.gen(),
+                    },
+                    add: AddAction {
+                        add: rng.gen_range(-MAX_ADD..=MAX_ADD),
+                    },
+                };
+                history.act_segment(action, random_range(len));
+            }
+            1 => {
+                history.insert(rng.gen_range(0..=len), rng.gen_range(-MAX_ADD..=MAX_ADD));
+                len += 1;
+            }
+            _ => {
+                if history.delete(rng.gen_range(0..=len)).is_some() {
+                    len -= 1;
+                }
+            }
+        }
+    }
+    history.tree().assert_correctness();
+    let edited: Vec<i32> = history.tree().iter().cloned().collect();
+
+    let edits = history.undo_len();
+    for _ in 0..edits {
+        history.undo().unwrap();
+    }
+    assert_eq!(history.undo(), Err(history::HistoryError::NothingToUndo));
+    history.tree().assert_correctness();
+    assert_eq!(history.tree().iter().cloned().collect::<Vec<_>>(), original);
+
+    for _ in 0..edits {
+        history.redo().unwrap();
+    }
+    assert_eq!(history.redo(), Err(history::HistoryError::NothingToRedo));
+    history.tree().assert_correctness();
+    assert_eq!(history.tree().iter().cloned().collect::<Vec<_>>(), edited);
+}
+
+/// Checks that replaying the trace recorded by a [`Recorder`](trace::Recorder)
+/// reproduces the recorded tree, both in the same tree type and in a treap.
+pub fn check_trace<T>(rounds: u32)
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut recorder = trace::Recorder::new((0..100).collect::<T>());
+    let mut len = 100;
+    for _ in 0..rounds {
+        match rng.gen_range(0..4) {
+            0 => recorder.act_segment(random_action(&mut rng), random_range(len)),
+            1 => {
+                recorder.insert(rng.gen_range(0..=len), rng.gen_range(-MAX_ADD..=MAX_ADD));
+                len += 1;
+            }
+            2 => {
+                if recorder.delete(rng.gen_range(0..=len)).is_some() {
+                    len -= 1;
+                }
+            }
+            _ => {
+                let right = recorder.split_off(rng.gen_range(0..=len));
+                if rng.gen() {
+                    recorder.append(right);
+                } else {
+                    len -= right.subtree_summary().size();
+                }
+            }
+        }
+    }
+    let recorded: Vec<i32> = recorder.tree().iter().cloned().collect();
+    let (_, trace) = recorder.into_inner();
+
+    let mut tree: T = trace::replay(&trace);
+    tree.assert_correctness();
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), recorded);
+    let mut treap: treap::Treap<StdNum> = trace::replay(&trace);
+    treap.assert_correctness();
+    assert_eq!(treap.iter().cloned().collect::<Vec<_>>(), recorded);
+}
+
+/// Checks that the versions committed to a [`VersionedTree`](versioned::VersionedTree)
+/// keep their values, and that the diffs between them turn one into the other.
+pub fn check_versioned(rounds: u32) {
+    use algorithms::EditOp;
+    use example_data::{HashSummary, Unit};
+    use versioned::VersionedTree;
+    type D = (i32, HashSummary<1_000_003, 1_000_000_007>, Unit);
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..50).collect();
+    let mut versioned: VersionedTree<D> = VersionedTree::new(arr.iter().cloned().collect());
+    let mut committed = vec![(versioned.commit(), arr.clone())];
+    for _ in 0..rounds {
+        match rng.gen_range(0..4) {
+            0 => {
+                let index = rng.gen_range(0..=arr.len());
+                let value = rng.gen_range(0..10);
+                versioned.tree_mut().insert(index..index, value);
+                arr.insert(index, value);
+            }
+            1 if !arr.is_empty() => {
+                let index = rng.gen_range(0..arr.len());
+                let value = versioned.tree_mut().delete(index..=index);
+                assert_eq!(value, Some(arr.remove(index)));
+            }
+            2 => {
+                // discard the uncommitted edits
+                let (id, values) = &committed[rng.gen_range(0..committed.len())];
+                versioned.checkout(*id);
+                arr = values.clone();
+            }
+            _ => committed.push((versioned.commit(), arr.clone())),
+        }
+        assert_eq!(versioned.tree().iter().collect::<Vec<_>>(), arr);
+    }
+    assert_eq!(versioned.num_versions(), committed.len());
+
+    for _ in 0..100 {
+        let (from_id, from) = &committed[rng.gen_range(0..committed.len())];
+        let (to_id, to) = &committed[rng.gen_range(0..committed.len())];
+        let version = versioned.version(*to_id).unwrap();
+        assert_eq!(&version.iter().collect::<Vec<_>>(), to);
+        let mut res = vec![];
+        let mut cursor = 0;
+        for op in versioned.diff(*from_id, *to_id) {
+            match op {
+                EditOp::Retain(count) => {
+                    res.extend_from_slice(&from[cursor..cursor + count]);
+                    cursor += count;
+                }
+                EditOp::Delete(count) => cursor += count,
+                EditOp::Insert(values) => res.extend(values),
+            }
+        }
+        assert_eq!(cursor, from.len());
+        assert_eq!(&res, to);
+    }
+}
+
+/// Checks a [`Rope`](collections::Rope) against a [`String`], with random edits and queries.
+pub fn check_rope(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let alphabet: Vec<char> = "ab\nçΩ€🌳".chars().collect();
+    let random_text = |rng: &mut rand::prelude::ThreadRng| -> String {
+        let len = rng.gen_range(0..300);
+        (0..len)
+            .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+            .collect()
+    };
+    let mut text = random_text(&mut rng);
+    let mut rope = collections::Rope::from(text.as_str());
+    for _ in 0..rounds {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let len = chars.len();
+        let byte_of = |i: usize| chars.get(i).map_or(text.len(), |&(b, _)| b);
+        let range = random_range(len);
+        match rng.gen_range(0..5) {
+            0 => {
+                let inserted = random_text(&mut rng);
+                let at = rng.gen_range(0..=len);
+                rope.insert(at, &inserted);
+                text.insert_str(byte_of(at), &inserted);
+            }
+            1 => {
+                rope.remove(range.clone());
+                text.replace_range(byte_of(range.start)..byte_of(range.end), "");
+            }
+            2 => {
+                assert_eq!(
+                    rope.slice(range.clone()),
+                    text[byte_of(range.start)..byte_of(range.end)]
+                );
+            }
+            3 => {
+                let at = rng.gen_range(0..=len);
+                assert_eq!(rope.char_to_byte(at), byte_of(at));
+                assert_eq!(rope.byte_to_char(byte_of(at)), at);
+                let line = text[..byte_of(at)].matches('\n').count();
+                assert_eq!(rope.char_to_line(at), line);
+                let line_start = text[..byte_of(at)].rfind('\n').map_or(0, |b| b + 1);
+                assert_eq!(rope.line_to_char(line), text[..line_start].chars().count());
+            }
+            _ => {
+                let at = rng.gen_range(0..=len);
+                let tail = rope.split_off(at);
+                assert_eq!(String::from(tail.clone()), text[byte_of(at)..]);
+                rope.append(tail);
+            }
+        }
+        assert_eq!(rope.len_bytes(), text.len());
+        assert_eq!(rope.len_lines(), text.matches('\n').count() + 1);
+    }
+    rope.tree().assert_correctness();
+    assert!(rope
+        .chunks()
+        .all(|chunk| chunk.len() <= collections::rope::MAX_CHUNK));
+    assert_eq!(rope.chars().collect::<String>(), text);
+}
+
+/// Checks that the handles of an [`OrderList`](collections::OrderList) compare by their
+/// order in the list, while elements are inserted and removed at random.
+pub fn check_order_list(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut list = collections::OrderList::new();
+    let mut handles = vec![list.push_back()];
+    for _ in 0..rounds {
+        let index = rng.gen_range(0..handles.len());
+        match rng.gen_range(0..5) {
+            // repeated insertions at the same place force relabeling
+            0 | 1 => {
+                let new = list.insert_after(&handles[index]);
+                handles.insert(index + 1, new);
+            }
+            2 => {
+                let new = list.insert_before(&handles[index]);
+                handles.insert(index, new);
+            }
+            3 => handles.push(list.push_back()),
+            _ if handles.len() > 1 => list.remove(&handles.remove(index)),
+            _ => handles.insert(0, list.push_front()),
+        }
+    }
+    list.assert_correctness();
+    assert_eq!(list.len(), handles.len());
+    assert!(handles.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(list.iter().eq(handles.iter()));
+}
+
+/// Checks a [`LinkCutForest`](link_cut::LinkCutForest) against a forest stored as
+/// adjacency lists, with random links, cuts, path queries and path actions.
+pub fn check_link_cut(rounds: u32) {
+    const N: usize = 50;
+    let mut rng = rand::thread_rng();
+    let mut values: Vec<i32> = (0..N).map(|_| rng.gen_range(-MAX_ADD..=MAX_ADD)).collect();
+    let mut forest: link_cut::LinkCutForest<StdNum> = values.iter().cloned().collect();
+    let mut edges: Vec<Vec<usize>> = vec![vec![]; N];
+
+    // the path from `u` to `v` in the model, if they are connected
+    let path = |edges: &Vec<Vec<usize>>, u: usize, v: usize| -> Option<Vec<usize>> {
+        let mut father = vec![None; N];
+        let mut stack = vec![u];
+        father[u] = Some(u);
+        while let Some(w) = stack.pop() {
+            for &x in &edges[w] {
+                if father[x].is_none() {
+                    father[x] = Some(w);
+                    stack.push(x);
+                }
+            }
+        }
+        father[v]?;
+        let mut res = vec![v];
+        while *res.last().unwrap() != u {
+            res.push(father[*res.last().unwrap()].unwrap());
+        }
+        Some(res)
+    };
+
+    for _ in 0..rounds {
+        let (u, v) = (rng.gen_range(0..N), rng.gen_range(0..N));
+        let connected = path(&edges, u, v);
+        match rng.gen_range(0..4) {
+            0 => {
+                let expected = match connected {
+                    None => Ok(()),
+                    Some(_) => Err(link_cut::LinkCutError::AlreadyConnected),
+                };
+                let root = forest.find_root(u);
+                assert_eq!(forest.link(u, v), expected);
+                if connected.is_some() {
+                    // a failed link doesn't reroot the tree
+                    assert_eq!(forest.find_root(u), root);
+                }
+                if connected.is_none() {
+                    edges[u].push(v);
+                    edges[v].push(u);
+                }
+            }
+            1 => {
+                // cut an existing edge, or try to cut a missing one
+                let v = if !edges[u].is_empty() && rng.gen() {
+                    edges[u][rng.gen_range(0..edges[u].len())]
+                } else {
+                    v
+                };
+                let exists = edges[u].contains(&v);
+                let expected = if exists {
+                    Ok(())
+                } else {
+                    Err(link_cut::LinkCutError::NoSuchEdge)
+                };
+                assert_eq!(forest.cut(u, v), expected);
+                edges[u].retain(|&x| x != v);
+                edges[v].retain(|&x| x != u);
+            }
+            2 => {
+                let expected = connected.map(|path| path.iter().map(|&x| values[x]).sum());
+                assert_eq!(forest.path_summary(u, v).map(|s| s.sum), expected);
+                assert_eq!(forest.connected(u, v), expected.is_some());
+            }
+            _ => {
+                let action = RevAffineAction {
+                    to_reverse: false,
+                    mul: if rng.gen() { 1 } else { -1 },
+                    add: rng.gen_range(-MAX_ADD..=MAX_ADD),
+                };
+                let expected = match connected {
+                    Some(_) => Ok(()),
+                    None => Err(link_cut::LinkCutError::NotConnected),
+                };
+                assert_eq!(forest.act_path(u, v, action), expected);
+                for x in connected.into_iter().flatten() {
+                    values[x] = action.act(values[x]);
+                }
+            }
+        }
+    }
+    forest.assert_correctness();
+    for (vertex, value) in values.into_iter().enumerate() {
+        assert_eq!(*forest.value(vertex), value);
+    }
+}
+
+/// Checks a [`TreeVec`](collections::TreeVec) against a [`Vec`], with random operations.
+pub fn check_tree_vec(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut vec: Vec<i32> = (0..100).collect();
+    let mut tree_vec: collections::TreeVec<i32> = vec.iter().cloned().collect();
+    for _ in 0..rounds {
+        let len = vec.len();
+        let value = rng.gen_range(-MAX_ADD..=MAX_ADD);
+        match rng.gen_range(0..7) {
+            0 => {
+                let index = rng.gen_range(0..=len);
+                vec.insert(index, value);
+                tree_vec.insert(index, value);
+            }
+            1 if len > 0 => {
+                let index = rng.gen_range(0..len);
+                assert_eq!(tree_vec.remove(index), vec.remove(index));
+            }
+            2 => {
+                vec.push(value);
+                tree_vec.push(value);
+            }
+            3 => assert_eq!(tree_vec.pop(), vec.pop()),
+            4 => {
+                let index = rng.gen_range(0..=len);
+                assert_eq!(tree_vec.get(index), vec.get(index));
+                if let Some(x) = tree_vec.get_mut(index) {
+                    *x += value;
+                    vec[index] += value;
+                }
+            }
+            5 => {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len);
+                let mut range = tree_vec.range(start..end);
+                let mut expected = vec[start..end].iter();
+                loop {
+                    assert_eq!(range.len(), expected.len());
+                    let next = if rng.gen() {
+                        (range.next(), expected.next())
+                    } else {
+                        (range.next_back(), expected.next_back())
+                    };
+                    assert_eq!(next.0, next.1);
+                    if next.0.is_none() {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                let at = rng.gen_range(0..=len);
+                let mut tail = tree_vec.split_off(at);
+                assert!(tail.iter().eq(vec[at..].iter()));
+                tree_vec.append(&mut tail);
+                assert!(tail.is_empty());
+            }
+        }
+        assert_eq!(tree_vec.len(), vec.len());
+    }
+    for x in tree_vec.iter_mut() {
+        *x *= 2;
+    }
+    tree_vec.tree().assert_correctness();
+    let doubled: Vec<i32> = vec.iter().map(|x| x * 2).collect();
+    assert_eq!(Vec::from(tree_vec), doubled);
+}
+
+/// Checks a [`TreeHeap`](collections::TreeHeap) against a sorted [`Vec`],
+/// with random pushes, pops and melds.
+pub fn check_tree_heap(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut heap = collections::TreeHeap::new();
+    let mut sorted: Vec<i32> = vec![];
+    for _ in 0..rounds {
+        match rng.gen_range(0..5) {
+            0 | 1 => {
+                let value = rng.gen_range(-MAX_ADD..=MAX_ADD);
+                heap.push(value);
+                let index = sorted.partition_point(|&x| x < value);
+                sorted.insert(index, value);
+            }
+            2 => {
+                assert_eq!(
+                    heap.pop_min(),
+                    (!sorted.is_empty()).then(|| sorted.remove(0))
+                );
+            }
+            3 => assert_eq!(heap.pop_max(), sorted.pop()),
+            _ => {
+                let len = rng.gen_range(0..20);
+                let other: Vec<i32> = (0..len)
+                    .map(|_| rng.gen_range(-MAX_ADD..=MAX_ADD))
+                    .collect();
+                heap.meld(other.iter().cloned().collect());
+                sorted.extend(other);
+                sorted.sort();
+            }
+        }
+        assert_eq!(heap.len(), sorted.len());
+        assert_eq!(heap.peek_min(), sorted.first());
+        assert_eq!(heap.peek_max(), sorted.last());
+    }
+    heap.tree().assert_correctness();
+    assert!(heap.iter().eq(sorted.iter()));
+}
+
+/// Checks a [`SparseSegmentTree`](collections::SparseSegmentTree) against a [`Vec`],
+/// with random actions, assignments and queries.
+pub fn check_sparse_segment_tree(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut arr = vec![7; 1000];
+    let mut tree = collections::SparseSegmentTree::<StdNum>::new(arr.len() as u64, 7);
+    for _ in 0..rounds {
+        let range = random_range(arr.len());
+        let range64 = range.start as u64..range.end as u64;
+        match rng.gen_range(0..4) {
+            0 => {
+                let action = random_action(&mut rng);
+                // the sequence is uniform inside runs, so reversals are tested as well
+                tree.act_segment(action, range64);
+                let segment = &mut arr[range];
+                if action.to_reverse {
+                    segment.reverse();
+                }
+                for val in segment {
+                    *val = action.act(*val);
+                }
+            }
+            1 => {
+                let index = rng.gen_range(0..arr.len());
+                let value = rng.gen_range(-MAX_ADD..=MAX_ADD);
+                tree.set(index as u64, value);
+                arr[index] = value;
+            }
+            2 => {
+                let index = rng.gen_range(0..arr.len());
+                assert_eq!(tree.get(index as u64), arr[index]);
+            }
+            _ => {
+                let summary = tree.segment_summary(range64);
+                assert_eq!(summary.sum, arr[range.clone()].iter().sum::<i32>());
+                assert_eq!(summary.size, range.len() as i32);
+                assert_eq!(summary.max, arr[range].iter().max().cloned());
+            }
+        }
+    }
+    assert!(tree.num_runs() <= arr.len());
+}
+
+/// Checks the run-length encoded sequence against a vector, and checks that
+/// its adjacent runs are always merged.
+pub fn check_rle_vec(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..200).map(|i| i / 20).collect();
+    let mut rle: collections::RleVec<StdNum> = arr.iter().cloned().collect();
+    for _ in 0..rounds {
+        let range = random_range(arr.len());
+        let range64 = range.start as u64..range.end as u64;
+        let value = rng.gen_range(0..3);
+        match rng.gen_range(0..5) {
+            0 => {
+                let action = RevAffineAction {
+                    to_reverse: rng.gen(),
+                    mul: 1,
+                    add: rng.gen_range(-1..=1),
+                };
+                rle.act_segment(action, range64);
+                let segment = &mut arr[range];
+                if action.to_reverse {
+                    segment.reverse();
+                }
+                for val in segment {
+                    *val = action.act(*val);
+                }
+            }
+            1 if !arr.is_empty() => {
+                let index = rng.gen_range(0..arr.len());
+                rle.set(index as u64, value);
+                arr[index] = value;
+            }
+            2 => {
+                let count = rng.gen_range(0..30);
+                rle.insert_run(range.start as u64, value, count as u64);
+                arr.splice(range.start..range.start, vec![value; count]);
+            }
+            3 => {
+                rle.remove(range64);
+                arr.drain(range);
+            }
+            _ => {
+                let summary = rle.segment_summary(range64);
+                assert_eq!(summary.sum, arr[range.clone()].iter().sum::<i32>());
+                assert_eq!(summary.max, arr[range].iter().max().cloned());
+            }
+        }
+        assert_eq!(rle.len(), arr.len() as u64);
+        // actions are injective, so runs that were merged can't become equal inside of a segment
+        let runs: Vec<(i32, u64)> = rle.runs().map(|(value, count)| (*value, count)).collect();
+        let mut expected: Vec<(i32, u64)> = vec![];
+        for &value in &arr {
+            match expected.last_mut() {
+                Some((last, count)) if *last == value => *count += 1,
+                _ => expected.push((value, 1)),
+            }
+        }
+        assert_eq!(runs, expected);
+    }
+}
+
+/// Checks the [`collections::ChunkedVec`] against a vector, with actions that reverse segments,
+/// and with indexed actions, which have to be applied to the values of a chunk by their offsets.
+pub fn check_chunked_vec(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..200).collect();
+    let mut vec: collections::ChunkedVec<StdNum, 4> = arr.iter().cloned().collect();
+    for _ in 0..rounds {
+        let range = random_range(arr.len());
+        match rng.gen_range(0..4) {
+            0 => {
+                let action = RevAffineAction {
+                    to_reverse: rng.gen(),
+                    mul: 1,
+                    add: rng.gen_range(-10..=10),
+                };
+                vec.act_segment(action, range.clone());
+                let segment = &mut arr[range];
+                if action.to_reverse {
+                    segment.reverse();
+                }
+                for val in segment {
+                    *val = action.act(*val);
+                }
+            }
+            1 => {
+                let value = rng.gen_range(0..100);
+                vec.insert(range.start, value);
+                arr.insert(range.start, value);
+            }
+            2 if !arr.is_empty() => {
+                let index = rng.gen_range(0..arr.len());
+                assert_eq!(vec.remove(index), arr.remove(index));
+            }
+            _ => {
+                let summary = vec.segment_summary(range.clone());
+                assert_eq!(summary.sum, arr[range.clone()].iter().sum::<i32>());
+                assert_eq!(summary.max, arr[range].iter().max().cloned());
+            }
+        }
+        assert_eq!(vec.len(), arr.len());
+    }
+    assert_eq!(vec.iter().cloned().collect::<Vec<_>>(), arr);
+
+    let mut arr: Vec<i32> = (0..200).collect();
+    let mut vec: collections::ChunkedVec<ProgressionNum, 4> = arr.iter().cloned().collect();
+    for _ in 0..rounds {
+        let action = ProgressionAction {
+            add: rng.gen_range(-10..=10),
+            step: rng.gen_range(-3..=3),
+        };
+        let range = random_range(200);
+        for (i, value) in arr[range.clone()].iter_mut().enumerate() {
+            *value += action.add + action.step * i as i32;
+        }
+        vec.act_segment(action, range);
+
+        let index = rng.gen_range(0..200);
+        assert_eq!(vec.get(index), Some(arr[index]));
+        let range = random_range(200);
+        let expected = arr[range.clone()]
+            .iter()
+            .fold(PolyNum::default(), |summary, value| {
+                summary + value.to_summary()
+            });
+        assert_eq!(vec.segment_summary(range), expected);
+    }
+    assert_eq!(vec.iter().cloned().collect::<Vec<_>>(), arr);
+}
+
+/// Checks the [`collections::SmallTree`] against a vector, while it moves its values
+/// between a vector and a tree.
+pub fn check_small_tree<T>(rounds: u32)
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = vec![];
+    let mut seq: collections::SmallTree<StdNum, T, 8> = collections::SmallTree::new();
+    for _ in 0..rounds {
+        let range = random_range(arr.len());
+        match rng.gen_range(0..5) {
+            0 => {
+                let action = random_action(&mut rng);
+                seq.act_segment(action, range.clone());
+                let segment = &mut arr[range];
+                if action.to_reverse {
+                    segment.reverse();
+                }
+                for val in segment {
+                    *val = action.act(*val);
+                }
+            }
+            // keep the length around the threshold
+            1 | 2 if arr.len() < 12 => {
+                let value = rng.gen_range(0..100);
+                seq.insert(range.start, value);
+                arr.insert(range.start, value);
+            }
+            1..=3 if !arr.is_empty() => {
+                let index = rng.gen_range(0..arr.len());
+                assert_eq!(seq.remove(index), arr.remove(index));
+            }
+            _ => {
+                let summary = seq.segment_summary(range.clone());
+                assert_eq!(summary.sum, arr[range.clone()].iter().sum::<i32>());
+                assert_eq!(summary.max, arr[range].iter().max().cloned());
+            }
+        }
+        assert_eq!(seq.len(), arr.len());
+        assert!(seq.is_inline() || arr.len() > 4);
+        assert!(!seq.is_inline() || arr.len() <= 8);
+    }
+    assert_eq!(seq.iter().cloned().collect::<Vec<_>>(), arr);
+}
+
+/// Checks the [`collections::compat::BTreeMap`] against the standard [`std::collections::BTreeMap`].
+pub fn check_compat_btree_map(rounds: u32) {
+    use std::collections::BTreeMap;
+    let mut rng = rand::thread_rng();
+    let mut expected: BTreeMap<i32, i32> = BTreeMap::new();
+    let mut map: collections::compat::BTreeMap<i32, i32> = collections::compat::BTreeMap::new();
+    for _ in 0..rounds {
+        let key = rng.gen_range(0..100);
+        let value = rng.gen_range(0..100);
+        match rng.gen_range(0..7) {
+            0 => assert_eq!(map.insert(key, value), expected.insert(key, value)),
+            1 => assert_eq!(map.remove(&key), expected.remove(&key)),
+            2 => {
+                *map.entry(key).and_modify(|v| *v += value).or_default() += 1;
+                *expected.entry(key).and_modify(|v| *v += value).or_default() += 1;
+            }
+            3 => {
+                let end = rng.gen_range(key..=100);
+                assert!(map.range(key..end).eq(expected.range(key..end)));
+                assert!(map.range(..=key).rev().eq(expected.range(..=key).rev()));
+                assert_eq!(map.range(key..).len(), expected.range(key..).count());
+            }
+            4 => {
+                map.retain(|k, v| (*k + *v) % 5 != 0);
+                expected.retain(|k, v| (*k + *v) % 5 != 0);
+            }
+            5 => {
+                let mut right = map.split_off(&key);
+                let mut expected_right = expected.split_off(&key);
+                assert!(right.iter().eq(expected_right.iter()));
+                map.append(&mut right);
+                expected.append(&mut expected_right);
+            }
+            _ => {
+                assert_eq!(map.get(&key), expected.get(&key));
+                assert_eq!(map.first_key_value(), expected.first_key_value());
+                assert_eq!(map.last_key_value(), expected.last_key_value());
+            }
+        }
+        assert_eq!(map.len(), expected.len());
+    }
+    assert!(map.iter().rev().eq(expected.iter().rev()));
+    assert!(map.keys().rev().eq(expected.keys().rev()));
+    assert_eq!(map.values().len(), expected.values().len());
+    for (index, key) in expected.keys().enumerate() {
+        assert_eq!(map.index_of(key), Some(index));
+    }
+    assert!(map.into_iter().eq(expected));
+}
+
+/// Checks that [`algorithms::diff`] returns a shortest edit script, by applying it
+/// and by comparing its length against the longest common subsequence.
+pub fn check_diff(rounds: u32) {
+    use algorithms::EditOp;
+    use example_data::{HashSummary, Unit};
+    type D = (i32, HashSummary<1_000_003, 1_000_000_007>, Unit);
+    let mut rng = rand::thread_rng();
+    for _ in 0..rounds {
+        let len = rng.gen_range(0..40);
+        let a_values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..4)).collect();
+        // b is a after a few random edits
+        let mut b_values = a_values.clone();
+        for _ in 0..rng.gen_range(0..5) {
+            let index = rng.gen_range(0..=b_values.len());
+            if index < b_values.len() && rng.gen() {
+                b_values.remove(index);
+            } else {
+                b_values.insert(index, rng.gen_range(0..4));
+            }
+        }
+        let mut a: treap::Treap<D> = a_values.iter().cloned().collect();
+        let mut b: treap::Treap<D> = b_values.iter().cloned().collect();
+        let ops = algorithms::diff(&mut a, &mut b);
+
+        let mut res = vec![];
+        let mut cursor = 0;
+        let mut edits = 0;
+        for op in ops {
+            match op {
+                EditOp::Retain(count) => {
+                    res.extend_from_slice(&a_values[cursor..cursor + count]);
+                    cursor += count;
+                }
+                EditOp::Delete(count) => {
+                    cursor += count;
+                    edits += count;
+                }
+                EditOp::Insert(values) => {
+                    edits += values.len();
+                    res.extend(values);
+                }
+            }
+        }
+        res.extend_from_slice(&a_values[cursor..]);
+        assert_eq!(cursor, a_values.len());
+        assert_eq!(res, b_values);
+
+        let (n, m) = (a_values.len(), b_values.len());
+        let mut lcs = vec![vec![0; m + 1]; n + 1];
+        for i in 0..n {
+            for j in 0..m {
+                lcs[i + 1][j + 1] = if a_values[i] == b_values[j] {
+                    lcs[i][j] + 1
+                } else {
+                    lcs[i][j + 1].max(lcs[i + 1][j])
+                };
+            }
+        }
+        assert_eq!(edits, n + m - 2 * lcs[n][m]);
+    }
+}
+
+/// Checks the [`algorithms`] module against brute force, on random sequences with repetitions.
+pub fn check_algorithms(rounds: u32) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..rounds {
+        let len = rng.gen_range(0..60);
+        let values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..20)).collect();
+
+        let inversions = (0..len)
+            .flat_map(|j| (0..j).map(move |i| (i, j)))
+            .filter(|&(i, j)| values[i] > values[j])
+            .count();
+        assert_eq!(algorithms::count_inversions(&values), inversions);
+
+        let window = rng.gen_range(1..=10);
+        let k = rng.gen_range(0..window);
+        let expected: Vec<&i32> = values
+            .windows(window)
+            .map(|window| {
+                let mut sorted: Vec<&i32> = window.iter().collect();
+                sorted.sort();
+                sorted[k]
+            })
+            .collect();
+        assert_eq!(algorithms::sliding_window_kth(&values, window, k), expected);
+
+        let queries: Vec<(usize, usize)> = (0..20)
+            .map(|_| (rng.gen_range(0..=len), rng.gen_range(0..=len)))
+            .collect();
+        let expected: Vec<Option<&i32>> = queries
+            .iter()
+            .map(|&(prefix, k)| {
+                let mut sorted: Vec<&i32> = values[..prefix].iter().collect();
+                sorted.sort();
+                sorted.get(k).cloned()
+            })
+            .collect();
+        assert_eq!(algorithms::prefix_kth(&values, &queries), expected);
+    }
+}
+
+/// Checks that stepping a walker back and forth with [`SomeWalker::step_next`] and
+/// [`SomeWalker::step_prev`], from a random starting position, visits the values in order.
+pub fn check_walker_steps<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let arr: Vec<_> = (0..200).collect();
+    let n = arr.len();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        // `Ok(i)` is the position of the `i`-th value,
+        // and `Err(i)` is the empty position before the `i`-th value.
+        let start = rng.gen_range(0..=n);
+        let mut position: Result<usize, usize> = Err(start);
+        let mut walker = tree.search(start..start);
+        for _ in 0..50 {
+            if rng.gen() {
+                position = match position {
+                    Ok(i) if i + 1 < n => Ok(i + 1),
+                    Err(i) if i < n => Ok(i),
+                    _ => Err(n),
+                };
+                assert_eq!(walker.step_next(), position.ok().map(|i| &arr[i]));
+            } else {
+                position = match position {
+                    Ok(i) | Err(i) if i > 0 => Ok(i - 1),
+                    _ => Err(0),
+                };
+                assert_eq!(walker.step_prev(), position.ok().map(|i| &arr[i]));
+            }
+        }
+    }
+    tree.assert_correctness();
+}
+
+/// Checks that [`Bookmark`](locators::Bookmark)s, updated after every insertion and removal,
+/// keep locating the same values, using values that are all distinct.
+pub fn check_bookmarks<T>(num_rounds: u32)
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..100).collect();
+    let mut next_value = arr.len() as i32;
+    let mut tree: T = arr.iter().cloned().collect();
+    let mut bookmarks: Vec<(locators::Bookmark, i32)> = vec![];
+    for _ in 0..num_rounds {
+        match rng.gen_range(0..3) {
+            0 if !arr.is_empty() => {
+                let index = rng.gen_range(0..arr.len());
+                let walker = tree.search(index);
+                bookmarks.push((walker.bookmark(), arr[index]));
+            }
+            1 => {
+                let index = rng.gen_range(0..=arr.len());
+                let count = rng.gen_range(1..4);
+                for i in 0..count {
+                    tree.search(index + i..index + i)
+                        .insert(next_value)
+                        .unwrap();
+                    arr.insert(index + i, next_value);
+                    next_value += 1;
+                }
+                for (bookmark, _) in bookmarks.iter_mut() {
+                    bookmark.on_insert(index, count);
+                }
+            }
+            _ => {
+                let range = random_range(arr.len());
+                tree.delete_segment(range.clone());
+                arr.drain(range.clone());
+                bookmarks.retain_mut(|(bookmark, _)| {
+                    let expected = if range.contains(&bookmark.index()) {
+                        Err(locators::BookmarkError::ValueRemoved)
+                    } else {
+                        Ok(())
+                    };
+                    let res = bookmark.on_remove(range.clone());
+                    assert_eq!(res, expected);
+                    res.is_ok()
+                });
+            }
+        }
+        for (bookmark, value) in bookmarks.iter() {
+            assert_eq!(tree.search(*bookmark).value(), Some(value));
+        }
+    }
+    tree.assert_correctness();
+}
+
+/// Checks that [`SomeWalker::peek_left`] and [`SomeWalker::peek_right`] return the values that
+/// going down would reach, including pending actions, and don't move the walker.
+pub fn check_peek<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut tree: T = (0..200).collect();
+    for _ in 0..num_rounds {
+        tree.act_segment(random_action(&mut rng), random_range(200));
+        let mut walker = tree.search(rng.gen_range(0..200));
+        let depth = walker.depth();
+        let left = walker.peek_left().cloned();
+        let right = walker.peek_right().cloned();
+        assert_eq!(walker.depth(), depth);
+        if walker.go_left().is_ok() {
+            assert_eq!(walker.value().cloned(), left);
+            walker.go_up().unwrap();
+        }
+        if walker.go_right().is_ok() {
+            assert_eq!(walker.value().cloned(), right);
+        }
+    }
+    tree.assert_correctness();
+}
+
+/// Checks that taking a subtree out with [`SplittableWalker::take_subtree`], and putting it back
+/// elsewhere with [`SplittableWalker::put_subtree`], moves the right values,
+/// and keeps both trees correct.
+pub fn check_subtree_surgery<T>(num_rounds: u32)
+where
+    T: SplittableTree<StdNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..200).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let mut walker = tree.splittable_walker();
+        for _ in 0..rng.gen_range(0..5) {
+            let _ = if rng.gen() {
+                walker.go_left()
+            } else {
+                walker.go_right()
+            };
+        }
+        let start = walker.far_left_summary().size();
+        let subtree = walker.take_subtree();
+        drop(walker);
+        let len = subtree.subtree_summary().size();
+        let taken: Vec<i32> = arr.drain(start..start + len).collect();
+        subtree.assert_correctness();
+        tree.assert_correctness();
+        assert_eq!(tree.subtree_summary().size(), arr.len());
+
+        let index = rng.gen_range(0..=arr.len());
+        let mut walker = tree.splittable_search(index..index);
+        assert!(walker.put_subtree(subtree).is_ok());
+        drop(walker);
+        arr.splice(index..index, taken);
+        tree.assert_correctness();
+    }
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), arr);
+}
+
+/// Checks that moving a walker with [`SomeWalker::go_to_index`] and [`SomeWalker::go_to_key`]
+/// from arbitrary positions reaches the same values as searching from the root.
+pub fn check_finger_search<T>(num_rounds: u32)
+where
+    T: SomeTree<SizeData<i32>>,
+{
+    let mut rng = rand::thread_rng();
+    // sorted, with repeated keys
+    let arr: Vec<i32> = (0..300).map(|x| x / 3 * 2).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    let mut walker = tree.walker();
+    for _ in 0..num_rounds {
+        let index = rng.gen_range(0..arr.len() + 5);
+        let res = walker.go_to_index(index);
+        assert_eq!(res.is_ok(), index < arr.len());
+        assert_eq!(walker.value(), arr.get(index));
+        if res.is_ok() {
+            assert_eq!(walker.left_summary().size(), index);
+        }
+
+        let key = rng.gen_range(-5..arr.len() as i32);
+        let res = walker.go_to_key(&key);
+        let position = arr.partition_point(|&x| x < key);
+        assert_eq!(res.is_ok(), arr.contains(&key));
+        match res {
+            Ok(()) => assert_eq!(walker.value(), Some(&key)),
+            Err(_) => assert_eq!(walker.left_summary().size(), position),
+        }
+    }
+    drop(walker);
+    tree.assert_correctness();
+}
+
+type ProgressionNum = (i32, PolyNum<2>, ProgressionAction);
+
+/// Checks actions that depend on the index, by comparing against a vector.
+/// Also checks the immutable segment queries if `immutable_queries` is true.
+pub fn check_indexed_actions<T>(num_rounds: u32, immutable_queries: bool)
+where
+    T: SomeTree<ProgressionNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..200).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let action = ProgressionAction {
+            add: rng.gen_range(-10..=10),
+            step: rng.gen_range(-3..=3),
+        };
+        let range = random_range(200);
+        for (i, value) in arr[range.clone()].iter_mut().enumerate() {
+            *value += action.add + action.step * i as i32;
+        }
+        tree.act_segment(action, range);
+
+        // move a value, so that actions are pushed down through different shapes
+        let (from, to) = (rng.gen_range(0..200), rng.gen_range(0..200));
+        let value = arr.remove(from);
+        arr.insert(to, value);
+        assert_eq!(tree.search(from).delete(), Some(value));
+        tree.search(to..to).insert(value).unwrap();
+
+        let range = random_range(200);
+        let expected = arr[range.clone()]
+            .iter()
+            .fold(PolyNum::default(), |summary, value| {
+                summary + value.to_summary()
+            });
+        if immutable_queries {
+            assert_eq!(tree.segment_summary_imm(range.clone()), expected);
+        }
+        assert_eq!(tree.segment_summary(range), expected);
+    }
+    assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    tree.assert_correctness();
+}
+
+/// Checks [`SomeTree::unact_segment`] and [`SomeTree::segment_summary_after_act`],
+/// by comparing against a vector.
+pub fn check_unact_segment<T>(num_rounds: u32)
+where
+    T: SomeTree<(i32, NumSummary, example_data::RevAddAction)>,
+{
+    use example_data::{AddAction, RevAddAction};
+    let mut rng = rand::thread_rng();
+    let arr: Vec<i32> = (0..200).collect();
+    let mut tree: T = arr.iter().cloned().collect();
+    for _ in 0..num_rounds {
+        let action = RevAddAction {
+            to_reverse: RevAction {
+                to_reverse: rng.gen(),
+            },
+            add: AddAction {
+                add: rng.gen_range(-MAX_ADD..=MAX_ADD),
+            },
+        };
+        let (segment, query) = (random_range(200), random_range(200));
+        let mut acted = arr.clone();
+        if action.to_reverse.to_reverse {
+            acted[segment.clone()].reverse();
+        }
+        for value in &mut acted[segment.clone()] {
+            *value += action.add.add;
+        }
+        let expected = acted[query.clone()]
+            .iter()
+            .fold(Default::default(), |summary, value| {
+                summary + value.to_summary()
+            });
+        assert_eq!(
+            tree.segment_summary_after_act(action, segment.clone(), query),
+            expected
+        );
+
+        tree.act_segment(action, segment.clone());
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), acted);
+        tree.unact_segment(action, segment);
+        assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), arr);
+    }
+    tree.assert_correctness();
+}
+
+thread_local! {
+    static HOOK_CALLS: std::cell::Cell<(usize, usize)> = const { std::cell::Cell::new((0, 0)) };
+}
+
+/// A [`Data`] marker like [`StdNum`], whose hooks check that they receive up to date
+/// values and summaries, and count how many times they were called.
+pub struct HookedNum;
+
+impl HookedNum {
+    fn check(value: &i32, summary: &NumSummary) {
+        assert!(summary.size >= 1);
+        assert!(summary.min <= Some(*value) && Some(*value) <= summary.max);
+    }
+}
+
+impl Data for HookedNum {
+    type Value = i32;
+    type Summary = NumSummary;
+    type Action = RevAffineAction;
+
+    fn on_access(value: &i32, summary: &NumSummary) {
+        Self::check(value, summary);
+        HOOK_CALLS.with(|calls| calls.set((calls.get().0 + 1, calls.get().1)));
+    }
+
+    fn on_rebuild(value: &i32, summary: &NumSummary) {
+        Self::check(value, summary);
+        HOOK_CALLS.with(|calls| calls.set((calls.get().0, calls.get().1 + 1)));
+    }
+}
+
+/// Checks that the [`Data::on_access`] and [`Data::on_rebuild`] hooks are called.
+pub fn check_hooks<T>(num_rounds: u32)
+where
+    T: SomeTree<HookedNum>,
+{
+    let mut rng = rand::thread_rng();
+    let mut tree: T = (0..200).collect();
+    HOOK_CALLS.with(|calls| calls.set((0, 0)));
+    for _ in 0..num_rounds {
+        tree.act_segment(random_action(&mut rng), random_range(200));
+        let _ = tree.segment_summary(random_range(200));
+        let index = rng.gen_range(0..200);
+        let value = tree.search(index).delete().unwrap();
+        tree.search(index..index).insert(value).unwrap();
+    }
+    let (accesses, rebuilds) = HOOK_CALLS.with(|calls| calls.get());
+    assert!(accesses > 0);
+    assert!(rebuilds > num_rounds as usize);
+    tree.assert_correctness();
+}
@@ -4,7 +4,10 @@ pub mod common;
 pub use common::*;
 
 use grove::data::example_data::*;
-use grove::{avl::AVLTree, basic_tree::BasicTree, splay::SplayTree, treap::Treap};
+use grove::SomeTree;
+use grove::{
+    arena::ArenaTree, avl::AVLTree, basic_tree::BasicTree, splay::SplayTree, treap::Treap,
+};
 
 const NUM_ROUNDS: u32 = if cfg!(not(miri)) { 10_000 } else { 100 }; // miri is too slow
 const NUM_ROUNDS_SLOW: u32 = if cfg!(not(miri)) { 100 } else { 10 }; // miri is too slow
@@ -43,6 +46,38 @@ fn treap_consistency_noncommutative() {
     check_consistency::<(i32, PolyNum<3>, RevAffineAction), Treap<_>, Treap<_>>(NUM_ROUNDS_SLOW);
 }
 
+#[test]
+fn arena_and_treap_consistency() {
+    check_consistency::<StdNum, ArenaTree<_>, Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_and_treap_consistency_noncommutative() {
+    check_consistency::<(i32, PolyNum<3>, RevAffineAction), ArenaTree<_>, Treap<_>>(
+        NUM_ROUNDS_SLOW,
+    );
+}
+
+#[test]
+fn splay_string_concatenation() {
+    check_string_concatenation::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_string_concatenation() {
+    check_string_concatenation::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_string_concatenation() {
+    check_string_concatenation::<ArenaTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_string_concatenation() {
+    check_string_concatenation::<AVLTree<_>>(NUM_ROUNDS);
+}
+
 #[test]
 fn splay_insert() {
     check_insert::<SplayTree<_>>(true);
@@ -58,6 +93,11 @@ fn treap_insert() {
     check_insert::<Treap<_>>(true);
 }
 
+#[test]
+fn arena_insert() {
+    check_insert::<ArenaTree<_>>(true);
+}
+
 #[test]
 fn basic_insert() {
     check_insert::<BasicTree<_>>(true);
@@ -78,7 +118,1006 @@ fn treap_delete() {
     check_delete::<Treap<_>>();
 }
 
+#[test]
+fn arena_delete() {
+    check_delete::<ArenaTree<_>>();
+}
+
 #[test]
 fn basic_delete() {
     check_delete::<BasicTree<_>>();
 }
+
+#[test]
+fn splay_double_ended_iteration() {
+    check_double_ended_iteration::<SplayTree<_>, _>(100);
+}
+
+#[test]
+fn avl_double_ended_iteration() {
+    check_double_ended_iteration::<AVLTree<_>, _>(100);
+}
+
+#[test]
+fn treap_double_ended_iteration() {
+    check_double_ended_iteration::<Treap<_>, _>(100);
+}
+
+#[test]
+fn basic_double_ended_iteration() {
+    check_double_ended_iteration::<BasicTree<_>, _>(100);
+}
+
+#[test]
+fn splay_wide_value_iteration() {
+    check_wide_value_iteration::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_wide_value_iteration() {
+    check_wide_value_iteration::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_wide_value_iteration() {
+    check_wide_value_iteration::<Treap<_>>();
+}
+
+#[test]
+fn arena_wide_value_iteration() {
+    check_wide_value_iteration::<ArenaTree<_>>();
+}
+
+#[test]
+fn basic_wide_value_iteration() {
+    check_wide_value_iteration::<BasicTree<_>>();
+}
+
+#[test]
+#[should_panic(expected = "must not reverse")]
+fn splay_unmarked_reversal() {
+    check_unmarked_reversal::<SplayTree<_>>();
+}
+
+#[test]
+#[should_panic(expected = "must not reverse")]
+fn avl_unmarked_reversal() {
+    check_unmarked_reversal::<AVLTree<_>>();
+}
+
+#[test]
+#[should_panic(expected = "must not reverse")]
+fn treap_unmarked_reversal() {
+    check_unmarked_reversal::<Treap<_>>();
+}
+
+#[test]
+#[should_panic(expected = "must not reverse")]
+fn arena_unmarked_reversal() {
+    check_unmarked_reversal::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_iter_mut() {
+    check_iter_mut::<SplayTree<_>>(100);
+}
+
+#[test]
+fn avl_iter_mut() {
+    check_iter_mut::<AVLTree<_>>(100);
+}
+
+#[test]
+fn treap_iter_mut() {
+    check_iter_mut::<Treap<_>>(100);
+}
+
+#[test]
+fn arena_iter_mut() {
+    check_iter_mut::<ArenaTree<_>>(100);
+}
+
+#[test]
+fn basic_iter_mut() {
+    check_iter_mut::<BasicTree<_>>(100);
+}
+
+#[test]
+fn splay_drain() {
+    check_drain::<SplayTree<_>>(20);
+}
+
+#[test]
+fn avl_drain() {
+    check_drain::<AVLTree<_>>(20);
+}
+
+#[test]
+fn treap_drain() {
+    check_drain::<Treap<_>>(20);
+}
+
+#[test]
+fn arena_drain() {
+    check_drain::<ArenaTree<_>>(20);
+}
+
+#[test]
+fn avl_debug_structure() {
+    let mut tree: AVLTree<StdNum> = (1..=7).collect();
+    let printed = format!("{:?}", tree);
+    assert_eq!(printed.lines().count(), 7);
+    assert!(printed.starts_with("value: 4"));
+    assert!(printed.contains("alg_data: 3"));
+    assert!(!printed.contains("pending action"));
+
+    tree.act_segment(
+        RevAffineAction {
+            to_reverse: true,
+            mul: 1,
+            add: 0,
+        },
+        ..,
+    );
+    let printed = format!("{:?}", tree);
+    assert!(printed.contains("pending action"));
+
+    // after the reversal, the value at index 5 is 2
+    let walker = tree.search(5);
+    let printed = format!("{:?}", walker);
+    assert!(printed.starts_with("path: [Right]"));
+    assert!(printed.contains("subtree:\n  value: 2"));
+}
+
+#[test]
+fn splay_debug_deep_tree() {
+    let len = 5000;
+    let mut tree: SplayTree<StdNum> = (0..len).collect();
+    // accessing every value in order makes the tree a path
+    for i in 0..len as usize {
+        tree.search(i);
+    }
+    // printing the tree shouldn't depend on the size of the stack
+    let printed = std::thread::Builder::new()
+        .stack_size(1 << 16)
+        .spawn(move || format!("{:?}", tree))
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(printed.lines().count(), len as usize);
+    assert!(printed.starts_with(&format!("value: {}", len - 1)));
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn splay_arbitrary_operations() {
+    check_arbitrary_operations::<SplayTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn avl_arbitrary_operations() {
+    check_arbitrary_operations::<AVLTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn treap_arbitrary_operations() {
+    check_arbitrary_operations::<Treap<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[cfg(feature = "test_utils")]
+#[test]
+fn splay_reference_model() {
+    check_reference_model::<SplayTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[cfg(feature = "test_utils")]
+#[test]
+fn avl_reference_model() {
+    check_reference_model::<AVLTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[cfg(feature = "test_utils")]
+#[test]
+fn treap_reference_model() {
+    check_reference_model::<Treap<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn splay_avl_conversion() {
+    let mut splay: SplayTree<StdNum> = (0..500).collect();
+    splay.act_segment(
+        RevAffineAction {
+            to_reverse: true,
+            mul: -1,
+            add: 3,
+        },
+        100..400,
+    );
+    let expected: Vec<_> = splay.iter().cloned().collect();
+
+    let mut avl = AVLTree::from(splay);
+    avl.assert_correctness();
+    assert_eq!(avl.iter().cloned().collect::<Vec<_>>(), expected);
+
+    let mut splay = SplayTree::from(avl);
+    splay.assert_correctness();
+    assert_eq!(splay.iter().cloned().collect::<Vec<_>>(), expected);
+
+    let mut treap: Treap<StdNum> = splay.convert();
+    treap.assert_correctness();
+    assert_eq!(treap.iter().cloned().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn vec_conversion() {
+    check_vec_conversion::<BasicTree<_>>();
+    check_vec_conversion::<SplayTree<_>>();
+    check_vec_conversion::<AVLTree<_>>();
+    check_vec_conversion::<Treap<_>>();
+}
+
+#[test]
+fn splay_insert_run() {
+    check_insert_run::<SplayTree<_>>(50);
+}
+
+#[test]
+fn avl_insert_run() {
+    check_insert_run::<AVLTree<_>>(50);
+}
+
+#[test]
+fn treap_insert_run() {
+    check_insert_run::<Treap<_>>(50);
+}
+
+#[test]
+fn arena_insert_run() {
+    check_insert_run::<ArenaTree<_>>(50);
+}
+
+#[test]
+fn splay_delete_segment() {
+    check_delete_segment::<SplayTree<_>>(20);
+}
+
+#[test]
+fn avl_delete_segment() {
+    check_delete_segment::<AVLTree<_>>(20);
+}
+
+#[test]
+fn treap_delete_segment() {
+    check_delete_segment::<Treap<_>>(20);
+}
+
+#[test]
+fn arena_delete_segment() {
+    check_delete_segment::<ArenaTree<_>>(20);
+}
+
+#[cfg(feature = "instrument")]
+#[test]
+fn splay_sequential_access_rotations() {
+    // By the sequential access theorem, accessing all of the values of a splay tree
+    // in order takes a linear number of rotations in total.
+    let n = 10_000;
+    let mut tree: SplayTree<StdNum> = (0..n as i32).collect();
+    let ((), stats) = grove::instrument::measure(&mut tree, |tree| {
+        for i in 0..n {
+            tree.search(i);
+        }
+    });
+    assert!(stats.rotations <= 5 * n);
+    assert!(stats.splay_steps <= stats.rotations);
+    tree.assert_correctness();
+}
+
+#[cfg(feature = "instrument")]
+#[test]
+fn instrument_counts_per_tree() {
+    use grove::instrument::{self, Instrumented};
+    let mut splay: SplayTree<StdNum> = (0..100).collect();
+    let mut avl: AVLTree<StdNum> = (0..100).collect();
+    for i in 0..100 {
+        splay.search(i);
+    }
+    assert!(splay.stats().splays >= 100);
+    assert_eq!(avl.stats(), Default::default());
+
+    // resetting the counters while measuring doesn't underflow
+    let ((), stats) = instrument::measure(&mut avl, |avl| {
+        avl.search(50);
+        avl.reset_stats();
+    });
+    assert_eq!(stats, Default::default());
+
+    // copying the shared nodes is counted for the tree that was modified
+    let mut tree: grove::persistent::PersistentTree<StdNum> = (0..100).collect();
+    let snapshot = tree.clone();
+    let action = RevAffineAction {
+        to_reverse: false,
+        mul: 1,
+        add: 1,
+    };
+    tree.act_segment(action, 10..20);
+    assert!(tree.stats().nodes_created > 0);
+    assert_eq!(snapshot.stats(), Default::default());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn splay_serde_round_trip() {
+    check_serde_round_trip::<SplayTree<_>>();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn avl_serde_round_trip() {
+    check_serde_round_trip::<AVLTree<_>>();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn basic_serde_round_trip() {
+    check_serde_round_trip::<BasicTree<_>>();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_depth_sequence() {
+    // a perfectly balanced tree with seven values, as `(depth, value)` pairs
+    let json = "[[2,0],[1,1],[2,2],[0,3],[2,4],[1,5],[2,6]]";
+    let tree: AVLTree<StdNum> = serde_json::from_str(json).unwrap();
+    tree.assert_correctness();
+    assert_eq!(serde_json::to_string(&tree).unwrap(), json);
+
+    // a chain is a valid tree, but it isn't balanced
+    let chain = "[[0,0],[1,1],[2,2]]";
+    let tree: BasicTree<StdNum> = serde_json::from_str(chain).unwrap();
+    assert_eq!(serde_json::to_string(&tree).unwrap(), chain);
+    assert!(serde_json::from_str::<AVLTree<StdNum>>(chain).is_err());
+
+    // two adjacent values of the same depth
+    assert!(serde_json::from_str::<BasicTree<StdNum>>("[[1,0],[1,1]]").is_err());
+    // a right son that is too deep
+    assert!(serde_json::from_str::<BasicTree<StdNum>>("[[0,0],[2,1]]").is_err());
+    // the root's depth isn't zero
+    assert!(serde_json::from_str::<SplayTree<StdNum>>("[[1,0]]").is_err());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn splay_par_iter() {
+    check_par_iter::<SplayTree<_>>();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn avl_par_iter() {
+    check_par_iter::<AVLTree<_>>();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn treap_par_iter() {
+    check_par_iter::<Treap<_>>();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn basic_par_iter() {
+    check_par_iter::<BasicTree<_>>();
+}
+
+#[cfg(all(feature = "rayon", feature = "serde"))]
+#[test]
+fn avl_from_par_iter_is_balanced() {
+    use rayon::prelude::*;
+    // building a tree of seven values in parallel gives a perfectly balanced tree
+    let tree: AVLTree<StdNum> = (0..7).into_par_iter().collect();
+    tree.assert_correctness();
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(json, "[[2,0],[1,1],[2,2],[0,3],[2,4],[1,5],[2,6]]");
+}
+
+#[test]
+fn validate_reports_broken_summary() {
+    use grove::{ValidateTree, Violation};
+    let mut tree: BasicTree<StdNum> = (0..10).collect();
+    assert_eq!(tree.validate(), Ok(()));
+    // changing a value directly doesn't update the stored summary
+    *tree.node_mut().unwrap().node_value_mut() += 100;
+    let err = tree.validate().unwrap_err();
+    assert_eq!(err.violation, Violation::Summary);
+    assert!(err.path.is_empty());
+}
+
+#[cfg(feature = "instrument")]
+#[test]
+fn splay_amortized_bound() {
+    use grove::{instrument::PotentialTracker, SomeWalker};
+    let n = 2000;
+    let mut tree: SplayTree<StdNum> = (0..n as i32).collect();
+    let tracker = PotentialTracker::new(&tree);
+    for i in 0..n {
+        let (start, end) = ((i * 7919) % n, (i * 104729) % n);
+        tree.search(start);
+        tree.act_segment(
+            RevAffineAction {
+                to_reverse: true,
+                mul: 1,
+                add: 1,
+            },
+            start.min(end)..start.max(end),
+        );
+    }
+    tracker.assert_bound(&tree);
+
+    // walking down without splaying isn't paid for
+    let tracker = PotentialTracker::new(&tree);
+    for _ in 0..n {
+        let mut walker = tree.basic_walker();
+        while walker.go_left().is_ok() {}
+    }
+    assert!(tracker.work(&tree) as f64 > tracker.amortized_bound(&tree));
+    tree.assert_correctness();
+}
+
+#[test]
+fn splay_clone() {
+    check_clone::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_clone() {
+    check_clone::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_clone() {
+    check_clone::<Treap<_>>();
+}
+
+#[test]
+fn splay_eq_hash() {
+    check_eq_hash::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_eq_hash() {
+    check_eq_hash::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_eq_hash() {
+    check_eq_hash::<Treap<_>>();
+}
+
+#[test]
+fn splay_ord() {
+    check_ord::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_ord() {
+    check_ord::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_ord() {
+    check_ord::<Treap<_>>();
+}
+
+#[test]
+fn splay_from_unsorted_iter() {
+    check_from_unsorted_iter::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_from_unsorted_iter() {
+    check_from_unsorted_iter::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_from_unsorted_iter() {
+    check_from_unsorted_iter::<Treap<_>>();
+}
+
+#[test]
+fn arena_from_unsorted_iter() {
+    check_from_unsorted_iter::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_dedup() {
+    check_dedup::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_dedup() {
+    check_dedup::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_dedup() {
+    check_dedup::<Treap<_>>();
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn splay_sample_segment() {
+    check_sample_segment::<SplayTree<_>>();
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn avl_sample_segment() {
+    check_sample_segment::<AVLTree<_>>();
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn treap_sample_segment() {
+    check_sample_segment::<Treap<_>>();
+}
+
+#[test]
+fn splay_predecessor_successor() {
+    check_predecessor_successor::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_predecessor_successor() {
+    check_predecessor_successor::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_predecessor_successor() {
+    check_predecessor_successor::<Treap<_>>();
+}
+
+#[test]
+fn arena_predecessor_successor() {
+    check_predecessor_successor::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_flush_actions() {
+    check_flush_actions::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_flush_actions() {
+    check_flush_actions::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_flush_actions() {
+    check_flush_actions::<Treap<_>>();
+}
+
+#[test]
+fn arena_flush_actions() {
+    check_flush_actions::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_between() {
+    check_between::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_between() {
+    check_between::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_between() {
+    check_between::<Treap<_>>();
+}
+
+#[test]
+fn arena_between() {
+    check_between::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_extend_append() {
+    check_extend_append::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_extend_append() {
+    check_extend_append::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_extend_append() {
+    check_extend_append::<Treap<_>>();
+}
+
+#[test]
+fn arena_extend_append() {
+    check_extend_append::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_insert_tree_at() {
+    check_insert_tree_at::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_insert_tree_at() {
+    check_insert_tree_at::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_insert_tree_at() {
+    check_insert_tree_at::<Treap<_>>();
+}
+
+#[test]
+fn arena_insert_tree_at() {
+    check_insert_tree_at::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_concat_all() {
+    check_concat_all::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_concat_all() {
+    check_concat_all::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_concat_all() {
+    check_concat_all::<Treap<_>>();
+}
+
+#[test]
+fn arena_concat_all() {
+    check_concat_all::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_act_buffer() {
+    check_act_buffer::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_act_buffer() {
+    check_act_buffer::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_act_buffer() {
+    check_act_buffer::<Treap<_>>();
+}
+
+#[test]
+fn arena_act_buffer() {
+    check_act_buffer::<ArenaTree<_>>();
+}
+
+#[test]
+fn splay_snapshot() {
+    check_snapshot::<SplayTree<_>>();
+}
+
+#[test]
+fn avl_snapshot() {
+    check_snapshot::<AVLTree<_>>();
+}
+
+#[test]
+fn treap_snapshot() {
+    check_snapshot::<Treap<_>>();
+}
+
+#[test]
+fn persistent_snapshots() {
+    check_persistent_snapshots(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_tree_consistency() {
+    check_arena_tree(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_trace() {
+    check_trace::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn avl_trace() {
+    check_trace::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn treap_trace() {
+    check_trace::<Treap<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn splay_history() {
+    check_history::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn avl_history() {
+    check_history::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn treap_history() {
+    check_history::<Treap<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn rope_consistency() {
+    check_rope(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn order_list_consistency() {
+    check_order_list(NUM_ROUNDS);
+}
+
+#[test]
+fn link_cut_consistency() {
+    check_link_cut(NUM_ROUNDS);
+}
+
+#[test]
+fn tree_vec_consistency() {
+    check_tree_vec(NUM_ROUNDS);
+}
+
+#[test]
+fn tree_heap_consistency() {
+    check_tree_heap(NUM_ROUNDS);
+}
+
+#[test]
+fn sparse_segment_tree_consistency() {
+    check_sparse_segment_tree(NUM_ROUNDS);
+}
+
+#[test]
+#[should_panic(expected = "must not depend on the index")]
+fn sparse_segment_tree_indexed_action() {
+    use grove::collections::SparseSegmentTree;
+    let mut tree = SparseSegmentTree::<(i32, PolyNum<2>, ProgressionAction)>::new(4, 0);
+    tree.act_segment(ProgressionAction { add: 0, step: 1 }, 0..4);
+}
+
+#[test]
+fn diff_consistency() {
+    check_diff(1000);
+}
+
+#[test]
+fn versioned_consistency() {
+    check_versioned(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn rle_vec_consistency() {
+    check_rle_vec(NUM_ROUNDS);
+}
+
+#[test]
+#[should_panic(expected = "must not depend on the index")]
+fn rle_vec_indexed_action() {
+    use grove::collections::RleVec;
+    let mut vec = RleVec::<(i32, PolyNum<2>, ProgressionAction)>::new();
+    vec.insert_run(0, 0, 4);
+    vec.act_segment(ProgressionAction { add: 0, step: 1 }, 0..4);
+}
+
+#[test]
+fn chunked_vec_consistency() {
+    check_chunked_vec(NUM_ROUNDS);
+}
+
+#[test]
+fn small_tree_consistency() {
+    check_small_tree::<Treap<_>>(NUM_ROUNDS);
+    check_small_tree::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn compat_btree_map_consistency() {
+    check_compat_btree_map(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_walker_steps() {
+    check_walker_steps::<SplayTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn avl_walker_steps() {
+    check_walker_steps::<AVLTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn treap_walker_steps() {
+    check_walker_steps::<Treap<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn arena_walker_steps() {
+    check_walker_steps::<ArenaTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn splay_bookmarks() {
+    check_bookmarks::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn avl_bookmarks() {
+    check_bookmarks::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn treap_bookmarks() {
+    check_bookmarks::<Treap<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn arena_bookmarks() {
+    check_bookmarks::<ArenaTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn splay_peek() {
+    check_peek::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn avl_peek() {
+    check_peek::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn treap_peek() {
+    check_peek::<Treap<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn arena_peek() {
+    check_peek::<ArenaTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn splay_subtree_surgery() {
+    check_subtree_surgery::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn avl_subtree_surgery() {
+    check_subtree_surgery::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn treap_subtree_surgery() {
+    check_subtree_surgery::<Treap<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn arena_subtree_surgery() {
+    check_subtree_surgery::<ArenaTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn splay_finger_search() {
+    check_finger_search::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_finger_search() {
+    check_finger_search::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_finger_search() {
+    check_finger_search::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_finger_search() {
+    check_finger_search::<ArenaTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_indexed_actions() {
+    check_indexed_actions::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10, false);
+}
+
+#[test]
+fn avl_indexed_actions() {
+    check_indexed_actions::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10, true);
+}
+
+#[test]
+fn treap_indexed_actions() {
+    check_indexed_actions::<Treap<_>>(NUM_ROUNDS_SLOW * 10, true);
+}
+
+#[test]
+fn arena_indexed_actions() {
+    check_indexed_actions::<ArenaTree<_>>(NUM_ROUNDS_SLOW * 10, true);
+}
+
+#[test]
+fn splay_unact_segment() {
+    check_unact_segment::<SplayTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn avl_unact_segment() {
+    check_unact_segment::<AVLTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn treap_unact_segment() {
+    check_unact_segment::<Treap<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn arena_unact_segment() {
+    check_unact_segment::<ArenaTree<_>>(NUM_ROUNDS_SLOW * 10);
+}
+
+#[test]
+fn splay_hooks() {
+    check_hooks::<SplayTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn avl_hooks() {
+    check_hooks::<AVLTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn treap_hooks() {
+    check_hooks::<Treap<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn arena_hooks() {
+    check_hooks::<ArenaTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn algorithms_consistency() {
+    check_algorithms(NUM_ROUNDS_SLOW);
+}
+
+// The trees are `Send` and `Sync` exactly when their data is.
+static_assertions::assert_impl_all!(BasicTree<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(SplayTree<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(AVLTree<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(Treap<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::persistent::PersistentTree<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::arena::ArenaTree<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::snapshot::Snapshot<StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::basic_tree::ImmBasicWalker<'static, StdNum>: Send, Sync);
+static_assertions::assert_not_impl_any!(SplayTree<(std::rc::Rc<i32>, Unit, Unit)>: Send, Sync);
+static_assertions::assert_not_impl_any!(AVLTree<(std::cell::Cell<i32>, Unit, Unit)>: Sync);
+static_assertions::assert_impl_all!(grove::basic_tree::BasicWalker<'static, StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::splay::SplayWalker<'static, StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::avl::AVLWalker<'static, StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::treap::TreapWalker<'static, StdNum>: Send, Sync);
+static_assertions::assert_impl_all!(grove::arena::ArenaWalker<'static, StdNum>: Send, Sync);
+static_assertions::assert_not_impl_any!(grove::splay::SplayWalker<'static, (std::rc::Rc<i32>, Unit, Unit)>: Send, Sync);
+static_assertions::assert_not_impl_any!(grove::avl::AVLWalker<'static, (std::cell::Cell<i32>, Unit, Unit)>: Sync);
+static_assertions::assert_not_impl_any!(grove::treap::TreapWalker<'static, (std::cell::Cell<i32>, Unit, Unit)>: Sync);